@@ -0,0 +1,84 @@
+@group(0) @binding(0) var<uniform> color_correction_data: ColorCorrectionData;
+@group(0) @binding(1) var source_texture: texture_2d<f32>;
+@group(0) @binding(2) var source_sampler: sampler;
+
+struct ColorCorrectionData {
+	saturation: f32,
+	brightness: f32,
+	exposure: f32,
+	tonemapper: u32,
+	bloom_threshold: f32,
+	bloom_strength: f32,
+	contrast: f32,
+}
+
+
+
+@vertex
+fn vs_main(
+	@builtin(vertex_index) index: u32
+) -> VertexOutput {
+	var output: VertexOutput;
+	
+	// hacky way to draw a single large triangle that convers the entire screen
+	output.screen_pos = vec4(
+		f32(i32(index) / 2) * 4.0 - 1.0,
+		f32(i32(index) & 1) * 4.0 - 1.0,
+		1.0,
+		1.0,
+	);
+	
+	output.tex_coords = output.screen_pos.xy * vec2(0.5, -0.5) + 0.5;
+	
+	return output;
+}
+
+
+
+struct VertexOutput {
+	@builtin(position) screen_pos: vec4f,
+	@location(0) tex_coords: vec2f,
+}
+
+
+
+// this is the downsampling filter from the 'dual kawase' blur, the center plus four diagonal samples
+fn sample_downsampled(tex_coords: vec2f) -> vec3f {
+	let half_texel = 0.5 / vec2f(textureDimensions(source_texture));
+	var output = textureSample(source_texture, source_sampler, tex_coords).rgb * 4.0;
+	output += textureSample(source_texture, source_sampler, tex_coords + vec2(-half_texel.x, -half_texel.y)).rgb;
+	output += textureSample(source_texture, source_sampler, tex_coords + vec2( half_texel.x, -half_texel.y)).rgb;
+	output += textureSample(source_texture, source_sampler, tex_coords + vec2(-half_texel.x,  half_texel.y)).rgb;
+	output += textureSample(source_texture, source_sampler, tex_coords + vec2( half_texel.x,  half_texel.y)).rgb;
+	return output / 8.0;
+}
+
+@fragment
+fn fs_bright_pass(in: VertexOutput) -> @location(0) vec4f {
+	let color = sample_downsampled(in.tex_coords);
+	// a soft knee would be nicer, but this keeps anything below the threshold from glowing at all
+	let brightness = max(color.r, max(color.g, color.b));
+	let contribution = max(brightness - color_correction_data.bloom_threshold, 0.0) / max(brightness, 0.0001);
+	return vec4(color * contribution, 1.0);
+}
+
+@fragment
+fn fs_downsample(in: VertexOutput) -> @location(0) vec4f {
+	return vec4(sample_downsampled(in.tex_coords), 1.0);
+}
+
+// this is the upsampling filter from the 'dual kawase' blur, a ring of eight samples around the center
+@fragment
+fn fs_upsample(in: VertexOutput) -> @location(0) vec4f {
+	let texel = 1.0 / vec2f(textureDimensions(source_texture));
+	var output = vec3(0.0);
+	output += textureSample(source_texture, source_sampler, in.tex_coords + vec2(-texel.x * 2.0, 0.0)).rgb;
+	output += textureSample(source_texture, source_sampler, in.tex_coords + vec2( texel.x * 2.0, 0.0)).rgb;
+	output += textureSample(source_texture, source_sampler, in.tex_coords + vec2(0.0, -texel.y * 2.0)).rgb;
+	output += textureSample(source_texture, source_sampler, in.tex_coords + vec2(0.0,  texel.y * 2.0)).rgb;
+	output += textureSample(source_texture, source_sampler, in.tex_coords + vec2(-texel.x, -texel.y)).rgb * 2.0;
+	output += textureSample(source_texture, source_sampler, in.tex_coords + vec2( texel.x, -texel.y)).rgb * 2.0;
+	output += textureSample(source_texture, source_sampler, in.tex_coords + vec2(-texel.x,  texel.y)).rgb * 2.0;
+	output += textureSample(source_texture, source_sampler, in.tex_coords + vec2( texel.x,  texel.y)).rgb * 2.0;
+	return vec4(output / 12.0, 1.0);
+}
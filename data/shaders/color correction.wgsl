@@ -3,6 +3,12 @@
 struct ColorCorrectionData {
 	saturation: f32,
 	brightness: f32,
+	exposure: f32,
+	tonemapper: u32, // 0: none, 1: reinhard, 2: aces
+	bloom_threshold: f32,
+	bloom_strength: f32,
+	contrast: f32,
+	encode_srgb: u32, // 1 when the surface isn't srgb, see `RenderContextData::manual_srgb_encode`
 }
 
 
@@ -35,19 +41,58 @@ struct VertexOutput {
 
 @group(0) @binding(1) var main_texture: texture_2d<f32>;
 @group(0) @binding(2) var main_sampler: sampler;
+@group(0) @binding(3) var bloom_texture: texture_2d<f32>;
 
 
 
+// this is Krzysztof Narkowicz's fit of the aces filmic curve: https://knarkowicz.wordpress.com/2016/01/06/aces-filmic-tone-mapping-curve/
+fn tonemap_aces(color: vec3f) -> vec3f {
+	let a = 2.51;
+	let b = 0.03;
+	let c = 2.43;
+	let d = 0.59;
+	let e = 0.14;
+	return clamp((color * (a * color + b)) / (color * (c * color + d) + e), vec3(0.0), vec3(1.0));
+}
+
+// same as `utils::linear_to_srgb()`
+fn linear_to_srgb(color: vec3f) -> vec3f {
+	let low = color * 12.92;
+	let high = 1.055 * pow(color, vec3(1.0 / 2.4)) - 0.055;
+	return select(high, low, color <= vec3(0.0031308));
+}
+
+fn tonemap(color: vec3f) -> vec3f {
+	switch (color_correction_data.tonemapper) {
+		case 1u: {return color / (color + 1.0);}
+		case 2u: {return tonemap_aces(color);}
+		default: {return clamp(color, vec3(0.0), vec3(1.0));}
+	}
+}
+
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4f {
 	var color = textureSample(main_texture, main_sampler, in.tex_coords).rgb;
 	
+	// the bloom texture is stale when bloom is disabled, but it's multiplied by 0 in that case
+	color += textureSample(bloom_texture, main_sampler, in.tex_coords).rgb * color_correction_data.bloom_strength;
+	
+	// the main texture is hdr, so it has to be brought down to 0 to 1 before the rest of the color correction
+	color = tonemap(color * color_correction_data.exposure);
+	
 	// saturation
 	let color_lum = dot(color, vec3(0.2125, 0.7154, 0.0721));
 	color = mix(vec3(color_lum), color, color_correction_data.saturation);
 	
+	// contrast (around middle gray)
+	color = (color - 0.5) * color_correction_data.contrast + 0.5;
+	
 	// brightness
 	color *= color_correction_data.brightness;
 	
+	color = max(color, vec3(0.0));
+	if (color_correction_data.encode_srgb == 1u) {
+		color = linear_to_srgb(color);
+	}
 	return vec4(color, 1.0);
 }
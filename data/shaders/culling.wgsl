@@ -0,0 +1,50 @@
+@group(0) @binding(0) var<uniform> culling_settings: CullingSettings;
+@group(0) @binding(1) var<storage, read> total_instances: array<f32>;
+@group(0) @binding(2) var<storage, read_write> culled_instances: array<f32>;
+@group(0) @binding(3) var<storage, read_write> visible_count: atomic<u32>;
+
+struct CullingSettings {
+	frustum_planes: array<vec4f, 5>,
+	camera_pos: vec3f,
+	bounding_radius: f32,
+	max_draw_distance: f32,
+	draw_distance_fade_width: f32,
+	instance_count: u32,
+}
+
+// HELP: `RawInstanceData` is a mat4x4 followed by the fade (17 floats, no padding), which doesn't match the layout wgsl
+// would give a struct, so the instances are read and written as plain floats
+const INSTANCE_STRIDE: u32 = 17u;
+const FADE_OFFSET: u32 = 16u;
+
+
+
+@compute @workgroup_size(64)
+fn cs_main(@builtin(global_invocation_id) id: vec3u) {
+	let index = id.x;
+	if (index >= culling_settings.instance_count) {return;}
+	let start = index * INSTANCE_STRIDE;
+	
+	// the position is the translation part of the model matrix (the last column)
+	let pos = vec3(total_instances[start + 12u], total_instances[start + 13u], total_instances[start + 14u]);
+	let dist = distance(pos, culling_settings.camera_pos);
+	if (culling_settings.max_draw_distance > 0.0 && dist > culling_settings.max_draw_distance) {return;}
+	for (var i = 0u; i < 5u; i++) {
+		let plane = culling_settings.frustum_planes[i];
+		if (dot(plane.xyz, pos) <= plane.w - culling_settings.bounding_radius) {return;}
+	}
+	
+	// the order of the culled instances depends on which invocations finish first, which is fine since only opaque meshes use them
+	let culled_start = atomicAdd(&visible_count, 1u) * INSTANCE_STRIDE;
+	for (var i = 0u; i < FADE_OFFSET; i++) {
+		culled_instances[culled_start + i] = total_instances[start + i];
+	}
+	culled_instances[culled_start + FADE_OFFSET] = get_distance_fade(dist);
+}
+
+// same as `render::get_distance_fade()`
+fn get_distance_fade(dist: f32) -> f32 {
+	if (culling_settings.max_draw_distance <= 0.0) {return 1.0;}
+	if (culling_settings.draw_distance_fade_width <= 0.0) {return select(0.0, 1.0, dist <= culling_settings.max_draw_distance);}
+	return clamp((culling_settings.max_draw_distance - dist) / culling_settings.draw_distance_fade_width, 0.0, 1.0);
+}
@@ -0,0 +1,40 @@
+@group(0) @binding(0) var<uniform> camera_data: CameraData;
+
+struct CameraData {
+	proj_view_mat: mat4x4f,
+	inv_proj_mat: mat4x4f,
+	view_mat: mat4x4f,
+	resolution: vec2f,
+	inv_resolution: vec2f,
+}
+
+struct VertexInput {
+	@location(0) pos: vec3f,
+	@location(1) color: vec3f,
+}
+
+
+
+@vertex
+fn vs_main(
+	vertex: VertexInput,
+) -> VertexOutput {
+	var out: VertexOutput;
+	out.screen_pos = camera_data.proj_view_mat * vec4(vertex.pos, 1.0);
+	out.color = vertex.color;
+	return out;
+}
+
+
+
+struct VertexOutput {
+	@builtin(position) screen_pos: vec4f,
+	@location(0) color: vec3f,
+}
+
+
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4f {
+	return vec4(in.color, 1.0);
+}
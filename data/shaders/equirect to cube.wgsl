@@ -0,0 +1,52 @@
+@group(0) @binding(0) var equirect_texture: texture_2d<f32>;
+@group(0) @binding(1) var cube_faces: texture_storage_2d_array<rgba16float, write>;
+
+const PI: f32 = 3.14159265359;
+
+
+
+// HELP: this runs once when an equirectangular skybox is loaded, each invocation writes one texel of one face (the z id is the face index)
+@compute @workgroup_size(8, 8, 1)
+fn cs_main(@builtin(global_invocation_id) id: vec3u) {
+	let face_size = textureDimensions(cube_faces).x;
+	if (id.x >= face_size || id.y >= face_size) {
+		return;
+	}
+	
+	// -1 to 1 across the face, with y going down like the texel rows
+	let face_uv = (vec2f(id.xy) + 0.5) / f32(face_size) * 2.0 - 1.0;
+	let direction = normalize(get_face_direction(id.z, face_uv));
+	
+	let longitude = atan2(direction.z, direction.x);
+	let latitude = asin(clamp(direction.y, -1.0, 1.0));
+	let equirect_uv = vec2(longitude / (2.0 * PI) + 0.5, 0.5 - latitude / PI);
+	
+	textureStore(cube_faces, id.xy, id.z, vec4(sample_bilinear(equirect_uv), 1.0));
+}
+
+// the faces are in the order +x, -x, +y, -y, +z, -z, same as wgpu's cube textures
+fn get_face_direction(face: u32, uv: vec2f) -> vec3f {
+	switch (face) {
+		case 0u {return vec3(1.0, -uv.y, -uv.x);}
+		case 1u {return vec3(-1.0, -uv.y, uv.x);}
+		case 2u {return vec3(uv.x, 1.0, uv.y);}
+		case 3u {return vec3(uv.x, -1.0, -uv.y);}
+		case 4u {return vec3(uv.x, -uv.y, 1.0);}
+		default {return vec3(-uv.x, -uv.y, -1.0);}
+	}
+}
+
+// NOTE: float32 textures usually can't be filtered by a sampler, so the filtering is done manually (wrapping horizontally and clamping vertically)
+fn sample_bilinear(uv: vec2f) -> vec3f {
+	let dimensions = vec2i(textureDimensions(equirect_texture));
+	let texel_pos = uv * vec2f(dimensions) - 0.5;
+	let base = vec2i(floor(texel_pos));
+	let t = texel_pos - floor(texel_pos);
+	let x0 = (base.x % dimensions.x + dimensions.x) % dimensions.x;
+	let x1 = (x0 + 1) % dimensions.x;
+	let y0 = clamp(base.y, 0, dimensions.y - 1);
+	let y1 = clamp(base.y + 1, 0, dimensions.y - 1);
+	let top = mix(textureLoad(equirect_texture, vec2(x0, y0), 0).rgb, textureLoad(equirect_texture, vec2(x1, y0), 0).rgb, t.x);
+	let bottom = mix(textureLoad(equirect_texture, vec2(x0, y1), 0).rgb, textureLoad(equirect_texture, vec2(x1, y1), 0).rgb, t.x);
+	return mix(top, bottom, t.y);
+}
@@ -0,0 +1,78 @@
+@group(0) @binding(0) var<uniform> camera_data: CameraData;
+@group(0) @binding(1) var<uniform> foliage_settings: FoliageSettings;
+@group(0) @binding(2) var foliage_texture: texture_2d<f32>;
+@group(0) @binding(3) var foliage_sampler: sampler;
+
+struct CameraData {
+	proj_view_mat: mat4x4f,
+	inv_proj_mat: mat4x4f,
+	view_mat: mat4x4f,
+	resolution: vec2f,
+	inv_resolution: vec2f,
+}
+
+struct FoliageSettings {
+	wind_strength: f32,
+	wind_speed: f32,
+	total_time: f32,
+}
+
+struct InstanceInput {
+	@location(0) pos: vec3f,
+	@location(1) rot: f32,
+	@location(2) scale: f32,
+}
+
+
+
+@vertex
+fn vs_main(
+	@builtin(vertex_index) index: u32,
+	instance: InstanceInput,
+) -> VertexOutput {
+
+	// each instance is two crossed quads (12 vertices) so that it looks full from every direction
+	var quad_corners = array<vec2f, 6>(
+		vec2(-0.5, 0.0),
+		vec2( 0.5, 0.0),
+		vec2( 0.5, 1.0),
+		vec2(-0.5, 0.0),
+		vec2( 0.5, 1.0),
+		vec2(-0.5, 1.0),
+	);
+	let corner = quad_corners[index % 6u];
+	let quad_rot = instance.rot + f32(index / 6u) * 1.5707964;
+	let local_pos = vec3(corner.x * cos(quad_rot), corner.y, corner.x * sin(quad_rot));
+	
+	// the wind only moves the top of the quads, and the phase depends on the position so the whole field doesn't sway in sync
+	let phase = foliage_settings.total_time * foliage_settings.wind_speed + dot(instance.pos.xz, vec2(0.35, 0.25));
+	let sway = vec3(sin(phase), 0.0, sin(phase * 0.7 + 1.3) * 0.5) * foliage_settings.wind_strength * corner.y * corner.y;
+	let world_pos = instance.pos + (local_pos + sway) * instance.scale;
+	
+	var out: VertexOutput;
+	out.screen_pos = camera_data.proj_view_mat * vec4(world_pos, 1.0);
+	out.texcoords = vec2(corner.x + 0.5, 1.0 - corner.y);
+	out.height = corner.y;
+	return out;
+}
+
+
+
+struct VertexOutput {
+	@builtin(position) screen_pos: vec4f,
+	@location(0) texcoords: vec2f,
+	@location(1) height: f32,
+};
+
+
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4f {
+	let color = textureSample(foliage_texture, foliage_sampler, in.texcoords);
+	// alpha-tested instead of blended so that the foliage doesn't need to be sorted
+	if (color.a < 0.5) {
+		discard;
+	}
+	// fake ambient occlusion, darker near the ground
+	return vec4(color.rgb * mix(0.6, 1.0, in.height), 1.0);
+}
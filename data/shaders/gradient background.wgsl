@@ -0,0 +1,42 @@
+@group(0) @binding(0) var<uniform> gradient_data: GradientData;
+
+struct GradientData {
+	top_color: vec4f,
+	bottom_color: vec4f,
+}
+
+
+
+@vertex
+fn vs_main(
+	@builtin(vertex_index) index: u32
+) -> VertexOutput {
+	var output: VertexOutput;
+	
+	// hacky way to draw a single large triangle that convers the entire screen
+	output.screen_pos = vec4(
+		f32(i32(index) / 2) * 4.0 - 1.0,
+		f32(i32(index) & 1) * 4.0 - 1.0,
+		1.0,
+		1.0,
+	);
+	
+	// 0 at the bottom of the screen, 1 at the top
+	output.height = output.screen_pos.y * 0.5 + 0.5;
+	
+	return output;
+}
+
+
+
+struct VertexOutput {
+	@builtin(position) screen_pos: vec4f,
+	@location(0) height: f32,
+}
+
+
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4f {
+	return mix(gradient_data.bottom_color, gradient_data.top_color, clamp(in.height, 0.0, 1.0));
+}
@@ -0,0 +1,153 @@
+@group(0) @binding(0) var environment_texture: texture_cube<f32>;
+@group(0) @binding(1) var environment_sampler: sampler;
+@group(0) @binding(2) var output_faces: texture_storage_2d_array<rgba16float, write>;
+@group(0) @binding(3) var<uniform> prefilter_settings: PrefilterSettings;
+@group(0) @binding(4) var brdf_lut: texture_storage_2d<rgba16float, write>;
+
+struct PrefilterSettings {
+	roughness: f32,
+}
+
+const PI: f32 = 3.14159265359;
+const IRRADIANCE_PHI_STEPS: u32 = 64u;
+const IRRADIANCE_THETA_STEPS: u32 = 16u;
+const PREFILTER_SAMPLE_COUNT: u32 = 256u;
+const BRDF_SAMPLE_COUNT: u32 = 512u;
+
+// HELP: these only run once when the skybox is loaded. Each invocation of the cube entry points writes one texel of one
+// face (the z id is the face index), see https://learnopengl.com/PBR/IBL/Diffuse-irradiance and https://learnopengl.com/PBR/IBL/Specular-IBL
+
+
+
+// the average incoming light over the hemisphere around each direction, weighted by the angle (multiplying this by the albedo gives the diffuse light)
+@compute @workgroup_size(8, 8, 1)
+fn cs_irradiance(@builtin(global_invocation_id) id: vec3u) {
+	let face_size = textureDimensions(output_faces).x;
+	if (id.x >= face_size || id.y >= face_size) {
+		return;
+	}
+	let normal = get_texel_direction(id, face_size);
+	let tangent_space = get_tangent_space(normal);
+	
+	var total = vec3(0.0);
+	for (var phi_index = 0u; phi_index < IRRADIANCE_PHI_STEPS; phi_index++) {
+		let phi = (f32(phi_index) + 0.5) / f32(IRRADIANCE_PHI_STEPS) * 2.0 * PI;
+		for (var theta_index = 0u; theta_index < IRRADIANCE_THETA_STEPS; theta_index++) {
+			let theta = (f32(theta_index) + 0.5) / f32(IRRADIANCE_THETA_STEPS) * 0.5 * PI;
+			let sample_dir = tangent_space * vec3(sin(theta) * cos(phi), sin(theta) * sin(phi), cos(theta));
+			// cos for the angle to the normal, sin because the samples are denser near the top of the hemisphere
+			total += textureSampleLevel(environment_texture, environment_sampler, sample_dir, 0.0).rgb * cos(theta) * sin(theta);
+		}
+	}
+	let irradiance = PI * total / f32(IRRADIANCE_PHI_STEPS * IRRADIANCE_THETA_STEPS);
+	
+	textureStore(output_faces, id.xy, id.z, vec4(irradiance, 1.0));
+}
+
+
+
+// the environment blurred by the ggx distribution for one roughness (one dispatch per mip), assuming that the view direction is the reflection direction
+@compute @workgroup_size(8, 8, 1)
+fn cs_prefilter(@builtin(global_invocation_id) id: vec3u) {
+	let face_size = textureDimensions(output_faces).x;
+	if (id.x >= face_size || id.y >= face_size) {
+		return;
+	}
+	let normal = get_texel_direction(id, face_size);
+	let tangent_space = get_tangent_space(normal);
+	
+	var total = vec3(0.0);
+	var total_weight = 0.0;
+	for (var i = 0u; i < PREFILTER_SAMPLE_COUNT; i++) {
+		let halfway = tangent_space * importance_sample_ggx(hammersley(i, PREFILTER_SAMPLE_COUNT), prefilter_settings.roughness);
+		let light_dir = normalize(2.0 * dot(normal, halfway) * halfway - normal);
+		let n_dot_l = dot(normal, light_dir);
+		if (n_dot_l > 0.0) {
+			total += textureSampleLevel(environment_texture, environment_sampler, light_dir, 0.0).rgb * n_dot_l;
+			total_weight += n_dot_l;
+		}
+	}
+	
+	textureStore(output_faces, id.xy, id.z, vec4(total / max(total_weight, 0.0001), 1.0));
+}
+
+
+
+// the scale (r) and bias (g) that are applied to the surface's base reflectivity, indexed by n_dot_v (x) and roughness (y)
+@compute @workgroup_size(8, 8, 1)
+fn cs_brdf_lut(@builtin(global_invocation_id) id: vec3u) {
+	let size = textureDimensions(brdf_lut);
+	if (id.x >= size.x || id.y >= size.y) {
+		return;
+	}
+	let n_dot_v = (f32(id.x) + 0.5) / f32(size.x);
+	let roughness = (f32(id.y) + 0.5) / f32(size.y);
+	let view_dir = vec3(sqrt(1.0 - n_dot_v * n_dot_v), 0.0, n_dot_v);
+	
+	var scale = 0.0;
+	var bias = 0.0;
+	for (var i = 0u; i < BRDF_SAMPLE_COUNT; i++) {
+		let halfway = importance_sample_ggx(hammersley(i, BRDF_SAMPLE_COUNT), roughness);
+		let light_dir = normalize(2.0 * dot(view_dir, halfway) * halfway - view_dir);
+		let n_dot_l = max(light_dir.z, 0.0);
+		let n_dot_h = max(halfway.z, 0.0);
+		let v_dot_h = max(dot(view_dir, halfway), 0.0);
+		if (n_dot_l > 0.0) {
+			let visibility = geometry_smith(n_dot_v, n_dot_l, roughness) * v_dot_h / (n_dot_h * n_dot_v);
+			let fresnel = pow(1.0 - v_dot_h, 5.0);
+			scale += (1.0 - fresnel) * visibility;
+			bias += fresnel * visibility;
+		}
+	}
+	
+	textureStore(brdf_lut, id.xy, vec4(scale / f32(BRDF_SAMPLE_COUNT), bias / f32(BRDF_SAMPLE_COUNT), 0.0, 1.0));
+}
+
+
+
+
+
+// the faces are in the order +x, -x, +y, -y, +z, -z, same as wgpu's cube textures
+fn get_texel_direction(id: vec3u, face_size: u32) -> vec3f {
+	// -1 to 1 across the face, with y going down like the texel rows
+	let uv = (vec2f(id.xy) + 0.5) / f32(face_size) * 2.0 - 1.0;
+	var direction: vec3f;
+	switch (id.z) {
+		case 0u {direction = vec3(1.0, -uv.y, -uv.x);}
+		case 1u {direction = vec3(-1.0, -uv.y, uv.x);}
+		case 2u {direction = vec3(uv.x, 1.0, uv.y);}
+		case 3u {direction = vec3(uv.x, -1.0, -uv.y);}
+		case 4u {direction = vec3(uv.x, -uv.y, 1.0);}
+		default {direction = vec3(-uv.x, -uv.y, -1.0);}
+	}
+	return normalize(direction);
+}
+
+// converts from a space where z is `normal` to world space
+fn get_tangent_space(normal: vec3f) -> mat3x3f {
+	let up = select(vec3(0.0, 0.0, 1.0), vec3(0.0, 1.0, 0.0), abs(normal.y) < 0.999);
+	let right = normalize(cross(up, normal));
+	return mat3x3(right, cross(normal, right), normal);
+}
+
+// evenly spread out points in 0..1 x 0..1
+fn hammersley(i: u32, count: u32) -> vec2f {
+	return vec2(f32(i) / f32(count), f32(reverseBits(i)) * 2.3283064365386963e-10);
+}
+
+// a halfway vector (in tangent space) that's more likely to be where the ggx distribution is large
+fn importance_sample_ggx(xi: vec2f, roughness: f32) -> vec3f {
+	let a = roughness * roughness;
+	let phi = 2.0 * PI * xi.x;
+	let cos_theta = sqrt((1.0 - xi.y) / (1.0 + (a * a - 1.0) * xi.y));
+	let sin_theta = sqrt(1.0 - cos_theta * cos_theta);
+	return vec3(cos(phi) * sin_theta, sin(phi) * sin_theta, cos_theta);
+}
+
+fn geometry_smith(n_dot_v: f32, n_dot_l: f32, roughness: f32) -> f32 {
+	// HELP: ibl uses a different k than direct lighting
+	let k = roughness * roughness / 2.0;
+	let ggx_v = n_dot_v / (n_dot_v * (1.0 - k) + k);
+	let ggx_l = n_dot_l / (n_dot_l * (1.0 - k) + k);
+	return ggx_v * ggx_l;
+}
@@ -1,13 +1,59 @@
 @group(0) @binding(0) var<uniform> camera_data: CameraData;
-@group(0) @binding(1) var<uniform> shadow_caster_proj_mat: mat4x4f;
+@group(0) @binding(1) var<uniform> shadow_cascades: ShadowCascades;
 @group(0) @binding(2) var material_sampler: sampler;
-@group(0) @binding(3) var shadowmap_texture: texture_depth_2d;
+@group(0) @binding(3) var shadowmap_texture: texture_depth_2d_array;
 @group(0) @binding(4) var shadowmap_sampler: sampler_comparison;
+@group(0) @binding(5) var<uniform> lighting_settings: LightingSettings;
+@group(0) @binding(6) var<storage, read> lights: array<Light>;
+@group(0) @binding(7) var<uniform> directional_light: DirectionalLight;
+@group(0) @binding(8) var ibl_irradiance_texture: texture_cube<f32>;
+@group(0) @binding(9) var ibl_prefiltered_texture: texture_cube<f32>;
+@group(0) @binding(10) var ibl_brdf_lut: texture_2d<f32>;
+@group(0) @binding(11) var ibl_sampler: sampler;
 
 struct CameraData {
 	proj_view_mat: mat4x4f,
 	inv_proj_mat: mat4x4f,
 	view_mat: mat4x4f,
+	resolution: vec2f,
+	inv_resolution: vec2f,
+}
+
+struct ShadowCascades {
+	proj_mats: array<mat4x4f, 4>,
+	split_depths: vec4f, // the view-space depth where each cascade ends
+	depth_scales: vec4f, // how much the shadowmap depth changes per shadowmap uv
+	count: u32,
+}
+
+struct LightingSettings {
+	flat_shading: u32,
+	debug_view: u32, // 0: none, 1: geometry normals, 2: shading normals
+	light_count: u32,
+	pcf_kernel_size: u32, // 1, 3, or 5
+	spot_pos: vec3f,
+	shadow_caster_kind: u32, // 0: directional, 1: spot
+	spot_dir: vec3f,
+	spot_cos_outer: f32,
+	spot_cos_inner: f32,
+	spot_range: f32,
+	shadowmap_texel_size: f32,
+	environment_rotation: f32, // the skybox's yaw, in radians
+}
+
+struct Light {
+	pos: vec3f,
+	kind: u32, // 0: point, 1: directional
+	direction: vec3f,
+	range: f32,
+	color: vec3f,
+	intensity: f32,
+}
+
+struct DirectionalLight {
+	direction: vec3f, // the direction the light travels in
+	intensity: f32,
+	color: vec3f,
 }
 
 struct BasicVertexInput {
@@ -16,7 +62,8 @@ struct BasicVertexInput {
 
 struct ExtendedVertexInput {
 	@location(1) texcoords: vec2f,
-	@location(2) normal: vec2f,
+	@location(2) normal: vec3f,
+	@location(8) tangent: vec4f, // w is the handedness of the bitangent
 }
 
 struct InstanceInput {
@@ -24,6 +71,7 @@ struct InstanceInput {
 	@location(4) model_mat_1: vec4f,
 	@location(5) model_mat_2: vec4f,
 	@location(6) model_mat_3: vec4f,
+	@location(7) fade: f32,
 };
 
 
@@ -47,7 +95,11 @@ fn vs_main(
 	var out: VertexOutput;
 	out.screen_pos = camera_data.proj_view_mat * world_pos;
 	out.world_pos = world_pos.xyz;
-	out.texcoords = vertex_extended.texcoords;
+	out.view_depth = -(camera_data.view_mat * world_pos).z;
+	out.texcoords = (uv_transform * vec3(vertex_extended.texcoords, 1.0)).xy;
+	out.normal = (instance_mat * vec4(vertex_extended.normal, 0.0)).xyz;
+	out.tangent = vec4((instance_mat * vec4(vertex_extended.tangent.xyz, 0.0)).xyz, vertex_extended.tangent.w);
+	out.fade = instance.fade;
 	return out;
 }
 
@@ -57,28 +109,201 @@ struct VertexOutput {
 	@builtin(position) screen_pos: vec4f,
 	@location(0) world_pos: vec3f,
 	@location(1) texcoords: vec2f,
+	@location(2) normal: vec3f,
+	@location(3) @interpolate(flat) fade: f32,
+	@location(4) view_depth: f32,
+	@location(5) tangent: vec4f,
 };
 
 @group(1) @binding(0) var material_texture: texture_2d<f32>;
+@group(1) @binding(1) var<uniform> uv_transform: mat3x3f;
+@group(1) @binding(2) var normal_map_texture: texture_2d<f32>;
+@group(1) @binding(3) var<uniform> material_settings: MaterialSettings;
+
+struct MaterialSettings {
+	alpha_cutoff: f32, // 0 unless the material is alpha tested
+}
 
 
 
-fn sample_shadows(world_pos: vec3f) -> f32 {
-	var shadowmap_pos = shadow_caster_proj_mat * vec4(world_pos, 1.0);
+// HELP: the pipeline's depth bias only keeps the center texel from self-shadowing, the outer
+// samples land on parts of the shadowmap where sloped surfaces are closer to the light, so they
+// get an extra bias that grows with their distance from the center (this covers slopes up to 45
+// degrees, `depth_scales` accounts for each cascade's xy and z covering different distances)
+const PCF_SLOPE_BIAS: f32 = 1.0;
+
+fn get_shadow_cascade(view_depth: f32) -> u32 {
+	for (var i = 0u; i + 1u < shadow_cascades.count; i++) {
+		if (view_depth < shadow_cascades.split_depths[i]) {
+			return i;
+		}
+	}
+	return shadow_cascades.count - 1u;
+}
+
+fn sample_shadows(world_pos: vec3f, view_depth: f32) -> f32 {
+	let cascade = get_shadow_cascade(view_depth);
+	var shadowmap_pos = shadow_cascades.proj_mats[cascade] * vec4(world_pos, 1.0);
+	// the divide only matters for the spot shadow caster (the directional one always has w = 1)
+	shadowmap_pos = vec4(shadowmap_pos.xyz / shadowmap_pos.w, 1.0);
 	// shadowmap_pos starts in range -1 to 1 with y going up, but we need 0 to 1 with y going down
 	shadowmap_pos = vec4(shadowmap_pos.xyz * vec3(0.5, -0.5, 0.5) + 0.5, 1.0);
-	return textureSampleCompareLevel(shadowmap_texture, shadowmap_sampler, shadowmap_pos.xy, shadowmap_pos.z);
+	
+	// percentage-closer filtering, each sample is already bilinearly filtered by the comparison sampler
+	let radius = i32(lighting_settings.pcf_kernel_size / 2u);
+	let slope_bias = PCF_SLOPE_BIAS * shadow_cascades.depth_scales[cascade];
+	var total = 0.0;
+	for (var y = -radius; y <= radius; y++) {
+		for (var x = -radius; x <= radius; x++) {
+			let offset = vec2(f32(x), f32(y)) * lighting_settings.shadowmap_texel_size;
+			let depth = shadowmap_pos.z - length(offset) * slope_bias;
+			total += textureSampleCompareLevel(shadowmap_texture, shadowmap_sampler, shadowmap_pos.xy + offset, cascade, depth);
+		}
+	}
+	let sample_count = f32((radius * 2 + 1) * (radius * 2 + 1));
+	return total / sample_count;
+}
+
+fn get_geometry_normal(in: VertexOutput) -> vec3f {
+	// the derivatives of the world position lie on the triangle's plane, so their cross product is the face normal (dpdy is first because screen-space y goes down)
+	return normalize(cross(dpdy(in.world_pos), dpdx(in.world_pos)));
+}
+
+// `normal_map_sample` is the tangent-space normal, meshes without a normal map use a flat one so this gives back the vertex normal
+fn get_normal(in: VertexOutput, normal_map_sample: vec3f) -> vec3f {
+	if (lighting_settings.flat_shading != 0u) {
+		return get_geometry_normal(in);
+	}
+	let vertex_normal = normalize(in.normal);
+	// the interpolated tangent isn't perpendicular to the interpolated normal anymore, so it's re-orthogonalized
+	let tangent = in.tangent.xyz - vertex_normal * dot(vertex_normal, in.tangent.xyz);
+	if (dot(tangent, tangent) < 0.000001) {
+		return vertex_normal;
+	}
+	let tangent_dir = normalize(tangent);
+	let bitangent_dir = cross(vertex_normal, tangent_dir) * in.tangent.w;
+	let tbn = mat3x3(tangent_dir, bitangent_dir, vertex_normal);
+	return normalize(tbn * normal_map_sample);
+}
+
+// the direction towards the light
+fn get_shadow_caster_dir(world_pos: vec3f) -> vec3f {
+	if (lighting_settings.shadow_caster_kind == 1u) {
+		return normalize(lighting_settings.spot_pos - world_pos);
+	}
+	return -directional_light.direction;
+}
+
+// 1 for the directional shadow caster, for the spot this fades out at the edge of the cone and at the end of its range
+fn get_shadow_caster_falloff(world_pos: vec3f) -> f32 {
+	if (lighting_settings.shadow_caster_kind != 1u) {
+		return 1.0;
+	}
+	let offset = world_pos - lighting_settings.spot_pos;
+	let distance = length(offset);
+	let cos_angle = dot(offset / max(distance, 0.0001), lighting_settings.spot_dir);
+	let cone = smoothstep(lighting_settings.spot_cos_outer, lighting_settings.spot_cos_inner, cos_angle);
+	let range = clamp(1.0 - distance / lighting_settings.spot_range, 0.0, 1.0);
+	return cone * range * range;
+}
+
+fn get_lights_contribution(world_pos: vec3f, normal: vec3f) -> vec3f {
+	var output = vec3(0.0);
+	for (var i = 0u; i < lighting_settings.light_count; i++) {
+		let light = lights[i];
+		var light_dir: vec3f;
+		var attenuation = 1.0;
+		if (light.kind == 0u) {
+			let offset = light.pos - world_pos;
+			let distance = length(offset);
+			light_dir = offset / max(distance, 0.0001);
+			// smooth falloff that reaches exactly 0 at the light's range
+			let falloff = clamp(1.0 - distance / light.range, 0.0, 1.0);
+			attenuation = falloff * falloff;
+		} else {
+			light_dir = -light.direction;
+		}
+		output += light.color * light.intensity * attenuation * max(dot(normal, light_dir), 0.0);
+	}
+	return output;
+}
+
+// HELP: the materials don't have their own roughness or metalness yet, so every surface is treated as a fairly rough dielectric
+const IBL_ROUGHNESS: f32 = 0.6;
+const IBL_BASE_REFLECTIVITY: vec3f = vec3(0.04);
+
+// returns the diffuse light (which is multiplied by the albedo) and the specular light (which isn't)
+// NOTE: explicit mip levels are used since this runs after branches, where implicit derivatives aren't allowed
+fn get_ibl_light(world_pos: vec3f, normal: vec3f) -> array<vec3f, 2> {
+	// the camera position is the view matrix's translation undone by its rotation
+	let view_rot = mat3x3(camera_data.view_mat[0].xyz, camera_data.view_mat[1].xyz, camera_data.view_mat[2].xyz);
+	let camera_pos = -(transpose(view_rot) * camera_data.view_mat[3].xyz);
+	let view_dir = normalize(camera_pos - world_pos);
+	let n_dot_v = max(dot(normal, view_dir), 0.0);
+	
+	// the environment maps are rotated along with the skybox (rotating the sampled directions by the opposite angle)
+	let sin_rot = sin(lighting_settings.environment_rotation);
+	let cos_rot = cos(lighting_settings.environment_rotation);
+	let environment_rot = mat3x3(cos_rot, 0.0, sin_rot, 0.0, 1.0, 0.0, -sin_rot, 0.0, cos_rot);
+	
+	let fresnel = IBL_BASE_REFLECTIVITY + (max(vec3(1.0 - IBL_ROUGHNESS), IBL_BASE_REFLECTIVITY) - IBL_BASE_REFLECTIVITY) * pow(1.0 - n_dot_v, 5.0);
+	let irradiance = textureSampleLevel(ibl_irradiance_texture, ibl_sampler, environment_rot * normal, 0.0).rgb;
+	let diffuse = (1.0 - fresnel) * irradiance;
+	
+	let reflect_dir = environment_rot * reflect(-view_dir, normal);
+	let max_mip = f32(textureNumLevels(ibl_prefiltered_texture) - 1u);
+	let prefiltered = textureSampleLevel(ibl_prefiltered_texture, ibl_sampler, reflect_dir, IBL_ROUGHNESS * max_mip).rgb;
+	let brdf = textureSampleLevel(ibl_brdf_lut, ibl_sampler, vec2(n_dot_v, IBL_ROUGHNESS), 0.0).rg;
+	let specular = prefiltered * (fresnel * brdf.x + brdf.y);
+	
+	return array(diffuse, specular);
+}
+
+// the models pass doesn't use blending (which would need sorting), so instances are faded out using an ordered dither pattern instead
+fn get_dither_threshold(screen_pos: vec2f) -> f32 {
+	var bayer_matrix = array<f32, 16>(
+		0.0,  8.0,  2.0,  10.0,
+		12.0, 4.0,  14.0, 6.0,
+		3.0,  11.0, 1.0,  9.0,
+		15.0, 7.0,  13.0, 5.0,
+	);
+	let x = u32(screen_pos.x) % 4u;
+	let y = u32(screen_pos.y) % 4u;
+	return (bayer_matrix[y * 4u + x] + 0.5) / 16.0;
 }
 
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4f {
+	if (in.fade < get_dither_threshold(in.screen_pos.xy)) {
+		discard;
+	}
+	
+	// derivatives have to be taken in uniform control flow, so this is calculated before any branches
+	let geometry_normal = get_geometry_normal(in);
 	let color = textureSample(material_texture, material_sampler, in.texcoords);
+	let normal_map_sample = textureSample(normal_map_texture, material_sampler, in.texcoords).xyz * 2.0 - 1.0;
+	let normal = get_normal(in, normal_map_sample);
+	if (color.a < material_settings.alpha_cutoff) {
+		discard;
+	}
+	
+	// world-space normals, remapped from -1..1 to 0..1
+	if (lighting_settings.debug_view == 1u) {
+		return vec4(geometry_normal * 0.5 + 0.5, 1.0);
+	}
+	if (lighting_settings.debug_view == 2u) {
+		return vec4(normal * 0.5 + 0.5, 1.0);
+	}
+	
 	var color_rgb = color.rgb;
 	let color_a = color.a;
 	
-	let ambient_light = vec3(0.9, 0.9, 1.0) * 0.5;
-	let shadowcaster_light = vec3(1.0, 0.9, 0.7) * sample_shadows(in.world_pos);
-	color_rgb *= ambient_light + shadowcaster_light;
+	let diffuse = max(dot(normal, get_shadow_caster_dir(in.world_pos)), 0.0) * get_shadow_caster_falloff(in.world_pos);
+	
+	let ibl_light = get_ibl_light(in.world_pos, normal);
+	let shadowcaster_light = directional_light.color * directional_light.intensity * sample_shadows(in.world_pos, in.view_depth) * diffuse;
+	color_rgb *= ibl_light[0] + shadowcaster_light + get_lights_contribution(in.world_pos, normal);
+	color_rgb += ibl_light[1];
 	
 	return vec4(color_rgb, color.a);
 }
@@ -0,0 +1,39 @@
+@group(0) @binding(0) var<uniform> camera_data: CameraData;
+
+struct CameraData {
+	proj_view_mat: mat4x4f,
+	inv_proj_mat: mat4x4f,
+	view_mat: mat4x4f,
+	resolution: vec2f,
+	inv_resolution: vec2f,
+}
+
+struct BoxInput {
+	@location(0) min_pos: vec3f,
+	@location(1) max_pos: vec3f,
+}
+
+
+
+// HELP: there's no fragment shader, the occlusion query only counts how many samples pass the depth test
+@vertex
+fn vs_main(
+	@builtin(vertex_index) index: u32,
+	occlusion_box: BoxInput,
+) -> @builtin(position) vec4f {
+	
+	// two triangles per face, the bits of each corner pick max (1) or min (0) for x, y, and z
+	var corners = array<u32, 36>(
+		0u, 2u, 6u, 0u, 6u, 4u, // -x
+		1u, 5u, 7u, 1u, 7u, 3u, // +x
+		0u, 4u, 5u, 0u, 5u, 1u, // -y
+		2u, 3u, 7u, 2u, 7u, 6u, // +y
+		0u, 1u, 3u, 0u, 3u, 2u, // -z
+		4u, 6u, 7u, 4u, 7u, 5u, // +z
+	);
+	let corner = corners[index];
+	let use_max = vec3(corner & 1u, corner & 2u, corner & 4u) != vec3(0u);
+	let pos = select(occlusion_box.min_pos, occlusion_box.max_pos, use_max);
+	
+	return camera_data.proj_view_mat * vec4(pos, 1.0);
+}
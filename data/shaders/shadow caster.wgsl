@@ -27,6 +27,7 @@ fn vs_main(
 	);
 	
 	var out = proj_mat * instance_mat * vec4(vertex_basic.pos, 1.0);
-	out.z = out.z * 0.5 + 0.5;
+	// same as `z * 0.5 + 0.5` after the perspective divide, this way it also works for the spot shadow caster's perspective matrix
+	out.z = (out.z + out.w) * 0.5;
 	return out;
 }
@@ -0,0 +1,89 @@
+@group(0) @binding(0) var<uniform> camera_data: CameraData;
+@group(0) @binding(1) var<uniform> shadow_cascades: ShadowCascades;
+@group(0) @binding(2) var shadowmap_texture: texture_depth_2d_array;
+@group(0) @binding(3) var shadowmap_sampler: sampler_comparison;
+@group(0) @binding(4) var<uniform> shadow_catcher_settings: ShadowCatcherSettings;
+
+struct CameraData {
+	proj_view_mat: mat4x4f,
+	inv_proj_mat: mat4x4f,
+	view_mat: mat4x4f,
+	resolution: vec2f,
+	inv_resolution: vec2f,
+}
+
+struct ShadowCascades {
+	proj_mats: array<mat4x4f, 4>,
+	split_depths: vec4f, // the view-space depth where each cascade ends
+	depth_scales: vec4f,
+	count: u32,
+}
+
+struct ShadowCatcherSettings {
+	center: vec2f, // follows the camera on the xz plane
+	height: f32,
+	size: f32,
+	opacity: f32,
+}
+
+
+
+@vertex
+fn vs_main(
+	@builtin(vertex_index) index: u32
+) -> VertexOutput {
+	var quad_corners = array<vec2f, 6>(
+		vec2(-0.5, -0.5),
+		vec2( 0.5, -0.5),
+		vec2( 0.5,  0.5),
+		vec2(-0.5, -0.5),
+		vec2( 0.5,  0.5),
+		vec2(-0.5,  0.5),
+	);
+	let corner = quad_corners[index] * shadow_catcher_settings.size + shadow_catcher_settings.center;
+	let world_pos = vec3(corner.x, shadow_catcher_settings.height, corner.y);
+	
+	var out: VertexOutput;
+	out.screen_pos = camera_data.proj_view_mat * vec4(world_pos, 1.0);
+	out.world_pos = world_pos;
+	out.view_depth = -(camera_data.view_mat * vec4(world_pos, 1.0)).z;
+	return out;
+}
+
+
+
+struct VertexOutput {
+	@builtin(position) screen_pos: vec4f,
+	@location(0) world_pos: vec3f,
+	@location(1) view_depth: f32,
+}
+
+
+
+fn get_shadow_cascade(view_depth: f32) -> u32 {
+	for (var i = 0u; i + 1u < shadow_cascades.count; i++) {
+		if (view_depth < shadow_cascades.split_depths[i]) {
+			return i;
+		}
+	}
+	return shadow_cascades.count - 1u;
+}
+
+fn sample_shadows(world_pos: vec3f, view_depth: f32) -> f32 {
+	let cascade = get_shadow_cascade(view_depth);
+	var shadowmap_pos = shadow_cascades.proj_mats[cascade] * vec4(world_pos, 1.0);
+	shadowmap_pos = vec4(shadowmap_pos.xyz / shadowmap_pos.w, 1.0);
+	// shadowmap_pos starts in range -1 to 1 with y going up, but we need 0 to 1 with y going down
+	shadowmap_pos = vec4(shadowmap_pos.xyz * vec3(0.5, -0.5, 0.5) + 0.5, 1.0);
+	let shadow = textureSampleCompareLevel(shadowmap_texture, shadowmap_sampler, shadowmap_pos.xy, cascade, shadowmap_pos.z);
+	// anything outside of the shadowmap counts as lit, otherwise the edges of the shadowmap would show up on the plane
+	let is_inside = all(shadowmap_pos.xyz >= vec3(0.0)) && all(shadowmap_pos.xyz <= vec3(1.0));
+	return select(1.0, shadow, is_inside);
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4f {
+	let shadow_amount = 1.0 - sample_shadows(in.world_pos, in.view_depth);
+	// the plane itself is invisible, only the shadows on it are drawn
+	return vec4(0.0, 0.0, 0.0, shadow_amount * shadow_catcher_settings.opacity);
+}
@@ -0,0 +1,73 @@
+@group(0) @binding(0) var shadowmap_texture: texture_2d_array<f32>; // not a depth texture type since the gl backend can't `textureLoad()` from those
+@group(0) @binding(1) var<uniform> lighting_settings: LightingSettings;
+
+// only `shadow_caster_kind` and `spot_range` are used, but the whole struct has to match models.wgsl
+struct LightingSettings {
+	flat_shading: u32,
+	debug_view: u32,
+	light_count: u32,
+	pcf_kernel_size: u32,
+	spot_pos: vec3f,
+	shadow_caster_kind: u32, // 0: directional, 1: spot
+	spot_dir: vec3f,
+	spot_cos_outer: f32,
+	spot_cos_inner: f32,
+	spot_range: f32,
+	shadowmap_texel_size: f32,
+	environment_rotation: f32,
+}
+
+const SPOT_NEAR: f32 = 0.1; // same as `ShadowCasterData::SPOT_NEAR`
+
+
+
+// HELP: the viewport is set to the corner that the cascade is shown in, and the instance index is the cascade
+@vertex
+fn vs_main(
+	@builtin(vertex_index) index: u32,
+	@builtin(instance_index) cascade: u32,
+) -> VertexOutput {
+	var output: VertexOutput;
+	
+	// hacky way to draw a single large triangle that convers the entire viewport
+	output.screen_pos = vec4(
+		f32(i32(index) / 2) * 4.0 - 1.0,
+		f32(i32(index) & 1) * 4.0 - 1.0,
+		1.0,
+		1.0,
+	);
+	
+	output.tex_coords = output.screen_pos.xy * vec2(0.5, -0.5) + 0.5;
+	output.cascade = cascade;
+	
+	return output;
+}
+
+
+
+struct VertexOutput {
+	@builtin(position) screen_pos: vec4f,
+	@location(0) tex_coords: vec2f,
+	@location(1) @interpolate(flat) cascade: u32,
+}
+
+
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4f {
+	// NOTE: depth textures can't be filtered, so this just loads the nearest texel
+	let size = textureDimensions(shadowmap_texture);
+	let texel = min(vec2u(in.tex_coords * vec2f(size)), size - 1u);
+	var depth = textureLoad(shadowmap_texture, texel, in.cascade, 0).r;
+	
+	// the spot's depth is perspective, so almost everything would be close to white without un-projecting it first (the directional's is already linear)
+	if (lighting_settings.shadow_caster_kind == 1u) {
+		let far = lighting_settings.spot_range;
+		let ndc_depth = depth * 2.0 - 1.0; // the spot uses a gl-style projection, see `ShadowCasterData`
+		let view_depth = 2.0 * SPOT_NEAR * far / (far + SPOT_NEAR - ndc_depth * (far - SPOT_NEAR));
+		depth = (view_depth - SPOT_NEAR) / (far - SPOT_NEAR);
+	}
+	
+	// close to the light is white and far from the light (or empty) is black
+	return vec4(vec3(1.0 - depth), 1.0);
+}
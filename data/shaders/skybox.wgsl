@@ -4,6 +4,8 @@ struct CameraData {
 	proj_view_mat: mat4x4f,
 	inv_proj_mat: mat4x4f,
 	view_mat: mat4x4f,
+	resolution: vec2f,
+	inv_resolution: vec2f,
 }
 
 
@@ -41,10 +43,23 @@ struct VertexOutput {
 
 @group(0) @binding(1) var skybox_texture: texture_cube<f32>;
 @group(0) @binding(2) var skybox_sampler: sampler;
+@group(0) @binding(3) var secondary_skybox_texture: texture_cube<f32>;
+@group(0) @binding(4) var<uniform> skybox_settings: SkyboxSettings;
+
+struct SkyboxSettings {
+	blend: f32,
+	rotation: f32, // yaw, in radians
+}
 
 
 
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4f {
-	return textureSample(skybox_texture, skybox_sampler, in.texcoords);
+	// rotating the skybox by some angle is the same as rotating the sampled direction by the opposite angle
+	let sin_rot = sin(skybox_settings.rotation);
+	let cos_rot = cos(skybox_settings.rotation);
+	let dir = vec3(in.texcoords.x * cos_rot - in.texcoords.z * sin_rot, in.texcoords.y, in.texcoords.x * sin_rot + in.texcoords.z * cos_rot);
+	let main_color = textureSample(skybox_texture, skybox_sampler, dir);
+	let secondary_color = textureSample(secondary_skybox_texture, skybox_sampler, dir);
+	return mix(main_color, secondary_color, skybox_settings.blend);
 }
@@ -0,0 +1,70 @@
+@group(0) @binding(0) var<uniform> text_settings: TextSettings;
+
+struct TextSettings {
+	screen_size: vec2f,
+	cell_size: f32,
+	encode_srgb: u32, // 1 when the surface isn't srgb, see `RenderContextData::manual_srgb_encode`
+}
+
+// same as `TextGlyphData::WIDTH` and `TextGlyphData::HEIGHT`
+const GLYPH_WIDTH: u32 = 3u;
+const GLYPH_HEIGHT: u32 = 5u;
+
+
+
+struct GlyphInput {
+	@location(0) pos: vec2f,
+	@location(1) color: vec3f,
+	@location(2) bits: u32,
+}
+
+// HELP: every glyph is an instance, and the six vertices of its quad are made from the vertex index
+@vertex
+fn vs_main(
+	@builtin(vertex_index) index: u32,
+	glyph: GlyphInput,
+) -> VertexOutput {
+	var output: VertexOutput;
+	
+	var corners = array<vec2f, 6>(vec2f(0.0, 0.0), vec2f(1.0, 0.0), vec2f(0.0, 1.0), vec2f(0.0, 1.0), vec2f(1.0, 0.0), vec2f(1.0, 1.0));
+	let glyph_size = vec2f(f32(GLYPH_WIDTH), f32(GLYPH_HEIGHT));
+	let font_pos = corners[index] * glyph_size;
+	let pixel_pos = glyph.pos + font_pos * text_settings.cell_size;
+	output.screen_pos = vec4(pixel_pos / text_settings.screen_size * vec2(2.0, -2.0) + vec2(-1.0, 1.0), 0.0, 1.0);
+	
+	output.font_pos = font_pos;
+	output.color = glyph.color;
+	output.bits = glyph.bits;
+	
+	return output;
+}
+
+
+
+struct VertexOutput {
+	@builtin(position) screen_pos: vec4f,
+	@location(0) font_pos: vec2f,
+	@location(1) color: vec3f,
+	@location(2) @interpolate(flat) bits: u32,
+}
+
+
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4f {
+	// see `TextGlyphData::get_bits()` for how the pixels are stored
+	let cell = min(vec2u(in.font_pos), vec2u(GLYPH_WIDTH - 1u, GLYPH_HEIGHT - 1u));
+	let bit = cell.y * GLYPH_WIDTH + (GLYPH_WIDTH - 1u - cell.x);
+	if (((in.bits >> bit) & 1u) == 0u) {discard;}
+	if (text_settings.encode_srgb == 1u) {
+		return vec4(linear_to_srgb(in.color), 1.0);
+	}
+	return vec4(in.color, 1.0);
+}
+
+// same as `utils::linear_to_srgb()`
+fn linear_to_srgb(color: vec3f) -> vec3f {
+	let low = color * 12.92;
+	let high = 1.055 * pow(color, vec3(1.0 / 2.4)) - 0.055;
+	return select(high, low, color <= vec3(0.0031308));
+}
@@ -0,0 +1,154 @@
+use crate::prelude::*;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// HELP: The purpose of this is to read rendered textures back to the cpu so they can be saved to disk
+
+
+
+pub fn save_texture_to_png(texture: &wgpu::Texture, path: impl AsRef<Path>, render_context: &RenderContextData) -> Result<()> {
+	let path = path.as_ref();
+	let image = read_texture_to_image(texture, render_context).context("Failed to read texture from gpu.")?;
+	image.save_with_format(path, image::ImageFormat::Png).with_context(|| format!("Failed to save image to {path:?}."))?;
+	Ok(())
+}
+
+
+
+// saves to 'screenshots/screenshot_<unix time in ms>.png' (in the same folder as the engine config), and returns the path that was used
+pub fn capture_screenshot(surface_texture: &wgpu::Texture, render_context: &RenderContextData) -> Result<PathBuf> {
+	let screenshots_folder = utils::get_program_file_path("screenshots");
+	fs::create_dir_all(&screenshots_folder).add_path_to_error(&screenshots_folder)?;
+	let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis();
+	let path = screenshots_folder.join(format!("screenshot_{timestamp}.png"));
+	save_texture_to_png(surface_texture, &path, render_context)?;
+	Ok(path)
+}
+
+
+
+// renders the next frame into the headless texture and reads it back, this only works with `load::load_headless_program_data()`
+pub fn render_headless_frame(program_data: &mut ProgramData) -> Result<image::RgbaImage> {
+	// the texture is taken out while rendering since `render()` needs the rest of the program data
+	let headless_texture = program_data.render_context.headless_texture.take().context("Cannot render headless frame without a headless texture.")?;
+	render::render(&headless_texture, program_data);
+	let output = read_texture_to_image(&headless_texture, &program_data.render_context);
+	program_data.render_context.headless_texture = Some(headless_texture);
+	output
+}
+
+
+
+// HELP: this is what `--capture <path>` runs, it renders a single frame without creating a window (so it works on
+// machines without a display, like ci runners) and saves it as a png
+pub fn capture_headless_frame(start_time: Instant, engine_config: EngineConfig, path: impl AsRef<Path>) -> Result<()> {
+	let path = path.as_ref();
+	let mut program_data = load::load_headless_program_data(start_time, engine_config).context("Failed to load headless program data.")?;
+	let image = render_headless_frame(&mut program_data).context("Failed to render headless frame.")?;
+	image.save_with_format(path, image::ImageFormat::Png).with_context(|| format!("Failed to save image to {path:?}."))?;
+	Ok(())
+}
+
+
+
+// NOTE: for sRGB textures, the bytes in the texture are already gamma-encoded, so they can be saved to a png as-is
+pub fn read_texture_to_image(texture: &wgpu::Texture, render_context: &RenderContextData) -> Result<image::RgbaImage> {
+	let format = texture.format();
+	let swap_red_and_blue = match format {
+		wgpu::TextureFormat::Rgba8Unorm | wgpu::TextureFormat::Rgba8UnormSrgb => false,
+		wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb => true,
+		_ => return Err(Error::msg(format!("Cannot read textures with format {format:?}, only 8-bit rgba and bgra textures are supported."))),
+	};
+	if !texture.usage().contains(wgpu::TextureUsages::COPY_SRC) {
+		return Err(Error::msg("Cannot read texture because it wasn't created with `TextureUsages::COPY_SRC`."));
+	}
+	let width = texture.width();
+	let height = texture.height();
+	
+	// HELP: wgpu requires each row in the buffer to be aligned to 256 bytes, so the padding has to be removed afterwards
+	let unpadded_bytes_per_row = width * 4;
+	let padded_bytes_per_row = unpadded_bytes_per_row.next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+	let buffer = render_context.device.create_buffer(&wgpu::BufferDescriptor {
+		label: Some("texture_readback_buffer"),
+		size: padded_bytes_per_row as u64 * height as u64,
+		usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+		mapped_at_creation: false,
+	});
+	
+	let mut encoder = render_context.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {label: Some("texture_readback_encoder")});
+	encoder.copy_texture_to_buffer(
+		wgpu::ImageCopyTexture {
+			texture,
+			mip_level: 0,
+			origin: wgpu::Origin3d::ZERO,
+			aspect: wgpu::TextureAspect::All,
+		},
+		wgpu::ImageCopyBuffer {
+			buffer: &buffer,
+			layout: wgpu::ImageDataLayout {
+				offset: 0,
+				bytes_per_row: Some(padded_bytes_per_row),
+				rows_per_image: Some(height),
+			},
+		},
+		wgpu::Extent3d {
+			width,
+			height,
+			depth_or_array_layers: 1,
+		},
+	);
+	render_context.command_queue.submit(std::iter::once(encoder.finish()));
+	
+	let buffer_slice = buffer.slice(..);
+	let (sender, receiver) = std::sync::mpsc::channel();
+	buffer_slice.map_async(wgpu::MapMode::Read, move |result| {
+		let _ = sender.send(result);
+	});
+	render_context.device.poll(wgpu::Maintain::Wait);
+	receiver.recv().context("Failed to receive buffer mapping result.")?.context("Failed to map readback buffer.")?;
+	
+	let mut pixels = Vec::with_capacity((unpadded_bytes_per_row * height) as usize);
+	{
+		let padded_data = buffer_slice.get_mapped_range();
+		for row in padded_data.chunks_exact(padded_bytes_per_row as usize) {
+			pixels.extend_from_slice(&row[..unpadded_bytes_per_row as usize]);
+		}
+	}
+	buffer.unmap();
+	
+	if swap_red_and_blue {
+		for pixel in pixels.chunks_exact_mut(4) {
+			pixel.swap(0, 2);
+		}
+	}
+	
+	image::RgbaImage::from_raw(width, height, pixels).context("Readback buffer has the wrong size.")
+}
+
+
+
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	#[test]
+	fn capture_saves_a_headless_frame() {
+		let engine_config = EngineConfig {
+			window_width: 96,
+			window_height: 64,
+			compress_textures: false,
+			..EngineConfig::default()
+		};
+		if !load::headless_adapter_is_available(&engine_config) {
+			eprintln!("Skipping test because there's no adapter available.");
+			return;
+		}
+		let path = std::env::temp_dir().join(format!("wgpu_template_capture_{}.png", std::process::id()));
+		capture_headless_frame(Instant::now(), engine_config, &path).expect("Failed to capture headless frame.");
+		let image = image::open(&path).expect("Failed to open captured frame.");
+		let _ = fs::remove_file(&path);
+		assert_eq!((image.width(), image.height()), (96, 64));
+	}
+	
+}
@@ -1,5 +1,6 @@
 use crate::prelude::*;
 use winit::{dpi::PhysicalPosition, keyboard::KeyCode};
+use serde::Deserialize;
 
 
 
@@ -9,14 +10,40 @@ pub struct ProgramData<'a> {
 	pub start_time: Instant,
 	pub engine_config: EngineConfig,
 	pub input: EngineInput,
+	pub shader_watcher: Option<ShaderWatcher>,
+	pub config_watcher: Option<ConfigWatcher>,
+	pub gpu_timer: Option<GpuTimer>, // `None` when the adapter doesn't support timestamp queries
+	pub ui_state: UiState,
 	
 	// app data
 	pub camera_data: CameraData,
 	pub shadow_caster_data: ShadowCasterData,
-	pub example_model_instance_datas: Vec<InstanceData>,
+	pub directional_light: DirectionalLight,
+	pub lights: Vec<LightData>, // starts as `engine_config.lights`, and is re-uploaded every frame so lights can be added, removed, or moved at runtime
+	pub model_instance_datas: Vec<Vec<InstanceData>>, // one list per model in `render_assets.models`, so moving one model's instances doesn't touch the others
+	pub model_instance_lods: Vec<Vec<u8>>, // the lod of each instance in `model_instance_datas`, set by `update::update_instance_lods()` (empty for models without lods)
 	pub fps_counter: FpsCounter,
+	pub frame_pacer: FramePacer,
+	pub fatal_error: Option<FatalError>, // when this is set, the error is drawn instead of the scene until it's time to exit
 	pub is_moving_camera: bool,
+	pub screenshot_requested: bool, // the screenshot is taken right after the next frame is rendered
+	pub wireframe: bool, // only has an effect when `render_layouts.models_wireframe_pipeline` exists
+	pub render_skybox: bool, // only has an effect when the background mode is 'skybox', the models pass's clear color is shown instead when this is false
+	pub render_targets_resize_time: Option<Instant>, // when the size-dependent textures are waiting to be recreated, this is the time of the last resize (see `resize_render_targets()`)
+	pub show_shadowmap_debug: bool, // draws each shadow cascade's depth in the bottom-left corner
+	pub show_debug_grid: bool, // starts as `debug_grid_enabled`, toggled with 'g'
+	pub show_bounding_boxes: bool, // starts as `bounding_boxes_enabled`, toggled with 'x'
+	pub debug_lines: Vec<DebugLineVertexData>, // two vertices per line, filled with `push_debug_line()` during `update()` and cleared after each render
+	pub show_stats_text: bool, // starts as `stats_text_enabled`, toggled with f3
+	pub stats_text: Vec<String>, // the lines drawn in the top-left corner, rebuilt every frame by `update::update_stats_text()`
 	pub color_correction_settings: ColorCorrectionSettings,
+	pub frame_exporter: Option<FrameExporter>,
+	pub lighting_settings: LightingSettings,
+	pub skybox_settings: SkyboxSettings,
+	pub foliage_instance_datas: Vec<FoliageInstanceData>, // empty when `foliage_enabled` is false
+	pub total_time: f32, // advanced by `update::fixed_update()`, so it also follows the fixed dt when exporting frames
+	pub fixed_update_accumulator: f32, // the time (in seconds) that `update::fixed_update()` hasn't caught up on yet
+	pub fixed_update_alpha: f32, // how far this frame is between the last fixed update and the next one (0 to 1), used to interpolate between fixed updates when rendering
 	
 	// render data
 	pub render_context: RenderContextData<'a>,
@@ -27,30 +54,290 @@ pub struct ProgramData<'a> {
 	pub render_layouts: RenderLayouts,
 	pub render_assets: RenderAssets,
 	pub render_bindings: RenderBindings,
+	pub render_passes: Vec<Box<dyn render::RenderPass>>, // recorded in this order every frame, see `render::RenderPass`
 	pub frame_start_instant: Instant,
 	
 }
 
 impl<'a> ProgramData<'a> {
+	// HELP: after a long stall (like dragging the window on windows), the fixed updates would take longer than the time they're
+	// catching up on, which would make every frame after it slower and slower. Anything past this many updates is dropped instead
+	pub const MAX_FIXED_UPDATES_PER_FRAME: u32 = 8;
 	pub fn step_dt(&mut self) -> f32 {
 		let new_frame_instant = Instant::now();
 		let dt = (new_frame_instant - self.frame_start_instant).as_secs_f32();
 		self.frame_start_instant = new_frame_instant;
 		dt
 	}
+	pub fn get_fixed_dt(&self) -> f32 {
+		1.0 / self.engine_config.fixed_update_hz
+	}
+	// HELP: instances should only be changed through this after loading, so that their part of `total_instances_buffer` gets re-uploaded
+	pub fn set_model_instance(&mut self, model_index: usize, instance_index: usize, instance_data: InstanceData) {
+		self.model_instance_datas[model_index][instance_index] = instance_data;
+		self.render_assets.models[model_index].mark_instance_dirty(instance_index);
+	}
+	// `total_time` only changes with the fixed updates, so this is used for rendering to keep animations smooth at any frame rate
+	pub fn get_interpolated_time(&self) -> f32 {
+		self.total_time + self.fixed_update_alpha * self.get_fixed_dt()
+	}
+	// the line is only drawn for the current frame, so anything that should stay on screen has to be pushed every frame
+	pub fn push_debug_line(&mut self, a: glam::Vec3, b: glam::Vec3, color: [f32; 3]) {
+		self.debug_lines.push(DebugLineVertexData { pos: a.to_array(), color });
+		self.debug_lines.push(DebugLineVertexData { pos: b.to_array(), color });
+	}
 }
 
 
 
 
 
+// HELP: the entries in 'engine config.hjson' have the same names as these fields (unless they're renamed), and any
+// missing or invalid entries use the values from `EngineConfig::default()` (see `load::engine_config_from_map()`)
+#[derive(Deserialize, Debug)]
+#[serde(default)]
 pub struct EngineConfig {
+	#[serde(deserialize_with = "load::deserialize_rendering_backend")]
 	pub rendering_backend: wgpu::Backends,
+	#[serde(deserialize_with = "load::deserialize_present_mode")]
 	pub present_mode: wgpu::PresentMode,
 	pub desired_frame_latency: u32,
-	pub min_frame_time: Duration,
+	#[serde(deserialize_with = "load::deserialize_seconds")]
+	pub min_frame_time: Duration, // replaced by `max_fps` when it's set, zero means uncapped
+	pub max_fps: Option<f64>, // converted to `min_frame_time` by `load::fix_engine_config_values()`, 0 means uncapped
 	pub shadowmap_size: u32,
 	pub compress_textures: bool,
+	pub flat_shading: bool,
+	#[serde(deserialize_with = "load::deserialize_optional_program_path")]
+	pub secondary_skybox_path: Option<PathBuf>,
+	pub skybox_blend: f32, // only the starting value, see `SkyboxSettings::blend`
+	pub skybox_blend_speed: f32, // per second, 0 means the blend only changes from the debug panel
+	pub models_depth_bias_constant: i32,
+	pub models_depth_bias_slope: f32,
+	pub double_buffer_instances: bool,
+	pub anisotropy: u16,
+	#[serde(deserialize_with = "load::deserialize_background_mode")]
+	pub background_mode: BackgroundMode,
+	pub gradient_background_top_color: [f32; 3],
+	pub gradient_background_bottom_color: [f32; 3],
+	pub max_draw_distance: f32, // 0 means there's no limit
+	pub draw_distance_fade_width: f32,
+	pub export_fps: f32,
+	#[serde(deserialize_with = "load::deserialize_camera_keyframes")]
+	pub export_camera_path: Vec<CameraKeyframe>,
+	pub indexed_draws: bool,
+	pub indirect_draws: bool, // the opaque meshes are drawn with `draw_indexed_indirect()` (using `ModelsRenderData::indirect_args_buffer`), only used when the adapter supports indirect execution
+	pub occlusion_culling: bool, // see `OcclusionRenderData`, models that use `gpu_culling` aren't affected
+	pub gpu_culling: bool, // models without transparent meshes are culled by a compute shader instead of on the cpu (see `ComputeRenderData`), this turns on `indirect_draws` too
+	pub shadow_depth_clamp: bool, // only used when the adapter supports `DEPTH_CLIP_CONTROL`
+	pub texture_loading_threads: usize, // 0 means one thread per cpu core
+	pub clear_depth: f32, // used by both the shadow caster pass and the models pass
+	pub foliage_enabled: bool,
+	pub wind_strength: f32,
+	pub wind_speed: f32,
+	pub max_shadow_instances: usize, // 0 means there's no limit
+	#[serde(deserialize_with = "load::deserialize_clear_color")]
+	pub clear_color: [f32; 4], // rgb is always linear (the config value is converted when `clear_color_is_srgb` is true), alpha is never converted
+	pub clear_color_is_srgb: bool, // already applied to `clear_color`
+	#[serde(deserialize_with = "load::deserialize_lights")]
+	pub lights: Vec<LightData>,
+	#[serde(deserialize_with = "load::deserialize_shadow_caster_kind")]
+	pub shadow_caster_kind: ShadowCasterKind,
+	#[serde(rename = "spotlight_fov", deserialize_with = "load::deserialize_degrees")]
+	pub spotlight_fov_radians: f32,
+	pub spotlight_range: f32,
+	#[serde(deserialize_with = "load::deserialize_surface_format")]
+	pub surface_format: Option<wgpu::TextureFormat>, // `None` means the first srgb format that the surface supports (non-srgb formats still get srgb encoded, see `load::get_surface_view_format()`)
+	pub fps_smoothing: f32,
+	pub shadow_catcher_enabled: bool,
+	pub shadow_catcher_height: f32,
+	pub shadow_catcher_size: f32,
+	pub shadow_catcher_opacity: f32,
+	pub camera_move_speed: f32,
+	pub camera_look_sensitivity: f32,
+	#[serde(deserialize_with = "load::deserialize_camera_projection")]
+	pub camera_projection: CameraProjectionKind, // see `get_camera_projection()`
+	#[serde(rename = "camera_fov", deserialize_with = "load::deserialize_degrees")]
+	pub camera_fov_radians: f32, // only used by the perspective projection
+	pub camera_ortho_height: f32, // only used by the orthographic projection
+	pub model_paths: Vec<PathBuf>, // relative to the data folder, '.gltf' and '.glb' files are loaded as gltf and everything else is loaded as obj
+	pub lod_distances: Vec<f32>, // instances past the nth distance use lod n + 1, see `ModelsRenderData::lods`
+	pub axis_gizmo_enabled: bool,
+	pub axis_gizmo_size: f32,
+	#[serde(deserialize_with = "load::deserialize_axis_gizmo_mode")]
+	pub axis_gizmo_mode: DebugDrawMode,
+	pub debug_grid_enabled: bool,
+	pub debug_grid_spacing: f32,
+	pub debug_grid_size: f32,
+	pub bounding_boxes_enabled: bool,
+	#[serde(deserialize_with = "load::deserialize_bounding_boxes_mode")]
+	pub bounding_boxes_mode: BoundingBoxesMode,
+	pub stats_text_enabled: bool,
+	pub stats_text_scale: u32, // how many pixels wide each pixel of the font is
+	pub shadow_pcf_kernel_size: u32, // 1 (hard shadows), 3, or 5
+	pub shadow_cascade_count: usize, // 1 to `ShadowCasterData::MAX_CASCADES`, 1 means the shadowmap covers a fixed area around the camera
+	pub shadow_cascade_lambda: f32,
+	pub msaa_samples: u32, // 1 (disabled), 2, 4, or 8
+	pub exposure: f32,
+	#[serde(deserialize_with = "load::deserialize_tonemapper")]
+	pub tonemapper: u32, // one of `ColorCorrectionSettings::TONEMAPPER_*`
+	pub bloom_threshold: f32,
+	pub bloom_strength: f32,
+	#[serde(deserialize_with = "load::deserialize_vec3")]
+	pub directional_light_direction: glam::Vec3, // normalized, never zero
+	pub directional_light_color: [f32; 3],
+	pub directional_light_intensity: f32,
+	pub hot_reload_shaders: bool,
+	pub hot_reload_config: bool,
+	pub fullscreen: bool, // only the startup mode, f11 toggles it at runtime
+	pub window_width: u32, // the initial inner size, in physical pixels
+	pub window_height: u32,
+	pub window_title: String,
+	#[serde(deserialize_with = "load::deserialize_program_path")]
+	pub skybox_path: PathBuf, // either a '.hdr' / 2:1 equirectangular image, 6 square faces in one image (vertical / horizontal strip or cross), or a folder of 6 separate faces
+	pub skybox_rotation_speed: f32, // in radians per second, 0 means the skybox only rotates with ',' and '.'
+	pub render_skybox: bool, // only the startup value, 'o' toggles it at runtime
+	#[serde(deserialize_with = "load::deserialize_power_preference")]
+	pub power_preference: wgpu::PowerPreference,
+	#[serde(deserialize_with = "load::deserialize_optional_string")]
+	pub adapter_name: Option<String>, // only adapters whose name contains this (ignoring case) are picked, `None` means any adapter
+	pub fixed_update_hz: f32, // how many times per second `update::fixed_update()` runs, no matter the frame rate
+	#[serde(deserialize_with = "load::deserialize_frame_limiter")]
+	pub frame_limiter: FrameLimiter, // how `min_frame_time` is waited on
+}
+
+impl Default for EngineConfig {
+	fn default() -> Self {
+		Self {
+			rendering_backend: wgpu::Backends::all(),
+			present_mode: wgpu::PresentMode::AutoVsync,
+			desired_frame_latency: 1,
+			min_frame_time: Duration::from_secs_f64(0.002),
+			max_fps: None,
+			shadowmap_size: 512,
+			compress_textures: true,
+			flat_shading: false,
+			secondary_skybox_path: None,
+			skybox_blend: 0.0,
+			skybox_blend_speed: 0.0,
+			models_depth_bias_constant: 0,
+			models_depth_bias_slope: 0.0,
+			double_buffer_instances: false,
+			anisotropy: 1,
+			background_mode: BackgroundMode::Skybox,
+			gradient_background_top_color: [0.3, 0.5, 0.9],
+			gradient_background_bottom_color: [0.8, 0.85, 0.9],
+			max_draw_distance: 0.0,
+			draw_distance_fade_width: 20.0,
+			export_fps: 60.0,
+			export_camera_path: vec!(),
+			indexed_draws: true,
+			indirect_draws: false,
+			occlusion_culling: false,
+			gpu_culling: false,
+			shadow_depth_clamp: false,
+			texture_loading_threads: 0,
+			clear_depth: 1.0,
+			foliage_enabled: false,
+			wind_strength: 0.3,
+			wind_speed: 1.5,
+			max_shadow_instances: 0,
+			clear_color: [0.35, 0.48, 0.58, 1.0],
+			clear_color_is_srgb: true,
+			lights: vec!(),
+			shadow_caster_kind: ShadowCasterKind::Directional,
+			spotlight_fov_radians: 45f32.to_radians(),
+			spotlight_range: 50.0,
+			surface_format: None,
+			fps_smoothing: 0.1,
+			shadow_catcher_enabled: false,
+			shadow_catcher_height: -2.0,
+			shadow_catcher_size: 200.0,
+			shadow_catcher_opacity: 0.6,
+			camera_move_speed: 30.0,
+			camera_look_sensitivity: 0.005,
+			camera_projection: CameraProjectionKind::Perspective,
+			camera_fov_radians: 70f32.to_radians(),
+			camera_ortho_height: 20.0,
+			model_paths: vec!(PathBuf::from("assets/cube.obj")),
+			lod_distances: vec!(),
+			axis_gizmo_enabled: false,
+			axis_gizmo_size: 1.0,
+			axis_gizmo_mode: DebugDrawMode::Overlay,
+			debug_grid_enabled: false,
+			debug_grid_spacing: 1.0,
+			debug_grid_size: 100.0,
+			bounding_boxes_enabled: false,
+			bounding_boxes_mode: BoundingBoxesMode::Oriented,
+			stats_text_enabled: false,
+			stats_text_scale: 3,
+			shadow_pcf_kernel_size: 3,
+			shadow_cascade_count: 3,
+			shadow_cascade_lambda: 0.75,
+			msaa_samples: 1,
+			exposure: 1.0,
+			tonemapper: ColorCorrectionSettings::TONEMAPPER_ACES,
+			bloom_threshold: 1.0,
+			bloom_strength: 0.05,
+			directional_light_direction: glam::Vec3::new(0.7, -0.27, 0.65).normalize(),
+			directional_light_color: [1.0, 0.9, 0.7],
+			directional_light_intensity: 1.0,
+			hot_reload_shaders: false,
+			hot_reload_config: false,
+			fullscreen: false,
+			window_width: 1280,
+			window_height: 720,
+			window_title: String::from("WGPU Testing"),
+			skybox_path: utils::get_program_file_path("assets/skybox.png"),
+			skybox_rotation_speed: 0.0,
+			render_skybox: true,
+			power_preference: wgpu::PowerPreference::default(),
+			adapter_name: None,
+			fixed_update_hz: 60.0,
+			frame_limiter: FrameLimiter::Hybrid,
+		}
+	}
+}
+
+impl EngineConfig {
+	pub fn get_camera_projection(&self) -> CameraProjection {
+		match self.camera_projection {
+			CameraProjectionKind::Perspective => CameraProjection::Perspective {fov_radians: self.camera_fov_radians},
+			CameraProjectionKind::Orthographic => CameraProjection::Orthographic {height: self.camera_ortho_height},
+		}
+	}
+}
+
+
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BackgroundMode {
+	Skybox,
+	Gradient,
+	Solid, // just uses the models pass's clear color (`clear_color`)
+}
+
+
+
+// the config only picks the kind, the values come from `camera_fov` and `camera_ortho_height`
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CameraProjectionKind {
+	Perspective,
+	Orthographic,
+}
+
+
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DebugDrawMode {
+	DepthTested, // hidden behind the scene like any other geometry
+	Overlay, // always drawn on top of everything
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BoundingBoxesMode {
+	Oriented, // each model's box is rotated along with the instance
+	World, // the axis-aligned box that contains the rotated box, which is bigger but shows the space that the instance could take up
 }
 
 
@@ -60,27 +347,39 @@ pub struct EngineInput {
 	pub prev_pressed_keys: HashSet<KeyCode>,
 	pub mouse_pos: PhysicalPosition<f64>,
 	pub mouse_vel: PhysicalPosition<f64>,
+	pub scroll_delta: f32, // in lines, positive means scrolling up (away from the user)
 	pub capture_cursor: bool,
+	pub cursor_is_locked: bool, // when true, the mouse velocity comes from the raw `DeviceEvent::MouseMotion` deltas instead of re-centering the cursor
 	pub pressed_mouse_buttons: PressedMouseButtons,
 	pub prev_pressed_mouse_buttons: PressedMouseButtons,
+	pub is_focused: bool,
 }
 
 impl EngineInput {
+	// NOTE: the release events for anything held while the window loses focus never arrive, so everything is released here instead
+	pub fn set_focused(&mut self, is_focused: bool) {
+		self.is_focused = is_focused;
+		if !is_focused {
+			self.pressed_keys.clear();
+			self.pressed_mouse_buttons = PressedMouseButtons::default();
+			self.mouse_vel = PhysicalPosition::default();
+			self.scroll_delta = 0.0;
+		}
+	}
 	pub fn key_is_down(&self, key: KeyCode) -> bool {
 		self.pressed_keys.contains(&key)
 	}
 	pub fn key_just_pressed(&self, key: KeyCode) -> bool {
 		self.pressed_keys.contains(&key) && !self.prev_pressed_keys.contains(&key)
 	}
-	pub fn button_just_pressed(&self, button: MouseButton) -> bool {
-		match button {
-			MouseButton::Left    => self.pressed_mouse_buttons.left_is_down    && !self.prev_pressed_mouse_buttons.left_is_down,
-			MouseButton::Right   => self.pressed_mouse_buttons.right_is_down   && !self.prev_pressed_mouse_buttons.right_is_down,
-			MouseButton::Middle  => self.pressed_mouse_buttons.middle_is_down  && !self.prev_pressed_mouse_buttons.middle_is_down,
-			MouseButton::Back    => self.pressed_mouse_buttons.back_is_down    && !self.prev_pressed_mouse_buttons.back_is_down,
-			MouseButton::Forward => self.pressed_mouse_buttons.forward_is_down && !self.prev_pressed_mouse_buttons.forward_is_down,
-			MouseButton::Other (id) => self.pressed_mouse_buttons.others_down.contains(&id) && !self.prev_pressed_mouse_buttons.others_down.contains(&id),
-		}
+	pub fn mouse_is_down(&self, button: MouseButton) -> bool {
+		self.pressed_mouse_buttons.is_down(button)
+	}
+	pub fn mouse_just_pressed(&self, button: MouseButton) -> bool {
+		self.pressed_mouse_buttons.is_down(button) && !self.prev_pressed_mouse_buttons.is_down(button)
+	}
+	pub fn mouse_just_released(&self, button: MouseButton) -> bool {
+		!self.pressed_mouse_buttons.is_down(button) && self.prev_pressed_mouse_buttons.is_down(button)
 	}
 }
 
@@ -96,6 +395,36 @@ pub struct PressedMouseButtons {
 	pub others_down: HashSet<u16>,
 }
 
+impl PressedMouseButtons {
+	pub fn is_down(&self, button: MouseButton) -> bool {
+		match button {
+			MouseButton::Left    => self.left_is_down,
+			MouseButton::Right   => self.right_is_down,
+			MouseButton::Middle  => self.middle_is_down,
+			MouseButton::Back    => self.back_is_down,
+			MouseButton::Forward => self.forward_is_down,
+			MouseButton::Other (id) => self.others_down.contains(&id),
+		}
+	}
+	// used to set the state from `WindowEvent::MouseInput`
+	pub fn set_is_down(&mut self, button: MouseButton, is_down: bool) {
+		match button {
+			MouseButton::Left    => self.left_is_down    = is_down,
+			MouseButton::Right   => self.right_is_down   = is_down,
+			MouseButton::Middle  => self.middle_is_down  = is_down,
+			MouseButton::Back    => self.back_is_down    = is_down,
+			MouseButton::Forward => self.forward_is_down = is_down,
+			MouseButton::Other (id) => {
+				if is_down {
+					self.others_down.insert(id);
+				} else {
+					self.others_down.remove(&id);
+				}
+			}
+		}
+	}
+}
+
 
 
 
@@ -104,12 +433,20 @@ pub struct CameraData {
 	pub pos: glam::Vec3,
 	pub rot_xz: f32,
 	pub rot_y: f32,
-	pub fov_radians: f32,
+	pub projection: CameraProjection,
 	pub near: f32,
 	pub far: f32,
+	pub shake: CameraShake,
+	pub move_speed: f32, // in units per second
+	pub look_sensitivity: f32, // in radians per pixel of mouse movement
 }
 
 impl CameraData {
+	pub const ZOOM_STEP: f32 = 0.9; // scrolling up zooms in
+	pub const MIN_FOV_DEGREES: f32 = 10.0;
+	pub const MAX_FOV_DEGREES: f32 = 120.0;
+	pub const MIN_ORTHO_HEIGHT: f32 = 0.1;
+	pub const MAX_ORTHO_HEIGHT: f32 = 1000.0;
 	// HELP: Ideally you should use some sort of processing cpu-side that accounts for
 	// the fact that `glam` (and similar crates) expect a z-range of -1 to 1 while wgpu
 	// expects a z-range of 0 to 1, but I haven't been able to integrate this matrix with
@@ -121,20 +458,46 @@ impl CameraData {
 		0.0, 0.0, 0.5, 0.5,
 		0.0, 0.0, 0.0, 1.0,
 	]);
-	pub fn build_gpu_data(&self, aspect_ratio: f32) -> [f32; 16 + 16 + 16] {
-		let proj = glam::Mat4::perspective_rh(self.fov_radians, aspect_ratio, self.near, self.far);
-		let target = self.pos + glam::Vec3::new(
-			self.rot_xz.cos() * self.rot_y.cos(),
-			self.rot_y.sin(),
-			self.rot_xz.sin() * self.rot_y.cos(),
+	pub fn build_proj_mat(&self, aspect_ratio: f32) -> glam::Mat4 {
+		self.build_proj_mat_with_range(aspect_ratio, self.near, self.far)
+	}
+	// same as `build_proj_mat()` but with custom clip planes, which is used to get the slices of the view frustum for the shadow cascades
+	pub fn build_proj_mat_with_range(&self, aspect_ratio: f32, near: f32, far: f32) -> glam::Mat4 {
+		debug_assert!(near > 0.0 && far > near, "Invalid camera clip planes (near: {near}, far: {far}), near must be positive and less than far.");
+		match self.projection {
+			CameraProjection::Perspective {fov_radians} => glam::Mat4::perspective_rh(fov_radians, aspect_ratio, near, far),
+			CameraProjection::Orthographic {height} => {
+				let half_height = height * 0.5;
+				let half_width = half_height * aspect_ratio;
+				glam::Mat4::orthographic_rh(-half_width, half_width, -half_height, half_height, near, far)
+			}
+		}
+	}
+	pub fn build_view_mat(&self) -> glam::Mat4 {
+		// the shake is only applied here, so it never affects the camera controls
+		let pos = self.pos + self.shake.pos_offset;
+		let rot_xz = self.rot_xz + self.shake.rot_xz_offset;
+		let rot_y = self.rot_y + self.shake.rot_y_offset;
+		let target = pos + glam::Vec3::new(
+			rot_xz.cos() * rot_y.cos(),
+			rot_y.sin(),
+			rot_xz.sin() * rot_y.cos(),
 		);
-		let view = glam::Mat4::look_at_rh(self.pos, target, glam::Vec3::Y);
+		glam::Mat4::look_at_rh(pos, target, glam::Vec3::Y)
+	}
+	pub fn build_gpu_data(&self, render_context: &RenderContextData) -> [f32; 16 + 16 + 16 + 4] {
+		let proj = self.build_proj_mat(render_context.aspect_ratio);
+		let view = self.build_view_mat();
 		let inv_proj = proj.inverse();
 		let proj_view = proj * view;
-		let mut output = [0f32; 16 + 16 + 16];
+		let resolution = glam::Vec2::new(render_context.surface_size.width as f32, render_context.surface_size.height as f32);
+		let inv_resolution = resolution.recip();
+		let mut output = [0f32; 16 + 16 + 16 + 4];
 		output[..16].copy_from_slice(&proj_view.to_cols_array());
 		output[16..32].copy_from_slice(&inv_proj.to_cols_array());
 		output[32..48].copy_from_slice(&view.to_cols_array());
+		output[48..50].copy_from_slice(&resolution.to_array());
+		output[50..52].copy_from_slice(&inv_resolution.to_array());
 		output
 	}
 	pub fn new(pos: (f32, f32, f32)) -> Self {
@@ -142,90 +505,1015 @@ impl CameraData {
 			pos: pos.into(),
 			rot_xz: 0.0,
 			rot_y: 0.0,
-			fov_radians: 70.0f32.to_radians(),
+			projection: CameraProjection::Perspective {fov_radians: 70.0f32.to_radians()},
 			near: 0.1,
 			far: 500.0,
+			shake: CameraShake::default(),
+			move_speed: 30.0,
+			look_sensitivity: 0.005,
+		}
+	}
+	pub fn get_forward_dir(&self) -> glam::Vec3 {
+		glam::Vec3::new(
+			self.rot_xz.cos() * self.rot_y.cos(),
+			self.rot_y.sin(),
+			self.rot_xz.sin() * self.rot_y.cos(),
+		).normalize()
+	}
+	// WASD moves relative to where the camera is looking, Space / E go up, Shift / Q go down, and holding Alt moves faster
+	pub fn apply_input(&mut self, input: &EngineInput, dt: f32) {
+		let mut speed = self.move_speed * dt;
+		if input.key_is_down(KeyCode::AltLeft) || input.key_is_down(KeyCode::AltRight) {
+			speed *= 5.0;
+		}
+		let forward_dir = self.get_forward_dir();
+		let right_dir = forward_dir.cross(glam::Vec3::Y).normalize();
+		
+		if input.key_is_down(KeyCode::KeyW) {
+			self.pos += forward_dir * speed;
+		}
+		if input.key_is_down(KeyCode::KeyS) {
+			self.pos -= forward_dir * speed;
+		}
+		
+		if input.key_is_down(KeyCode::KeyD) {
+			self.pos += right_dir * speed;
+		}
+		if input.key_is_down(KeyCode::KeyA) {
+			self.pos -= right_dir * speed;
+		}
+		
+		if input.key_is_down(KeyCode::Space) || input.key_is_down(KeyCode::KeyE) {
+			self.pos.y += speed;
+		}
+		if input.key_is_down(KeyCode::ShiftLeft) || input.key_is_down(KeyCode::KeyQ) {
+			self.pos.y -= speed;
+		}
+		
+		let mouse_dt = (
+			input.mouse_vel.x.clamp(-50.0, 50.0) as f32 * self.look_sensitivity,
+			input.mouse_vel.y.clamp(-50.0, 50.0) as f32 * self.look_sensitivity,
+		);
+		self.rot_xz += mouse_dt.0;
+		// stopping just short of straight up / down keeps the view from flipping
+		self.rot_y = (self.rot_y - mouse_dt.1).clamp(-std::f32::consts::FRAC_PI_2 * 0.999, std::f32::consts::FRAC_PI_2 * 0.999);
+	}
+	// HELP: each line of scrolling multiplies the fov (or ortho height) by `ZOOM_STEP`, so zooming feels the same at every level
+	pub fn apply_zoom(&mut self, scroll_delta: f32) {
+		if scroll_delta == 0.0 {return;}
+		let zoom_mult = Self::ZOOM_STEP.powf(scroll_delta);
+		self.projection = match self.projection {
+			CameraProjection::Perspective {fov_radians} => CameraProjection::Perspective {
+				fov_radians: (fov_radians * zoom_mult).clamp(Self::MIN_FOV_DEGREES.to_radians(), Self::MAX_FOV_DEGREES.to_radians()),
+			},
+			CameraProjection::Orthographic {height} => CameraProjection::Orthographic {
+				height: (height * zoom_mult).clamp(Self::MIN_ORTHO_HEIGHT, Self::MAX_ORTHO_HEIGHT),
+			},
+		};
+	}
+	pub fn shake(&mut self, intensity: f32, duration: f32) {
+		self.shake.start(intensity, duration);
+	}
+}
+
+
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum CameraProjection {
+	Perspective {fov_radians: f32},
+	Orthographic {height: f32}, // how many world units fit vertically on the screen, the width follows the aspect ratio
+}
+
+
+
+#[derive(Copy, Clone, Debug, Default)]
+pub struct CameraShake {
+	pub intensity: f32,
+	pub duration: f32,
+	pub time_left: f32,
+	pub pos_offset: glam::Vec3,
+	pub rot_xz_offset: f32,
+	pub rot_y_offset: f32,
+}
+
+impl CameraShake {
+	pub const MAX_POS_OFFSET: f32 = 0.15;
+	pub const MAX_ROT_OFFSET: f32 = 0.03;
+	pub fn start(&mut self, intensity: f32, duration: f32) {
+		if intensity <= 0.0 || duration <= 0.0 {return;}
+		// a weaker shake shouldn't cut off a stronger one that's still going
+		if intensity < self.get_strength() {return;}
+		self.intensity = intensity;
+		self.duration = duration;
+		self.time_left = duration;
+	}
+	// quadratic falloff, so the shake starts with a jolt and then settles down
+	pub fn get_strength(&self) -> f32 {
+		if self.duration <= 0.0 {return 0.0;}
+		let progress_left = (self.time_left / self.duration).clamp(0.0, 1.0);
+		self.intensity * progress_left * progress_left
+	}
+	pub fn update(&mut self, dt: f32, total_time: f32) {
+		self.time_left = (self.time_left - dt).max(0.0);
+		let strength = self.get_strength();
+		if strength <= 0.0 {
+			self.pos_offset = glam::Vec3::ZERO;
+			self.rot_xz_offset = 0.0;
+			self.rot_y_offset = 0.0;
+			return;
+		}
+		// two sine waves per axis with unrelated frequencies are enough to look random
+		let noise = |freq: f32, phase: f32| (total_time * freq + phase).sin() * 0.6 + (total_time * freq * 2.31 + phase * 1.7).sin() * 0.4;
+		self.pos_offset = glam::Vec3::new(noise(23.0, 0.0), noise(19.0, 1.3), noise(21.0, 2.9)) * strength * Self::MAX_POS_OFFSET;
+		self.rot_xz_offset = noise(17.0, 4.1) * strength * Self::MAX_ROT_OFFSET;
+		self.rot_y_offset = noise(15.0, 5.7) * strength * Self::MAX_ROT_OFFSET;
+	}
+}
+
+
+
+#[derive(Copy, Clone, Debug)]
+pub struct CameraKeyframe {
+	pub pos: glam::Vec3,
+	pub rot_xz: f32,
+	pub rot_y: f32,
+}
+
+
+
+// HELP: this is only created when the program is started with `--export-frames <dir> <count>`
+pub struct FrameExporter {
+	pub output_folder: PathBuf,
+	pub total_frames: usize,
+	pub exported_frames: usize,
+	pub dt: f32,
+	pub camera_path: Vec<CameraKeyframe>,
+}
+
+impl FrameExporter {
+	
+	pub fn is_finished(&self) -> bool {
+		self.exported_frames >= self.total_frames
+	}
+	
+	pub fn next_frame_path(&self) -> PathBuf {
+		let digit_count = self.total_frames.saturating_sub(1).to_string().len();
+		self.output_folder.join(format!("frame_{:0digit_count$}.png", self.exported_frames))
+	}
+	
+	// moves the camera along the path so that the first frame is at the first keyframe and the last frame is at the last keyframe
+	pub fn apply_camera_path(&self, camera_data: &mut CameraData) {
+		let keyframe = match self.camera_path.len() {
+			0 => return,
+			1 => self.camera_path[0],
+			_ => {
+				let progress = self.exported_frames as f32 / self.total_frames.saturating_sub(1).max(1) as f32;
+				let segment_progress = progress.clamp(0.0, 1.0) * (self.camera_path.len() - 1) as f32;
+				let segment_index = (segment_progress as usize).min(self.camera_path.len() - 2);
+				let t = segment_progress - segment_index as f32;
+				let start = self.camera_path[segment_index];
+				let end = self.camera_path[segment_index + 1];
+				CameraKeyframe {
+					pos: start.pos.lerp(end.pos, t),
+					rot_xz: start.rot_xz + (end.rot_xz - start.rot_xz) * t,
+					rot_y: start.rot_y + (end.rot_y - start.rot_y) * t,
+				}
+			}
+		};
+		camera_data.pos = keyframe.pos;
+		camera_data.rot_xz = keyframe.rot_xz;
+		camera_data.rot_y = keyframe.rot_y;
+	}
+	
+}
+
+
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ShadowCasterKind {
+	Directional, // orthographic, centered on the camera
+	Spot, // perspective, like a flashlight
+}
+
+pub struct ShadowCasterData {
+	pub kind: ShadowCasterKind,
+	// directional data (the orientation comes from `DirectionalLight::direction`)
+	pub size: glam::Vec3, // only used when there's a single cascade, the z is also the minimum depth range of each cascade
+	pub cascade_count: usize, // always 1 for the spot shadow caster
+	pub cascade_lambda: f32, // 0 splits the view frustum uniformly, 1 splits it logarithmically
+	// spot data
+	pub pos: glam::Vec3,
+	pub dir: glam::Vec3,
+	pub fov_radians: f32,
+	pub range: f32,
+}
+
+impl ShadowCasterData {
+	pub const SPOT_NEAR: f32 = 0.1;
+	pub const MAX_CASCADES: usize = 4;
+	// HELP: both kinds output a z-range of -1 to 1 (that's why the spot uses `perspective_rh_gl()`), which the shaders then correct to 0 to 1
+	pub fn build_gpu_data(&self, camera_data: &CameraData, aspect_ratio: f32, light_direction: glam::Vec3, shadowmap_size: u32) -> ShadowCascadesGpuData {
+		let mut output = ShadowCascadesGpuData {
+			proj_mats: [glam::Mat4::IDENTITY.to_cols_array(); Self::MAX_CASCADES],
+			split_depths: [camera_data.far; Self::MAX_CASCADES],
+			depth_scales: [1.0; Self::MAX_CASCADES],
+			count: self.cascade_count as u32,
+			_padding: [0; 3],
+		};
+		match self.kind {
+			ShadowCasterKind::Directional if self.cascade_count > 1 => {
+				let view_mat = camera_data.build_view_mat();
+				let mut slice_near = camera_data.near;
+				for i in 0..self.cascade_count {
+					let slice_far = self.get_cascade_split(i, camera_data.near, camera_data.far);
+					let inv_proj_view = (camera_data.build_proj_mat_with_range(aspect_ratio, slice_near, slice_far) * view_mat).inverse();
+					let (proj_mat, depth_scale) = self.build_cascade_mat(&inv_proj_view, light_direction, shadowmap_size);
+					output.proj_mats[i] = proj_mat.to_cols_array();
+					output.split_depths[i] = slice_far;
+					output.depth_scales[i] = depth_scale;
+					slice_near = slice_far;
+				}
+			}
+			ShadowCasterKind::Directional => {
+				let trans_mat = glam::Mat4::from_translation(-camera_data.pos);
+				let rot_mat = Self::build_light_rot_mat(light_direction);
+				let scale_mat = glam::Mat4::from_scale(1.0 / self.size);
+				output.proj_mats[0] = (scale_mat * rot_mat * trans_mat).to_cols_array();
+			}
+			ShadowCasterKind::Spot => {
+				let up = if self.dir.y.abs() > 0.999 {glam::Vec3::Z} else {glam::Vec3::Y};
+				let view = glam::Mat4::look_to_rh(self.pos, self.dir, up);
+				let proj = glam::Mat4::perspective_rh_gl(self.fov_radians, 1.0, Self::SPOT_NEAR, self.range);
+				output.proj_mats[0] = (proj * view).to_cols_array();
+			}
+		}
+		output
+	}
+	// `look_to_lh()` makes the light's direction the local +z axis, which is what the shaders expect
+	pub fn build_light_rot_mat(light_direction: glam::Vec3) -> glam::Mat4 {
+		let up = if light_direction.y.abs() > 0.999 {glam::Vec3::Z} else {glam::Vec3::Y};
+		glam::Mat4::look_to_lh(glam::Vec3::ZERO, light_direction, up)
+	}
+	// the view-space depth where cascade `index` ends, this blends between a uniform split and a logarithmic split (see 'practical split scheme')
+	pub fn get_cascade_split(&self, index: usize, near: f32, far: f32) -> f32 {
+		let amount = (index + 1) as f32 / self.cascade_count as f32;
+		let log_split = near * (far / near).powf(amount);
+		let uniform_split = near + (far - near) * amount;
+		uniform_split + (log_split - uniform_split) * self.cascade_lambda
+	}
+	// fits an orthographic box around a slice of the view frustum, and returns the matrix and how much the shadowmap depth changes per shadowmap uv
+	pub fn build_cascade_mat(&self, slice_inv_proj_view: &glam::Mat4, light_direction: glam::Vec3, shadowmap_size: u32) -> (glam::Mat4, f32) {
+		let mut corners = [glam::Vec3::ZERO; 8];
+		for (i, corner) in corners.iter_mut().enumerate() {
+			// HELP: glam's non-gl projections have a z-range of 0 to 1
+			let ndc = glam::Vec3::new(
+				if i & 1 == 0 {-1.0} else {1.0},
+				if i & 2 == 0 {-1.0} else {1.0},
+				if i & 4 == 0 {0.0} else {1.0},
+			);
+			*corner = slice_inv_proj_view.project_point3(ndc);
+		}
+		let center = corners.iter().sum::<glam::Vec3>() / 8.0;
+		// a bounding sphere keeps the cascade's size the same when the camera rotates, which stops the shadow edges from shimmering
+		let radius = corners.iter().map(|corner| corner.distance(center)).fold(0.0, f32::max).ceil().max(1.0);
+		let depth_range = radius.max(self.size.z);
+		
+		let rot_mat = Self::build_light_rot_mat(light_direction);
+		let mut light_space_center = rot_mat.transform_point3(center);
+		// snapping to whole texels also stops the shadow edges from shimmering when the camera moves
+		let texel_size = radius * 2.0 / shadowmap_size as f32;
+		light_space_center.x = (light_space_center.x / texel_size).floor() * texel_size;
+		light_space_center.y = (light_space_center.y / texel_size).floor() * texel_size;
+		
+		let trans_mat = glam::Mat4::from_translation(-light_space_center);
+		let scale_mat = glam::Mat4::from_scale(glam::Vec3::new(1.0 / radius, 1.0 / radius, 1.0 / depth_range));
+		(scale_mat * trans_mat * rot_mat, radius / depth_range)
+	}
+	pub fn new(engine_config: &EngineConfig) -> Self {
+		Self {
+			kind: engine_config.shadow_caster_kind,
+			cascade_count: if engine_config.shadow_caster_kind == ShadowCasterKind::Spot {1} else {engine_config.shadow_cascade_count},
+			cascade_lambda: engine_config.shadow_cascade_lambda,
+			fov_radians: engine_config.spotlight_fov_radians,
+			range: engine_config.spotlight_range,
+			..Self::default()
+		}
+	}
+}
+
+impl Default for ShadowCasterData {
+	fn default() -> Self {
+		Self {
+			kind: ShadowCasterKind::Directional,
+			size: glam::Vec3::new(200.0, 200.0, 200.0),
+			cascade_count: 1,
+			cascade_lambda: 0.75,
+			pos: glam::Vec3::ZERO,
+			dir: glam::Vec3::NEG_Z,
+			fov_radians: 45.0f32.to_radians(),
+			range: 50.0,
 		}
 	}
 }
 
+// NOTE: the layout matches the wgsl struct `ShadowCascades`
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct ShadowCascadesGpuData {
+	pub proj_mats: [[f32; 16]; ShadowCasterData::MAX_CASCADES],
+	pub split_depths: [f32; ShadowCasterData::MAX_CASCADES], // the view-space depth where each cascade ends
+	pub depth_scales: [f32; ShadowCasterData::MAX_CASCADES], // how much the shadowmap depth changes per shadowmap uv, used to scale the pcf bias
+	pub count: u32,
+	pub _padding: [u32; 3],
+}
+
+
+
+// the main light, which is also the one that the shadow caster uses (the spot shadow caster only uses its color and intensity)
+pub struct DirectionalLight {
+	pub direction: glam::Vec3, // the direction the light travels in
+	pub color: glam::Vec3,
+	pub intensity: f32,
+}
+
+impl DirectionalLight {
+	pub fn new(engine_config: &EngineConfig) -> Self {
+		Self {
+			direction: engine_config.directional_light_direction,
+			color: glam::Vec3::from_array(engine_config.directional_light_color),
+			intensity: engine_config.directional_light_intensity,
+		}
+	}
+	// rotates the light around the world y axis
+	pub fn rotate_y(&mut self, angle: f32) {
+		self.direction = (glam::Quat::from_rotation_y(angle) * self.direction).normalize();
+	}
+	// NOTE: the layout matches the wgsl struct `DirectionalLight` (the vec3s are padded to 16 bytes)
+	pub fn build_gpu_data(&self) -> [f32; 8] {
+		let direction = self.direction.normalize_or_zero();
+		[
+			direction.x, direction.y, direction.z, self.intensity,
+			self.color.x, self.color.y, self.color.z, 0.0,
+		]
+	}
+}
+
+
+
+// HELP: this is only created when `hot_reload_shaders` is enabled, the watcher sends its events from
+// another thread so they're collected here until the start of the next frame
+pub struct ShaderWatcher {
+	pub _watcher: notify::RecommendedWatcher, // never used, but it has to be kept alive for the events to keep coming
+	pub events: std::sync::mpsc::Receiver<notify::Result<notify::Event>>,
+}
+
+impl ShaderWatcher {
+	// returns the file names (like "models.wgsl") of every shader that was changed since the last call
+	pub fn take_changed_shaders(&self) -> HashSet<String> {
+		take_changed_file_names(&self.events)
+	}
+}
+
+// HELP: this is only created when `hot_reload_config` is enabled, it watches the whole data folder
+// because some editors save by replacing the file, which would stop a watcher on the file itself
+pub struct ConfigWatcher {
+	pub _watcher: notify::RecommendedWatcher, // never used, but it has to be kept alive for the events to keep coming
+	pub events: std::sync::mpsc::Receiver<notify::Result<notify::Event>>,
+}
+
+impl ConfigWatcher {
+	pub fn take_config_changed(&self) -> bool {
+		take_changed_file_names(&self.events).contains("engine config.hjson")
+	}
+}
+
+pub fn take_changed_file_names(events: &std::sync::mpsc::Receiver<notify::Result<notify::Event>>) -> HashSet<String> {
+	let mut output = HashSet::new();
+	for event in events.try_iter() {
+		let event = match event {
+			StdResult::Ok(v) => v,
+			StdResult::Err(err) => {
+				warn!("Error while watching files: {err}");
+				continue;
+			}
+		};
+		// files that are saved by replacing them show up as a create instead of a modify
+		if !event.kind.is_modify() && !event.kind.is_create() {continue;}
+		output.extend(event.paths.iter().filter_map(|path| path.file_name()).map(|name| name.to_string_lossy().into_owned()));
+	}
+	output
+}
+
+
+
+// HELP: the window events are collected in `events` by `ui_utils::process_window_event()`, then they're
+// all given to egui at the start of the next frame by `ui_utils::run_ui()`
+pub struct UiState {
+	pub context: egui::Context,
+	pub renderer: egui_wgpu::Renderer,
+	pub screen_descriptor: egui_wgpu::ScreenDescriptor, // always matches the surface, see `resize()`
+	pub events: Vec<egui::Event>,
+	pub modifiers: egui::Modifiers,
+	pub pointer_pos: Option<egui::Pos2>, // in points, `None` when the cursor is outside the window
+	pub wants_pointer_input: bool, // true when the cursor is over the panel, so clicks shouldn't start moving the camera
+	pub wants_keyboard_input: bool, // true when one of the panel's text fields is being edited
+	pub is_visible: bool,
+}
+
+pub struct UiOutput {
+	pub paint_jobs: Vec<egui::ClippedPrimitive>,
+	pub textures_delta: egui::TexturesDelta,
+}
+
+
+
+// HELP: timestamps are written at the start and end of the frame and around the main passes, then copied to a
+// buffer that's read a frame or two later (waiting for it would stall the cpu), frames that are rendered while
+// the previous timestamps are still being read just aren't timed
+pub struct GpuTimer {
+	pub query_set: wgpu::QuerySet,
+	pub resolve_buffer: wgpu::Buffer, // each query gets `QUERY_RESOLVE_BUFFER_ALIGNMENT` bytes so they can be resolved one at a time
+	pub readback_buffer: wgpu::Buffer,
+	pub timestamp_period: f32, // nanoseconds per timestamp tick
+	pub is_recording: bool, // whether the current frame is being timed
+	// passes that are skipped (like the skybox when the background isn't a skybox) leave their queries unwritten, and those can't be resolved
+	pub written_queries: std::cell::Cell<u32>, // one bit per query index, for the frame that's being recorded
+	pub readback_written_queries: u32, // `written_queries` for the frame that's being read back
+	pub readback_state: std::sync::Arc<std::sync::atomic::AtomicU8>, // one of the `READBACK_*` values, set from wgpu's map callback
+}
+
+impl GpuTimer {
+	
+	// NOTE: the shadow caster pass is the first pass of the frame, so it starts at `FRAME_START`
+	pub const FRAME_START: u32 = 0;
+	pub const SHADOW_CASTER_END: u32 = 1;
+	pub const MODELS_START: u32 = 2;
+	pub const MODELS_END: u32 = 3;
+	pub const SKYBOX_START: u32 = 4;
+	pub const SKYBOX_END: u32 = 5;
+	pub const FRAME_END: u32 = 6;
+	pub const QUERY_COUNT: u32 = 7;
+	
+	pub const READBACK_IDLE: u8 = 0;
+	pub const READBACK_MAPPING: u8 = 1;
+	pub const READBACK_MAPPED: u8 = 2;
+	
+	// returns whether this frame will be timed
+	pub fn begin_frame(&mut self) -> bool {
+		use std::sync::atomic::Ordering;
+		self.is_recording = self.readback_state.load(Ordering::Acquire) == Self::READBACK_IDLE;
+		self.written_queries.set(0);
+		self.is_recording
+	}
+	
+	// `None` when this frame isn't being timed (or when there's nothing to write)
+	pub fn get_timestamp_writes(&self, beginning_index: Option<u32>, end_index: Option<u32>) -> Option<wgpu::RenderPassTimestampWrites<'_>> {
+		if !self.is_recording || (beginning_index.is_none() && end_index.is_none()) {return None;}
+		for index in beginning_index.into_iter().chain(end_index) {
+			self.written_queries.set(self.written_queries.get() | 1 << index);
+		}
+		Some(wgpu::RenderPassTimestampWrites {
+			query_set: &self.query_set,
+			beginning_of_pass_write_index: beginning_index,
+			end_of_pass_write_index: end_index,
+		})
+	}
+	
+	pub fn resolve(&self, encoder: &mut wgpu::CommandEncoder) {
+		if !self.is_recording {return;}
+		for index in (0..Self::QUERY_COUNT).filter(|index| self.written_queries.get() & 1 << index != 0) {
+			encoder.resolve_query_set(&self.query_set, index..index + 1, &self.resolve_buffer, index as u64 * wgpu::QUERY_RESOLVE_BUFFER_ALIGNMENT);
+		}
+		encoder.copy_buffer_to_buffer(&self.resolve_buffer, 0, &self.readback_buffer, 0, self.resolve_buffer.size());
+	}
+	
+	// this has to be called after the frame's commands are submitted
+	pub fn end_frame(&mut self) {
+		use std::sync::atomic::Ordering;
+		if !self.is_recording {return;}
+		self.is_recording = false;
+		self.readback_written_queries = self.written_queries.get();
+		self.readback_state.store(Self::READBACK_MAPPING, Ordering::Release);
+		let readback_state = self.readback_state.clone();
+		self.readback_buffer.slice(..).map_async(wgpu::MapMode::Read, move |result| {
+			let new_state = if result.is_ok() {Self::READBACK_MAPPED} else {Self::READBACK_IDLE};
+			readback_state.store(new_state, Ordering::Release);
+		});
+	}
+	
+	// returns the timings of the most recently read frame, if one finished reading since the last call
+	pub fn take_pass_timings(&mut self, device: &wgpu::Device) -> Option<PassTimings> {
+		use std::sync::atomic::Ordering;
+		device.poll(wgpu::Maintain::Poll);
+		if self.readback_state.load(Ordering::Acquire) != Self::READBACK_MAPPED {return None;}
+		let timestamps = {
+			let mapped_range = self.readback_buffer.slice(..).get_mapped_range();
+			let stride = wgpu::QUERY_RESOLVE_BUFFER_ALIGNMENT as usize;
+			(0..Self::QUERY_COUNT as usize).map(|i| bytemuck::pod_read_unaligned::<u64>(&mapped_range[i * stride .. i * stride + 8])).collect::<Vec<_>>()
+		};
+		self.readback_buffer.unmap();
+		self.readback_state.store(Self::READBACK_IDLE, Ordering::Release);
+		let get_duration = |start: u32, end: u32| {
+			let both_written = self.readback_written_queries & (1 << start | 1 << end) == (1 << start | 1 << end);
+			if !both_written {return Duration::ZERO;}
+			let ticks = timestamps[end as usize].saturating_sub(timestamps[start as usize]);
+			Duration::from_secs_f64(ticks as f64 * self.timestamp_period as f64 / 1_000_000_000.0)
+		};
+		Some(PassTimings {
+			total: get_duration(Self::FRAME_START, Self::FRAME_END),
+			shadow_caster: get_duration(Self::FRAME_START, Self::SHADOW_CASTER_END),
+			models: get_duration(Self::MODELS_START, Self::MODELS_END),
+			skybox: get_duration(Self::SKYBOX_START, Self::SKYBOX_END),
+		})
+	}
+	
+}
+
+// passes that didn't run have a time of zero
+#[derive(Copy, Clone, Debug, Default)]
+pub struct PassTimings {
+	pub total: Duration,
+	pub shadow_caster: Duration,
+	pub models: Duration,
+	pub skybox: Duration,
+}
+
+impl PassTimings {
+	pub fn add(&mut self, other: &PassTimings) {
+		self.total += other.total;
+		self.shadow_caster += other.shadow_caster;
+		self.models += other.models;
+		self.skybox += other.skybox;
+	}
+	pub fn div(&self, divisor: u32) -> PassTimings {
+		PassTimings {
+			total: self.total / divisor,
+			shadow_caster: self.shadow_caster / divisor,
+			models: self.models / divisor,
+			skybox: self.skybox / divisor,
+		}
+	}
+}
+
+
+
+pub struct FpsCounter {
+	pub frame_count: usize,
+	pub frame_time_total: Duration,
+	pub gpu_frame_count: usize, // not every frame has gpu timings
+	pub gpu_timings_total: PassTimings,
+	pub next_output_time: Instant,
+	// HELP: this is an exponentially weighted moving average, which is much steadier than the per-second average and is meant for on-screen displays
+	pub smoothed_frame_time: Option<f32>, // in seconds, `None` until the first frame
+	pub smoothing: f32, // how much each new frame affects the average (0 to 1, lower is smoother)
+}
+
+impl FpsCounter {
+	
+	pub fn new(smoothing: f32) -> Self {
+		Self {
+			frame_count: 0,
+			frame_time_total: Duration::ZERO,
+			gpu_frame_count: 0,
+			gpu_timings_total: PassTimings::default(),
+			next_output_time: Instant::now(),
+			smoothed_frame_time: None,
+			smoothing: smoothing.clamp(0.0, 1.0),
+		}
+	}
+	
+	// returns the fps, the average frame time, and the average gpu timings (when there are any) once per second
+	pub fn step(&mut self, frame_time: Duration, gpu_timings: Option<PassTimings>) -> Option<(usize, Duration, Option<PassTimings>)> {
+		
+		let frame_time_secs = frame_time.as_secs_f32();
+		self.smoothed_frame_time = Some(match self.smoothed_frame_time {
+			Some(smoothed_frame_time) => smoothed_frame_time + (frame_time_secs - smoothed_frame_time) * self.smoothing,
+			None => frame_time_secs,
+		});
+		
+		self.frame_count += 1;
+		self.frame_time_total += frame_time;
+		if let Some(gpu_timings) = gpu_timings {
+			self.gpu_frame_count += 1;
+			self.gpu_timings_total.add(&gpu_timings);
+		}
+		if self.next_output_time.elapsed().as_secs_f32() < 1.0 {return None;}
+		
+		let fps_output = self.frame_count;
+		let duration_output = self.frame_time_total / self.frame_count as u32;
+		let gpu_timings_output = (self.gpu_frame_count > 0).then(|| self.gpu_timings_total.div(self.gpu_frame_count as u32));
+		
+		self.frame_count = 0;
+		self.frame_time_total = Duration::ZERO;
+		self.gpu_frame_count = 0;
+		self.gpu_timings_total = PassTimings::default();
+		self.next_output_time += Duration::SECOND;
+		
+		Some((fps_output, duration_output, gpu_timings_output))
+	}
+	
+	pub fn get_smoothed_frame_time(&self) -> Option<Duration> {
+		self.smoothed_frame_time.map(Duration::from_secs_f32)
+	}
+	
+	pub fn get_smoothed_fps(&self) -> Option<f32> {
+		self.smoothed_frame_time.map(|frame_time| 1.0 / frame_time.max(0.000001))
+	}
+	
+}
+
+
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FrameLimiter {
+	Off, // `min_frame_time` is ignored
+	Sleep, // can overshoot by a few milliseconds, mostly on windows where the sleep timer is coarse
+	Hybrid, // sleeps until `FramePacer::SPIN_MARGIN` before the target, then busy-waits the rest (which uses more cpu, but is much more precise)
+}
+
+// HELP: the overshoot is how late each limited frame ended up compared to its target, and it's logged (as info) once per
+// second so that the limiters can be compared
+pub struct FramePacer {
+	pub limited_frame_count: u32,
+	pub overshoot_total: Duration,
+	pub max_overshoot: Duration,
+	pub next_output_time: Instant,
+}
+
+impl FramePacer {
+	
+	pub const SPIN_MARGIN: Duration = Duration::from_millis(1);
+	
+	pub fn new() -> Self {
+		Self {
+			limited_frame_count: 0,
+			overshoot_total: Duration::ZERO,
+			max_overshoot: Duration::ZERO,
+			next_output_time: Instant::now(),
+		}
+	}
+	
+	// waits until `min_frame_time` has passed since `frame_start_time`
+	pub fn wait(&mut self, frame_start_time: Instant, min_frame_time: Duration, frame_limiter: FrameLimiter) {
+		let target_time = frame_start_time + min_frame_time;
+		let now = Instant::now();
+		if now >= target_time {return;}
+		match frame_limiter {
+			FrameLimiter::Off => return,
+			FrameLimiter::Sleep => std::thread::sleep(target_time - now),
+			FrameLimiter::Hybrid => {
+				let time_left = target_time - now;
+				if time_left > Self::SPIN_MARGIN {
+					std::thread::sleep(time_left - Self::SPIN_MARGIN);
+				}
+				while Instant::now() < target_time {
+					std::hint::spin_loop();
+				}
+			}
+		}
+		
+		let overshoot = target_time.elapsed();
+		self.limited_frame_count += 1;
+		self.overshoot_total += overshoot;
+		self.max_overshoot = self.max_overshoot.max(overshoot);
+		if self.next_output_time.elapsed().as_secs_f32() < 1.0 {return;}
+		info!(
+			"Frame pacing ({frame_limiter:?}): avg overshoot: {:?}, max overshoot: {:?}, limited frames: {}",
+			self.overshoot_total / self.limited_frame_count, self.max_overshoot, self.limited_frame_count,
+		);
+		self.limited_frame_count = 0;
+		self.overshoot_total = Duration::ZERO;
+		self.max_overshoot = Duration::ZERO;
+		self.next_output_time = Instant::now() + Duration::SECOND;
+	}
+	
+}
+
+
+
+// HELP: when a frame fails, the error is shown in the window for a while before exiting, since people who start the
+// program without a terminal would otherwise only see the window disappear
+pub struct FatalError {
+	pub message: String,
+	pub start_time: Instant,
+}
+
+impl FatalError {
+	pub const DISPLAY_DURATION: Duration = Duration::from_secs(10);
+	pub const BACKGROUND_COLOR: wgpu::Color = wgpu::Color {r: 0.15, g: 0.02, b: 0.02, a: 1.0};
+	pub fn new(err: &Error) -> Self {
+		Self {
+			message: format!("{err:?}"), // the debug format includes the whole chain of contexts
+			start_time: Instant::now(),
+		}
+	}
+	pub fn time_left(&self) -> Duration {
+		Self::DISPLAY_DURATION.saturating_sub(self.start_time.elapsed())
+	}
+	// `BACKGROUND_COLOR` is linear, so it has to be encoded when the surface doesn't do it (see `RenderContextData::manual_srgb_encode`)
+	pub fn get_background_color(manual_srgb_encode: bool) -> wgpu::Color {
+		if !manual_srgb_encode {return Self::BACKGROUND_COLOR;}
+		let encode = |value: f64| utils::linear_to_srgb(value as f32) as f64;
+		wgpu::Color {
+			r: encode(Self::BACKGROUND_COLOR.r),
+			g: encode(Self::BACKGROUND_COLOR.g),
+			b: encode(Self::BACKGROUND_COLOR.b),
+			a: Self::BACKGROUND_COLOR.a,
+		}
+	}
+}
+
+
+
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct ColorCorrectionSettings {
+	pub saturation: f32,
+	pub brightness: f32,
+	pub exposure: f32, // the hdr color is multiplied by this before tone mapping
+	pub tonemapper: u32, // see `ColorCorrectionSettings::TONEMAPPER_*`
+	pub bloom_threshold: f32, // only the parts of the hdr color brighter than this are blurred
+	pub bloom_strength: f32, // 0 disables bloom
+	pub contrast: f32, // scales the colors away from (or towards) middle gray
+	pub encode_srgb: u32, // set from `RenderContextData::manual_srgb_encode` right before it's uploaded
+}
+
+impl ColorCorrectionSettings {
+	pub const TONEMAPPER_NONE: u32 = 0; // just clamps the color
+	pub const TONEMAPPER_REINHARD: u32 = 1;
+	pub const TONEMAPPER_ACES: u32 = 2;
+	pub const MIN_EXPOSURE: f32 = 0.01;
+	pub const MAX_EXPOSURE: f32 = 100.0;
+	// this is applied before every upload, so the runtime controls can't produce broken colors
+	pub fn clamped(&self) -> Self {
+		Self {
+			saturation: self.saturation.clamp(0.0, 3.0),
+			brightness: self.brightness.clamp(0.0, 4.0),
+			exposure: self.exposure.clamp(Self::MIN_EXPOSURE, Self::MAX_EXPOSURE),
+			bloom_threshold: self.bloom_threshold.clamp(0.0, 100.0),
+			bloom_strength: self.bloom_strength.clamp(0.0, 1.0),
+			contrast: self.contrast.clamp(0.0, 3.0),
+			..*self
+		}
+	}
+	pub fn new(engine_config: &EngineConfig) -> Self {
+		Self {
+			exposure: engine_config.exposure,
+			tonemapper: engine_config.tonemapper,
+			bloom_threshold: engine_config.bloom_threshold,
+			bloom_strength: engine_config.bloom_strength,
+			..Self::default()
+		}
+	}
+}
+
+impl Default for ColorCorrectionSettings {
+	fn default() -> Self {
+		Self {
+			saturation: 1.1,
+			brightness: 1.1,
+			exposure: 1.0,
+			tonemapper: Self::TONEMAPPER_ACES,
+			bloom_threshold: 1.0,
+			bloom_strength: 0.05,
+			contrast: 1.0,
+			encode_srgb: 0,
+		}
+	}
+}
+
+
+
+// HELP: bools can't be sent to the gpu directly, so flags are stored as u32-s (0 = false, 1 = true)
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct LightingSettings {
+	pub flat_shading: u32,
+	pub debug_view: u32, // see `LightingSettings::DEBUG_VIEW_*`
+	pub light_count: u32, // the lights buffer always holds at least one light and can have unused space, so this is needed to know how many are real
+	pub pcf_kernel_size: u32, // the shadows are softened by averaging this many by this many shadowmap samples, can be changed at runtime
+	// shadow caster data, set each frame by `set_shadow_caster()`
+	pub spot_pos: [f32; 3],
+	pub shadow_caster_kind: u32, // 0: directional, 1: spot
+	pub spot_dir: [f32; 3],
+	pub spot_cos_outer: f32,
+	pub spot_cos_inner: f32,
+	pub spot_range: f32,
+	pub shadowmap_texel_size: f32, // 1 / `shadowmap_size`, used to space out the pcf samples
+	pub environment_rotation: f32, // copied from `SkyboxSettings::rotation` each frame
+}
+
+impl LightingSettings {
+	pub const DEBUG_VIEW_NONE: u32 = 0;
+	pub const DEBUG_VIEW_GEOMETRY_NORMALS: u32 = 1; // the actual triangle normals
+	pub const DEBUG_VIEW_SHADING_NORMALS: u32 = 2; // the normals used for lighting
+	pub const DEBUG_VIEW_COUNT: u32 = 3;
+	pub const PCF_KERNEL_SIZES: [u32; 3] = [1, 3, 5];
+	pub fn new(engine_config: &EngineConfig) -> Self {
+		Self {
+			flat_shading: engine_config.flat_shading as u32,
+			debug_view: Self::DEBUG_VIEW_NONE,
+			light_count: engine_config.lights.len() as u32,
+			pcf_kernel_size: engine_config.shadow_pcf_kernel_size,
+			spot_pos: [0.0; 3],
+			shadow_caster_kind: 0,
+			spot_dir: [0.0, 0.0, -1.0],
+			spot_cos_outer: 0.0,
+			spot_cos_inner: 0.0,
+			spot_range: 0.0,
+			shadowmap_texel_size: 1.0 / engine_config.shadowmap_size as f32,
+			environment_rotation: 0.0,
+		}
+	}
+	pub fn set_shadow_caster(&mut self, shadow_caster_data: &ShadowCasterData) {
+		self.shadow_caster_kind = match shadow_caster_data.kind {
+			ShadowCasterKind::Directional => 0,
+			ShadowCasterKind::Spot => 1,
+		};
+		self.spot_pos = shadow_caster_data.pos.to_array();
+		self.spot_dir = shadow_caster_data.dir.normalize_or_zero().to_array();
+		// the light fades out over the outer 20% of the cone
+		let half_fov = shadow_caster_data.fov_radians * 0.5;
+		self.spot_cos_outer = half_fov.cos();
+		self.spot_cos_inner = (half_fov * 0.8).cos();
+		self.spot_range = shadow_caster_data.range;
+	}
+}
+
+
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LightKind {
+	Point,
+	Directional,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct LightData {
+	pub kind: LightKind,
+	pub pos: glam::Vec3, // only used by point lights
+	pub direction: glam::Vec3, // only used by directional lights, this is the direction the light travels in
+	pub color: [f32; 3],
+	pub intensity: f32,
+	pub range: f32, // only used by point lights
+}
+
+impl LightData {
+	pub const MAX_INTENSITY: f32 = 100.0;
+	pub fn to_raw(&self) -> RawLightData {
+		RawLightData {
+			pos: self.pos.to_array(),
+			kind: match self.kind {
+				LightKind::Point => 0,
+				LightKind::Directional => 1,
+			},
+			direction: self.direction.to_array(),
+			range: self.range,
+			color: self.color,
+			intensity: self.intensity,
+		}
+	}
+}
+
+// NOTE: the field order matches the wgsl struct's layout (each vec3 is padded to 16 bytes by the following scalar)
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct RawLightData {
+	pub pos: [f32; 3],
+	pub kind: u32,
+	pub direction: [f32; 3],
+	pub range: f32,
+	pub color: [f32; 3],
+	pub intensity: f32,
+}
+
+
+
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct SkyboxSettings {
+	pub blend: f32, // 0 = only the main skybox, 1 = only the secondary skybox
+	pub rotation: f32, // the skybox's yaw in radians, also applied to the ibl (see `LightingSettings::environment_rotation`)
+}
+
+impl SkyboxSettings {
+	pub fn new(engine_config: &EngineConfig) -> Self {
+		Self {
+			blend: engine_config.skybox_blend,
+			..Self::default()
+		}
+	}
+}
+
+impl Default for SkyboxSettings {
+	fn default() -> Self {
+		Self {
+			blend: 0.0,
+			rotation: 0.0,
+		}
+	}
+}
+
+
+
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct ShadowCatcherSettings {
+	pub center: [f32; 2], // the plane follows the camera so that it seems infinite
+	pub height: f32,
+	pub size: f32,
+	pub opacity: f32,
+	pub _padding: f32,
+}
+
+impl ShadowCatcherSettings {
+	pub fn new(engine_config: &EngineConfig, camera_pos: glam::Vec3) -> Self {
+		Self {
+			center: [camera_pos.x, camera_pos.z],
+			height: engine_config.shadow_catcher_height,
+			size: engine_config.shadow_catcher_size,
+			opacity: engine_config.shadow_catcher_opacity,
+			_padding: 0.0,
+		}
+	}
+}
+
+
+
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct TextSettings {
+	pub screen_size: [f32; 2],
+	pub cell_size: f32, // the size of one font pixel, in screen pixels
+	pub encode_srgb: u32, // same as `ColorCorrectionSettings::encode_srgb`
+}
 
 
-pub struct ShadowCasterData {
-	pub size: glam::Vec3,
-	pub rot: glam::Quat,
-}
 
-impl ShadowCasterData {
-	pub fn build_gpu_data(&self, center_pos: glam::Vec3) -> [f32; 16] {
-		let trans_mat = glam::Mat4::from_translation(-center_pos);
-		let rot_mat = glam::Mat4::from_quat(self.rot);
-		let scale_mat = glam::Mat4::from_scale(1.0 / self.size);
-		let output = scale_mat * rot_mat * trans_mat;
-		output.to_cols_array()
-	}
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct CullingSettings {
+	pub frustum_planes: [[f32; 4]; 5], // xyz = normal, w = distance, same order as `render::get_frustum_planes()`
+	pub camera_pos: [f32; 3],
+	pub bounding_radius: f32,
+	pub max_draw_distance: f32, // 0 means there's no limit
+	pub draw_distance_fade_width: f32,
+	pub instance_count: u32,
+	pub _padding: u32,
 }
 
-impl Default for ShadowCasterData {
-	fn default() -> Self {
-		const PI: f32 = std::f32::consts::PI;
+impl CullingSettings {
+	pub fn new(engine_config: &EngineConfig, frustum_planes: &[(glam::Vec3, f32); 5], camera_pos: glam::Vec3, model: &ModelsRenderData) -> Self {
 		Self {
-			size: glam::Vec3::new(200.0, 200.0, 200.0),
-			rot: glam::Quat::from_euler(glam::EulerRot::YXZ, PI * -0.25, PI * -0.125, 0.0),
+			frustum_planes: frustum_planes.map(|(normal, dist)| normal.extend(dist).to_array()),
+			camera_pos: camera_pos.to_array(),
+			bounding_radius: model.bounding_radius,
+			max_draw_distance: engine_config.max_draw_distance,
+			draw_distance_fade_width: engine_config.draw_distance_fade_width,
+			instance_count: model.total_instances_count,
+			_padding: 0,
 		}
 	}
 }
 
 
 
-pub struct FpsCounter {
-	pub frame_count: usize,
-	pub frame_time_total: Duration,
-	pub next_output_time: Instant,
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct FoliageSettings {
+	pub wind_strength: f32,
+	pub wind_speed: f32,
+	pub total_time: f32,
+	pub _padding: f32,
 }
 
-impl FpsCounter {
-	
-	pub fn new() -> Self {
+impl FoliageSettings {
+	pub fn new(engine_config: &EngineConfig, total_time: f32) -> Self {
 		Self {
-			frame_count: 0,
-			frame_time_total: Duration::ZERO,
-			next_output_time: Instant::now(),
+			wind_strength: engine_config.wind_strength,
+			wind_speed: engine_config.wind_speed,
+			total_time,
+			_padding: 0.0,
 		}
 	}
-	
-	pub fn step(&mut self, frame_time: Duration) -> Option<(usize, Duration)> {
-		
-		self.frame_count += 1;
-		self.frame_time_total += frame_time;
-		if self.next_output_time.elapsed().as_secs_f32() < 1.0 {return None;}
-		
-		let fps_output = self.frame_count;
-		let duration_output = self.frame_time_total / self.frame_count as u32;
-		
-		self.frame_count = 0;
-		self.frame_time_total = Duration::ZERO;
-		self.next_output_time += Duration::SECOND;
-		
-		Some((fps_output, duration_output))
-	}
-	
 }
 
 
 
 #[repr(C)]
 #[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
-pub struct ColorCorrectionSettings {
-	pub saturation: f32,
-	pub brightness: f32,
+pub struct GradientBackgroundSettings {
+	pub top_color: [f32; 4],
+	pub bottom_color: [f32; 4],
 }
 
-impl Default for ColorCorrectionSettings {
-	fn default() -> Self {
+impl GradientBackgroundSettings {
+	pub fn new(engine_config: &EngineConfig) -> Self {
+		let [top_r, top_g, top_b] = engine_config.gradient_background_top_color;
+		let [bottom_r, bottom_g, bottom_b] = engine_config.gradient_background_bottom_color;
 		Self {
-			saturation: 1.1,
-			brightness: 1.1,
+			top_color: [top_r, top_g, top_b, 1.0],
+			bottom_color: [bottom_r, bottom_g, bottom_b, 1.0],
 		}
 	}
 }
@@ -234,15 +1522,54 @@ impl Default for ColorCorrectionSettings {
 
 
 
+// HELP: in headless mode (see `load::load_headless_program_data()`) there's no window or surface, and every
+// frame is rendered into `headless_texture` instead, so it can be read back with `capture_utils::render_headless_frame()`
 pub struct RenderContextData<'a> {
-	pub window: &'a Window,
-	pub drawable_surface: wgpu::Surface<'a>,
+	pub window: Option<&'a Window>,
+	pub drawable_surface: Option<wgpu::Surface<'a>>,
+	pub headless_texture: Option<wgpu::Texture>, // only `Some` in headless mode
 	pub device: wgpu::Device,
 	pub command_queue: wgpu::Queue,
 	pub surface_config: wgpu::SurfaceConfiguration,
 	pub surface_size: winit::dpi::PhysicalSize<u32>,
 	pub surface_format: wgpu::TextureFormat,
+	pub surface_view_format: wgpu::TextureFormat, // what everything that draws to the surface uses, this is the srgb version of `surface_format` when the surface is viewed as srgb
+	pub manual_srgb_encode: bool, // true when the surface can't do the srgb encode, so the last passes (color correction and text) do it in the shader instead
+	pub supported_present_modes: Vec<wgpu::PresentMode>, // from the surface's capabilities, used to cycle the present mode at runtime
 	pub aspect_ratio: f32,
+	pub compression_format: CompressionFormat, // what `compress_textures` compresses to on this adapter, `None` when it's disabled
+}
+
+// HELP: bc7 is used on desktop, and mobile adapters usually only support astc or etc2 (the etc1 encoder's output is valid etc2)
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CompressionFormat {
+	None,
+	Bc7,
+	Astc4x4,
+	Etc2Rgb8,
+}
+
+impl CompressionFormat {
+	pub fn get_texture_format(self, is_srgb: bool) -> wgpu::TextureFormat {
+		match (self, is_srgb) {
+			(Self::None, true) => wgpu::TextureFormat::Rgba8UnormSrgb,
+			(Self::None, false) => wgpu::TextureFormat::Rgba8Unorm,
+			(Self::Bc7, true) => wgpu::TextureFormat::Bc7RgbaUnormSrgb,
+			(Self::Bc7, false) => wgpu::TextureFormat::Bc7RgbaUnorm,
+			(Self::Astc4x4, true) => wgpu::TextureFormat::Astc {block: wgpu::AstcBlock::B4x4, channel: wgpu::AstcChannel::UnormSrgb},
+			(Self::Astc4x4, false) => wgpu::TextureFormat::Astc {block: wgpu::AstcBlock::B4x4, channel: wgpu::AstcChannel::Unorm},
+			(Self::Etc2Rgb8, true) => wgpu::TextureFormat::Etc2Rgb8UnormSrgb,
+			(Self::Etc2Rgb8, false) => wgpu::TextureFormat::Etc2Rgb8Unorm,
+		}
+	}
+	// for the compressed formats this is one row of 4x4 blocks
+	pub fn get_bytes_per_row(self, width: u32) -> u32 {
+		match self {
+			Self::None => width * 4,
+			Self::Bc7 | Self::Astc4x4 => width.div_ceil(4) * 16,
+			Self::Etc2Rgb8 => width.div_ceil(4) * 8,
+		}
+	}
 }
 
 
@@ -255,17 +1582,58 @@ pub struct RenderLayouts {
 	
 	// models render data
 	pub models_pipeline: wgpu::RenderPipeline,
+	pub models_wireframe_pipeline: Option<wgpu::RenderPipeline>, // `None` when the adapter doesn't support line polygon mode
+	pub models_transparent_pipeline: wgpu::RenderPipeline, // alpha blended and doesn't write depth, see `MaterialAlphaMode::Blend`
+	pub models_double_sided_pipeline: wgpu::RenderPipeline, // same as `models_pipeline` but without back-face culling, see `MeshRenderData::double_sided`
 	pub models_bind_0_layout: wgpu::BindGroupLayout,
 	pub models_bind_1_layout: wgpu::BindGroupLayout,
 	
+	// culling render data
+	pub culling_pipeline: Option<wgpu::ComputePipeline>, // only exists when `gpu_culling` is enabled
+	pub culling_bind_0_layout: Option<wgpu::BindGroupLayout>,
+	
+	// occlusion render data
+	pub occlusion_pipeline: wgpu::RenderPipeline, // depth-tested but doesn't write anything, see `OcclusionRenderData`
+	pub occlusion_bind_0_layout: wgpu::BindGroupLayout,
+	
 	// skybox render data
 	pub skybox_pipeline: wgpu::RenderPipeline,
 	pub skybox_bind_0_layout: wgpu::BindGroupLayout,
 	
+	// gradient background render data
+	pub gradient_background_pipeline: wgpu::RenderPipeline,
+	pub gradient_background_bind_0_layout: wgpu::BindGroupLayout,
+	
+	// foliage render data
+	pub foliage_pipeline: wgpu::RenderPipeline,
+	pub foliage_bind_0_layout: wgpu::BindGroupLayout,
+	
+	// shadow catcher render data
+	pub shadow_catcher_pipeline: wgpu::RenderPipeline,
+	pub shadow_catcher_bind_0_layout: wgpu::BindGroupLayout,
+	
+	// debug lines render data
+	pub debug_lines_pipeline: wgpu::RenderPipeline,
+	pub debug_lines_overlay_pipeline: wgpu::RenderPipeline, // same as `debug_lines_pipeline` but ignores the depth buffer
+	pub debug_lines_bind_0_layout: wgpu::BindGroupLayout,
+	
 	// color correction data
+	pub bloom_bright_pass_pipeline: wgpu::RenderPipeline,
+	pub bloom_downsample_pipeline: wgpu::RenderPipeline,
+	pub bloom_upsample_pipeline: wgpu::RenderPipeline,
+	pub bloom_bind_0_layout: wgpu::BindGroupLayout,
+	
 	pub color_correction_pipeline: wgpu::RenderPipeline,
 	pub color_correction_bind_0_layout: wgpu::BindGroupLayout,
 	
+	// shadowmap debug render data
+	pub shadowmap_debug_pipeline: wgpu::RenderPipeline,
+	pub shadowmap_debug_bind_0_layout: wgpu::BindGroupLayout,
+	
+	// text render data
+	pub text_pipeline: wgpu::RenderPipeline,
+	pub text_bind_0_layout: wgpu::BindGroupLayout,
+	
 }
 
 
@@ -275,24 +1643,63 @@ pub struct RenderAssets {
 	
 	// general render data
 	pub depth: DepthRenderData,
-	pub main_tex_view: wgpu::TextureView,
+	pub main_tex_view: wgpu::TextureView, // hdr (see `RenderAssets::MAIN_TEX_FORMAT`), the color correction pass tone maps it to the surface
+	pub msaa_tex_view: Option<wgpu::TextureView>, // only exists when `msaa_samples` is above 1, the scene passes draw to this and resolve it into `main_tex_view`
 	pub camera: CameraRenderData,
 	pub default_sampler: wgpu::Sampler,
+	pub material_sampler: wgpu::Sampler, // same as `default_sampler` but repeating, so that uv transforms can tile textures
 	pub materials_storage: MaterialsStorage,
 	
 	// shadow_caster render data
 	pub shadow_caster: ShadowCasterRenderData,
 	
 	// models render data
-	pub example_models: ModelsRenderData,
+	pub models: Vec<ModelsRenderData>, // one for each entry in `model_paths`
+	
+	// occlusion render data
+	pub occlusion: Option<OcclusionRenderData>, // only loaded when `occlusion_culling` is true
 	
 	// skybox render data
-	pub skybox_material_id: MaterialId,
+	pub skybox_material_id: Option<MaterialId>, // only loaded when the background mode is 'skybox'
+	pub secondary_skybox_material_id: Option<MaterialId>,
 	pub skybox_sampler: wgpu::Sampler,
+	pub skybox_settings_buffer: wgpu::Buffer,
+	
+	// gradient background render data
+	pub gradient_background_buffer: wgpu::Buffer,
+	
+	// foliage render data
+	pub foliage: Option<FoliageRenderData>, // only loaded when `foliage_enabled` is true
+	
+	// shadow catcher render data
+	pub shadow_catcher_buffer: wgpu::Buffer,
+	
+	// debug lines render data
+	pub axis_gizmo: Option<DebugLinesRenderData>, // only loaded when `axis_gizmo_enabled` is true
+	pub debug_lines: DebugLinesRenderData, // holds `ProgramData::debug_lines`, re-written every frame
+	pub debug_lines_capacity: usize, // how many vertices fit in `debug_lines.vertex_buffer`, it's re-allocated when `ProgramData::debug_lines` outgrows it
+	
+	// text render data
+	pub text: TextRenderData,
+	
+	// bloom render data
+	pub bloom: BloomRenderData,
 	
 	// color correction data
 	pub color_correction_buffer: wgpu::Buffer,
 	
+	// lighting data
+	pub lighting_buffer: wgpu::Buffer,
+	pub lights_buffer: wgpu::Buffer,
+	pub lights_capacity: usize, // how many lights fit in `lights_buffer`, it's re-allocated when `ProgramData::lights` outgrows it
+	pub directional_light_buffer: wgpu::Buffer,
+	pub ibl: IblRenderData,
+	
+}
+
+impl RenderAssets {
+	// every scene pass draws to this format, which can hold values above 1 (the color correction pass is the only one that draws to the surface)
+	pub const MAIN_TEX_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
 }
 
 pub struct MaterialsStorage {
@@ -307,32 +1714,326 @@ impl MaterialsStorage {
 			list_cube: vec!(),
 		}
 	}
+	// these return the cached `MaterialId` when a material with the same path (and loaded the same way) already exists, so shared textures are only decoded and uploaded once
+	pub fn get_or_load_2d(&mut self, render_context: &RenderContextData, path: impl Into<PathBuf>, compress_textures: bool) -> Result<MaterialId> {
+		materials_storage_utils::insert_material_2d(path, self, render_context, compress_textures)
+	}
+	pub fn get_or_load_cube(&mut self, render_context: &RenderContextData, path: impl Into<PathBuf>, compress_textures: bool) -> Result<MaterialId> {
+		materials_storage_utils::insert_material_cube(path, self, render_context, compress_textures)
+	}
 }
 
 pub type MaterialId = usize;
 
 pub struct MaterialRenderData {
-	// `path` is used to make sure the same data isn't loaded multiple times
+	// `path`, `compression_format`, and `is_srgb` are used to make sure the same data isn't loaded multiple times (see `materials_storage_utils::get_material_id()`)
 	pub path: PathBuf,
+	pub compression_format: CompressionFormat,
+	pub is_srgb: bool, // false for textures that hold data instead of colors (like normal maps)
 	pub view: wgpu::TextureView,
 }
 
 pub struct ModelsRenderData {
 	// defines the data per model
-	pub culled_instances_buffer: wgpu::Buffer,
-	pub culled_instances_count: u32,
+	// HELP: when `double_buffer_instances` is enabled there are two culled instance buffers which are alternated each frame, so the buffer being written to is never the one the gpu might still be reading from for the previous frame
+	pub culled_instances_buffers: Vec<wgpu::Buffer>,
+	pub culled_instances_counts: Vec<u32>,
+	pub culled_instances_index: usize,
 	pub total_instances_buffer: wgpu::Buffer,
 	pub total_instances_count: u32,
+	// HELP: `total_instances_buffer` is only uploaded once, so any instances in `ProgramData::model_instance_datas` that are
+	// changed after loading need to be marked with `mark_instance_dirty()` (which `ProgramData::set_model_instance()` does),
+	// and only those parts of the buffer are re-uploaded
+	pub dirty_instance_ranges: Vec<std::ops::Range<usize>>,
+	// HELP: when `indirect_draws` is enabled, this holds one set of draw args per mesh (each `INDIRECT_ARGS_STRIDE` bytes apart), which are re-written every frame to match the culled instance count
+	pub indirect_args_buffer: Option<wgpu::Buffer>,
+	pub gpu_culling: Option<ComputeRenderData>, // only exists when `gpu_culling` is enabled and the model has instances but no transparent meshes
+	// HELP: when `max_shadow_instances` limits the shadow pass, only the nearest instances are written to this buffer each frame, otherwise the shadow pass uses `total_instances_buffer`
+	pub shadow_instances_buffer: Option<wgpu::Buffer>,
+	pub shadow_instances_count: u32,
 	pub bounding_radius: f32,
+	pub aabb: Aabb, // in model space, covers every mesh (but not the lods)
 	pub meshes: Vec<MeshRenderData>, // defines the data for a single model
+	// HELP: the lower detail versions of `meshes` (lod 1 and up), which are loaded from files next to the model with '_lod1', '_lod2', etc added
+	// to the name. Models with transparent meshes or `gpu_culling` don't use lods, since their instances can't be split up
+	pub lods: Vec<LodRenderData>,
+}
+
+impl ModelsRenderData {
+	// non-indexed meshes use the smaller `DrawIndirectArgs`, but every mesh gets the same amount of space so the offsets are easy to find
+	pub const INDIRECT_ARGS_STRIDE: wgpu::BufferAddress = std::mem::size_of::<wgpu::util::DrawIndexedIndirectArgs>() as wgpu::BufferAddress;
+	// `instance_count` is the second u32 in both kinds of draw args
+	pub const INDIRECT_INSTANCE_COUNT_OFFSET: wgpu::BufferAddress = 4;
+	pub fn culled_instances_buffer(&self) -> &wgpu::Buffer {
+		&self.culled_instances_buffers[self.culled_instances_index]
+	}
+	pub fn culled_instances_count(&self) -> u32 {
+		self.culled_instances_counts[self.culled_instances_index]
+	}
+	pub fn shadow_instances(&self) -> (&wgpu::Buffer, u32) {
+		match &self.shadow_instances_buffer {
+			Some(shadow_instances_buffer) => (shadow_instances_buffer, self.shadow_instances_count),
+			None => (&self.total_instances_buffer, self.total_instances_count),
+		}
+	}
+	pub fn has_transparent_meshes(&self) -> bool {
+		self.meshes.iter().any(|mesh| mesh.alpha_mode == MaterialAlphaMode::Blend)
+	}
+	pub fn rotate_culled_instances_buffers(&mut self) {
+		self.culled_instances_index = (self.culled_instances_index + 1) % self.culled_instances_buffers.len();
+	}
+	pub fn mark_instance_dirty(&mut self, index: usize) {
+		// instances are usually marked in order, so this catches most of the merging early
+		if let Some(last_range) = self.dirty_instance_ranges.last_mut() {
+			if last_range.contains(&index) {return;}
+			if last_range.end == index {
+				last_range.end += 1;
+				return;
+			}
+		}
+		self.dirty_instance_ranges.push(index..index + 1);
+	}
+	// returns the dirty ranges sorted and with any overlapping or touching ranges merged, so each one can be a single `write_buffer()`,
+	// and anything past `instance_count` is dropped since it has nothing to upload
+	pub fn take_dirty_instance_ranges(&mut self, instance_count: usize) -> Vec<std::ops::Range<usize>> {
+		let mut ranges = std::mem::take(&mut self.dirty_instance_ranges);
+		ranges.retain_mut(|range| {
+			range.end = range.end.min(instance_count);
+			range.start < range.end
+		});
+		ranges.sort_unstable_by_key(|range| range.start);
+		let mut output: Vec<std::ops::Range<usize>> = Vec::with_capacity(ranges.len());
+		for range in ranges {
+			if let Some(last_range) = output.last_mut() {
+				if range.start <= last_range.end {
+					last_range.end = last_range.end.max(range.end);
+					continue;
+				}
+			}
+			output.push(range);
+		}
+		output
+	}
 }
 
 pub struct MeshRenderData {
 	pub basic_vertex_buffer: wgpu::Buffer,
 	pub extended_vertex_buffer: wgpu::Buffer,
-	pub index_buffer: wgpu::Buffer,
+	pub index_buffer: Option<wgpu::Buffer>, // meshes without an index buffer are drawn as a plain list of triangles
 	pub index_count: u32,
+	pub vertex_count: u32,
+	pub material_id: MaterialId,
+	pub normal_map_material_id: MaterialId, // meshes without a normal map use a flat one
+	pub uv_transform: glam::Affine2, // applied to the tex coords in the vertex shader, used for tiling and atlases
+	pub uv_transform_buffer: wgpu::Buffer,
+	pub alpha_mode: MaterialAlphaMode,
+	pub double_sided: bool, // drawn with `models_double_sided_pipeline`, which doesn't cull back faces
+	pub material_settings_buffer: wgpu::Buffer,
+}
+
+// NOTE: none of the formats that `compress_textures` uses keep the alpha channel (see `CompressionFormat`), so `Blend` and `Mask` need it to be disabled
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum MaterialAlphaMode {
+	Opaque, // the texture's alpha is ignored
+	Blend, // drawn after the background in its own pass, sorted back-to-front
+	Mask {cutoff: f32}, // fragments with an alpha below the cutoff are discarded, meant for foliage, fences, etc
+}
+
+impl MaterialAlphaMode {
+	// NOTE: the layout matches the wgsl struct `MaterialSettings` (padded to 16 bytes)
+	pub fn build_gpu_data(&self) -> [f32; 4] {
+		let alpha_cutoff = match self {
+			Self::Mask {cutoff} => *cutoff,
+			Self::Opaque | Self::Blend => 0.0, // nothing has an alpha below 0, so nothing is discarded
+		};
+		[alpha_cutoff, 0.0, 0.0, 0.0]
+	}
+}
+
+// converts a uv transform to a wgsl `mat3x3f` (each column is padded to 16 bytes)
+pub fn uv_transform_to_gpu_data(uv_transform: glam::Affine2) -> [[f32; 4]; 3] {
+	let x_axis = uv_transform.matrix2.x_axis;
+	let y_axis = uv_transform.matrix2.y_axis;
+	let translation = uv_transform.translation;
+	[
+		[x_axis.x, x_axis.y, 0.0, 0.0],
+		[y_axis.x, y_axis.y, 0.0, 0.0],
+		[translation.x, translation.y, 1.0, 0.0],
+	]
+}
+
+pub struct FoliageRenderData {
+	pub instances_buffer: wgpu::Buffer,
+	pub instances_count: u32,
 	pub material_id: MaterialId,
+	pub settings_buffer: wgpu::Buffer,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Aabb {
+	pub min: glam::Vec3,
+	pub max: glam::Vec3,
+}
+
+impl Aabb {
+	// `add_point()` works on this without needing a first point, but it has to have at least one point added before it's used
+	pub const EMPTY: Self = Self {
+		min: glam::Vec3::INFINITY,
+		max: glam::Vec3::NEG_INFINITY,
+	};
+	// the pairs of indices into `get_corners()` that make up the 12 edges
+	pub const EDGES: [(usize, usize); 12] = [
+		(0, 1), (2, 3), (4, 5), (6, 7),
+		(0, 2), (1, 3), (4, 6), (5, 7),
+		(0, 4), (1, 5), (2, 6), (3, 7),
+	];
+	pub fn add_point(&mut self, point: glam::Vec3) {
+		self.min = self.min.min(point);
+		self.max = self.max.max(point);
+	}
+	pub fn is_empty(&self) -> bool {
+		self.min.cmpgt(self.max).any()
+	}
+	// bit 0 of the index picks the x side, bit 1 picks the y side, and bit 2 picks the z side
+	pub fn get_corners(&self) -> [glam::Vec3; 8] {
+		std::array::from_fn(|i| glam::Vec3::select(glam::BVec3::new(i & 1 != 0, i & 2 != 0, i & 4 != 0), self.max, self.min))
+	}
+	// the smallest axis-aligned box that contains this box after it's transformed
+	pub fn transformed(&self, transform: &glam::Mat4) -> Self {
+		let mut output = Self::EMPTY;
+		for corner in self.get_corners() {
+			output.add_point(transform.transform_point3(corner));
+		}
+		output
+	}
+}
+
+// the instances are sorted into lods after culling, so each lod gets its own buffer of visible instances
+// NOTE: unlike the culled instances buffers, these aren't double-buffered
+pub struct LodRenderData {
+	pub meshes: Vec<MeshRenderData>,
+	pub instances_buffer: wgpu::Buffer, // big enough for every instance of the model
+	pub instances_count: u32,
+}
+
+// HELP: the culling compute shader reads every instance from `ModelsRenderData::total_instances_buffer`, writes the visible
+// ones (with their fade) into the current culled instances buffer, and counts them in `visible_count_buffer`, which is
+// then copied into the `instance_count` of every mesh's draw args, so the cpu never needs to know how many are visible
+pub struct ComputeRenderData {
+	pub settings_buffer: wgpu::Buffer, // see `CullingSettings`
+	pub visible_count_buffer: wgpu::Buffer, // a single atomic u32, cleared before each dispatch
+}
+
+impl ComputeRenderData {
+	pub const WORKGROUP_SIZE: u32 = 64; // has to match `@workgroup_size` in 'culling.wgsl'
+}
+
+// HELP: each model's instances are split into groups of `GROUP_SIZE` consecutive instances (which are usually close
+// together), and after the opaque passes each group's bounding box is drawn against the depth buffer inside an occlusion
+// query. The results are read back the same way as `GpuTimer`'s, so a group is only skipped starting from the frame after
+// its query found it hidden (or later, if the readback is slow). Because of that one frame of latency, groups that come
+// out from behind something can pop in a frame late, which is the price of not stalling the cpu on the results
+pub struct OcclusionRenderData {
+	pub query_set: wgpu::QuerySet,
+	pub boxes_buffer: wgpu::Buffer, // one `OcclusionBox` per query
+	pub resolve_buffer: wgpu::Buffer,
+	pub readback_buffer: wgpu::Buffer,
+	pub occluded_groups: Vec<Vec<bool>>, // occluded_groups[i][j] is whether group j of render_assets.models[i] was hidden the last time it was queried
+	pub queried_groups: Vec<(usize, usize)>, // the (model index, group index) of each query in the frame that's being recorded
+	pub readback_queried_groups: Vec<(usize, usize)>, // `queried_groups` for the frame that's being read back
+	pub readback_state: std::sync::Arc<std::sync::atomic::AtomicU8>, // one of the `GpuTimer::READBACK_*` values
+}
+
+impl OcclusionRenderData {
+	
+	pub const GROUP_SIZE: usize = 64;
+	pub const MAX_QUERIES: u32 = 4096; // groups past this aren't queried, so they're always drawn
+	pub const RESULT_SIZE: wgpu::BufferAddress = std::mem::size_of::<u64>() as wgpu::BufferAddress; // occlusion results are the number of samples that passed
+	
+	pub fn is_occluded(&self, model_index: usize, instance_index: usize) -> bool {
+		self.occluded_groups[model_index][instance_index / Self::GROUP_SIZE]
+	}
+	
+	// queries can only be recorded while the previous results aren't being read
+	pub fn can_record(&self) -> bool {
+		use std::sync::atomic::Ordering;
+		self.readback_state.load(Ordering::Acquire) == GpuTimer::READBACK_IDLE
+	}
+	
+	pub fn resolve(&self, encoder: &mut wgpu::CommandEncoder) {
+		if self.queried_groups.is_empty() {return;}
+		let query_count = self.queried_groups.len() as u32;
+		encoder.resolve_query_set(&self.query_set, 0..query_count, &self.resolve_buffer, 0);
+		encoder.copy_buffer_to_buffer(&self.resolve_buffer, 0, &self.readback_buffer, 0, query_count as u64 * Self::RESULT_SIZE);
+	}
+	
+	// this has to be called after the frame's commands are submitted
+	pub fn end_frame(&mut self) {
+		use std::sync::atomic::Ordering;
+		if self.queried_groups.is_empty() {return;}
+		self.readback_queried_groups = std::mem::take(&mut self.queried_groups);
+		self.readback_state.store(GpuTimer::READBACK_MAPPING, Ordering::Release);
+		let readback_state = self.readback_state.clone();
+		self.readback_buffer.slice(..).map_async(wgpu::MapMode::Read, move |result| {
+			let new_state = if result.is_ok() {GpuTimer::READBACK_MAPPED} else {GpuTimer::READBACK_IDLE};
+			readback_state.store(new_state, Ordering::Release);
+		});
+	}
+	
+	// updates `occluded_groups` if a frame's results finished reading since the last call
+	pub fn take_results(&mut self, device: &wgpu::Device) {
+		use std::sync::atomic::Ordering;
+		device.poll(wgpu::Maintain::Poll);
+		if self.readback_state.load(Ordering::Acquire) != GpuTimer::READBACK_MAPPED {return;}
+		{
+			let mapped_range = self.readback_buffer.slice(..).get_mapped_range();
+			let stride = Self::RESULT_SIZE as usize;
+			for (i, (model_index, group_index)) in self.readback_queried_groups.iter().enumerate() {
+				let passed_samples = bytemuck::pod_read_unaligned::<u64>(&mapped_range[i * stride .. i * stride + 8]);
+				self.occluded_groups[*model_index][*group_index] = passed_samples == 0;
+			}
+		}
+		self.readback_buffer.unmap();
+		self.readback_state.store(GpuTimer::READBACK_IDLE, Ordering::Release);
+	}
+	
+}
+
+pub struct BloomRenderData {
+	pub mip_views: Vec<wgpu::TextureView>, // each mip is half the size of the previous one, starting at half the size of the surface
+}
+
+impl BloomRenderData {
+	pub const MAX_MIP_COUNT: usize = 6;
+}
+
+// image-based lighting, generated from the skybox when it's loaded (without a skybox these are 1x1 placeholders that just give a flat ambient light)
+pub struct IblRenderData {
+	pub irradiance_view: wgpu::TextureView, // the diffuse light coming from each direction
+	pub prefiltered_view: wgpu::TextureView, // the specular reflections, each mip is blurred for a higher roughness
+	pub brdf_lut_view: wgpu::TextureView,
+	pub sampler: wgpu::Sampler,
+}
+
+impl IblRenderData {
+	pub const IRRADIANCE_SIZE: u32 = 32;
+	pub const PREFILTERED_SIZE: u32 = 128;
+	pub const PREFILTERED_MIP_COUNT: u32 = 5;
+	pub const BRDF_LUT_SIZE: u32 = 128;
+	pub const PLACEHOLDER_AMBIENT_COLOR: [u8; 4] = [115, 115, 128, 255]; // (0.45, 0.45, 0.5), linear
+}
+
+pub struct DebugLinesRenderData {
+	pub vertex_buffer: wgpu::Buffer,
+	pub vertex_count: u32,
+	pub mode: DebugDrawMode,
+}
+
+pub struct TextRenderData {
+	pub settings_buffer: wgpu::Buffer,
+	pub glyphs_buffer: wgpu::Buffer, // re-written every frame from `ProgramData::stats_text`
+	pub glyphs_capacity: usize, // how many glyphs fit in `glyphs_buffer`, it's re-allocated when the text outgrows it
+	pub glyph_count: u32,
 }
 
 // HELP: Many structs like this only have whatever data is actually used, if you run into
@@ -343,9 +2044,11 @@ pub struct DepthRenderData {
 }
 
 pub struct ShadowCasterRenderData {
-	pub depth_tex_view: wgpu::TextureView,
+	pub depth_tex_view: wgpu::TextureView, // every cascade as a texture array, which is what the shaders sample
+	pub cascade_depth_tex_views: Vec<wgpu::TextureView>, // one layer each, which is what the shadow caster passes render to
 	pub depth_sampler: wgpu::Sampler,
-	pub proj_mat_buffer: wgpu::Buffer,
+	pub cascades_buffer: wgpu::Buffer, // holds `ShadowCascadesGpuData`
+	pub cascade_proj_mat_buffers: Vec<wgpu::Buffer>, // one per cascade, used by the shadow caster passes
 }
 
 // HELP: It may be a bit disorienting to have two Camera structs, but just keep this is
@@ -361,18 +2064,46 @@ pub struct CameraRenderData {
 pub struct RenderBindings {
 	
 	// shadow_caster render data
-	pub shadow_caster_bind_0: wgpu::BindGroup,
+	pub shadow_caster_bind_0s: Vec<wgpu::BindGroup>, // one per cascade
 	
 	// models render data
 	pub models_bind_0: wgpu::BindGroup,
-	pub example_models_bind_1s: Vec<wgpu::BindGroup>, // corresponds to the vec in render_assets.example_models.meshes
+	pub models_bind_1s: Vec<Vec<wgpu::BindGroup>>, // models_bind_1s[i][j] corresponds to render_assets.models[i].meshes[j]
+	pub models_lod_bind_1s: Vec<Vec<Vec<wgpu::BindGroup>>>, // models_lod_bind_1s[i][j][k] corresponds to render_assets.models[i].lods[j].meshes[k]
+	
+	// culling render data
+	pub culling_bind_0s: Vec<Vec<wgpu::BindGroup>>, // culling_bind_0s[i][j] writes to render_assets.models[i].culled_instances_buffers[j], empty for models without `gpu_culling`
+	
+	// occlusion render data
+	pub occlusion_bind_0: wgpu::BindGroup,
 	
 	// skybox render data
-	pub skybox_bind_0: wgpu::BindGroup,
+	pub skybox_bind_0: Option<wgpu::BindGroup>,
+	
+	// gradient background render data
+	pub gradient_background_bind_0: wgpu::BindGroup,
+	
+	// foliage render data
+	pub foliage_bind_0: Option<wgpu::BindGroup>,
+	
+	// shadow catcher render data
+	pub shadow_catcher_bind_0: wgpu::BindGroup,
+	
+	// debug lines render data
+	pub debug_lines_bind_0: wgpu::BindGroup,
 	
 	// color correction data
+	// `bloom_bind_0s[0]` reads from the main texture and `bloom_bind_0s[i]` reads from bloom mip `i - 1`
+	pub bloom_bind_0s: Vec<wgpu::BindGroup>,
+	
 	pub color_correction_bind_0: wgpu::BindGroup,
 	
+	// shadowmap debug render data
+	pub shadowmap_debug_bind_0: wgpu::BindGroup,
+	
+	// text render data
+	pub text_bind_0: wgpu::BindGroup,
+	
 }
 
 
@@ -404,12 +2135,15 @@ impl BasicVertexData {
 pub struct ExtendedVertexData {
 	pub tex_coords: [f32; 2],
 	pub normal: [f32; 3],
+	pub tangent: [f32; 4], // w is the handedness of the bitangent (1 or -1)
 }
 
 impl ExtendedVertexData {
-	pub const ATTRIBUTES: [wgpu::VertexAttribute; 2] = wgpu::vertex_attr_array![
+	// NOTE: the tangent is at location 8 because 3 to 7 are used by `RawInstanceData`
+	pub const ATTRIBUTES: [wgpu::VertexAttribute; 3] = wgpu::vertex_attr_array![
 		1 => Float32x2,
 		2 => Float32x3,
+		8 => Float32x4,
 	];
 	pub const fn get_layout() -> wgpu::VertexBufferLayout<'static> {
 		wgpu::VertexBufferLayout {
@@ -428,10 +2162,13 @@ pub struct InstanceData {
 }
 
 impl InstanceData {
+	pub fn get_transform(&self) -> glam::Mat4 {
+		glam::Mat4::from_translation(self.pos) * glam::Mat4::from_quat(self.rot)
+	}
 	pub fn to_raw(&self) -> RawInstanceData {
-		let model_data = glam::Mat4::from_translation(self.pos) * glam::Mat4::from_quat(self.rot);
 		RawInstanceData {
-			model: model_data.to_cols_array_2d(),
+			model: self.get_transform().to_cols_array_2d(),
+			fade: 1.0,
 		}
 	}
 }
@@ -440,14 +2177,16 @@ impl InstanceData {
 #[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
 pub struct RawInstanceData {
 	pub model: [[f32; 4]; 4],
+	pub fade: f32, // 1 = fully visible, 0 = fully faded out (used for the draw distance)
 }
 
 impl RawInstanceData {
-	pub const ATTRIBUTES: [wgpu::VertexAttribute; 4] = wgpu::vertex_attr_array![
+	pub const ATTRIBUTES: [wgpu::VertexAttribute; 5] = wgpu::vertex_attr_array![
 		3 => Float32x4,
 		4 => Float32x4,
 		5 => Float32x4,
-		6 => Float32x4
+		6 => Float32x4,
+		7 => Float32,
 	];
 	pub const fn get_layout() -> wgpu::VertexBufferLayout<'static> {
 		use std::mem;
@@ -458,3 +2197,306 @@ impl RawInstanceData {
 		}
 	}
 }
+
+
+
+// HELP: foliage doesn't use any vertex buffers, the quads are generated in the vertex shader
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct FoliageInstanceData {
+	pub pos: [f32; 3],
+	pub rot: f32, // rotation around the y axis, in radians
+	pub scale: f32,
+}
+
+impl FoliageInstanceData {
+	pub const ATTRIBUTES: [wgpu::VertexAttribute; 3] = wgpu::vertex_attr_array![
+		0 => Float32x3,
+		1 => Float32,
+		2 => Float32,
+	];
+	pub const fn get_layout() -> wgpu::VertexBufferLayout<'static> {
+		use std::mem;
+		wgpu::VertexBufferLayout {
+			array_stride: mem::size_of::<FoliageInstanceData>() as wgpu::BufferAddress,
+			step_mode: wgpu::VertexStepMode::Instance,
+			attributes: &Self::ATTRIBUTES,
+		}
+	}
+}
+
+
+
+// HELP: the boxes don't use any vertex buffers either, the 36 vertices of each box are generated in the vertex shader
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct OcclusionBox {
+	pub min: [f32; 3],
+	pub max: [f32; 3],
+}
+
+impl OcclusionBox {
+	pub const ATTRIBUTES: [wgpu::VertexAttribute; 2] = wgpu::vertex_attr_array![
+		0 => Float32x3,
+		1 => Float32x3,
+	];
+	pub const fn get_layout() -> wgpu::VertexBufferLayout<'static> {
+		use std::mem;
+		wgpu::VertexBufferLayout {
+			array_stride: mem::size_of::<OcclusionBox>() as wgpu::BufferAddress,
+			step_mode: wgpu::VertexStepMode::Instance,
+			attributes: &Self::ATTRIBUTES,
+		}
+	}
+}
+
+
+
+// HELP: each glyph is drawn as one instance of a quad, and the font is small enough that every glyph's pixels fit in
+// `bits` (see `TextGlyphData::get_bits()`), so the text pass doesn't need a font texture
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct TextGlyphData {
+	pub pos: [f32; 2], // the top-left corner, in screen pixels
+	pub color: [f32; 3],
+	pub bits: u32,
+}
+
+impl TextGlyphData {
+	pub const WIDTH: u32 = 3; // in font pixels, these have to match text.wgsl
+	pub const HEIGHT: u32 = 5;
+	pub const ADVANCE: u32 = Self::WIDTH + 1;
+	pub const LINE_HEIGHT: u32 = Self::HEIGHT + 2;
+	pub const ATTRIBUTES: [wgpu::VertexAttribute; 3] = wgpu::vertex_attr_array![
+		0 => Float32x2,
+		1 => Float32x3,
+		2 => Uint32,
+	];
+	pub const fn get_layout() -> wgpu::VertexBufferLayout<'static> {
+		wgpu::VertexBufferLayout {
+			array_stride: std::mem::size_of::<Self>() as wgpu::BufferAddress,
+			step_mode: wgpu::VertexStepMode::Instance,
+			attributes: &Self::ATTRIBUTES,
+		}
+	}
+	// each row is 3 bits with the leftmost pixel in the highest bit, and the rows go from top to bottom. Lowercase letters
+	// use the uppercase glyphs and anything else that isn't in the font is drawn as '?'
+	pub fn get_bits(character: char) -> u32 {
+		let rows: [u32; 5] = match character.to_ascii_uppercase() {
+			' ' => [0b000, 0b000, 0b000, 0b000, 0b000],
+			'0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+			'1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+			'2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+			'3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+			'4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+			'5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+			'6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+			'7' => [0b111, 0b001, 0b001, 0b001, 0b001],
+			'8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+			'9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+			'A' => [0b010, 0b101, 0b111, 0b101, 0b101],
+			'B' => [0b110, 0b101, 0b110, 0b101, 0b110],
+			'C' => [0b011, 0b100, 0b100, 0b100, 0b011],
+			'D' => [0b110, 0b101, 0b101, 0b101, 0b110],
+			'E' => [0b111, 0b100, 0b110, 0b100, 0b111],
+			'F' => [0b111, 0b100, 0b110, 0b100, 0b100],
+			'G' => [0b011, 0b100, 0b101, 0b101, 0b011],
+			'H' => [0b101, 0b101, 0b111, 0b101, 0b101],
+			'I' => [0b111, 0b010, 0b010, 0b010, 0b111],
+			'J' => [0b001, 0b001, 0b001, 0b101, 0b010],
+			'K' => [0b101, 0b101, 0b110, 0b101, 0b101],
+			'L' => [0b100, 0b100, 0b100, 0b100, 0b111],
+			'M' => [0b101, 0b111, 0b111, 0b101, 0b101],
+			'N' => [0b110, 0b101, 0b101, 0b101, 0b101],
+			'O' => [0b010, 0b101, 0b101, 0b101, 0b010],
+			'P' => [0b110, 0b101, 0b110, 0b100, 0b100],
+			'Q' => [0b010, 0b101, 0b101, 0b110, 0b011],
+			'R' => [0b110, 0b101, 0b110, 0b101, 0b101],
+			'S' => [0b011, 0b100, 0b010, 0b001, 0b110],
+			'T' => [0b111, 0b010, 0b010, 0b010, 0b010],
+			'U' => [0b101, 0b101, 0b101, 0b101, 0b111],
+			'V' => [0b101, 0b101, 0b101, 0b101, 0b010],
+			'W' => [0b101, 0b101, 0b111, 0b111, 0b101],
+			'X' => [0b101, 0b101, 0b010, 0b101, 0b101],
+			'Y' => [0b101, 0b101, 0b010, 0b010, 0b010],
+			'Z' => [0b111, 0b001, 0b010, 0b100, 0b111],
+			'.' => [0b000, 0b000, 0b000, 0b000, 0b010],
+			',' => [0b000, 0b000, 0b000, 0b010, 0b100],
+			':' => [0b000, 0b010, 0b000, 0b010, 0b000],
+			'-' => [0b000, 0b000, 0b111, 0b000, 0b000],
+			'/' => [0b001, 0b001, 0b010, 0b100, 0b100],
+			'(' => [0b001, 0b010, 0b010, 0b010, 0b001],
+			')' => [0b100, 0b010, 0b010, 0b010, 0b100],
+			'%' => [0b101, 0b001, 0b010, 0b100, 0b101],
+			_ => [0b111, 0b001, 0b011, 0b000, 0b010],
+		};
+		rows.iter().enumerate().fold(0, |bits, (i, row)| bits | row << (i as u32 * Self::WIDTH))
+	}
+}
+
+// HELP: debug lines are drawn as a plain line list, so every two vertices make one line
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct DebugLineVertexData {
+	pub pos: [f32; 3],
+	pub color: [f32; 3],
+}
+
+impl DebugLineVertexData {
+	pub const ATTRIBUTES: [wgpu::VertexAttribute; 2] = wgpu::vertex_attr_array![
+		0 => Float32x3,
+		1 => Float32x3,
+	];
+	pub const fn get_layout() -> wgpu::VertexBufferLayout<'static> {
+		wgpu::VertexBufferLayout {
+			array_stride: std::mem::size_of::<Self>() as wgpu::BufferAddress,
+			step_mode: wgpu::VertexStepMode::Vertex,
+			attributes: &Self::ATTRIBUTES,
+		}
+	}
+}
+
+
+
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	fn assert_close(actual: glam::Vec3, expected: glam::Vec3) {
+		assert!(actual.abs_diff_eq(expected, 1e-4), "expected {expected}, got {actual}");
+	}
+	fn assert_close_xy(actual: glam::Vec3, expected: glam::Vec2) {
+		assert!(actual.truncate().abs_diff_eq(expected, 1e-4), "expected {expected}, got {actual}");
+	}
+	
+	#[test]
+	fn perspective_maps_near_and_far_to_zero_and_one() {
+		let mut camera = CameraData::new((0.0, 0.0, 0.0));
+		camera.projection = CameraProjection::Perspective {fov_radians: 90.0f32.to_radians()};
+		let aspect_ratio = 2.0;
+		let proj = camera.build_proj_mat_with_range(aspect_ratio, 1.0, 100.0);
+		
+		assert_close(proj.project_point3(glam::Vec3::new(0.0, 0.0, -1.0)), glam::Vec3::new(0.0, 0.0, 0.0));
+		assert_close(proj.project_point3(glam::Vec3::new(0.0, 0.0, -100.0)), glam::Vec3::new(0.0, 0.0, 1.0));
+		// with a 90 degree fov, the top edge of the screen is as far up as the point is far away, and the side edge is that times the aspect ratio
+		assert_close_xy(proj.project_point3(glam::Vec3::new(2.0 * 10.0, 10.0, -10.0)), glam::Vec2::new(1.0, 1.0));
+		assert_close_xy(proj.project_point3(glam::Vec3::new(-2.0 * 10.0, -10.0, -10.0)), glam::Vec2::new(-1.0, -1.0));
+	}
+	
+	#[test]
+	fn orthographic_maps_near_and_far_to_zero_and_one() {
+		let mut camera = CameraData::new((0.0, 0.0, 0.0));
+		camera.projection = CameraProjection::Orthographic {height: 10.0};
+		let aspect_ratio = 1.5;
+		let proj = camera.build_proj_mat_with_range(aspect_ratio, 0.5, 50.0);
+		
+		assert_close(proj.project_point3(glam::Vec3::new(0.0, 0.0, -0.5)), glam::Vec3::new(0.0, 0.0, 0.0));
+		assert_close(proj.project_point3(glam::Vec3::new(0.0, 0.0, -50.0)), glam::Vec3::new(0.0, 0.0, 1.0));
+		// the corners don't depend on the distance, only on the height and aspect ratio
+		assert_close(proj.project_point3(glam::Vec3::new(7.5, 5.0, -0.5)), glam::Vec3::new(1.0, 1.0, 0.0));
+		assert_close(proj.project_point3(glam::Vec3::new(-7.5, -5.0, -50.0)), glam::Vec3::new(-1.0, -1.0, 1.0));
+	}
+	
+	#[test]
+	fn build_proj_mat_uses_the_camera_clip_planes() {
+		let camera = CameraData::new((0.0, 0.0, 0.0));
+		let aspect_ratio = 16.0 / 9.0;
+		assert_eq!(camera.build_proj_mat(aspect_ratio), camera.build_proj_mat_with_range(aspect_ratio, camera.near, camera.far));
+	}
+	
+	#[test]
+	fn losing_focus_releases_everything() {
+		let mut input = EngineInput {
+			pressed_keys: HashSet::new(),
+			prev_pressed_keys: HashSet::new(),
+			mouse_pos: PhysicalPosition::default(),
+			mouse_vel: PhysicalPosition::new(3.0, -2.0),
+			scroll_delta: 1.0,
+			capture_cursor: false,
+			cursor_is_locked: false,
+			pressed_mouse_buttons: PressedMouseButtons::default(),
+			prev_pressed_mouse_buttons: PressedMouseButtons::default(),
+			is_focused: true,
+		};
+		input.pressed_keys.insert(KeyCode::KeyW);
+		input.pressed_keys.insert(KeyCode::ShiftLeft);
+		let buttons = [MouseButton::Left, MouseButton::Right, MouseButton::Middle, MouseButton::Back, MouseButton::Forward, MouseButton::Other(7)];
+		for button in buttons {
+			input.pressed_mouse_buttons.set_is_down(button, true);
+		}
+		assert!(input.key_is_down(KeyCode::KeyW));
+		assert!(buttons.iter().all(|button| input.mouse_is_down(*button)));
+		
+		input.set_focused(false);
+		assert!(!input.is_focused);
+		assert!(!input.key_is_down(KeyCode::KeyW));
+		assert!(!input.key_is_down(KeyCode::ShiftLeft));
+		assert!(buttons.iter().all(|button| !input.mouse_is_down(*button)));
+		assert_eq!(input.mouse_vel, PhysicalPosition::default());
+		assert_eq!(input.scroll_delta, 0.0);
+	}
+	
+	#[test]
+	fn materials_are_only_loaded_once_per_path_and_flags() {
+		let mut engine_config = EngineConfig {
+			window_width: 64,
+			window_height: 64,
+			..EngineConfig::default()
+		};
+		if !load::headless_adapter_is_available(&engine_config) {
+			eprintln!("Skipping test because there's no adapter available.");
+			return;
+		}
+		let render_context = load::load_render_context_headless(&mut engine_config).expect("Failed to load headless render context.");
+		let mut materials_storage = MaterialsStorage::new();
+		
+		let first_id = materials_storage.get_or_load_2d(&render_context, "assets/happy-tree.png", false).expect("Failed to load texture.");
+		let second_id = materials_storage.get_or_load_2d(&render_context, "assets/happy-tree.png", false).expect("Failed to load texture.");
+		assert_eq!(first_id, second_id);
+		assert_eq!(materials_storage.list_2d.len(), 1);
+		
+		// compressing only makes a different texture when the adapter supports one of the compression formats
+		let compressed_id = materials_storage.get_or_load_2d(&render_context, "assets/happy-tree.png", true).expect("Failed to load texture.");
+		assert_eq!(compressed_id != first_id, render_context.compression_format != CompressionFormat::None);
+		
+		// the same pixels uploaded as colors and as data are different textures
+		let len_before = materials_storage.list_2d.len();
+		let srgb_id = materials_storage_utils::insert_material_2d_from_rgba8("#test_pixel", vec!(255; 4), (1, 1), &mut materials_storage, &render_context, false, true).expect("Failed to upload texture.");
+		let linear_id = materials_storage_utils::insert_material_2d_from_rgba8("#test_pixel", vec!(255; 4), (1, 1), &mut materials_storage, &render_context, false, false).expect("Failed to upload texture.");
+		assert_ne!(srgb_id, linear_id);
+		assert_eq!(materials_storage.list_2d.len(), len_before + 2);
+	}
+	
+	#[test]
+	fn only_changed_instances_are_re_uploaded() {
+		let engine_config = EngineConfig {
+			window_width: 64,
+			window_height: 64,
+			compress_textures: false,
+			..EngineConfig::default()
+		};
+		if !load::headless_adapter_is_available(&engine_config) {
+			eprintln!("Skipping test because there's no adapter available.");
+			return;
+		}
+		let mut program_data = load::load_headless_program_data(Instant::now(), engine_config).expect("Failed to load headless program data.");
+		let instance_count = program_data.model_instance_datas[0].len();
+		let moved_pos = glam::Vec3::new(1.0, 2.0, 3.0);
+		let moved_instance = || InstanceData {pos: moved_pos, rot: glam::Quat::IDENTITY};
+		program_data.set_model_instance(0, 3, moved_instance());
+		program_data.set_model_instance(0, 0, moved_instance());
+		program_data.set_model_instance(0, 2, moved_instance());
+		program_data.render_assets.models[0].mark_instance_dirty(instance_count + 10);
+		assert_eq!(program_data.model_instance_datas[0][3].pos, moved_pos);
+		assert_eq!(program_data.render_assets.models[0].take_dirty_instance_ranges(instance_count), vec!(0..1, 2..4));
+		
+		// the out of range index is dropped before the upload instead of panicking
+		program_data.set_model_instance(0, instance_count - 1, moved_instance());
+		program_data.render_assets.models[0].mark_instance_dirty(instance_count);
+		capture_utils::render_headless_frame(&mut program_data).expect("Failed to render headless frame.");
+		assert!(program_data.render_assets.models[0].dirty_instance_ranges.is_empty());
+	}
+	
+}
@@ -0,0 +1,661 @@
+// Started:      24/04/18
+// Last updated: 24/05/21
+
+// Learn Wgpu website: https://sotrh.github.io/learn-wgpu/
+// Learn Wgpu repo: https://github.com/sotrh/learn-wgpu
+// Skybox texture: https://opengameart.org/content/clouds-skybox-1
+
+
+
+#![feature(duration_constants)]
+
+#![allow(unused)]
+#![warn(unused_must_use)]
+
+#![allow(unused_doc_comments)]
+#![allow(clippy::new_without_default)]
+#![warn(clippy::todo)]
+#![deny(clippy::unwrap_used, clippy::panic)]
+
+// enable to see uses
+//#[warn(clippy::expect_used)]
+
+
+
+pub mod load;
+pub mod update;
+pub mod render;
+pub mod data;
+pub mod materials_storage_utils;
+pub mod capture_utils;
+pub mod ui_utils;
+pub mod utils;
+
+pub mod prelude {
+	pub use crate::{*, data::*, utils::IoResultFns};
+	pub use std::{
+		fs,
+		collections::{HashMap, HashSet},
+		path::{Path, PathBuf},
+		time::{Duration, Instant}
+	};
+	pub use std::result::Result as StdResult;
+	pub use log::{info, warn, debug, error};
+	pub use anyhow::*;
+}
+
+use crate::prelude::*;
+use std::env;
+use winit::{
+	application::ApplicationHandler,
+	dpi::{PhysicalPosition, PhysicalSize},
+	event::{DeviceEvent, DeviceId, KeyEvent, MouseButton, MouseScrollDelta, WindowEvent},
+	event_loop::{ActiveEventLoop, EventLoop},
+	keyboard::{KeyCode, PhysicalKey},
+	platform::pump_events::EventLoopExtPumpEvents,
+	window::{Fullscreen, Window, WindowId}
+};
+
+
+
+// this is everything that `main()` does, it's here so that 'main.rs' only has to call it
+pub fn run() -> Result<()> {
+	let start_time = Instant::now();
+	
+	if env::var("RUST_LOG").is_err() {
+		env::set_var("RUST_LOG", "warn");
+	}
+	env_logger::init();
+	
+	// HELP: With Winit 0.30.0, there's kinda a catch-22 here where A: we need the window
+	// to be available before we create the application struct, B: we need the
+	// application struct in order to start the event loop, and C: we need to start the
+	// event loop to create a window. So, we use EventLoopExtPumpEvents::pump_app_events
+	// to run the event loop until we can get a window, then use that to create the
+	// application struct, then use that to start the event loop. Although, I've heard
+	// that you can also store the window in an Option<Arc<>>, which allows you to store
+	// both the window and render context is the main state struct
+	let engine_config = load::load_engine_config().context("Failed to load engine config.")?;
+	
+	// `--capture <path>` renders one frame without a window and exits, see `capture_utils::capture_headless_frame()`
+	let args = env::args().collect::<Vec<_>>();
+	if let Some(index) = args.iter().position(|arg| arg == "--capture") {
+		let Some(output_path) = args.get(index + 1) else {return Err(Error::msg("Expected a file path after '--capture'."));};
+		capture_utils::capture_headless_frame(start_time, engine_config, output_path)?;
+		println!("Saved frame to {output_path:?}.");
+		return Ok(());
+	}
+	
+	info!("Running initialization event_loop...");
+	let mut event_loop = EventLoop::new().context("Failed to create event loop.")?;
+	let mut init_data = InitData {
+		window: None,
+		window_size: PhysicalSize::new(engine_config.window_width, engine_config.window_height),
+		window_title: engine_config.window_title.clone(),
+	};
+	let window = loop {
+		event_loop.pump_app_events(None, &mut init_data);
+		if let Some(window) = init_data.window {
+			break window;
+		}
+	};
+	
+	
+	info!("Done, initialing program...");
+	// the window is shown before loading so that the loading frame (see `render::render_loading_frame()`) is visible
+	window.set_visible(true);
+	let mut program_data = load::load_program_data(start_time, &window, engine_config)?;
+	// NOTE: the resulting `Resized` event is what updates the surface and render textures, same as any other resize
+	if program_data.engine_config.fullscreen {
+		window.set_fullscreen(Some(Fullscreen::Borderless(None)));
+	}
+	window.focus_window();
+	
+	println!("Done, starting main event_loop...");
+	event_loop.run_app(&mut program_data)?;
+	
+	Ok(())
+}
+
+
+
+
+
+// HELP: the entire purpose of this part is to get a usable window
+
+pub struct InitData {
+	pub window: Option<Window>,
+	pub window_size: PhysicalSize<u32>,
+	pub window_title: String,
+}
+
+impl ApplicationHandler for InitData {
+	
+	fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+		if self.window.is_none() {
+			let window_attributes = Window::default_attributes()
+				.with_title(&self.window_title)
+				.with_visible(false)
+				.with_inner_size(self.window_size);
+			let window = event_loop.create_window(window_attributes).expect("Could not init window.");
+			window.request_redraw();
+			self.window = Some(window);
+		}
+	}
+	
+	fn window_event(
+		&mut self,
+		_event_loop: &ActiveEventLoop,
+		_window_id: WindowId,
+		_event: WindowEvent,
+	) {}
+	
+	fn about_to_wait(&mut self, _event_loop: &ActiveEventLoop) {}
+}
+
+
+
+
+
+
+
+
+
+
+impl<'a> ApplicationHandler for ProgramData<'a> {
+	
+	
+	
+	fn resumed(&mut self, _event_loop: &ActiveEventLoop) {
+		warn!("Resumed, idk what to do here");
+	}
+	
+	
+	
+	fn window_event(
+		&mut self,
+		event_loop: &ActiveEventLoop,
+		_window_id: WindowId,
+		event: WindowEvent,
+	) {
+		let program_data = self;
+		
+		let pixels_per_point = program_data.render_context.window.map_or(1.0, |window| window.scale_factor() as f32);
+		ui_utils::process_window_event(&mut program_data.ui_state, &event, pixels_per_point, program_data.input.capture_cursor);
+		
+		match event {
+			
+			WindowEvent::ScaleFactorChanged {scale_factor, ..} => {
+				program_data.ui_state.screen_descriptor.pixels_per_point = scale_factor as f32;
+			}
+			
+			WindowEvent::RedrawRequested => {
+				if program_data.fatal_error.is_some() {
+					let result = redraw_fatal_error(program_data, event_loop);
+					if let Err(err) = result {
+						error!("Failed to show the fatal error, exiting: {err}");
+						event_loop.exit();
+					}
+					return;
+				}
+				let result = redraw_requested(program_data, event_loop);
+				if let Err(err) = result {
+					error!("Fatal error while processing frame: {err}");
+					program_data.fatal_error = Some(FatalError::new(&err));
+					update::set_cursor_captured(program_data, false);
+				}
+			}
+			
+			WindowEvent::Resized (new_size) => {
+				resize(program_data, new_size).expect("Failed to resize the window");
+			}
+			
+			WindowEvent::CloseRequested => {
+				event_loop.exit();
+			}
+			
+			WindowEvent::Focused (is_focused) => {
+				program_data.input.set_focused(is_focused);
+			}
+			
+			WindowEvent::KeyboardInput {
+				event: KeyEvent {
+					physical_key: PhysicalKey::Code (key),
+					state,
+					..
+				},
+				..
+			} => {
+				if state.is_pressed() {
+					program_data.input.pressed_keys.insert(key);
+				} else {
+					program_data.input.pressed_keys.remove(&key);
+				}
+			}
+			
+			WindowEvent::CursorMoved {device_id: _, position} => {
+				let input = &mut program_data.input;
+				let old_mouse_pos = input.mouse_pos;
+				input.mouse_pos = position;
+				if input.cursor_is_locked {
+					// the velocity is already handled in `device_event()`
+				} else if input.capture_cursor {
+					let size = program_data.render_context.surface_size;
+					let window_center = PhysicalPosition::new(size.width as f64 / 2.0, size.height as f64 / 2.0);
+					if position != window_center {
+						if let Some(window) = program_data.render_context.window {
+							let _ = window.set_cursor_position(window_center);
+						}
+					}
+					let old_mouse_pos = window_center; // this kinda shouldn't be needed, but it seems like the position reset is usually mixed with the next mouse movement. If `set_cursor_position()` always triggered its own separate event then this wouldn't be needed
+					input.mouse_vel = PhysicalPosition::new(input.mouse_vel.x + position.x - old_mouse_pos.x, input.mouse_vel.y + position.y - old_mouse_pos.y); // vel += new_pos - old_pos
+				} else {
+					input.mouse_vel = PhysicalPosition::new(input.mouse_vel.x + position.x - old_mouse_pos.x, input.mouse_vel.y + position.y - old_mouse_pos.y); // vel += new_pos - old_pos
+				}
+			}
+			
+			WindowEvent::MouseWheel {device_id: _, delta, phase: _} => {
+				let scroll_delta = match delta {
+					MouseScrollDelta::LineDelta (_x, y) => y,
+					// NOTE: trackpads give pixel deltas, so they're converted to roughly how many lines they would've been
+					MouseScrollDelta::PixelDelta (delta) => delta.y as f32 / PIXELS_PER_SCROLL_LINE,
+				};
+				program_data.input.scroll_delta += scroll_delta;
+			}
+			
+			WindowEvent::MouseInput {device_id: _, state, button} => {
+				program_data.input.pressed_mouse_buttons.set_is_down(button, state.is_pressed());
+			}
+			
+			_ => {},
+		}
+	}
+	
+	
+	
+	fn device_event(
+		&mut self,
+		_event_loop: &ActiveEventLoop,
+		_device_id: DeviceId,
+		event: DeviceEvent,
+	) {
+		let program_data = self;
+		
+		// HELP: a locked cursor doesn't move, so the raw mouse deltas are the only way to know how far the mouse moved
+		if let DeviceEvent::MouseMotion {delta} = event {
+			let input = &mut program_data.input;
+			if input.is_focused && input.capture_cursor && input.cursor_is_locked { // device events are sent even when the window isn't focused
+				input.mouse_vel = PhysicalPosition::new(input.mouse_vel.x + delta.0, input.mouse_vel.y + delta.1);
+			}
+		}
+	}
+	
+	
+	
+	fn about_to_wait(&mut self, _event_loop: &ActiveEventLoop) {
+		let program_data = self;
+		if let Some(window) = program_data.render_context.window {
+			window.request_redraw();
+		}
+	}
+	
+}
+
+
+
+pub const PIXELS_PER_SCROLL_LINE: f32 = 40.0;
+
+
+
+pub fn resize(program_data: &mut ProgramData, new_size: PhysicalSize<u32>) -> Result<()> {
+	let render_context = &mut program_data.render_context;
+	// the aspect ratio comes from the clamped size since that's what's actually rendered
+	let new_size = load::clamp_surface_size(new_size, render_context.device.limits().max_texture_dimension_2d);
+	render_context.surface_size = new_size;
+	render_context.aspect_ratio = new_size.width as f32 / new_size.height as f32;
+	render_context.surface_config.width = new_size.width;
+	render_context.surface_config.height = new_size.height;
+	program_data.ui_state.screen_descriptor.size_in_pixels = [new_size.width, new_size.height];
+	if new_size.width == 0 || new_size.height == 0 {return Ok(());}
+	if let Some(drawable_surface) = &render_context.drawable_surface {
+		drawable_surface.configure(&render_context.device, &render_context.surface_config);
+	}
+	if let Some(headless_texture) = &mut render_context.headless_texture {
+		*headless_texture = load::load_headless_texture(&render_context.device, &render_context.surface_config);
+	}
+	program_data.render_targets_resize_time = Some(Instant::now());
+	let camera_gpu_data = program_data.camera_data.build_gpu_data(render_context);
+	render_context.command_queue.write_buffer(&program_data.render_assets.camera.buffer, 0, bytemuck::cast_slice(&camera_gpu_data));
+	Ok(())
+}
+
+// HELP: dragging the edge of the window sends a resize event almost every frame, so instead of reallocating every
+// size-dependent texture each time, they're only recreated once the size has stopped changing for this long. Until
+// then the old textures are just stretched over the surface by the color correction pass
+pub const RENDER_TARGETS_RESIZE_DELAY: Duration = Duration::from_millis(100);
+
+pub fn resize_render_targets(program_data: &mut ProgramData) {
+	let render_context = &program_data.render_context;
+	let msaa_samples = program_data.engine_config.msaa_samples;
+	program_data.render_assets.depth = load::load_depth_render_data(render_context, msaa_samples);
+	program_data.render_assets.main_tex_view = load::load_main_tex_data(render_context);
+	program_data.render_assets.msaa_tex_view = load::load_msaa_tex_data(render_context, msaa_samples);
+	program_data.render_assets.bloom = load::load_bloom_render_data(render_context);
+	// the old bind groups still point at the old textures
+	program_data.render_bindings.bloom_bind_0s = load::load_bloom_bind_0s(render_context, &program_data.render_layouts, &program_data.render_assets);
+	program_data.render_bindings.color_correction_bind_0 = load::load_color_correction_bind_0(render_context, &program_data.render_layouts, &program_data.render_assets);
+}
+
+
+
+
+
+// HELP: only the settings that don't need a new device are applied (and only the ones listed here), everything else keeps the value it had at startup
+pub fn reload_engine_config(program_data: &mut ProgramData) -> Result<()> {
+	// invalid entries already fall back to their defaults, but a file that can't be decoded (or fails validation) is ignored until it's saved again
+	let new_engine_config = match load::load_engine_config() {
+		StdResult::Ok(v) => v,
+		StdResult::Err(err) => {
+			warn!("Failed to reload engine config, keeping the current one: {err:?}");
+			return Ok(());
+		}
+	};
+	let engine_config = &mut program_data.engine_config;
+	if new_engine_config.rendering_backend != engine_config.rendering_backend {
+		warn!("Changes to 'rendering_backend' need a restart to be applied.");
+	}
+	
+	engine_config.min_frame_time = new_engine_config.min_frame_time;
+	engine_config.frame_limiter = new_engine_config.frame_limiter;
+	
+	// same as when loading, an unsupported present mode falls back to one that's always supported
+	let new_present_mode = load::get_supported_present_mode(new_engine_config.present_mode, &program_data.render_context.supported_present_modes);
+	if new_present_mode != engine_config.present_mode || new_engine_config.desired_frame_latency != engine_config.desired_frame_latency {
+		engine_config.present_mode = new_present_mode;
+		engine_config.desired_frame_latency = new_engine_config.desired_frame_latency;
+		let render_context = &mut program_data.render_context;
+		render_context.surface_config.present_mode = new_present_mode;
+		render_context.surface_config.desired_maximum_frame_latency = new_engine_config.desired_frame_latency;
+		// minimized windows can't be configured, but `resize()` will use the new config once the window is restored
+		if let Some(drawable_surface) = &render_context.drawable_surface {
+			if render_context.surface_config.width > 0 && render_context.surface_config.height > 0 {
+				drawable_surface.configure(&render_context.device, &render_context.surface_config);
+			}
+		}
+	}
+	
+	// the max shadowmap size is only known once the device exists, so a size that's too big is ignored instead of stopping the program
+	let max_texture_size = program_data.render_context.device.limits().max_texture_dimension_2d;
+	if new_engine_config.shadowmap_size > max_texture_size {
+		warn!("Entry 'shadowmap_size' in 'engine config.hjson' must be at most {max_texture_size} on this device (found {}), keeping the current size.", new_engine_config.shadowmap_size);
+	} else if new_engine_config.shadowmap_size != engine_config.shadowmap_size {
+		engine_config.shadowmap_size = new_engine_config.shadowmap_size;
+		program_data.lighting_settings.shadowmap_texel_size = 1.0 / new_engine_config.shadowmap_size as f32;
+		program_data.render_assets.shadow_caster = load::load_shadow_caster_data(
+			&program_data.render_context,
+			new_engine_config.shadowmap_size,
+			&program_data.shadow_caster_data,
+			&program_data.directional_light,
+			&program_data.camera_data,
+		).context("Failed to recreate shadow caster render data.")?;
+		// several bind groups point at the shadowmap, so it's simplest to just recreate all of them
+		program_data.render_bindings = load::load_render_bindings(&program_data.render_context, &program_data.render_layouts, &program_data.render_assets)?;
+	}
+	
+	// the depth bias is baked into the models pipelines, so they have to be recreated for it to change
+	let engine_config = &mut program_data.engine_config;
+	if new_engine_config.models_depth_bias_constant != engine_config.models_depth_bias_constant || new_engine_config.models_depth_bias_slope != engine_config.models_depth_bias_slope {
+		engine_config.models_depth_bias_constant = new_engine_config.models_depth_bias_constant;
+		engine_config.models_depth_bias_slope = new_engine_config.models_depth_bias_slope;
+		let render_context = &program_data.render_context;
+		let engine_config = &program_data.engine_config;
+		match try_load_layouts(render_context, || load::load_models_layouts(render_context, engine_config)) {
+			StdResult::Ok((pipeline, wireframe_pipeline, transparent_pipeline, double_sided_pipeline, _, _)) => {
+				let render_layouts = &mut program_data.render_layouts;
+				render_layouts.models_pipeline = pipeline;
+				render_layouts.models_wireframe_pipeline = wireframe_pipeline;
+				render_layouts.models_transparent_pipeline = transparent_pipeline;
+				render_layouts.models_double_sided_pipeline = double_sided_pipeline;
+			}
+			StdResult::Err(err) => warn!("Failed to recreate the models pipelines with the new depth bias, keeping the old ones: {err:?}"),
+		}
+	}
+	
+	println!("Reloaded engine config.");
+	Ok(())
+}
+
+
+
+// HELP: only the pipelines are swapped, the bind group layouts that are created alongside them are
+// identical to the old ones (and wgpu deduplicates those), so the existing bind groups keep working
+pub fn reload_shaders(program_data: &mut ProgramData, changed_shaders: &HashSet<String>) {
+	let render_context = &program_data.render_context;
+	let engine_config = &program_data.engine_config;
+	let render_layouts = &mut program_data.render_layouts;
+	for shader_name in changed_shaders {
+		let result = match &**shader_name {
+			"shadow caster.wgsl" => try_load_layouts(render_context, || load::load_shadow_caster_layouts(render_context, engine_config)).map(|(pipeline, _)| {
+				render_layouts.shadow_caster_pipeline = pipeline;
+			}),
+			"models.wgsl" => try_load_layouts(render_context, || load::load_models_layouts(render_context, engine_config)).map(|(pipeline, wireframe_pipeline, transparent_pipeline, double_sided_pipeline, _, _)| {
+				render_layouts.models_pipeline = pipeline;
+				render_layouts.models_wireframe_pipeline = wireframe_pipeline;
+				render_layouts.models_transparent_pipeline = transparent_pipeline;
+				render_layouts.models_double_sided_pipeline = double_sided_pipeline;
+			}),
+			"culling.wgsl" if render_layouts.culling_pipeline.is_some() => try_load_layouts(render_context, || load::load_culling_layouts(render_context)).map(|(pipeline, _)| {
+				render_layouts.culling_pipeline = Some(pipeline);
+			}),
+			"occlusion boxes.wgsl" => try_load_layouts(render_context, || load::load_occlusion_layouts(render_context, engine_config)).map(|(pipeline, _)| {
+				render_layouts.occlusion_pipeline = pipeline;
+			}),
+			"skybox.wgsl" => try_load_layouts(render_context, || load::load_skybox_layouts(render_context, engine_config)).map(|(pipeline, _)| {
+				render_layouts.skybox_pipeline = pipeline;
+			}),
+			"gradient background.wgsl" => try_load_layouts(render_context, || load::load_gradient_background_layouts(render_context, engine_config)).map(|(pipeline, _)| {
+				render_layouts.gradient_background_pipeline = pipeline;
+			}),
+			"foliage.wgsl" => try_load_layouts(render_context, || load::load_foliage_layouts(render_context, engine_config)).map(|(pipeline, _)| {
+				render_layouts.foliage_pipeline = pipeline;
+			}),
+			"shadow catcher.wgsl" => try_load_layouts(render_context, || load::load_shadow_catcher_layouts(render_context, engine_config)).map(|(pipeline, _)| {
+				render_layouts.shadow_catcher_pipeline = pipeline;
+			}),
+			"debug lines.wgsl" => try_load_layouts(render_context, || load::load_debug_lines_layouts(render_context, engine_config)).map(|(pipeline, overlay_pipeline, _)| {
+				render_layouts.debug_lines_pipeline = pipeline;
+				render_layouts.debug_lines_overlay_pipeline = overlay_pipeline;
+			}),
+			"bloom.wgsl" => try_load_layouts(render_context, || load::load_bloom_layouts(render_context)).map(|(bright_pass_pipeline, downsample_pipeline, upsample_pipeline, _)| {
+				render_layouts.bloom_bright_pass_pipeline = bright_pass_pipeline;
+				render_layouts.bloom_downsample_pipeline = downsample_pipeline;
+				render_layouts.bloom_upsample_pipeline = upsample_pipeline;
+			}),
+			"color correction.wgsl" => try_load_layouts(render_context, || load::load_color_correction_layouts(render_context)).map(|(pipeline, _)| {
+				render_layouts.color_correction_pipeline = pipeline;
+			}),
+			"shadowmap debug.wgsl" => try_load_layouts(render_context, || load::load_shadowmap_debug_layouts(render_context)).map(|(pipeline, _)| {
+				render_layouts.shadowmap_debug_pipeline = pipeline;
+			}),
+			"text.wgsl" => try_load_layouts(render_context, || load::load_text_layouts(render_context)).map(|(pipeline, _)| {
+				render_layouts.text_pipeline = pipeline;
+			}),
+			_ => continue,
+		};
+		match result {
+			StdResult::Ok(()) => println!("Reloaded shader \"{shader_name}\"."),
+			StdResult::Err(err) => warn!("Failed to reload shader \"{shader_name}\", keeping the old pipeline: {err:?}"),
+		}
+	}
+}
+
+// NOTE: without the error scope, an invalid shader would go to wgpu's default error handler, which panics
+pub fn try_load_layouts<T>(render_context: &RenderContextData, load_fn: impl FnOnce() -> Result<T>) -> Result<T> {
+	render_context.device.push_error_scope(wgpu::ErrorFilter::Validation);
+	let output = load_fn();
+	if let Some(err) = async_std::task::block_on(render_context.device.pop_error_scope()) {
+		return Err(Error::msg(err.to_string()));
+	}
+	output
+}
+
+
+
+
+
+// shows the error until `FatalError::DISPLAY_DURATION` has passed or escape is pressed, then exits
+pub fn redraw_fatal_error(program_data: &mut ProgramData, event_loop: &ActiveEventLoop) -> Result<()> {
+	let Some(fatal_error) = &program_data.fatal_error else {return Ok(());};
+	if fatal_error.time_left().is_zero() || program_data.input.key_is_down(KeyCode::Escape) {
+		event_loop.exit();
+		return Ok(());
+	}
+	let size = program_data.render_context.surface_size;
+	if size.width == 0 || size.height == 0 {return Ok(());}
+	
+	let Some(drawable_surface) = &program_data.render_context.drawable_surface else {return Err(Error::msg("Cannot draw to the window without a surface."));};
+	// NOTE: the surface is only reconfigured here, the render targets might be what failed so they're left alone
+	let surface_output = match drawable_surface.get_current_texture() {
+		StdResult::Ok(v) => v,
+		StdResult::Err(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated) => {
+			drawable_surface.configure(&program_data.render_context.device, &program_data.render_context.surface_config);
+			drawable_surface.get_current_texture().context("Failed to get current window drawable texture, even after reconfiguring.")?
+		}
+		StdResult::Err(err) => return Err(err.into()),
+	};
+	let ui_output = ui_utils::run_fatal_error_ui(program_data);
+	render::render_fatal_error(&surface_output.texture, program_data, &ui_output);
+	surface_output.present();
+	
+	Ok(())
+}
+
+
+
+pub fn redraw_requested(program_data: &mut ProgramData, event_loop: &ActiveEventLoop) -> Result<()> {
+	
+	
+	let frame_start_time = Instant::now();
+	
+	let mut dt = program_data.step_dt();
+	if let Some(frame_exporter) = &program_data.frame_exporter {
+		dt = frame_exporter.dt;
+	}
+	if program_data.config_watcher.as_ref().is_some_and(|config_watcher| config_watcher.take_config_changed()) {
+		reload_engine_config(program_data).context("Failed to apply reloaded engine config.")?;
+	}
+	if let Some(shader_watcher) = &program_data.shader_watcher {
+		let changed_shaders = shader_watcher.take_changed_shaders();
+		if !changed_shaders.is_empty() {
+			reload_shaders(program_data, &changed_shaders);
+		}
+	}
+	let should_exit = update::update(program_data, dt)?;
+	if should_exit {
+		event_loop.exit();
+		return Ok(());
+	}
+	update::run_fixed_updates(program_data, dt);
+	if let Some(frame_exporter) = &program_data.frame_exporter {
+		frame_exporter.apply_camera_path(&mut program_data.camera_data);
+	}
+	
+	
+	// make sure to only render when the window is visible
+	let render_context = &program_data.render_context;
+	let size = render_context.surface_size;
+	if size.width > 0 && size.height > 0 {
+		
+		
+		// NOTE: headless program data never runs the event loop, so there's always a surface here
+		let Some(drawable_surface) = &render_context.drawable_surface else {return Err(Error::msg("Cannot draw to the window without a surface."));};
+		let surface_output_result = drawable_surface.get_current_texture();
+		let surface_output = match surface_output_result {
+			StdResult::Ok(v) => v,
+			StdResult::Err(wgpu::SurfaceError::Lost) => {
+				warn!("Surface was lost, attempting to resize...");
+				resize(program_data, render_context.surface_size).context("Failed to resize window.")?;
+				let drawable_surface = program_data.render_context.drawable_surface.as_ref().context("Surface was removed while resizing.")?;
+				drawable_surface.get_current_texture().context("Failed to get current window drawable texture, even after resize.")?
+			}
+			StdResult::Err(wgpu::SurfaceError::Outdated) => {
+				warn!("Surface is outdated, attempting to resize...");
+				resize(program_data, render_context.surface_size).context("Failed to resize window.")?;
+				let drawable_surface = program_data.render_context.drawable_surface.as_ref().context("Surface was removed while resizing.")?;
+				drawable_surface.get_current_texture().context("Failed to get current window drawable texture, even after resize.")?
+			}
+			StdResult::Err(wgpu::SurfaceError::OutOfMemory) => {
+				warn!("OutOfMemory error while rendering, exiting process.");
+				event_loop.exit();
+				return Ok(());
+			}
+			StdResult::Err(err) => return Err(err.into()),
+		};
+		
+		if program_data.render_targets_resize_time.is_some_and(|resize_time| resize_time.elapsed() >= RENDER_TARGETS_RESIZE_DELAY) {
+			program_data.render_targets_resize_time = None;
+			resize_render_targets(program_data);
+		}
+		
+		render::render(&surface_output.texture, program_data);
+		
+		// NOTE: this has to happen before `present()`, since the surface texture can't be read after it's presented
+		if program_data.screenshot_requested {
+			program_data.screenshot_requested = false;
+			match capture_utils::capture_screenshot(&surface_output.texture, &program_data.render_context) {
+				StdResult::Ok(path) => println!("Saved screenshot to {path:?}."),
+				StdResult::Err(err) => warn!("Failed to take screenshot: {err:?}"),
+			}
+		}
+		
+		if let Some(frame_exporter) = &mut program_data.frame_exporter {
+			let frame_path = frame_exporter.next_frame_path();
+			capture_utils::save_texture_to_png(&surface_output.texture, &frame_path, &program_data.render_context).context("Failed to export frame.")?;
+			frame_exporter.exported_frames += 1;
+			if frame_exporter.is_finished() {
+				println!("Done exporting {} frames to {:?}.", frame_exporter.total_frames, frame_exporter.output_folder);
+				event_loop.exit();
+			}
+		}
+		
+		
+		let engine_config = &program_data.engine_config;
+		program_data.frame_pacer.wait(frame_start_time, engine_config.min_frame_time, engine_config.frame_limiter);
+		
+		let gpu_timings = program_data.gpu_timer.as_mut().and_then(|gpu_timer| gpu_timer.take_pass_timings(&program_data.render_context.device));
+		let fps_counter_output = program_data.fps_counter.step(frame_start_time.elapsed(), gpu_timings);
+		if let Some((average_fps, average_frame_time, average_gpu_timings)) = fps_counter_output {
+			match average_gpu_timings {
+				Some(gpu_timings) => println!(
+					"FPS: {average_fps}  (avg frame time: {average_frame_time:?}, avg gpu time: {:?}, shadows: {:?}, models: {:?}, skybox: {:?})",
+					gpu_timings.total, gpu_timings.shadow_caster, gpu_timings.models, gpu_timings.skybox,
+				),
+				None => println!("FPS: {average_fps}  (avg frame time: {average_frame_time:?})"),
+			}
+			//unsafe { // for benchmarking
+			//	static mut TOTAL_TIME: Duration = Duration::ZERO;
+			//	static mut TOTAL_FRAMES: u32 = 0;
+			//	TOTAL_TIME += average_frame_time;
+			//	TOTAL_FRAMES += 1;
+			//	println!("total average: {:?}", TOTAL_TIME / TOTAL_FRAMES);
+			//}
+		}
+		
+		
+		if let Some(window) = program_data.render_context.window {
+			window.pre_present_notify();
+		}
+		surface_output.present();
+		
+		let input = &mut program_data.input;
+		input.mouse_vel = PhysicalPosition::default();
+		input.scroll_delta = 0.0;
+		input.prev_pressed_keys.clone_from(&input.pressed_keys);
+		input.prev_pressed_mouse_buttons = input.pressed_mouse_buttons.clone();
+		
+		
+	}
+	
+	
+	Ok(())
+}
@@ -0,0 +1,426 @@
+use crate::prelude::*;
+use serde::{Deserialize, Deserializer, de::{self, IntoDeserializer, Visitor, value::{Error as DeError, MapAccessDeserializer, SeqDeserializer}}};
+use serde_hjson::{Map, Value};
+
+// HELP: serde-hjson is built on serde 0.8, so its `Value` can't be given to anything that derives serde 1's
+// `Deserialize`. These wrap the values so that `EngineConfig` can be deserialized straight from the decoded file
+
+
+
+pub fn deserialize_hjson_map<T: de::DeserializeOwned>(map: &Map<String, Value>) -> StdResult<T, DeError> {
+	T::deserialize(MapAccessDeserializer::new(HjsonMapAccess::new(map)))
+}
+
+// HELP: an invalid entry is warned about and removed (so that it uses its default value) instead of failing the whole
+// struct. The entries before an invalid one are read again after it's removed, so their warnings can show up twice
+pub fn deserialize_hjson_map_lenient<T: de::DeserializeOwned>(map: &Map<String, Value>, file_name: &str) -> StdResult<T, DeError> {
+	let mut map = map.clone();
+	loop {
+		let mut map_access = HjsonMapAccess::new(&map);
+		let err = match T::deserialize(MapAccessDeserializer::new(&mut map_access)) {
+			StdResult::Ok(output) => return StdResult::Ok(output),
+			StdResult::Err(err) => err,
+		};
+		// errors that don't come from an entry can't be fixed by removing one
+		let Some(key) = map_access.last_key.cloned() else {return StdResult::Err(err);};
+		warn!("Invalid value for entry '{key}' in '{file_name}', using the default value.  (error: {err})");
+		map.remove(&key);
+	}
+}
+
+// HELP: serde only gives a struct's field names (after renames, and without the skipped fields) to `deserialize_struct()`,
+// so this deserializer just records them and then stops
+pub fn get_struct_field_names<T: de::DeserializeOwned>() -> &'static [&'static str] {
+	let mut field_names: &'static [&'static str] = &[];
+	let _ = T::deserialize(FieldNamesDeserializer (&mut field_names));
+	field_names
+}
+
+pub struct FieldNamesDeserializer<'a> (pub &'a mut &'static [&'static str]);
+
+impl<'de, 'a> Deserializer<'de> for FieldNamesDeserializer<'a> {
+	type Error = DeError;
+	
+	fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> StdResult<V::Value, DeError> {
+		StdResult::Err(de::Error::custom("only structs have field names"))
+	}
+	
+	fn deserialize_struct<V: Visitor<'de>>(self, _name: &'static str, fields: &'static [&'static str], _visitor: V) -> StdResult<V::Value, DeError> {
+		*self.0 = fields;
+		StdResult::Err(de::Error::custom("the field names have been recorded"))
+	}
+	
+	serde::forward_to_deserialize_any! {
+		bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf option
+		unit unit_struct newtype_struct seq tuple tuple_struct map enum identifier ignored_any
+	}
+	
+}
+
+
+
+#[derive(Copy, Clone)]
+pub struct HjsonDeserializer<'a> (pub &'a Value);
+
+impl<'de, 'a> Deserializer<'de> for HjsonDeserializer<'a> {
+	type Error = DeError;
+	
+	fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> StdResult<V::Value, DeError> {
+		match self.0 {
+			Value::Null => visitor.visit_unit(),
+			Value::Bool (value) => visitor.visit_bool(*value),
+			Value::I64 (value) => visitor.visit_i64(*value),
+			Value::U64 (value) => visitor.visit_u64(*value),
+			Value::F64 (value) => visitor.visit_f64(*value),
+			Value::String (value) => visitor.visit_str(value),
+			Value::Array (items) => {
+				// `end()` makes sure that lists with too many items (like `[1, 2, 3, 4]` for an `[f32; 3]`) are errors instead of being cut off
+				let mut items = SeqDeserializer::new(items.iter().map(HjsonDeserializer));
+				let output = visitor.visit_seq(&mut items)?;
+				items.end()?;
+				StdResult::Ok(output)
+			}
+			Value::Object (map) => visitor.visit_map(HjsonMapAccess::new(map)),
+		}
+	}
+	
+	fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> StdResult<V::Value, DeError> {
+		match self.0 {
+			Value::Null => visitor.visit_none(),
+			_ => visitor.visit_some(self),
+		}
+	}
+	
+	fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> StdResult<V::Value, DeError> {
+		visitor.visit_newtype_struct(self)
+	}
+	
+	serde::forward_to_deserialize_any! {
+		bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+		bytes byte_buf unit unit_struct seq tuple tuple_struct map struct enum identifier ignored_any
+	}
+	
+}
+
+impl<'de, 'a> IntoDeserializer<'de, DeError> for HjsonDeserializer<'a> {
+	type Deserializer = Self;
+	fn into_deserializer(self) -> Self {
+		self
+	}
+}
+
+
+
+// NOTE: this is used instead of `serde::de::value::MapDeserializer` so that errors can say which entry they came from
+pub struct HjsonMapAccess<'a> {
+	pub entries: std::vec::IntoIter<(&'a String, &'a Value)>,
+	pub current_entry: Option<(&'a String, &'a Value)>,
+	pub last_key: Option<&'a String>, // after an error, this is the entry that caused it
+}
+
+impl<'a> HjsonMapAccess<'a> {
+	pub fn new(map: &'a Map<String, Value>) -> Self {
+		Self {
+			entries: map.iter().collect::<Vec<_>>().into_iter(),
+			current_entry: None,
+			last_key: None,
+		}
+	}
+}
+
+impl<'de, 'a> de::MapAccess<'de> for HjsonMapAccess<'a> {
+	type Error = DeError;
+	
+	fn next_key_seed<K: de::DeserializeSeed<'de>>(&mut self, seed: K) -> StdResult<Option<K::Value>, DeError> {
+		let Some((key, value)) = self.entries.next() else {return StdResult::Ok(None);};
+		self.current_entry = Some((key, value));
+		self.last_key = Some(key);
+		seed.deserialize(key.as_str().into_deserializer()).map(Some)
+	}
+	
+	fn next_value_seed<V: de::DeserializeSeed<'de>>(&mut self, seed: V) -> StdResult<V::Value, DeError> {
+		let Some((key, value)) = self.current_entry.take() else {return StdResult::Err(de::Error::custom("tried to read a value before its key"));};
+		seed.deserialize(HjsonDeserializer(value)).map_err(|err| de::Error::custom(format!("entry '{key}': {err}")))
+	}
+	
+	fn size_hint(&self) -> Option<usize> {
+		Some(self.entries.len())
+	}
+	
+}
+
+
+
+
+
+// HELP: unknown values are warned about and replaced with the first choice (which is always the default) instead of
+// failing the whole config, so a typo in one of these doesn't stop the program from starting
+pub fn deserialize_choice<'de, D: Deserializer<'de>, T: Copy>(deserializer: D, key: &str, choices: &[(&str, T)]) -> StdResult<T, D::Error> {
+	let value = String::deserialize(deserializer)?.to_lowercase();
+	if let Some((_, choice)) = choices.iter().find(|(name, _)| *name == value) {
+		return StdResult::Ok(*choice);
+	}
+	let names = choices.iter().map(|(name, _)| format!("'{name}'")).collect::<Vec<_>>().join(", ");
+	let (default_name, default) = choices[0];
+	warn!("Unknown value for entry '{key}' in 'engine config.hjson', must be one of: {names}, defaulting to \"{default_name}\".");
+	StdResult::Ok(default)
+}
+
+pub fn deserialize_rendering_backend<'de, D: Deserializer<'de>>(deserializer: D) -> StdResult<wgpu::Backends, D::Error> {
+	deserialize_choice(deserializer, "rendering_backend", &[
+		("auto", wgpu::Backends::all()),
+		("vulkan", wgpu::Backends::VULKAN),
+		("dx12", wgpu::Backends::DX12),
+		("metal", wgpu::Backends::METAL),
+		("opengl", wgpu::Backends::GL),
+	])
+}
+
+pub fn deserialize_present_mode<'de, D: Deserializer<'de>>(deserializer: D) -> StdResult<wgpu::PresentMode, D::Error> {
+	deserialize_choice(deserializer, "present_mode", &[
+		("auto_vsync", wgpu::PresentMode::AutoVsync),
+		("auto_no_vsync", wgpu::PresentMode::AutoNoVsync),
+		("fifo", wgpu::PresentMode::Fifo),
+		("fifo_relaxed", wgpu::PresentMode::FifoRelaxed),
+		("immediate", wgpu::PresentMode::Immediate),
+		("mailbox", wgpu::PresentMode::Mailbox),
+	])
+}
+
+pub fn deserialize_surface_format<'de, D: Deserializer<'de>>(deserializer: D) -> StdResult<Option<wgpu::TextureFormat>, D::Error> {
+	deserialize_choice(deserializer, "surface_format", &[
+		("auto", None),
+		("bgra8unorm_srgb", Some(wgpu::TextureFormat::Bgra8UnormSrgb)),
+		("rgba8unorm_srgb", Some(wgpu::TextureFormat::Rgba8UnormSrgb)),
+		("bgra8unorm", Some(wgpu::TextureFormat::Bgra8Unorm)),
+		("rgba8unorm", Some(wgpu::TextureFormat::Rgba8Unorm)),
+		("rgba16float", Some(wgpu::TextureFormat::Rgba16Float)),
+		("rgb10a2unorm", Some(wgpu::TextureFormat::Rgb10a2Unorm)),
+	])
+}
+
+pub fn deserialize_background_mode<'de, D: Deserializer<'de>>(deserializer: D) -> StdResult<BackgroundMode, D::Error> {
+	deserialize_choice(deserializer, "background_mode", &[
+		("skybox", BackgroundMode::Skybox),
+		("gradient", BackgroundMode::Gradient),
+		("solid", BackgroundMode::Solid),
+	])
+}
+
+pub fn deserialize_axis_gizmo_mode<'de, D: Deserializer<'de>>(deserializer: D) -> StdResult<DebugDrawMode, D::Error> {
+	deserialize_choice(deserializer, "axis_gizmo_mode", &[
+		("overlay", DebugDrawMode::Overlay),
+		("depth_tested", DebugDrawMode::DepthTested),
+	])
+}
+
+pub fn deserialize_bounding_boxes_mode<'de, D: Deserializer<'de>>(deserializer: D) -> StdResult<BoundingBoxesMode, D::Error> {
+	deserialize_choice(deserializer, "bounding_boxes_mode", &[
+		("oriented", BoundingBoxesMode::Oriented),
+		("world", BoundingBoxesMode::World),
+	])
+}
+
+pub fn deserialize_tonemapper<'de, D: Deserializer<'de>>(deserializer: D) -> StdResult<u32, D::Error> {
+	deserialize_choice(deserializer, "tonemapper", &[
+		("aces", ColorCorrectionSettings::TONEMAPPER_ACES),
+		("reinhard", ColorCorrectionSettings::TONEMAPPER_REINHARD),
+		("none", ColorCorrectionSettings::TONEMAPPER_NONE),
+	])
+}
+
+pub fn deserialize_shadow_caster_kind<'de, D: Deserializer<'de>>(deserializer: D) -> StdResult<ShadowCasterKind, D::Error> {
+	deserialize_choice(deserializer, "shadow_caster_kind", &[
+		("directional", ShadowCasterKind::Directional),
+		("spot", ShadowCasterKind::Spot),
+	])
+}
+
+pub fn deserialize_power_preference<'de, D: Deserializer<'de>>(deserializer: D) -> StdResult<wgpu::PowerPreference, D::Error> {
+	deserialize_choice(deserializer, "power_preference", &[
+		("default", wgpu::PowerPreference::default()),
+		("low", wgpu::PowerPreference::LowPower),
+		("high", wgpu::PowerPreference::HighPerformance),
+	])
+}
+
+pub fn deserialize_camera_projection<'de, D: Deserializer<'de>>(deserializer: D) -> StdResult<CameraProjectionKind, D::Error> {
+	deserialize_choice(deserializer, "camera_projection", &[
+		("perspective", CameraProjectionKind::Perspective),
+		("orthographic", CameraProjectionKind::Orthographic),
+	])
+}
+
+pub fn deserialize_frame_limiter<'de, D: Deserializer<'de>>(deserializer: D) -> StdResult<FrameLimiter, D::Error> {
+	deserialize_choice(deserializer, "frame_limiter", &[
+		("hybrid", FrameLimiter::Hybrid),
+		("sleep", FrameLimiter::Sleep),
+		("off", FrameLimiter::Off),
+	])
+}
+
+
+
+pub fn deserialize_seconds<'de, D: Deserializer<'de>>(deserializer: D) -> StdResult<Duration, D::Error> {
+	let seconds = f64::deserialize(deserializer)?;
+	Duration::try_from_secs_f64(seconds).map_err(|_| de::Error::custom(format!("must be a positive number of seconds (found {seconds})")))
+}
+
+pub fn deserialize_degrees<'de, D: Deserializer<'de>>(deserializer: D) -> StdResult<f32, D::Error> {
+	let degrees = f32::deserialize(deserializer)?;
+	StdResult::Ok(degrees.to_radians())
+}
+
+// the path is relative to the data folder
+pub fn deserialize_program_path<'de, D: Deserializer<'de>>(deserializer: D) -> StdResult<PathBuf, D::Error> {
+	let path = String::deserialize(deserializer)?;
+	StdResult::Ok(utils::get_program_file_path(path))
+}
+
+// an empty string means `None`, otherwise it's relative to the data folder
+pub fn deserialize_optional_program_path<'de, D: Deserializer<'de>>(deserializer: D) -> StdResult<Option<PathBuf>, D::Error> {
+	let path = String::deserialize(deserializer)?;
+	StdResult::Ok(if path.is_empty() {None} else {Some(utils::get_program_file_path(path))})
+}
+
+// an empty string means `None`
+pub fn deserialize_optional_string<'de, D: Deserializer<'de>>(deserializer: D) -> StdResult<Option<String>, D::Error> {
+	let value = String::deserialize(deserializer)?;
+	StdResult::Ok(if value.is_empty() {None} else {Some(value)})
+}
+
+pub fn deserialize_vec3<'de, D: Deserializer<'de>>(deserializer: D) -> StdResult<glam::Vec3, D::Error> {
+	let value = <[f32; 3]>::deserialize(deserializer)?;
+	StdResult::Ok(glam::Vec3::from_array(value))
+}
+
+// the alpha can be left out, in which case it's 1
+pub fn deserialize_clear_color<'de, D: Deserializer<'de>>(deserializer: D) -> StdResult<[f32; 4], D::Error> {
+	let channels = Vec::<f32>::deserialize(deserializer)?;
+	match channels[..] {
+		[r, g, b] => StdResult::Ok([r, g, b, 1.0]),
+		[r, g, b, a] => StdResult::Ok([r, g, b, a]),
+		_ => StdResult::Err(de::Error::custom(format!("must be a list of 3 or 4 numbers (found {} numbers)", channels.len()))),
+	}
+}
+
+// each keyframe is [x, y, z, rot_xz, rot_y]
+pub fn deserialize_camera_keyframes<'de, D: Deserializer<'de>>(deserializer: D) -> StdResult<Vec<CameraKeyframe>, D::Error> {
+	let keyframes = Vec::<[f32; 5]>::deserialize(deserializer)?;
+	StdResult::Ok(keyframes.into_iter().map(|[x, y, z, rot_xz, rot_y]| CameraKeyframe {
+		pos: glam::Vec3::new(x, y, z),
+		rot_xz,
+		rot_y,
+	}).collect())
+}
+
+
+
+
+// HELP: this keeps an item's error instead of returning it, so that one bad item can be skipped without failing the whole list
+pub struct MaybeValid<T> (pub StdResult<T, String>);
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for MaybeValid<T> {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> StdResult<Self, D::Error> {
+		StdResult::Ok(Self (T::deserialize(deserializer).map_err(|err| err.to_string())))
+	}
+}
+
+// the entries of each item in 'lights', any missing entries use these defaults
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct LightConfig {
+	#[serde(rename = "type")]
+	pub kind: String,
+	pub position: [f32; 3],
+	pub direction: [f32; 3],
+	pub color: [f32; 3],
+	pub intensity: f32,
+	pub range: f32,
+}
+
+impl Default for LightConfig {
+	fn default() -> Self {
+		Self {
+			kind: String::new(),
+			position: [0.0, 0.0, 0.0],
+			direction: [0.0, -1.0, 0.0],
+			color: [1.0, 1.0, 1.0],
+			intensity: 1.0,
+			range: 10.0,
+		}
+	}
+}
+
+impl LightConfig {
+	pub fn to_light_data(&self) -> StdResult<LightData, String> {
+		let kind = match &*self.kind.to_lowercase() {
+			"point" => LightKind::Point,
+			"directional" => LightKind::Directional,
+			_ => return StdResult::Err(String::from("'type' must be 'point' or 'directional'")),
+		};
+		let Some(direction) = glam::Vec3::from_array(self.direction).try_normalize() else {return StdResult::Err(String::from("'direction' cannot be zero"));};
+		StdResult::Ok(LightData {
+			kind,
+			pos: glam::Vec3::from_array(self.position),
+			direction,
+			color: self.color.map(|v| v.max(0.0)),
+			intensity: self.intensity.clamp(0.0, LightData::MAX_INTENSITY),
+			range: self.range.max(0.001),
+		})
+	}
+}
+
+// any malformed lights are skipped
+pub fn deserialize_lights<'de, D: Deserializer<'de>>(deserializer: D) -> StdResult<Vec<LightData>, D::Error> {
+	let lights = Vec::<MaybeValid<LightConfig>>::deserialize(deserializer)?;
+	StdResult::Ok(lights.into_iter()
+		.enumerate()
+		.filter_map(|(i, light)| {
+			let output = light.0.and_then(|light| light.to_light_data());
+			if let StdResult::Err(err) = &output {
+				warn!("Item {i} of entry 'lights' in 'engine config.hjson' is not a valid light ({err}), skipping it.");
+			}
+			output.ok()
+		})
+		.collect()
+	)
+}
+
+
+
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	fn parse_present_mode(name: &str) -> wgpu::PresentMode {
+		deserialize_present_mode(HjsonDeserializer(&Value::String(String::from(name)))).expect("Failed to deserialize present mode.")
+	}
+	
+	#[test]
+	fn every_present_mode_name_is_accepted() {
+		assert_eq!(parse_present_mode("auto_vsync"), wgpu::PresentMode::AutoVsync);
+		assert_eq!(parse_present_mode("auto_no_vsync"), wgpu::PresentMode::AutoNoVsync);
+		assert_eq!(parse_present_mode("fifo"), wgpu::PresentMode::Fifo);
+		assert_eq!(parse_present_mode("fifo_relaxed"), wgpu::PresentMode::FifoRelaxed);
+		assert_eq!(parse_present_mode("immediate"), wgpu::PresentMode::Immediate);
+		assert_eq!(parse_present_mode("mailbox"), wgpu::PresentMode::Mailbox);
+	}
+	
+	#[test]
+	fn present_mode_names_ignore_case() {
+		assert_eq!(parse_present_mode("Auto_No_Vsync"), wgpu::PresentMode::AutoNoVsync);
+		assert_eq!(parse_present_mode("MAILBOX"), wgpu::PresentMode::Mailbox);
+	}
+	
+	#[test]
+	fn unknown_present_mode_defaults_to_auto_vsync() {
+		assert_eq!(parse_present_mode("no_vsync"), wgpu::PresentMode::AutoVsync);
+		assert_eq!(parse_present_mode(""), wgpu::PresentMode::AutoVsync);
+		// non-strings are still errors though
+		assert!(deserialize_present_mode(HjsonDeserializer(&Value::I64(1))).is_err());
+	}
+	
+}
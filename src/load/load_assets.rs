@@ -1,42 +1,77 @@
 use crate::prelude::*;
 use std::io::{BufReader, Cursor};
 use wgpu::util::DeviceExt;
+use bytemuck::Zeroable;
 
 
 
+#[allow(clippy::too_many_arguments)]
 pub fn load_render_assets(
 	camera_data: &CameraData,
 	shadow_caster_data: &ShadowCasterData,
-	example_model_instance_datas: &[InstanceData],
+	directional_light: &DirectionalLight,
+	lights: &[LightData],
+	model_instance_datas: &[Vec<InstanceData>],
+	foliage_instance_datas: &[FoliageInstanceData],
 	render_context: &RenderContextData,
-	shadowmap_size: u32,
+	engine_config: &EngineConfig,
 	color_correction_settings: &ColorCorrectionSettings,
-	compress_textures: bool,
+	lighting_settings: &LightingSettings,
+	skybox_settings: &SkyboxSettings,
 ) -> Result<RenderAssets> {
+	let compress_textures = engine_config.compress_textures;
 	
 	// general data
 	let camera = load_camera_render_data(render_context, camera_data).context("Failed to load camera render data.")?;
-	let depth = load_depth_render_data(render_context);
+	let depth = load_depth_render_data(render_context, engine_config.msaa_samples);
 	let main_tex_view = load_main_tex_data(render_context);
+	let msaa_tex_view = load_msaa_tex_data(render_context, engine_config.msaa_samples);
+	// HELP: the loaded textures have mipmaps, so trilinear filtering is always used (which also allows anisotropic filtering)
 	let default_sampler = render_context.device.create_sampler(&wgpu::SamplerDescriptor {
 		address_mode_u: wgpu::AddressMode::ClampToEdge,
 		address_mode_v: wgpu::AddressMode::ClampToEdge,
 		address_mode_w: wgpu::AddressMode::ClampToEdge,
 		mag_filter: wgpu::FilterMode::Linear,
-		min_filter: wgpu::FilterMode::Nearest,
-		mipmap_filter: wgpu::FilterMode::Nearest,
+		min_filter: wgpu::FilterMode::Linear,
+		mipmap_filter: wgpu::FilterMode::Linear,
+		anisotropy_clamp: engine_config.anisotropy,
+		..Default::default()
+	});
+	let material_sampler = render_context.device.create_sampler(&wgpu::SamplerDescriptor {
+		address_mode_u: wgpu::AddressMode::Repeat,
+		address_mode_v: wgpu::AddressMode::Repeat,
+		address_mode_w: wgpu::AddressMode::Repeat,
+		mag_filter: wgpu::FilterMode::Linear,
+		min_filter: wgpu::FilterMode::Linear,
+		mipmap_filter: wgpu::FilterMode::Linear,
+		anisotropy_clamp: engine_config.anisotropy,
 		..Default::default()
 	});
 	let mut materials_storage = MaterialsStorage::new();
 	
 	// shadow_caster data
-	let shadow_caster = load_shadow_caster_data(render_context, shadowmap_size, shadow_caster_data, camera_data).context("Failed to load shadow caster render data.")?;
+	let shadow_caster = load_shadow_caster_data(render_context, engine_config.shadowmap_size, shadow_caster_data, directional_light, camera_data).context("Failed to load shadow caster render data.")?;
 	
 	// models data
-	let example_models = load_example_models_render_data(render_context, &mut materials_storage, example_model_instance_datas, compress_textures).context("Failed to load model render data.")?;
+	let mut models = Vec::with_capacity(engine_config.model_paths.len());
+	for (i, (model_path, instance_datas)) in engine_config.model_paths.iter().zip(model_instance_datas).enumerate() {
+		models.push(load_models_render_data(render_context, &mut materials_storage, model_path, i, instance_datas, engine_config).with_context(|| format!("Failed to load model render data for {model_path:?}."))?);
+	}
+	
+	// occlusion data
+	let occlusion = engine_config.occlusion_culling.then(|| load_occlusion_render_data(render_context, model_instance_datas));
 	
 	// skybox data
-	let skybox_material_id = load_skybox_material(render_context, &mut materials_storage, compress_textures).context("Failed to load skybox render data.")?;
+	let load_skybox = engine_config.background_mode == BackgroundMode::Skybox;
+	let skybox_material_id = if load_skybox {
+		Some(load_skybox_material(render_context, &mut materials_storage, &engine_config.skybox_path, compress_textures).context("Failed to load skybox render data.")?)
+	} else {
+		None
+	};
+	let secondary_skybox_material_id = match &engine_config.secondary_skybox_path {
+		Some(path) if load_skybox => Some(materials_storage.get_or_load_cube(render_context, path, compress_textures).context("Failed to load secondary skybox render data.")?),
+		_ => None,
+	};
 	let skybox_sampler = render_context.device.create_sampler(&wgpu::SamplerDescriptor {
 		address_mode_u: wgpu::AddressMode::ClampToEdge,
 		address_mode_v: wgpu::AddressMode::ClampToEdge,
@@ -46,6 +81,57 @@ pub fn load_render_assets(
 		mipmap_filter: wgpu::FilterMode::Nearest,
 		..Default::default()
 	});
+	let skybox_settings_buffer = render_context.device.create_buffer_init(
+		&wgpu::util::BufferInitDescriptor {
+			label: Some("skybox_settings_buffer"),
+			contents: bytemuck::bytes_of(skybox_settings),
+			usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+		}
+	);
+	
+	// gradient background data
+	let gradient_background_buffer = render_context.device.create_buffer_init(
+		&wgpu::util::BufferInitDescriptor {
+			label: Some("gradient_background_buffer"),
+			contents: bytemuck::bytes_of(&GradientBackgroundSettings::new(engine_config)),
+			usage: wgpu::BufferUsages::UNIFORM,
+		}
+	);
+	
+	// foliage data
+	let foliage = if engine_config.foliage_enabled {
+		Some(load_foliage_render_data(render_context, &mut materials_storage, foliage_instance_datas, engine_config).context("Failed to load foliage render data.")?)
+	} else {
+		None
+	};
+	
+	// shadow catcher data
+	let shadow_catcher_buffer = render_context.device.create_buffer_init(
+		&wgpu::util::BufferInitDescriptor {
+			label: Some("shadow_catcher_buffer"),
+			contents: bytemuck::bytes_of(&ShadowCatcherSettings::new(engine_config, camera_data.pos)),
+			usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+		}
+	);
+	
+	// debug lines data
+	let axis_gizmo = if engine_config.axis_gizmo_enabled {
+		Some(load_axis_gizmo_render_data(render_context, engine_config.axis_gizmo_size, engine_config.axis_gizmo_mode))
+	} else {
+		None
+	};
+	let (debug_lines_vertex_buffer, debug_lines_capacity) = load_debug_lines_buffer(render_context, 0);
+	let debug_lines = DebugLinesRenderData {
+		vertex_buffer: debug_lines_vertex_buffer,
+		vertex_count: 0,
+		mode: DebugDrawMode::DepthTested,
+	};
+	
+	// text data
+	let text = load_text_render_data(render_context);
+	
+	// bloom data
+	let bloom = load_bloom_render_data(render_context);
 	
 	// color correction data
 	let color_correction_buffer = render_context.device.create_buffer_init(
@@ -56,23 +142,68 @@ pub fn load_render_assets(
 		}
 	);
 	
+	// lighting data
+	let lighting_buffer = render_context.device.create_buffer_init(
+		&wgpu::util::BufferInitDescriptor {
+			label: Some("lighting_buffer"),
+			contents: bytemuck::bytes_of(lighting_settings),
+			usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+		}
+	);
+	let (lights_buffer, lights_capacity) = load_lights_buffer(render_context, lights, lights.len());
+	let directional_light_buffer = render_context.device.create_buffer_init(
+		&wgpu::util::BufferInitDescriptor {
+			label: Some("directional_light_buffer"),
+			contents: bytemuck::cast_slice(&directional_light.build_gpu_data()),
+			usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+		}
+	);
+	let skybox_view = skybox_material_id.map(|skybox_material_id| &materials_storage.list_cube[skybox_material_id].view);
+	let ibl = load_ibl_render_data(render_context, skybox_view).context("Failed to load ibl render data.")?;
+	
 	Ok(RenderAssets {
 		
 		depth,
 		main_tex_view,
+		msaa_tex_view,
 		camera,
 		default_sampler,
+		material_sampler,
 		materials_storage,
 		
 		shadow_caster,
 		
-		example_models,
+		models,
+		
+		occlusion,
 		
 		skybox_material_id,
+		secondary_skybox_material_id,
 		skybox_sampler,
+		skybox_settings_buffer,
+		
+		gradient_background_buffer,
+		
+		foliage,
+		
+		shadow_catcher_buffer,
+		
+		axis_gizmo,
+		debug_lines,
+		debug_lines_capacity,
+		
+		text,
+		
+		bloom,
 		
 		color_correction_buffer,
 		
+		lighting_buffer,
+		lights_buffer,
+		lights_capacity,
+		directional_light_buffer,
+		ibl,
+		
 	})
 }
 
@@ -80,12 +211,233 @@ pub fn load_render_assets(
 
 
 
+// HELP: The irradiance map, prefiltered map, and brdf lut are generated with one-time compute passes ('shaders/ibl.wgsl')
+pub fn load_ibl_render_data(render_context: &RenderContextData, environment_view: Option<&wgpu::TextureView>) -> Result<IblRenderData> {
+	const WORKGROUP_SIZE: u32 = 8;
+	
+	let sampler = render_context.device.create_sampler(&wgpu::SamplerDescriptor {
+		address_mode_u: wgpu::AddressMode::ClampToEdge,
+		address_mode_v: wgpu::AddressMode::ClampToEdge,
+		address_mode_w: wgpu::AddressMode::ClampToEdge,
+		mag_filter: wgpu::FilterMode::Linear,
+		min_filter: wgpu::FilterMode::Linear,
+		mipmap_filter: wgpu::FilterMode::Linear,
+		..Default::default()
+	});
+	
+	let Some(environment_view) = environment_view else {
+		// the prefiltered map is black, so there aren't any reflections
+		return Ok(IblRenderData {
+			irradiance_view: load_ibl_placeholder_texture(render_context, "ibl_irradiance_placeholder_texture", IblRenderData::PLACEHOLDER_AMBIENT_COLOR, wgpu::TextureViewDimension::Cube),
+			prefiltered_view: load_ibl_placeholder_texture(render_context, "ibl_prefiltered_placeholder_texture", [0, 0, 0, 255], wgpu::TextureViewDimension::Cube),
+			brdf_lut_view: load_ibl_placeholder_texture(render_context, "ibl_brdf_lut_placeholder_texture", [0, 0, 0, 255], wgpu::TextureViewDimension::D2),
+			sampler,
+		});
+	};
+	
+	let shader_path = utils::get_program_file_path("shaders/ibl.wgsl");
+	let shader_source = fs::read_to_string(&shader_path).add_path_to_error(&shader_path)?;
+	let shader = render_context.device.create_shader_module(wgpu::ShaderModuleDescriptor {
+		label: Some("ibl_shader_module"),
+		source: wgpu::ShaderSource::Wgsl(shader_source.into()),
+	});
+	// NOTE: these pipelines are only used once, so they use the layouts that wgpu generates from the shader instead of having their own entries in `RenderLayouts`
+	let create_ibl_pipeline = |label: &str, entry_point: &str| render_context.device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+		label: Some(label),
+		layout: None,
+		module: &shader,
+		entry_point,
+		compilation_options: wgpu::PipelineCompilationOptions::default(),
+	});
+	let irradiance_pipeline = create_ibl_pipeline("ibl_irradiance_pipeline", "cs_irradiance");
+	let prefilter_pipeline = create_ibl_pipeline("ibl_prefilter_pipeline", "cs_prefilter");
+	let brdf_lut_pipeline = create_ibl_pipeline("ibl_brdf_lut_pipeline", "cs_brdf_lut");
+	let environment_sampler = render_context.device.create_sampler(&wgpu::SamplerDescriptor {
+		mag_filter: wgpu::FilterMode::Linear,
+		min_filter: wgpu::FilterMode::Linear,
+		..Default::default()
+	});
+	
+	let create_ibl_texture = |label: &str, size: u32, mip_level_count: u32, depth_or_array_layers: u32| render_context.device.create_texture(&wgpu::TextureDescriptor {
+		label: Some(label),
+		size: wgpu::Extent3d {
+			width: size,
+			height: size,
+			depth_or_array_layers,
+		},
+		mip_level_count,
+		sample_count: 1,
+		dimension: wgpu::TextureDimension::D2,
+		format: wgpu::TextureFormat::Rgba16Float,
+		usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::STORAGE_BINDING,
+		view_formats: &[],
+	});
+	let irradiance_texture = create_ibl_texture("ibl_irradiance_texture", IblRenderData::IRRADIANCE_SIZE, 1, 6);
+	let prefiltered_texture = create_ibl_texture("ibl_prefiltered_texture", IblRenderData::PREFILTERED_SIZE, IblRenderData::PREFILTERED_MIP_COUNT, 6);
+	let brdf_lut_texture = create_ibl_texture("ibl_brdf_lut_texture", IblRenderData::BRDF_LUT_SIZE, 1, 1);
+	let get_storage_view = |texture: &wgpu::Texture, mip_level: u32| texture.create_view(&wgpu::TextureViewDescriptor {
+		dimension: Some(wgpu::TextureViewDimension::D2Array),
+		base_mip_level: mip_level,
+		mip_level_count: Some(1),
+		..Default::default()
+	});
+	
+	let mut encoder = render_context.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {label: Some("ibl_encoder")});
+	
+	// irradiance
+	let irradiance_storage_view = get_storage_view(&irradiance_texture, 0);
+	let irradiance_bind_0 = render_context.device.create_bind_group(&wgpu::BindGroupDescriptor {
+		label: Some("ibl_irradiance_bind_0"),
+		layout: &irradiance_pipeline.get_bind_group_layout(0),
+		entries: &[
+			wgpu::BindGroupEntry {
+				binding: 0,
+				resource: wgpu::BindingResource::TextureView (environment_view),
+			},
+			wgpu::BindGroupEntry {
+				binding: 1,
+				resource: wgpu::BindingResource::Sampler (&environment_sampler),
+			},
+			wgpu::BindGroupEntry {
+				binding: 2,
+				resource: wgpu::BindingResource::TextureView (&irradiance_storage_view),
+			},
+		],
+	});
+	{
+		let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {label: Some("ibl_irradiance_pass"), timestamp_writes: None});
+		compute_pass.set_pipeline(&irradiance_pipeline);
+		compute_pass.set_bind_group(0, &irradiance_bind_0, &[]);
+		let workgroup_count = IblRenderData::IRRADIANCE_SIZE.div_ceil(WORKGROUP_SIZE);
+		compute_pass.dispatch_workgroups(workgroup_count, workgroup_count, 6);
+	}
+	
+	// prefiltered, one dispatch per mip
+	for mip_level in 0..IblRenderData::PREFILTERED_MIP_COUNT {
+		let roughness = mip_level as f32 / (IblRenderData::PREFILTERED_MIP_COUNT - 1) as f32;
+		let prefilter_settings_buffer = render_context.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+			label: Some(&format!("ibl_prefilter_mip_{mip_level}_settings_buffer")),
+			contents: bytemuck::cast_slice(&[roughness, 0.0, 0.0, 0.0]),
+			usage: wgpu::BufferUsages::UNIFORM,
+		});
+		let mip_storage_view = get_storage_view(&prefiltered_texture, mip_level);
+		let prefilter_bind_0 = render_context.device.create_bind_group(&wgpu::BindGroupDescriptor {
+			label: Some(&format!("ibl_prefilter_mip_{mip_level}_bind_0")),
+			layout: &prefilter_pipeline.get_bind_group_layout(0),
+			entries: &[
+				wgpu::BindGroupEntry {
+					binding: 0,
+					resource: wgpu::BindingResource::TextureView (environment_view),
+				},
+				wgpu::BindGroupEntry {
+					binding: 1,
+					resource: wgpu::BindingResource::Sampler (&environment_sampler),
+				},
+				wgpu::BindGroupEntry {
+					binding: 2,
+					resource: wgpu::BindingResource::TextureView (&mip_storage_view),
+				},
+				wgpu::BindGroupEntry {
+					binding: 3,
+					resource: prefilter_settings_buffer.as_entire_binding(),
+				},
+			],
+		});
+		let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {label: Some(&format!("ibl_prefilter_mip_{mip_level}_pass")), timestamp_writes: None});
+		compute_pass.set_pipeline(&prefilter_pipeline);
+		compute_pass.set_bind_group(0, &prefilter_bind_0, &[]);
+		let workgroup_count = (IblRenderData::PREFILTERED_SIZE >> mip_level).max(1).div_ceil(WORKGROUP_SIZE);
+		compute_pass.dispatch_workgroups(workgroup_count, workgroup_count, 6);
+	}
+	
+	// brdf lut
+	let brdf_lut_view = brdf_lut_texture.create_view(&wgpu::TextureViewDescriptor::default());
+	let brdf_lut_bind_0 = render_context.device.create_bind_group(&wgpu::BindGroupDescriptor {
+		label: Some("ibl_brdf_lut_bind_0"),
+		layout: &brdf_lut_pipeline.get_bind_group_layout(0),
+		entries: &[
+			wgpu::BindGroupEntry {
+				binding: 4,
+				resource: wgpu::BindingResource::TextureView (&brdf_lut_view),
+			},
+		],
+	});
+	{
+		let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {label: Some("ibl_brdf_lut_pass"), timestamp_writes: None});
+		compute_pass.set_pipeline(&brdf_lut_pipeline);
+		compute_pass.set_bind_group(0, &brdf_lut_bind_0, &[]);
+		let workgroup_count = IblRenderData::BRDF_LUT_SIZE.div_ceil(WORKGROUP_SIZE);
+		compute_pass.dispatch_workgroups(workgroup_count, workgroup_count, 1);
+	}
+	
+	render_context.command_queue.submit(Some(encoder.finish()));
+	
+	let cube_view_descriptor = wgpu::TextureViewDescriptor {
+		dimension: Some(wgpu::TextureViewDimension::Cube),
+		..Default::default()
+	};
+	Ok(IblRenderData {
+		irradiance_view: irradiance_texture.create_view(&cube_view_descriptor),
+		prefiltered_view: prefiltered_texture.create_view(&cube_view_descriptor),
+		brdf_lut_view,
+		sampler,
+	})
+}
+
+// a 1x1 texture with every layer set to `color`, `view_dimension` should be either `Cube` or `D2`
+pub fn load_ibl_placeholder_texture(render_context: &RenderContextData, label: &str, color: [u8; 4], view_dimension: wgpu::TextureViewDimension) -> wgpu::TextureView {
+	let layer_count = if view_dimension == wgpu::TextureViewDimension::Cube {6} else {1};
+	let texture = render_context.device.create_texture_with_data(
+		&render_context.command_queue,
+		&wgpu::TextureDescriptor {
+			label: Some(label),
+			size: wgpu::Extent3d {
+				width: 1,
+				height: 1,
+				depth_or_array_layers: layer_count,
+			},
+			mip_level_count: 1,
+			sample_count: 1,
+			dimension: wgpu::TextureDimension::D2,
+			format: wgpu::TextureFormat::Rgba8Unorm,
+			usage: wgpu::TextureUsages::TEXTURE_BINDING,
+			view_formats: &[],
+		},
+		wgpu::util::TextureDataOrder::LayerMajor,
+		&color.repeat(layer_count as usize),
+	);
+	texture.create_view(&wgpu::TextureViewDescriptor {
+		dimension: Some(view_dimension),
+		..Default::default()
+	})
+}
+
+
+
+// returns the buffer and how many lights it can hold, which is at least `min_capacity`
+pub fn load_lights_buffer(render_context: &RenderContextData, lights: &[LightData], min_capacity: usize) -> (wgpu::Buffer, usize) {
+	// HELP: storage buffers can't be empty, so there's always room for at least one light (`light_count` tells the shader to ignore the unused ones)
+	let capacity = min_capacity.max(lights.len()).max(1);
+	let mut raw_lights = lights.iter().map(LightData::to_raw).collect::<Vec<_>>();
+	raw_lights.resize(capacity, RawLightData::zeroed());
+	let lights_buffer = render_context.device.create_buffer_init(
+		&wgpu::util::BufferInitDescriptor {
+			label: Some("lights_buffer"),
+			contents: bytemuck::cast_slice(&raw_lights),
+			usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+		}
+	);
+	(lights_buffer, capacity)
+}
+
+
+
 pub fn load_camera_render_data(render_context: &RenderContextData, camera_data: &CameraData) -> Result<CameraRenderData> {
 	
 	let buffer = render_context.device.create_buffer_init(
 		&wgpu::util::BufferInitDescriptor {
 			label: Some("camera_buffer"),
-			contents: bytemuck::cast_slice(&camera_data.build_gpu_data(render_context.aspect_ratio)),
+			contents: bytemuck::cast_slice(&camera_data.build_gpu_data(render_context)),
 			usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
 		}
 	);
@@ -99,7 +451,8 @@ pub fn load_camera_render_data(render_context: &RenderContextData, camera_data:
 
 
 
-pub fn load_depth_render_data(render_context: &RenderContextData) -> DepthRenderData {
+// `sample_count` has to match the color target's sample count
+pub fn load_depth_render_data(render_context: &RenderContextData, sample_count: u32) -> DepthRenderData {
 	
 	let size = wgpu::Extent3d {
 		width: render_context.surface_config.width,
@@ -110,7 +463,7 @@ pub fn load_depth_render_data(render_context: &RenderContextData) -> DepthRender
 		label: Some("depth_tex"),
 		size,
 		mip_level_count: 1,
-		sample_count: 1,
+		sample_count,
 		dimension: wgpu::TextureDimension::D2,
 		format: wgpu::TextureFormat::Depth32Float,
 		usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
@@ -142,28 +495,168 @@ pub fn load_main_tex_data(render_context: &RenderContextData) -> wgpu::TextureVi
 		mip_level_count: 1,
 		sample_count: 1,
 		dimension: wgpu::TextureDimension::D2,
-		format: render_context.surface_format,
+		format: RenderAssets::MAIN_TEX_FORMAT,
 		usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
 		view_formats: &[],
 	};
 	let texture = render_context.device.create_texture(&desc);
 	
-	let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+	texture.create_view(&wgpu::TextureViewDescriptor::default())
 	
-	view
+}
+
+
+
+
+
+pub fn load_msaa_tex_data(render_context: &RenderContextData, sample_count: u32) -> Option<wgpu::TextureView> {
+	if sample_count <= 1 {return None;}
 	
+	let size = wgpu::Extent3d {
+		width: render_context.surface_config.width,
+		height: render_context.surface_config.height,
+		depth_or_array_layers: 1,
+	};
+	let desc = wgpu::TextureDescriptor {
+		label: Some("msaa_texture"),
+		size,
+		mip_level_count: 1,
+		sample_count,
+		dimension: wgpu::TextureDimension::D2,
+		format: RenderAssets::MAIN_TEX_FORMAT,
+		usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+		view_formats: &[],
+	};
+	let texture = render_context.device.create_texture(&desc);
+	
+	Some(texture.create_view(&wgpu::TextureViewDescriptor::default()))
+	
+}
+
+
+
+
+
+pub fn load_bloom_render_data(render_context: &RenderContextData) -> BloomRenderData {
+	
+	let mut mip_views = Vec::with_capacity(BloomRenderData::MAX_MIP_COUNT);
+	let mut width = render_context.surface_config.width;
+	let mut height = render_context.surface_config.height;
+	for i in 0..BloomRenderData::MAX_MIP_COUNT {
+		// there's always at least one mip since the color correction pass samples the first one
+		if i > 0 && (width < 2 || height < 2) {break;}
+		width = (width / 2).max(1);
+		height = (height / 2).max(1);
+		let desc = wgpu::TextureDescriptor {
+			label: Some(&format!("bloom_mip_{i}_texture")),
+			size: wgpu::Extent3d {
+				width,
+				height,
+				depth_or_array_layers: 1,
+			},
+			mip_level_count: 1,
+			sample_count: 1,
+			dimension: wgpu::TextureDimension::D2,
+			format: RenderAssets::MAIN_TEX_FORMAT,
+			usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+			view_formats: &[],
+		};
+		let texture = render_context.device.create_texture(&desc);
+		mip_views.push(texture.create_view(&wgpu::TextureViewDescriptor::default()));
+	}
+	
+	BloomRenderData {
+		mip_views,
+	}
 }
 
 
 
 
 
-pub fn load_shadow_caster_data(render_context: &RenderContextData, shadowmap_size: u32, shadow_caster_data: &ShadowCasterData, camera_data: &CameraData) -> Result<ShadowCasterRenderData> {
+pub fn load_axis_gizmo_render_data(render_context: &RenderContextData, size: f32, mode: DebugDrawMode) -> DebugLinesRenderData {
+	
+	let line = |dir: glam::Vec3, color: [f32; 3]| [
+		DebugLineVertexData { pos: [0.0; 3], color },
+		DebugLineVertexData { pos: (dir * size).to_array(), color },
+	];
+	let vertices = [
+		line(glam::Vec3::X, [1.0, 0.0, 0.0]),
+		line(glam::Vec3::Y, [0.0, 1.0, 0.0]),
+		line(glam::Vec3::Z, [0.0, 0.0, 1.0]),
+	].concat();
+	
+	let vertex_buffer = render_context.device.create_buffer_init(
+		&wgpu::util::BufferInitDescriptor {
+			label: Some("axis_gizmo_vertex_buffer"),
+			contents: bytemuck::cast_slice(&vertices),
+			usage: wgpu::BufferUsages::VERTEX,
+		}
+	);
+	
+	DebugLinesRenderData {
+		vertex_buffer,
+		vertex_count: vertices.len() as u32,
+		mode,
+	}
+}
+
+// the settings and glyphs are written by `render::update_gpu_buffers()`
+pub fn load_text_render_data(render_context: &RenderContextData) -> TextRenderData {
+	let settings_buffer = render_context.device.create_buffer(&wgpu::BufferDescriptor {
+		label: Some("text_settings_buffer"),
+		size: std::mem::size_of::<TextSettings>() as wgpu::BufferAddress,
+		usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+		mapped_at_creation: false,
+	});
+	let (glyphs_buffer, glyphs_capacity) = load_text_glyphs_buffer(render_context, 0);
+	TextRenderData {
+		settings_buffer,
+		glyphs_buffer,
+		glyphs_capacity,
+		glyph_count: 0,
+	}
+}
+
+// returns the buffer and how many glyphs it can hold, which is at least `min_capacity`
+pub fn load_text_glyphs_buffer(render_context: &RenderContextData, min_capacity: usize) -> (wgpu::Buffer, usize) {
+	let capacity = min_capacity.max(64);
+	let glyphs_buffer = render_context.device.create_buffer(&wgpu::BufferDescriptor {
+		label: Some("text_glyphs_buffer"),
+		size: (capacity * std::mem::size_of::<TextGlyphData>()) as wgpu::BufferAddress,
+		usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+		mapped_at_creation: false,
+	});
+	(glyphs_buffer, capacity)
+}
+
+// returns the buffer and how many vertices it can hold, which is at least `min_capacity` (the contents are written by `render::update_gpu_buffers()`)
+pub fn load_debug_lines_buffer(render_context: &RenderContextData, min_capacity: usize) -> (wgpu::Buffer, usize) {
+	let capacity = min_capacity.max(2);
+	let vertex_buffer = render_context.device.create_buffer(&wgpu::BufferDescriptor {
+		label: Some("debug_lines_vertex_buffer"),
+		size: (capacity * std::mem::size_of::<DebugLineVertexData>()) as wgpu::BufferAddress,
+		usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+		mapped_at_creation: false,
+	});
+	(vertex_buffer, capacity)
+}
+
+
+
+
+
+pub fn load_shadow_caster_data(render_context: &RenderContextData, shadowmap_size: u32, shadow_caster_data: &ShadowCasterData, directional_light: &DirectionalLight, camera_data: &CameraData) -> Result<ShadowCasterRenderData> {
+	
+	let max_texture_size = render_context.device.limits().max_texture_dimension_2d;
+	if shadowmap_size > max_texture_size {
+		return Err(Error::msg(format!("Entry 'shadowmap_size' in 'engine config.hjson' must be at most {max_texture_size} on this device (found {shadowmap_size}).")));
+	}
 	
 	let size = wgpu::Extent3d {
 		width: shadowmap_size,
 		height: shadowmap_size,
-		depth_or_array_layers: 1,
+		depth_or_array_layers: shadow_caster_data.cascade_count as u32,
 	};
 	let desc = wgpu::TextureDescriptor {
 		label: Some("shadow_caster_depth_tex"),
@@ -176,7 +669,18 @@ pub fn load_shadow_caster_data(render_context: &RenderContextData, shadowmap_siz
 		view_formats: &[],
 	};
 	let depth_tex = render_context.device.create_texture(&desc);
-	let depth_tex_view = depth_tex.create_view(&wgpu::TextureViewDescriptor::default());
+	// HELP: the view dimension has to be given explicitly, otherwise a texture with a single layer would get a plain `D2` view
+	let depth_tex_view = depth_tex.create_view(&wgpu::TextureViewDescriptor {
+		dimension: Some(wgpu::TextureViewDimension::D2Array),
+		..Default::default()
+	});
+	let cascade_depth_tex_views = (0..shadow_caster_data.cascade_count as u32).map(|i| depth_tex.create_view(&wgpu::TextureViewDescriptor {
+		label: Some(&format!("shadow_caster_cascade_{i}_depth_tex_view")),
+		dimension: Some(wgpu::TextureViewDimension::D2),
+		base_array_layer: i,
+		array_layer_count: Some(1),
+		..Default::default()
+	})).collect::<Vec<_>>();
 	let depth_sampler = render_context.device.create_sampler(&wgpu::SamplerDescriptor {
 		address_mode_u: wgpu::AddressMode::ClampToEdge,
 		address_mode_v: wgpu::AddressMode::ClampToEdge,
@@ -185,21 +689,32 @@ pub fn load_shadow_caster_data(render_context: &RenderContextData, shadowmap_siz
 		min_filter: wgpu::FilterMode::Linear,
 		mipmap_filter: wgpu::FilterMode::Nearest,
 		compare: Some(wgpu::CompareFunction::LessEqual),
+		anisotropy_clamp: 1, // WARNING: comparison samplers can't use anisotropic filtering, so this must never use the `anisotropy` config value
 		..Default::default()
 	});
 	
-	let proj_mat_buffer = render_context.device.create_buffer_init(
+	let cascades_gpu_data = shadow_caster_data.build_gpu_data(camera_data, render_context.aspect_ratio, directional_light.direction, shadowmap_size);
+	let cascades_buffer = render_context.device.create_buffer_init(
 		&wgpu::util::BufferInitDescriptor {
-			label: Some("shadow_caster_buffer"),
-			contents: bytemuck::cast_slice(&shadow_caster_data.build_gpu_data(camera_data.pos)),
+			label: Some("shadow_caster_cascades_buffer"),
+			contents: bytemuck::bytes_of(&cascades_gpu_data),
 			usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
 		}
 	);
+	let cascade_proj_mat_buffers = cascades_gpu_data.proj_mats[..shadow_caster_data.cascade_count].iter().enumerate().map(|(i, proj_mat)| render_context.device.create_buffer_init(
+		&wgpu::util::BufferInitDescriptor {
+			label: Some(&format!("shadow_caster_cascade_{i}_buffer")),
+			contents: bytemuck::cast_slice(proj_mat),
+			usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+		}
+	)).collect::<Vec<_>>();
 	
 	Ok(ShadowCasterRenderData {
 		depth_tex_view,
+		cascade_depth_tex_views,
 		depth_sampler,
-		proj_mat_buffer,
+		cascades_buffer,
+		cascade_proj_mat_buffers,
 	})
 }
 
@@ -207,55 +722,205 @@ pub fn load_shadow_caster_data(render_context: &RenderContextData, shadowmap_siz
 
 
 
-pub fn load_example_models_render_data(
+// HELP: `model_index` is only used for the buffer labels
+pub fn load_models_render_data(
 	render_context: &RenderContextData,
 	materials_storage: &mut MaterialsStorage,
+	model_path: &Path,
+	model_index: usize,
 	instance_datas: &[InstanceData],
-	compress_textures: bool,
+	engine_config: &EngineConfig,
 ) -> Result<ModelsRenderData> {
 	
-	let (example_model_meshes, bounding_radius) = load_model(utils::get_program_file_path("assets/cube.obj"), render_context, materials_storage, compress_textures)?;
+	let model_path = utils::get_program_file_path(model_path);
+	let (meshes, bounding_radius, aabb) = load_model_meshes(&model_path, render_context, materials_storage, engine_config)?;
 	
-	let example_model_instance_datas = instance_datas.iter().map(InstanceData::to_raw).collect::<Vec<_>>();
-	let culled_instances_buffer = render_context.device.create_buffer_init(
-		&wgpu::util::BufferInitDescriptor {
-			label: Some("example_models_instances_buffer"),
-			contents: bytemuck::cast_slice(&example_model_instance_datas),
-			usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
-		}
-	);
+	let raw_instance_datas = instance_datas.iter().map(InstanceData::to_raw).collect::<Vec<_>>();
+	let has_transparent_meshes = meshes.iter().any(|mesh| mesh.alpha_mode == MaterialAlphaMode::Blend);
+	// HELP: the transparent meshes need their instances sorted back-to-front, which the compute shader can't do, and storage buffers can't be empty
+	let use_gpu_culling = engine_config.gpu_culling && !raw_instance_datas.is_empty() && !has_transparent_meshes;
+	let instances_usage = if use_gpu_culling {
+		wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::STORAGE
+	} else {
+		wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST
+	};
+	let culled_instances_buffer_count = if engine_config.double_buffer_instances {2} else {1};
+	let culled_instances_buffers = (0..culled_instances_buffer_count).map(|i| {
+		render_context.device.create_buffer_init(
+			&wgpu::util::BufferInitDescriptor {
+				label: Some(&format!("model_{model_index}_culled_instances_buffer_{i}")),
+				contents: bytemuck::cast_slice(&raw_instance_datas),
+				usage: instances_usage,
+			}
+		)
+	}).collect::<Vec<_>>();
 	let total_instances_buffer = render_context.device.create_buffer_init(
 		&wgpu::util::BufferInitDescriptor {
-			label: Some("example_models_instances_buffer"),
-			contents: bytemuck::cast_slice(&example_model_instance_datas),
-			usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+			label: Some(&format!("model_{model_index}_instances_buffer")),
+			contents: bytemuck::cast_slice(&raw_instance_datas),
+			usage: instances_usage,
 		}
 	);
+	let max_shadow_instances = engine_config.max_shadow_instances;
+	let shadow_instances_buffer = (max_shadow_instances > 0 && max_shadow_instances < raw_instance_datas.len()).then(|| {
+		render_context.device.create_buffer_init(
+			&wgpu::util::BufferInitDescriptor {
+				label: Some(&format!("model_{model_index}_shadow_instances_buffer")),
+				contents: bytemuck::cast_slice(&raw_instance_datas[..max_shadow_instances]),
+				usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+			}
+		)
+	});
+	let shadow_instances_count = if shadow_instances_buffer.is_some() {max_shadow_instances as u32} else {0};
+	// the args are written by `render::update_gpu_buffers()` before the first draw
+	let indirect_args_buffer = engine_config.indirect_draws.then(|| {
+		render_context.device.create_buffer(&wgpu::BufferDescriptor {
+			label: Some(&format!("model_{model_index}_indirect_args_buffer")),
+			size: ModelsRenderData::INDIRECT_ARGS_STRIDE * meshes.len() as wgpu::BufferAddress,
+			usage: wgpu::BufferUsages::INDIRECT | wgpu::BufferUsages::COPY_DST,
+			mapped_at_creation: false,
+		})
+	});
+	// the settings are written by `render::update_gpu_buffers()` before the first dispatch
+	let gpu_culling = use_gpu_culling.then(|| {
+		let settings_buffer = render_context.device.create_buffer(&wgpu::BufferDescriptor {
+			label: Some(&format!("model_{model_index}_culling_settings_buffer")),
+			size: std::mem::size_of::<CullingSettings>() as wgpu::BufferAddress,
+			usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+			mapped_at_creation: false,
+		});
+		let visible_count_buffer = render_context.device.create_buffer(&wgpu::BufferDescriptor {
+			label: Some(&format!("model_{model_index}_culling_visible_count_buffer")),
+			size: std::mem::size_of::<u32>() as wgpu::BufferAddress,
+			usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC | wgpu::BufferUsages::COPY_DST,
+			mapped_at_creation: false,
+		});
+		ComputeRenderData {
+			settings_buffer,
+			visible_count_buffer,
+		}
+	});
+	
+	// the lods stop at the first one that doesn't have a file, the lod meshes keep using `bounding_radius` since they should be roughly the same shape
+	let mut lods = vec!();
+	if !use_gpu_culling && !has_transparent_meshes {
+		for lod in 1..=engine_config.lod_distances.len() {
+			let lod_path = get_lod_path(&model_path, lod);
+			if !lod_path.exists() {break;}
+			let (lod_meshes, _, _) = load_model_meshes(&lod_path, render_context, materials_storage, engine_config).with_context(|| format!("Failed to load lod {lod} model {lod_path:?}."))?;
+			let instances_buffer = render_context.device.create_buffer_init(
+				&wgpu::util::BufferInitDescriptor {
+					label: Some(&format!("model_{model_index}_lod_{lod}_instances_buffer")),
+					contents: bytemuck::cast_slice(&raw_instance_datas),
+					usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+				}
+			);
+			lods.push(LodRenderData {
+				meshes: lod_meshes,
+				instances_buffer,
+				instances_count: 0,
+			});
+		}
+	}
 	
 	Ok(ModelsRenderData {
-		culled_instances_buffer,
-		culled_instances_count: example_model_instance_datas.len() as u32,
+		culled_instances_buffers,
+		culled_instances_counts: vec!(raw_instance_datas.len() as u32; culled_instances_buffer_count),
+		culled_instances_index: 0,
 		total_instances_buffer,
-		total_instances_count: example_model_instance_datas.len() as u32,
+		total_instances_count: raw_instance_datas.len() as u32,
+		dirty_instance_ranges: vec!(),
+		indirect_args_buffer,
+		gpu_culling,
+		shadow_instances_buffer,
+		shadow_instances_count,
 		bounding_radius,
-		meshes: example_model_meshes,
+		aabb,
+		meshes,
+		lods,
 	})
 }
 
+// '.gltf' and '.glb' files are loaded as gltf and everything else is loaded as obj
+pub fn load_model_meshes(
+	model_path: &Path,
+	render_context: &RenderContextData,
+	materials_storage: &mut MaterialsStorage,
+	engine_config: &EngineConfig,
+) -> Result<(Vec<MeshRenderData>, f32, Aabb)> {
+	let model_extension = model_path.extension().and_then(|v| v.to_str()).map(str::to_lowercase);
+	match model_extension.as_deref() {
+		Some("gltf" | "glb") => load_gltf_model(model_path, render_context, materials_storage, engine_config),
+		_ => load_obj_model(model_path, render_context, materials_storage, engine_config),
+	}
+}
+
+// for example, lod 2 of 'assets/tree.obj' is 'assets/tree_lod2.obj'
+pub fn get_lod_path(model_path: &Path, lod: usize) -> PathBuf {
+	let stem = model_path.file_stem().map(|stem| stem.to_string_lossy()).unwrap_or_default();
+	let file_name = match model_path.extension() {
+		Some(extension) => format!("{stem}_lod{lod}.{}", extension.to_string_lossy()),
+		None => format!("{stem}_lod{lod}"),
+	};
+	model_path.with_file_name(file_name)
+}
 
 
-pub fn load_model(
+
+pub fn load_occlusion_render_data(render_context: &RenderContextData, model_instance_datas: &[Vec<InstanceData>]) -> OcclusionRenderData {
+	let query_set = render_context.device.create_query_set(&wgpu::QuerySetDescriptor {
+		label: Some("occlusion_query_set"),
+		ty: wgpu::QueryType::Occlusion,
+		count: OcclusionRenderData::MAX_QUERIES,
+	});
+	let boxes_buffer = render_context.device.create_buffer(&wgpu::BufferDescriptor {
+		label: Some("occlusion_boxes_buffer"),
+		size: OcclusionRenderData::MAX_QUERIES as u64 * std::mem::size_of::<OcclusionBox>() as u64,
+		usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+		mapped_at_creation: false,
+	});
+	let results_size = OcclusionRenderData::MAX_QUERIES as u64 * OcclusionRenderData::RESULT_SIZE;
+	let resolve_buffer = render_context.device.create_buffer(&wgpu::BufferDescriptor {
+		label: Some("occlusion_resolve_buffer"),
+		size: results_size,
+		usage: wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC,
+		mapped_at_creation: false,
+	});
+	let readback_buffer = render_context.device.create_buffer(&wgpu::BufferDescriptor {
+		label: Some("occlusion_readback_buffer"),
+		size: results_size,
+		usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+		mapped_at_creation: false,
+	});
+	OcclusionRenderData {
+		query_set,
+		boxes_buffer,
+		resolve_buffer,
+		readback_buffer,
+		occluded_groups: model_instance_datas.iter().map(|instance_datas| vec!(false; instance_datas.len().div_ceil(OcclusionRenderData::GROUP_SIZE))).collect(),
+		queried_groups: vec!(),
+		readback_queried_groups: vec!(),
+		readback_state: std::sync::Arc::new(std::sync::atomic::AtomicU8::new(GpuTimer::READBACK_IDLE)),
+	}
+}
+
+
+
+pub fn load_obj_model(
 	file_path: impl AsRef<Path>,
 	render_context: &RenderContextData,
 	materials_storage: &mut MaterialsStorage,
-	compress_textures: bool,
-) -> Result<(Vec<MeshRenderData>, f32)> {
+	engine_config: &EngineConfig,
+) -> Result<(Vec<MeshRenderData>, f32, Aabb)> {
+	let compress_textures = engine_config.compress_textures;
 	let file_path = file_path.as_ref();
 	let obj_text = fs::read_to_string(file_path).add_path_to_error(file_path)?;
 	let obj_cursor = Cursor::new(obj_text);
 	let mut obj_reader = BufReader::new(obj_cursor);
 	let parent_folder = file_path.parent().expect("Cannot load mesh at root directory");
 	
+	// HELP: tobj only keeps its own error kind, so the io error is stored here to say which file couldn't be read and why
+	let mtl_read_error = std::cell::Cell::new(None);
 	let (models, model_materials) = tobj::load_obj_buf(
 		&mut obj_reader,
 		&tobj::LoadOptions {
@@ -263,45 +928,102 @@ pub fn load_model(
 			single_index: true,
 			..Default::default()
 		},
-		move |p| {
-			let mat_text =
-				fs::read_to_string(parent_folder.join(p))
-				//.add_path_to_error(&file_path) // this would just be thrown away in the next map_err()
-				.map_err(|_err| tobj::LoadError::OpenFileFailed)?;
+		|p| {
+			let mtl_path = parent_folder.join(p);
+			let mat_text = fs::read_to_string(&mtl_path).add_path_to_error(&mtl_path).map_err(|err| {
+				mtl_read_error.set(Some(err));
+				tobj::LoadError::OpenFileFailed
+			})?;
 			tobj::load_mtl_buf(&mut BufReader::new(Cursor::new(mat_text)))
 		}
-	).context("Failed to decode model data.")?;
-	let model_materials = model_materials.context("Failed to read model materials")?;
+	).with_context(|| format!("Failed to decode model data for {file_path:?}."))?;
+	let model_materials = match (model_materials, mtl_read_error.take()) {
+		(StdResult::Err(_), Some(err)) => return Err(err.context(format!("Failed to read model materials for {file_path:?}."))),
+		(model_materials, _) => model_materials.with_context(|| format!("Failed to read model materials for {file_path:?} (the .mtl file might be missing)."))?,
+	};
 	
+	let flat_normal_map_id = materials_storage_utils::insert_flat_normal_map(materials_storage, render_context)?;
 	let mut material_ids = Vec::new();
-	for material in model_materials {
-		let Some(diffuse_texture_name) = material.diffuse_texture else {
+	let mut normal_map_material_ids = Vec::new();
+	let mut material_uv_transforms = Vec::new();
+	let mut material_alpha_modes = Vec::new();
+	let mut texture_paths = Vec::new();
+	let mut texture_indices = Vec::new();
+	let mut normal_map_paths = Vec::new();
+	let mut normal_map_indices = Vec::new();
+	for (i, material) in model_materials.into_iter().enumerate() {
+		material_ids.push(0);
+		normal_map_material_ids.push(flat_normal_map_id);
+		material_uv_transforms.push(glam::Affine2::IDENTITY);
+		// HELP: 'd' (dissolve) is the mtl's opacity, the texture's alpha is what's actually used for the blending though. There's
+		// no standard alpha testing in mtl files, so materials with a 'map_d' (dissolve texture) are treated as double-sided cutouts
+		let alpha_mode = if material.dissolve_texture.is_some() {
+			MaterialAlphaMode::Mask {cutoff: 0.5}
+		} else if material.dissolve.is_some_and(|dissolve| dissolve < 1.0) {
+			MaterialAlphaMode::Blend
+		} else {
+			MaterialAlphaMode::Opaque
+		};
+		material_alpha_modes.push(alpha_mode);
+		// HELP: tobj reads `map_Bump` / `bump` lines into `normal_texture`, which is the line most exporters use for normal maps
+		if let Some(normal_texture_line) = &material.normal_texture {
+			let (normal_texture_name, _uv_transform) = parse_mtl_texture_options(normal_texture_line); // the diffuse texture's uv transform is used for both
+			let normal_map_path = parent_folder.join(normal_texture_name);
+			fs::metadata(&normal_map_path).add_path_to_error(&normal_map_path).with_context(|| format!("Material '{}' in {file_path:?} references a normal map that can't be found.", material.name))?;
+			normal_map_paths.push(normal_map_path);
+			normal_map_indices.push(i);
+		}
+		let Some(diffuse_texture_line) = material.diffuse_texture else {
 			warn!("diffuse texture in material is `None`.");
-			material_ids.push(0);
 			continue;
 		};
-		let path = parent_folder.join(&diffuse_texture_name);
-		let material_id = match materials_storage_utils::get_material_id(&path, &materials_storage.list_2d) {
-			Some(v) => v,
-			None => materials_storage_utils::insert_material_2d(path, materials_storage, render_context, compress_textures)?,
-		};
-		material_ids.push(material_id);
+		let (diffuse_texture_name, uv_transform) = parse_mtl_texture_options(&diffuse_texture_line);
+		material_uv_transforms[i] = uv_transform;
+		let texture_path = parent_folder.join(diffuse_texture_name);
+		// checked here so that the error says which material is wrong, instead of failing later on a worker thread
+		fs::metadata(&texture_path).add_path_to_error(&texture_path).with_context(|| format!("Material '{}' in {file_path:?} references a texture that can't be found.", material.name))?;
+		texture_paths.push(texture_path);
+		texture_indices.push(i);
+	}
+	let thread_count = match engine_config.texture_loading_threads {
+		0 => std::thread::available_parallelism().map(|v| v.get()).unwrap_or(1),
+		v => v,
+	};
+	let texture_ids = materials_storage_utils::insert_materials_2d(texture_paths, materials_storage, render_context, compress_textures, true, thread_count, |finished_count, total_count| {
+		info!("Loaded texture {finished_count}/{total_count} for {file_path:?}");
+	})?;
+	for (i, texture_id) in texture_indices.into_iter().zip(texture_ids) {
+		material_ids[i] = texture_id;
+	}
+	let normal_map_ids = materials_storage_utils::insert_materials_2d(normal_map_paths, materials_storage, render_context, compress_textures, false, thread_count, |finished_count, total_count| {
+		info!("Loaded normal map {finished_count}/{total_count} for {file_path:?}");
+	})?;
+	for (i, normal_map_id) in normal_map_indices.into_iter().zip(normal_map_ids) {
+		normal_map_material_ids[i] = normal_map_id;
 	}
 	
 	let mut bounding_radius = 0.0f32;
+	let mut aabb = Aabb::EMPTY;
 	let meshes = models
 		.into_iter()
 		.map(|model| {
 			let pos_count = model.mesh.positions.len() / 3;
+			let has_tex_coords = model.mesh.texcoords.len() >= pos_count * 2;
+			let normals = if model.mesh.normals.len() >= pos_count * 3 {
+				model.mesh.normals.chunks_exact(3).map(|normal| [normal[0], normal[1], normal[2]]).collect::<Vec<_>>()
+			} else {
+				get_smooth_normals(&model.mesh.positions, &model.mesh.indices)
+			};
 			let mut basic_vertices = Vec::with_capacity(pos_count);
 			let mut extended_vertices = Vec::with_capacity(pos_count);
-			for i in 0..pos_count {
+			for (i, normal) in normals.into_iter().enumerate() {
 				let pos = (
 					model.mesh.positions[i * 3],
 					model.mesh.positions[i * 3 + 1],
 					model.mesh.positions[i * 3 + 2],
 				);
 				bounding_radius = bounding_radius.max((pos.0 * pos.0 + pos.1 * pos.1 + pos.2 * pos.2).sqrt());
+				aabb.add_point(glam::Vec3::new(pos.0, pos.1, pos.2));
 				basic_vertices.push(BasicVertexData {
 					pos: [
 						pos.0,
@@ -310,48 +1032,384 @@ pub fn load_model(
 					],
 				});
 				extended_vertices.push(ExtendedVertexData {
-					tex_coords: [model.mesh.texcoords[i * 2], 1.0 - model.mesh.texcoords[i * 2 + 1]],
-					normal: [
-						model.mesh.normals[i * 3],
-						model.mesh.normals[i * 3 + 1],
-						model.mesh.normals[i * 3 + 2],
-					],
+					tex_coords: if has_tex_coords {[model.mesh.texcoords[i * 2], 1.0 - model.mesh.texcoords[i * 2 + 1]]} else {[0.0, 0.0]},
+					normal,
+					tangent: [0.0; 4],
 				});
 			}
-			
-			let basic_vertex_buffer = render_context.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-				label: Some(&format!("'{:?}'_basic_vertex_buffer", &file_path)),
-				contents: bytemuck::cast_slice(&basic_vertices),
-				usage: wgpu::BufferUsages::VERTEX,
-			});
-			let extended_vertex_buffer = render_context.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-				label: Some(&format!("'{:?}'_extended_vertex_buffer", &file_path)),
-				contents: bytemuck::cast_slice(&extended_vertices),
-				usage: wgpu::BufferUsages::VERTEX,
-			});
-			let index_buffer = render_context.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-				label: Some(&format!("'{:?}'_index_buffer", &file_path)),
-				contents: bytemuck::cast_slice(&model.mesh.indices),
-				usage: wgpu::BufferUsages::INDEX,
-			});
+			set_tangents(&basic_vertices, &mut extended_vertices, &model.mesh.indices);
 			
 			let material_id = material_ids[model.mesh.material_id.unwrap_or(0)];
-			
-			MeshRenderData {
-				basic_vertex_buffer,
-				extended_vertex_buffer,
-				index_buffer,
-				index_count: model.mesh.indices.len() as u32,
-				material_id,
-			}
+			let normal_map_material_id = model.mesh.material_id.and_then(|i| normal_map_material_ids.get(i).copied()).unwrap_or(flat_normal_map_id);
+			let uv_transform = model.mesh.material_id.and_then(|i| material_uv_transforms.get(i).copied()).unwrap_or(glam::Affine2::IDENTITY);
+			let alpha_mode = model.mesh.material_id.and_then(|i| material_alpha_modes.get(i).copied()).unwrap_or(MaterialAlphaMode::Opaque);
+			let double_sided = matches!(alpha_mode, MaterialAlphaMode::Mask {..});
+			create_mesh_render_data(file_path, basic_vertices, extended_vertices, &model.mesh.indices, material_id, normal_map_material_id, uv_transform, alpha_mode, double_sided, render_context, engine_config)
 		})
 		.collect::<Vec<_>>();
 	
-	Ok((meshes, bounding_radius))
+	Ok((meshes, bounding_radius, aabb))
+}
+
+
+
+// for obj files without `vn` lines, each vertex gets the average of the normals of every face that touches its position (weighted by the face areas)
+pub fn get_smooth_normals(positions: &[f32], indices: &[u32]) -> Vec<[f32; 3]> {
+	let get_pos = |i: u32| glam::Vec3::new(positions[i as usize * 3], positions[i as usize * 3 + 1], positions[i as usize * 3 + 2]);
+	// vertices that share a position but have different tex coords are separate in the index buffer, so the normals are summed by position to avoid seams
+	let get_key = |i: u32| get_pos(i).to_array().map(f32::to_bits);
+	let mut normal_sums: HashMap<[u32; 3], glam::Vec3> = HashMap::new();
+	for triangle in indices.chunks_exact(3) {
+		let [a, b, c] = [triangle[0], triangle[1], triangle[2]].map(get_pos);
+		let face_normal = (b - a).cross(c - a); // not normalized, so bigger faces have more weight
+		for i in triangle {
+			*normal_sums.entry(get_key(*i)).or_insert(glam::Vec3::ZERO) += face_normal;
+		}
+	}
+	(0..(positions.len() / 3) as u32)
+		.map(|i| normal_sums.get(&get_key(i)).copied().unwrap_or(glam::Vec3::ZERO).normalize_or_zero().to_array())
+		.collect()
+}
+
+
+
+// fills in the tangents using each triangle's uv directions, vertices that are shared between triangles get the average
+// NOTE: vertices without usable uvs still get a tangent (any direction perpendicular to the normal), which is fine since their normal map is sampled at a single point anyway
+pub fn set_tangents(basic_vertices: &[BasicVertexData], extended_vertices: &mut [ExtendedVertexData], indices: &[u32]) {
+	let mut tangent_sums = vec!(glam::Vec3::ZERO; extended_vertices.len());
+	let mut bitangent_sums = vec!(glam::Vec3::ZERO; extended_vertices.len());
+	let mut add_triangle = |triangle: [usize; 3]| {
+		let [a, b, c] = triangle.map(|i| glam::Vec3::from_array(basic_vertices[i].pos));
+		let [uv_a, uv_b, uv_c] = triangle.map(|i| glam::Vec2::from_array(extended_vertices[i].tex_coords));
+		let (edge_1, edge_2) = (b - a, c - a);
+		let (uv_edge_1, uv_edge_2) = (uv_b - uv_a, uv_c - uv_a);
+		let det = uv_edge_1.perp_dot(uv_edge_2);
+		if det.abs() < f32::EPSILON {return;}
+		let tangent = (edge_1 * uv_edge_2.y - edge_2 * uv_edge_1.y) / det;
+		// NOTE: this points towards -v, which is up in the texture (where opengl-style normal maps have their +y)
+		let bitangent = (edge_1 * uv_edge_2.x - edge_2 * uv_edge_1.x) / det;
+		for i in triangle {
+			tangent_sums[i] += tangent;
+			bitangent_sums[i] += bitangent;
+		}
+	};
+	if indices.is_empty() {
+		for i in (0..extended_vertices.len()).step_by(3).filter(|i| i + 2 < extended_vertices.len()) {
+			add_triangle([i, i + 1, i + 2]);
+		}
+	} else {
+		for triangle in indices.chunks_exact(3) {
+			add_triangle([0, 1, 2].map(|i| triangle[i] as usize));
+		}
+	}
+	for (i, vertex) in extended_vertices.iter_mut().enumerate() {
+		let normal = glam::Vec3::from_array(vertex.normal);
+		// gram-schmidt, so the tangent is perpendicular to the (smoothed) normal
+		let tangent = (tangent_sums[i] - normal * normal.dot(tangent_sums[i])).try_normalize().unwrap_or_else(|| normal.any_orthonormal_vector());
+		let handedness = if normal.cross(tangent).dot(bitangent_sums[i]) < 0.0 {-1.0} else {1.0};
+		vertex.tangent = [tangent.x, tangent.y, tangent.z, handedness];
+	}
+}
+
+
+
+// HELP: when indexed draws are disabled, the vertices are expanded so that every triangle has its own three vertices
+#[allow(clippy::too_many_arguments)]
+pub fn create_mesh_render_data(
+	file_path: &Path,
+	mut basic_vertices: Vec<BasicVertexData>,
+	mut extended_vertices: Vec<ExtendedVertexData>,
+	indices: &[u32],
+	material_id: MaterialId,
+	normal_map_material_id: MaterialId,
+	uv_transform: glam::Affine2,
+	alpha_mode: MaterialAlphaMode,
+	double_sided: bool,
+	render_context: &RenderContextData,
+	engine_config: &EngineConfig,
+) -> MeshRenderData {
+	
+	if !engine_config.indexed_draws && !indices.is_empty() {
+		basic_vertices = indices.iter().map(|i| basic_vertices[*i as usize]).collect();
+		extended_vertices = indices.iter().map(|i| extended_vertices[*i as usize]).collect();
+	}
+	let use_index_buffer = engine_config.indexed_draws && !indices.is_empty();
+	
+	let basic_vertex_buffer = render_context.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+		label: Some(&format!("'{:?}'_basic_vertex_buffer", &file_path)),
+		contents: bytemuck::cast_slice(&basic_vertices),
+		usage: wgpu::BufferUsages::VERTEX,
+	});
+	let extended_vertex_buffer = render_context.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+		label: Some(&format!("'{:?}'_extended_vertex_buffer", &file_path)),
+		contents: bytemuck::cast_slice(&extended_vertices),
+		usage: wgpu::BufferUsages::VERTEX,
+	});
+	let index_buffer = use_index_buffer.then(|| render_context.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+		label: Some(&format!("'{:?}'_index_buffer", &file_path)),
+		contents: bytemuck::cast_slice(indices),
+		usage: wgpu::BufferUsages::INDEX,
+	}));
+	
+	let uv_transform_buffer = render_context.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+		label: Some(&format!("'{:?}'_uv_transform_buffer", &file_path)),
+		contents: bytemuck::cast_slice(&uv_transform_to_gpu_data(uv_transform)),
+		usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+	});
+	let material_settings_buffer = render_context.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+		label: Some(&format!("'{:?}'_material_settings_buffer", &file_path)),
+		contents: bytemuck::cast_slice(&alpha_mode.build_gpu_data()),
+		usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+	});
+	
+	MeshRenderData {
+		basic_vertex_buffer,
+		extended_vertex_buffer,
+		index_buffer,
+		index_count: if use_index_buffer {indices.len() as u32} else {0},
+		vertex_count: basic_vertices.len() as u32,
+		material_id,
+		normal_map_material_id,
+		uv_transform,
+		uv_transform_buffer,
+		alpha_mode,
+		double_sided,
+		material_settings_buffer,
+	}
+}
+
+
+
+// HELP: each primitive of each mesh becomes its own `MeshRenderData`, and the node
+// transforms are baked into the vertices since the whole file is drawn as one model
+pub fn load_gltf_model(
+	file_path: impl AsRef<Path>,
+	render_context: &RenderContextData,
+	materials_storage: &mut MaterialsStorage,
+	engine_config: &EngineConfig,
+) -> Result<(Vec<MeshRenderData>, f32, Aabb)> {
+	let file_path = file_path.as_ref();
+	let Some(parent_folder) = file_path.parent() else {
+		return Err(Error::msg(format!("Cannot load gltf model {file_path:?} because it doesn't have a parent folder.")));
+	};
+	let (document, buffers, images) = gltf::import(file_path).with_context(|| format!("Failed to decode gltf model {file_path:?}."))?;
+	
+	// images are keyed by their path when they're separate files, and by a made-up path when they're embedded
+	// normal maps hold directions instead of colors, so the images they use are uploaded without srgb (and get their own path so they don't clash with color textures)
+	let normal_map_images = document.materials().filter_map(|material| material.normal_texture()).map(|info| info.texture().source().index()).collect::<HashSet<_>>();
+	let flat_normal_map_id = materials_storage_utils::insert_flat_normal_map(materials_storage, render_context)?;
+	let mut image_material_ids = Vec::with_capacity(images.len());
+	for (i, (image, image_data)) in document.images().zip(images).enumerate() {
+		let mut material_path = match image.source() {
+			gltf::image::Source::Uri {uri, ..} if !uri.starts_with("data:") => parent_folder.join(uri),
+			_ => file_path.join(format!("#image_{i}")),
+		};
+		let is_normal_map = normal_map_images.contains(&i);
+		if is_normal_map {
+			material_path.as_mut_os_string().push("#normal_map");
+		}
+		let Some(rgba_bytes) = gltf_image_to_rgba8(&image_data) else {
+			warn!("Unsupported format {:?} for image {i} in {file_path:?}, skipping it.", image_data.format);
+			image_material_ids.push(None);
+			continue;
+		};
+		let material_id = materials_storage_utils::insert_material_2d_from_rgba8(material_path, rgba_bytes, (image_data.width, image_data.height), materials_storage, render_context, engine_config.compress_textures, !is_normal_map)?;
+		image_material_ids.push(Some(material_id));
+	}
+	
+	let Some(scene) = document.default_scene().or_else(|| document.scenes().next()) else {
+		return Err(Error::msg(format!("Gltf model {file_path:?} does not have any scenes.")));
+	};
+	let mut meshes = vec!();
+	let mut bounding_radius = 0.0f32;
+	let mut aabb = Aabb::EMPTY;
+	let mut nodes = scene.nodes().map(|node| (node, glam::Mat4::IDENTITY)).collect::<Vec<_>>();
+	while let Some((node, parent_transform)) = nodes.pop() {
+		let transform = parent_transform * glam::Mat4::from_cols_array_2d(&node.transform().matrix());
+		nodes.extend(node.children().map(|child| (child, transform)));
+		let Some(mesh) = node.mesh() else {continue;};
+		let normal_transform = glam::Mat3::from_mat4(transform).inverse().transpose();
+		// mirrored transforms flip the bitangent
+		let handedness_sign = glam::Mat3::from_mat4(transform).determinant().signum();
+		
+		for primitive in mesh.primitives() {
+			if primitive.mode() != gltf::mesh::Mode::Triangles {
+				warn!("Skipping primitive {} of mesh {:?} in {file_path:?} because it isn't made of triangles.", primitive.index(), mesh.name());
+				continue;
+			}
+			let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()]));
+			let Some(positions) = reader.read_positions() else {
+				warn!("Skipping primitive {} of mesh {:?} in {file_path:?} because it doesn't have positions.", primitive.index(), mesh.name());
+				continue;
+			};
+			let positions = positions.map(|pos| transform.transform_point3(glam::Vec3::from_array(pos))).collect::<Vec<_>>();
+			
+			let base_color_texture = primitive.material().pbr_metallic_roughness().base_color_texture();
+			let tex_coord_set = base_color_texture.as_ref().map(|info| info.tex_coord()).unwrap_or(0);
+			let tex_coords = match reader.read_tex_coords(tex_coord_set) {
+				Some(tex_coords) => tex_coords.into_f32().collect::<Vec<_>>(),
+				None => vec!([0.0, 0.0]; positions.len()),
+			};
+			let mut indices = match reader.read_indices() {
+				Some(indices) => indices.into_u32().collect::<Vec<_>>(),
+				None => (0..positions.len() as u32).collect(),
+			};
+			// NOTE: these are checked here so that a broken file gives an error instead of panicking when the vertices are indexed below
+			if tex_coords.len() != positions.len() {
+				return Err(Error::msg(format!("Primitive {} of mesh {:?} in {file_path:?} has {} texture coordinates but {} positions.", primitive.index(), mesh.name(), tex_coords.len(), positions.len())));
+			}
+			if let Some(index) = indices.iter().find(|i| **i as usize >= positions.len()) {
+				return Err(Error::msg(format!("Primitive {} of mesh {:?} in {file_path:?} has index {index}, but only {} vertices.", primitive.index(), mesh.name(), positions.len())));
+			}
+			
+			let mut basic_vertices = positions.iter().map(|pos| BasicVertexData {pos: pos.to_array()}).collect::<Vec<_>>();
+			let mut extended_vertices = match reader.read_normals() {
+				Some(normals) if normals.len() != positions.len() => {
+					return Err(Error::msg(format!("Primitive {} of mesh {:?} in {file_path:?} has {} normals but {} positions.", primitive.index(), mesh.name(), normals.len(), positions.len())));
+				}
+				Some(normals) => normals.zip(&tex_coords).map(|(normal, tex_coords)| ExtendedVertexData {
+					tex_coords: *tex_coords,
+					normal: (normal_transform * glam::Vec3::from_array(normal)).normalize_or_zero().to_array(),
+					tangent: [0.0; 4],
+				}).collect::<Vec<_>>(),
+				// without normals every triangle needs its own vertices so that they can all use the face normal
+				None => {
+					let mut extended_vertices = Vec::with_capacity(indices.len());
+					for triangle in indices.chunks_exact(3) {
+						let [a, b, c] = [0, 1, 2].map(|i| positions[triangle[i] as usize]);
+						let normal = (b - a).cross(c - a).normalize_or_zero().to_array();
+						extended_vertices.extend(triangle.iter().map(|i| ExtendedVertexData {
+							tex_coords: tex_coords[*i as usize],
+							normal,
+							tangent: [0.0; 4],
+						}));
+					}
+					basic_vertices = indices.iter().map(|i| basic_vertices[*i as usize]).collect();
+					indices = (0..basic_vertices.len() as u32).collect();
+					extended_vertices
+				}
+			};
+			// the file's own tangents are only usable if the vertices weren't split up above
+			match reader.read_tangents().filter(|_| basic_vertices.len() == positions.len()) {
+				Some(tangents) => {
+					for (vertex, tangent) in extended_vertices.iter_mut().zip(tangents) {
+						let direction = transform.transform_vector3(glam::Vec3::new(tangent[0], tangent[1], tangent[2])).normalize_or_zero();
+						vertex.tangent = [direction.x, direction.y, direction.z, tangent[3] * handedness_sign];
+					}
+				}
+				None => set_tangents(&basic_vertices, &mut extended_vertices, &indices),
+			}
+			
+			for pos in &positions {
+				bounding_radius = bounding_radius.max(pos.length());
+				aabb.add_point(*pos);
+			}
+			
+			let material_id = base_color_texture.as_ref().and_then(|info| image_material_ids[info.texture().source().index()]).unwrap_or(0);
+			let normal_map_material_id = primitive.material().normal_texture().and_then(|info| image_material_ids[info.texture().source().index()]).unwrap_or(flat_normal_map_id);
+			// HELP: the KHR_texture_transform rotation goes the opposite way from glam's
+			let uv_transform = match base_color_texture.as_ref().and_then(|info| info.texture_transform()) {
+				Some(texture_transform) => glam::Affine2::from_scale_angle_translation(
+					glam::Vec2::from_array(texture_transform.scale()),
+					-texture_transform.rotation(),
+					glam::Vec2::from_array(texture_transform.offset()),
+				),
+				None => glam::Affine2::IDENTITY,
+			};
+			
+			let alpha_mode = match primitive.material().alpha_mode() {
+				gltf::material::AlphaMode::Opaque => MaterialAlphaMode::Opaque,
+				gltf::material::AlphaMode::Blend => MaterialAlphaMode::Blend,
+				gltf::material::AlphaMode::Mask => MaterialAlphaMode::Mask {cutoff: primitive.material().alpha_cutoff().unwrap_or(0.5)},
+			};
+			let double_sided = primitive.material().double_sided();
+			
+			meshes.push(create_mesh_render_data(file_path, basic_vertices, extended_vertices, &indices, material_id, normal_map_material_id, uv_transform, alpha_mode, double_sided, render_context, engine_config));
+		}
+	}
+	
+	Ok((meshes, bounding_radius, aabb))
+}
+
+pub fn gltf_image_to_rgba8(image_data: &gltf::image::Data) -> Option<Vec<u8>> {
+	match image_data.format {
+		gltf::image::Format::R8G8B8A8 => Some(image_data.pixels.clone()),
+		gltf::image::Format::R8G8B8 => Some(image_data.pixels.chunks_exact(3).flat_map(|pixel| [pixel[0], pixel[1], pixel[2], 255]).collect()),
+		gltf::image::Format::R8G8 => Some(image_data.pixels.chunks_exact(2).flat_map(|pixel| [pixel[0], pixel[0], pixel[0], pixel[1]]).collect()),
+		gltf::image::Format::R8 => Some(image_data.pixels.iter().flat_map(|value| [*value, *value, *value, 255]).collect()),
+		_ => None,
+	}
+}
+
+
+
+// HELP: tobj gives the entire rest of the `map_Kd` line, so options like `-o` (offset) and
+// `-s` (scale) have to be separated from the file name here. Other options are skipped.
+pub fn parse_mtl_texture_options(line: &str) -> (String, glam::Affine2) {
+	let mut offset = glam::Vec2::ZERO;
+	let mut scale = glam::Vec2::ONE;
+	let mut tokens = line.split_whitespace().peekable();
+	let mut name_parts = vec!();
+	while let Some(token) = tokens.next() {
+		if !token.starts_with('-') || !name_parts.is_empty() {
+			name_parts.push(token);
+			continue;
+		}
+		// each option takes up to three numbers (u, v, w), but w is never used for 2d textures
+		let mut values = vec!();
+		while values.len() < 3 {
+			let Some(value) = tokens.peek().and_then(|v| v.parse::<f32>().ok()) else {break;};
+			values.push(value);
+			tokens.next();
+		}
+		match token {
+			"-o" => offset = glam::Vec2::new(values.first().copied().unwrap_or(0.0), values.get(1).copied().unwrap_or(0.0)),
+			"-s" => scale = glam::Vec2::new(values.first().copied().unwrap_or(1.0), values.get(1).copied().unwrap_or(1.0)),
+			_ => warn!("Unsupported texture option '{token}' in material, ignoring it."),
+		}
+	}
+	// the v coordinate is flipped when the vertices are loaded, so the transform has to be flipped too (v' = v * scale + offset becomes v' = v * scale + 1 - scale - offset)
+	let uv_transform = glam::Affine2::from_scale_angle_translation(scale, 0.0, glam::Vec2::new(offset.x, 1.0 - scale.y - offset.y));
+	(name_parts.join(" "), uv_transform)
+}
+
+
+
+pub fn load_foliage_render_data(
+	render_context: &RenderContextData,
+	materials_storage: &mut MaterialsStorage,
+	instance_datas: &[FoliageInstanceData],
+	engine_config: &EngineConfig,
+) -> Result<FoliageRenderData> {
+	
+	// NOTE: the foliage texture is never compressed because the bc7 settings that are used don't keep the alpha channel, which is needed for the alpha testing
+	let material_id = materials_storage.get_or_load_2d(render_context, utils::get_program_file_path("assets/grass.png"), false)?;
+	
+	let instances_buffer = render_context.device.create_buffer_init(
+		&wgpu::util::BufferInitDescriptor {
+			label: Some("foliage_instances_buffer"),
+			contents: bytemuck::cast_slice(instance_datas),
+			usage: wgpu::BufferUsages::VERTEX,
+		}
+	);
+	let settings_buffer = render_context.device.create_buffer_init(
+		&wgpu::util::BufferInitDescriptor {
+			label: Some("foliage_settings_buffer"),
+			contents: bytemuck::bytes_of(&FoliageSettings::new(engine_config, 0.0)),
+			usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+		}
+	);
+	
+	Ok(FoliageRenderData {
+		instances_buffer,
+		instances_count: instance_datas.len() as u32,
+		material_id,
+		settings_buffer,
+	})
 }
 
 
 
-pub fn load_skybox_material(render_context: &RenderContextData, materials_storage: &mut MaterialsStorage, compress_textures: bool) -> Result<usize> {
-	materials_storage_utils::insert_material_cube(utils::get_program_file_path("assets/skybox.png"), materials_storage, render_context, compress_textures)
+pub fn load_skybox_material(render_context: &RenderContextData, materials_storage: &mut MaterialsStorage, skybox_path: &Path, compress_textures: bool) -> Result<usize> {
+	materials_storage.get_or_load_cube(render_context, skybox_path, compress_textures)
 }
@@ -6,22 +6,165 @@ pub fn load_render_bindings(render_context: &RenderContextData, render_layouts:
 	
 	
 	
-	let shadow_caster_bind_0 = render_context.device.create_bind_group(&wgpu::BindGroupDescriptor {
-		label: Some("shadow_caster_bind_0"),
-		layout: &render_layouts.shadow_caster_bind_0_layout,
+	let shadow_caster_bind_0s = render_assets.shadow_caster.cascade_proj_mat_buffers.iter().enumerate().map(|(i, proj_mat_buffer)| {
+		render_context.device.create_bind_group(&wgpu::BindGroupDescriptor {
+			label: Some(&format!("shadow_caster_cascade_{i}_bind_0")),
+			layout: &render_layouts.shadow_caster_bind_0_layout,
+			entries: &[
+				wgpu::BindGroupEntry {
+					binding: 0,
+					resource: proj_mat_buffer.as_entire_binding(),
+				},
+			],
+		})
+	}).collect::<Vec<_>>();
+	
+	
+	
+	let models_bind_0 = load_models_bind_0(render_context, render_layouts, render_assets);
+	
+	let mut models_bind_1s = Vec::with_capacity(render_assets.models.len());
+	let mut models_lod_bind_1s = Vec::with_capacity(render_assets.models.len());
+	for (model_index, model) in render_assets.models.iter().enumerate() {
+		let model_bind_1s = model.meshes.iter().enumerate().map(|(i, mesh)| {
+			load_mesh_bind_1(render_context, render_layouts, render_assets, mesh, &format!("model_{model_index}_mesh_{i}_bind_1"))
+		}).collect::<Result<Vec<_>>>()?;
+		models_bind_1s.push(model_bind_1s);
+		let lod_bind_1s = model.lods.iter().enumerate().map(|(lod_index, lod)| {
+			lod.meshes.iter().enumerate().map(|(i, mesh)| {
+				load_mesh_bind_1(render_context, render_layouts, render_assets, mesh, &format!("model_{model_index}_lod_{}_mesh_{i}_bind_1", lod_index + 1))
+			}).collect::<Result<Vec<_>>>()
+		}).collect::<Result<Vec<_>>>()?;
+		models_lod_bind_1s.push(lod_bind_1s);
+	}
+	
+	
+	
+	// one bind group per culled instances buffer, since `double_buffer_instances` alternates which one is written to
+	let culling_bind_0s = render_assets.models.iter().enumerate().map(|(model_index, model)| {
+		let (Some(culling_bind_0_layout), Some(gpu_culling)) = (&render_layouts.culling_bind_0_layout, &model.gpu_culling) else {return vec!();};
+		model.culled_instances_buffers.iter().enumerate().map(|(i, culled_instances_buffer)| {
+			render_context.device.create_bind_group(&wgpu::BindGroupDescriptor {
+				label: Some(&format!("model_{model_index}_culling_{i}_bind_0")),
+				layout: culling_bind_0_layout,
+				entries: &[
+					wgpu::BindGroupEntry {
+						binding: 0,
+						resource: gpu_culling.settings_buffer.as_entire_binding(),
+					},
+					wgpu::BindGroupEntry {
+						binding: 1,
+						resource: model.total_instances_buffer.as_entire_binding(),
+					},
+					wgpu::BindGroupEntry {
+						binding: 2,
+						resource: culled_instances_buffer.as_entire_binding(),
+					},
+					wgpu::BindGroupEntry {
+						binding: 3,
+						resource: gpu_culling.visible_count_buffer.as_entire_binding(),
+					},
+				],
+			})
+		}).collect::<Vec<_>>()
+	}).collect::<Vec<_>>();
+	
+	
+	
+	let occlusion_bind_0 = render_context.device.create_bind_group(&wgpu::BindGroupDescriptor {
+		label: Some("occlusion_bind_0"),
+		layout: &render_layouts.occlusion_bind_0_layout,
 		entries: &[
 			wgpu::BindGroupEntry {
 				binding: 0,
-				resource: render_assets.shadow_caster.proj_mat_buffer.as_entire_binding(),
+				resource: render_assets.camera.buffer.as_entire_binding(),
 			},
 		],
 	});
 	
 	
 	
-	let models_bind_0 = render_context.device.create_bind_group(&wgpu::BindGroupDescriptor {
-		label: Some("models_bind_0"),
-		layout: &render_layouts.models_bind_0_layout,
+	// the skybox bind group only exists when the background mode is 'skybox'
+	let skybox_bind_0 = render_assets.skybox_material_id.map(|skybox_material_id| {
+		let skybox_view = &render_assets.materials_storage.list_cube[skybox_material_id].view;
+		// HELP: the binding can't be left empty, so the main skybox is used as a placeholder when there's no secondary skybox (the blend is forced to 0 in that case)
+		let secondary_skybox_id = render_assets.secondary_skybox_material_id.unwrap_or(skybox_material_id);
+		let secondary_skybox_view = &render_assets.materials_storage.list_cube[secondary_skybox_id].view;
+		render_context.device.create_bind_group(&wgpu::BindGroupDescriptor {
+			label: Some("skybox_bind_0"),
+			layout: &render_layouts.skybox_bind_0_layout,
+			entries: &[
+				wgpu::BindGroupEntry {
+					binding: 0,
+					resource: render_assets.camera.buffer.as_entire_binding(),
+				},
+				wgpu::BindGroupEntry {
+					binding: 1,
+					resource: wgpu::BindingResource::TextureView (skybox_view),
+				},
+				wgpu::BindGroupEntry {
+					binding: 2,
+					resource: wgpu::BindingResource::Sampler (&render_assets.skybox_sampler),
+				},
+				wgpu::BindGroupEntry {
+					binding: 3,
+					resource: wgpu::BindingResource::TextureView (secondary_skybox_view),
+				},
+				wgpu::BindGroupEntry {
+					binding: 4,
+					resource: render_assets.skybox_settings_buffer.as_entire_binding(),
+				},
+			],
+		})
+	});
+	
+	
+	
+	let gradient_background_bind_0 = render_context.device.create_bind_group(&wgpu::BindGroupDescriptor {
+		label: Some("gradient_background_bind_0"),
+		layout: &render_layouts.gradient_background_bind_0_layout,
+		entries: &[
+			wgpu::BindGroupEntry {
+				binding: 0,
+				resource: render_assets.gradient_background_buffer.as_entire_binding(),
+			},
+		],
+	});
+	
+	
+	
+	// the foliage bind group only exists when `foliage_enabled` is true
+	let foliage_bind_0 = render_assets.foliage.as_ref().map(|foliage| {
+		let foliage_view = &render_assets.materials_storage.list_2d[foliage.material_id].view;
+		render_context.device.create_bind_group(&wgpu::BindGroupDescriptor {
+			label: Some("foliage_bind_0"),
+			layout: &render_layouts.foliage_bind_0_layout,
+			entries: &[
+				wgpu::BindGroupEntry {
+					binding: 0,
+					resource: render_assets.camera.buffer.as_entire_binding(),
+				},
+				wgpu::BindGroupEntry {
+					binding: 1,
+					resource: foliage.settings_buffer.as_entire_binding(),
+				},
+				wgpu::BindGroupEntry {
+					binding: 2,
+					resource: wgpu::BindingResource::TextureView (foliage_view),
+				},
+				wgpu::BindGroupEntry {
+					binding: 3,
+					resource: wgpu::BindingResource::Sampler (&render_assets.default_sampler),
+				},
+			],
+		})
+	});
+	
+	
+	
+	let shadow_catcher_bind_0 = render_context.device.create_bind_group(&wgpu::BindGroupDescriptor {
+		label: Some("shadow_catcher_bind_0"),
+		layout: &render_layouts.shadow_catcher_bind_0_layout,
 		entries: &[
 			wgpu::BindGroupEntry {
 				binding: 0,
@@ -29,64 +172,213 @@ pub fn load_render_bindings(render_context: &RenderContextData, render_layouts:
 			},
 			wgpu::BindGroupEntry {
 				binding: 1,
-				resource: render_assets.shadow_caster.proj_mat_buffer.as_entire_binding(),
+				resource: render_assets.shadow_caster.cascades_buffer.as_entire_binding(),
 			},
 			wgpu::BindGroupEntry {
 				binding: 2,
-				resource: wgpu::BindingResource::Sampler (&render_assets.default_sampler),
+				resource: wgpu::BindingResource::TextureView (&render_assets.shadow_caster.depth_tex_view),
 			},
 			wgpu::BindGroupEntry {
 				binding: 3,
-				resource: wgpu::BindingResource::TextureView (&render_assets.shadow_caster.depth_tex_view),
+				resource: wgpu::BindingResource::Sampler (&render_assets.shadow_caster.depth_sampler),
 			},
 			wgpu::BindGroupEntry {
 				binding: 4,
-				resource: wgpu::BindingResource::Sampler (&render_assets.shadow_caster.depth_sampler),
+				resource: render_assets.shadow_catcher_buffer.as_entire_binding(),
 			},
 		],
 	});
 	
-	let mut example_models_bind_1s = vec!();
-	for (i, mesh) in render_assets.example_models.meshes.iter().enumerate() {
-		let material_view = &render_assets.materials_storage.list_2d[mesh.material_id].view;
-		let bind = render_context.device.create_bind_group(&wgpu::BindGroupDescriptor {
-			label: Some(&format!("example_model_mesh_{i}_bind_1")),
-			layout: &render_layouts.models_bind_1_layout,
-			entries: &[
-				wgpu::BindGroupEntry {
-					binding: 0,
-					resource: wgpu::BindingResource::TextureView (material_view),
-				},
-			],
-		});
-		example_models_bind_1s.push(bind);
-	}
-	
 	
 	
-	let skybox_view = &render_assets.materials_storage.list_cube[render_assets.skybox_material_id].view;
-	let skybox_bind_0 = render_context.device.create_bind_group(&wgpu::BindGroupDescriptor {
-		label: Some("skybox_bind_0"),
-		layout: &render_layouts.skybox_bind_0_layout,
+	let debug_lines_bind_0 = render_context.device.create_bind_group(&wgpu::BindGroupDescriptor {
+		label: Some("debug_lines_bind_0"),
+		layout: &render_layouts.debug_lines_bind_0_layout,
 		entries: &[
 			wgpu::BindGroupEntry {
 				binding: 0,
 				resource: render_assets.camera.buffer.as_entire_binding(),
 			},
+		],
+	});
+	
+	
+	
+	let bloom_bind_0s = load_bloom_bind_0s(render_context, render_layouts, render_assets);
+	
+	
+	
+	let color_correction_bind_0 = load_color_correction_bind_0(render_context, render_layouts, render_assets);
+	
+	
+	
+	let shadowmap_debug_bind_0 = render_context.device.create_bind_group(&wgpu::BindGroupDescriptor {
+		label: Some("shadowmap_debug_bind_0"),
+		layout: &render_layouts.shadowmap_debug_bind_0_layout,
+		entries: &[
+			wgpu::BindGroupEntry {
+				binding: 0,
+				resource: wgpu::BindingResource::TextureView (&render_assets.shadow_caster.depth_tex_view),
+			},
 			wgpu::BindGroupEntry {
 				binding: 1,
-				resource: wgpu::BindingResource::TextureView (skybox_view),
+				resource: render_assets.lighting_buffer.as_entire_binding(),
 			},
+		],
+	});
+	
+	
+	
+	let text_bind_0 = render_context.device.create_bind_group(&wgpu::BindGroupDescriptor {
+		label: Some("text_bind_0"),
+		layout: &render_layouts.text_bind_0_layout,
+		entries: &[
 			wgpu::BindGroupEntry {
-				binding: 2,
-				resource: wgpu::BindingResource::Sampler (&render_assets.skybox_sampler),
+				binding: 0,
+				resource: render_assets.text.settings_buffer.as_entire_binding(),
 			},
 		],
 	});
 	
 	
 	
-	let color_correction_bind_0 = render_context.device.create_bind_group(&wgpu::BindGroupDescriptor {
+	Ok(RenderBindings {
+		
+		shadow_caster_bind_0s,
+		
+		models_bind_0,
+		models_bind_1s,
+		models_lod_bind_1s,
+		
+		culling_bind_0s,
+		
+		occlusion_bind_0,
+		
+		skybox_bind_0,
+		
+		gradient_background_bind_0,
+		
+		foliage_bind_0,
+		
+		shadow_catcher_bind_0,
+		
+		debug_lines_bind_0,
+		
+		bloom_bind_0s,
+		
+		color_correction_bind_0,
+		
+		shadowmap_debug_bind_0,
+		
+		text_bind_0,
+		
+	})
+}
+
+
+
+// this is separate so that the bind group can be re-created when `lights_buffer` is re-allocated
+pub fn load_models_bind_0(render_context: &RenderContextData, render_layouts: &RenderLayouts, render_assets: &RenderAssets) -> wgpu::BindGroup {
+	render_context.device.create_bind_group(&wgpu::BindGroupDescriptor {
+		label: Some("models_bind_0"),
+		layout: &render_layouts.models_bind_0_layout,
+		entries: &[
+			wgpu::BindGroupEntry {
+				binding: 0,
+				resource: render_assets.camera.buffer.as_entire_binding(),
+			},
+			wgpu::BindGroupEntry {
+				binding: 1,
+				resource: render_assets.shadow_caster.cascades_buffer.as_entire_binding(),
+			},
+			wgpu::BindGroupEntry {
+				binding: 2,
+				resource: wgpu::BindingResource::Sampler (&render_assets.material_sampler),
+			},
+			wgpu::BindGroupEntry {
+				binding: 3,
+				resource: wgpu::BindingResource::TextureView (&render_assets.shadow_caster.depth_tex_view),
+			},
+			wgpu::BindGroupEntry {
+				binding: 4,
+				resource: wgpu::BindingResource::Sampler (&render_assets.shadow_caster.depth_sampler),
+			},
+			wgpu::BindGroupEntry {
+				binding: 5,
+				resource: render_assets.lighting_buffer.as_entire_binding(),
+			},
+			wgpu::BindGroupEntry {
+				binding: 6,
+				resource: render_assets.lights_buffer.as_entire_binding(),
+			},
+			wgpu::BindGroupEntry {
+				binding: 7,
+				resource: render_assets.directional_light_buffer.as_entire_binding(),
+			},
+			wgpu::BindGroupEntry {
+				binding: 8,
+				resource: wgpu::BindingResource::TextureView (&render_assets.ibl.irradiance_view),
+			},
+			wgpu::BindGroupEntry {
+				binding: 9,
+				resource: wgpu::BindingResource::TextureView (&render_assets.ibl.prefiltered_view),
+			},
+			wgpu::BindGroupEntry {
+				binding: 10,
+				resource: wgpu::BindingResource::TextureView (&render_assets.ibl.brdf_lut_view),
+			},
+			wgpu::BindGroupEntry {
+				binding: 11,
+				resource: wgpu::BindingResource::Sampler (&render_assets.ibl.sampler),
+			},
+		],
+	})
+}
+
+
+
+// this is separate so that the lod meshes can use it too
+// NOTE: the models pass picks each mesh's bind group by index, so each one has to be made from that mesh's own materials, and a
+// material id that doesn't exist is an error here instead of a panic (or a mesh silently drawn with the wrong textures)
+pub fn load_mesh_bind_1(render_context: &RenderContextData, render_layouts: &RenderLayouts, render_assets: &RenderAssets, mesh: &MeshRenderData, label: &str) -> Result<wgpu::BindGroup> {
+	let list_2d = &render_assets.materials_storage.list_2d;
+	let Some(material) = list_2d.get(mesh.material_id) else {
+		return Err(Error::msg(format!("Cannot create {label} because its material id ({}) doesn't exist.", mesh.material_id)));
+	};
+	let Some(normal_map) = list_2d.get(mesh.normal_map_material_id) else {
+		return Err(Error::msg(format!("Cannot create {label} because its normal map material id ({}) doesn't exist.", mesh.normal_map_material_id)));
+	};
+	let material_view = &material.view;
+	let normal_map_view = &normal_map.view;
+	Ok(render_context.device.create_bind_group(&wgpu::BindGroupDescriptor {
+		label: Some(label),
+		layout: &render_layouts.models_bind_1_layout,
+		entries: &[
+			wgpu::BindGroupEntry {
+				binding: 0,
+				resource: wgpu::BindingResource::TextureView (material_view),
+			},
+			wgpu::BindGroupEntry {
+				binding: 1,
+				resource: mesh.uv_transform_buffer.as_entire_binding(),
+			},
+			wgpu::BindGroupEntry {
+				binding: 2,
+				resource: wgpu::BindingResource::TextureView (normal_map_view),
+			},
+			wgpu::BindGroupEntry {
+				binding: 3,
+				resource: mesh.material_settings_buffer.as_entire_binding(),
+			},
+		],
+	}))
+}
+
+
+
+// this is separate so that the bind group can be re-created when `main_tex_view` is re-created (when the window is resized)
+pub fn load_color_correction_bind_0(render_context: &RenderContextData, render_layouts: &RenderLayouts, render_assets: &RenderAssets) -> wgpu::BindGroup {
+	render_context.device.create_bind_group(&wgpu::BindGroupDescriptor {
 		label: Some("color_correction_bind_0"),
 		layout: &render_layouts.color_correction_bind_0_layout,
 		entries: &[
@@ -102,21 +394,98 @@ pub fn load_render_bindings(render_context: &RenderContextData, render_layouts:
 				binding: 2,
 				resource: wgpu::BindingResource::Sampler (&render_assets.default_sampler),
 			},
+			wgpu::BindGroupEntry {
+				binding: 3,
+				resource: wgpu::BindingResource::TextureView (&render_assets.bloom.mip_views[0]),
+			},
 		],
-	});
-	
+	})
+}
+
+
+
+// this is separate so that the bind groups can be re-created when the bloom textures are re-created (when the window is resized)
+pub fn load_bloom_bind_0s(render_context: &RenderContextData, render_layouts: &RenderLayouts, render_assets: &RenderAssets) -> Vec<wgpu::BindGroup> {
+	let source_views = std::iter::once(&render_assets.main_tex_view).chain(&render_assets.bloom.mip_views);
+	source_views.enumerate().map(|(i, source_view)| {
+		render_context.device.create_bind_group(&wgpu::BindGroupDescriptor {
+			label: Some(&format!("bloom_bind_0_{i}")),
+			layout: &render_layouts.bloom_bind_0_layout,
+			entries: &[
+				wgpu::BindGroupEntry {
+					binding: 0,
+					resource: render_assets.color_correction_buffer.as_entire_binding(),
+				},
+				wgpu::BindGroupEntry {
+					binding: 1,
+					resource: wgpu::BindingResource::TextureView (source_view),
+				},
+				wgpu::BindGroupEntry {
+					binding: 2,
+					resource: wgpu::BindingResource::Sampler (&render_assets.default_sampler),
+				},
+			],
+		})
+	}).collect()
+}
+
+
+
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
 	
+	const TWO_MATERIALS_OBJ: &str = "mtllib two materials.mtl
+v 0.0 0.0 0.0
+v 1.0 0.0 0.0
+v 0.0 1.0 0.0
+v 1.0 1.0 0.0
+vt 0.0 0.0
+vt 1.0 0.0
+vt 0.0 1.0
+vt 1.0 1.0
+vn 0.0 0.0 1.0
+usemtl first
+f 1/1/1 2/2/1 3/3/1
+usemtl second
+f 2/2/1 4/4/1 3/3/1
+";
+	const TWO_MATERIALS_MTL: &str = "newmtl first
+map_Kd cube-diffuse.jpg
+newmtl second
+map_Kd happy-tree.png
+";
 	
-	Ok(RenderBindings {
+	#[test]
+	fn each_mesh_of_a_two_material_model_gets_its_own_bind_1() {
+		let folder = std::env::temp_dir().join(format!("wgpu_template_two_materials_{}", std::process::id()));
+		fs::create_dir_all(&folder).expect("Failed to create temp folder.");
+		fs::write(folder.join("two materials.obj"), TWO_MATERIALS_OBJ).expect("Failed to write obj file.");
+		fs::write(folder.join("two materials.mtl"), TWO_MATERIALS_MTL).expect("Failed to write mtl file.");
+		for texture in ["cube-diffuse.jpg", "happy-tree.png"] {
+			fs::copy(utils::get_program_file_path("assets").join(texture), folder.join(texture)).expect("Failed to copy texture.");
+		}
 		
-		shadow_caster_bind_0,
+		let engine_config = EngineConfig {
+			window_width: 64,
+			window_height: 64,
+			compress_textures: false,
+			model_paths: vec!(folder.join("two materials.obj")),
+			..EngineConfig::default()
+		};
+		if !load::headless_adapter_is_available(&engine_config) {
+			eprintln!("Skipping test because there's no adapter available.");
+			return;
+		}
+		let program_data = load::load_headless_program_data(Instant::now(), engine_config).expect("Failed to load headless program data.");
+		let _ = fs::remove_dir_all(&folder);
 		
-		models_bind_0,
-		example_models_bind_1s,
-		
-		skybox_bind_0,
-		
-		color_correction_bind_0,
-		
-	})
+		let meshes = &program_data.render_assets.models[0].meshes;
+		assert_eq!(meshes.len(), 2);
+		assert_ne!(meshes[0].material_id, meshes[1].material_id, "The two meshes should use different textures.");
+		assert_eq!(program_data.render_bindings.models_bind_1s[0].len(), 2);
+	}
+	
 }
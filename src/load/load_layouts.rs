@@ -2,44 +2,134 @@ use crate::prelude::*;
 
 
 
-pub fn load_render_layouts(render_context: &RenderContextData) -> Result<RenderLayouts> {
+pub fn load_render_layouts(render_context: &RenderContextData, engine_config: &EngineConfig) -> Result<RenderLayouts> {
 	
 	let (
 		shadow_caster_pipeline,
 		shadow_caster_bind_0_layout,
-	) = load_shadow_caster_layouts(render_context)?;
+	) = load_shadow_caster_layouts(render_context, engine_config)?;
 	
 	let (
 		models_pipeline,
+		models_wireframe_pipeline,
+		models_transparent_pipeline,
+		models_double_sided_pipeline,
 		models_bind_0_layout,
 		models_bind_1_layout,
-	) = load_models_layouts(render_context)?;
+	) = load_models_layouts(render_context, engine_config)?;
+	
+	// NOTE: compute pipelines can't be created on adapters without compute shaders, so this is only loaded when it's used
+	let (
+		culling_pipeline,
+		culling_bind_0_layout,
+	) = if engine_config.gpu_culling {
+		let (culling_pipeline, culling_bind_0_layout) = load_culling_layouts(render_context)?;
+		(Some(culling_pipeline), Some(culling_bind_0_layout))
+	} else {
+		(None, None)
+	};
+	
+	let (
+		occlusion_pipeline,
+		occlusion_bind_0_layout,
+	) = load_occlusion_layouts(render_context, engine_config)?;
 	
 	let (
 		skybox_pipeline,
 		skybox_bind_0_layout,
-	) = load_skybox_layouts(render_context)?;
+	) = load_skybox_layouts(render_context, engine_config)?;
+	
+	let (
+		gradient_background_pipeline,
+		gradient_background_bind_0_layout,
+	) = load_gradient_background_layouts(render_context, engine_config)?;
+	
+	let (
+		foliage_pipeline,
+		foliage_bind_0_layout,
+	) = load_foliage_layouts(render_context, engine_config)?;
+	
+	let (
+		shadow_catcher_pipeline,
+		shadow_catcher_bind_0_layout,
+	) = load_shadow_catcher_layouts(render_context, engine_config)?;
+	
+	let (
+		debug_lines_pipeline,
+		debug_lines_overlay_pipeline,
+		debug_lines_bind_0_layout,
+	) = load_debug_lines_layouts(render_context, engine_config)?;
+	
+	let (
+		bloom_bright_pass_pipeline,
+		bloom_downsample_pipeline,
+		bloom_upsample_pipeline,
+		bloom_bind_0_layout,
+	) = load_bloom_layouts(render_context)?;
 	
 	let (
 		color_correction_pipeline,
 		color_correction_bind_0_layout,
 	) = load_color_correction_layouts(render_context)?;
 	
+	let (
+		shadowmap_debug_pipeline,
+		shadowmap_debug_bind_0_layout,
+	) = load_shadowmap_debug_layouts(render_context)?;
+	
+	let (
+		text_pipeline,
+		text_bind_0_layout,
+	) = load_text_layouts(render_context)?;
+	
 	Ok(RenderLayouts {
 		
 		shadow_caster_pipeline,
 		shadow_caster_bind_0_layout,
 		
 		models_pipeline,
+		models_wireframe_pipeline,
+		models_transparent_pipeline,
+		models_double_sided_pipeline,
 		models_bind_0_layout,
 		models_bind_1_layout,
 		
+		culling_pipeline,
+		culling_bind_0_layout,
+		
+		occlusion_pipeline,
+		occlusion_bind_0_layout,
+		
 		skybox_pipeline,
 		skybox_bind_0_layout,
 		
+		gradient_background_pipeline,
+		gradient_background_bind_0_layout,
+		
+		foliage_pipeline,
+		foliage_bind_0_layout,
+		
+		shadow_catcher_pipeline,
+		shadow_catcher_bind_0_layout,
+		
+		debug_lines_pipeline,
+		debug_lines_overlay_pipeline,
+		debug_lines_bind_0_layout,
+		
+		bloom_bright_pass_pipeline,
+		bloom_downsample_pipeline,
+		bloom_upsample_pipeline,
+		bloom_bind_0_layout,
+		
 		color_correction_pipeline,
 		color_correction_bind_0_layout,
 		
+		shadowmap_debug_pipeline,
+		shadowmap_debug_bind_0_layout,
+		
+		text_pipeline,
+		text_bind_0_layout,
+		
 	})
 }
 
@@ -47,7 +137,7 @@ pub fn load_render_layouts(render_context: &RenderContextData) -> Result<RenderL
 
 
 
-pub fn load_shadow_caster_layouts(render_context: &RenderContextData) -> Result<(
+pub fn load_shadow_caster_layouts(render_context: &RenderContextData, engine_config: &EngineConfig) -> Result<(
 	wgpu::RenderPipeline,
 	wgpu::BindGroupLayout,
 )> {
@@ -104,7 +194,7 @@ pub fn load_shadow_caster_layouts(render_context: &RenderContextData) -> Result<
 			front_face: wgpu::FrontFace::Ccw,
 			cull_mode: Some(wgpu::Face::Front), // I'm not exactly sure why this needs to be different from the models pipeline
 			polygon_mode: wgpu::PolygonMode::Fill,
-			unclipped_depth: false,
+			unclipped_depth: engine_config.shadow_depth_clamp, // keeps geometry behind the shadow caster's near plane from being clipped
 			conservative: false,
 		},
 		depth_stencil: Some(wgpu::DepthStencilState {
@@ -137,7 +227,10 @@ pub fn load_shadow_caster_layouts(render_context: &RenderContextData) -> Result<
 
 
 
-pub fn load_models_layouts(render_context: &RenderContextData) -> Result<(
+pub fn load_models_layouts(render_context: &RenderContextData, engine_config: &EngineConfig) -> Result<(
+	wgpu::RenderPipeline,
+	Option<wgpu::RenderPipeline>,
+	wgpu::RenderPipeline,
 	wgpu::RenderPipeline,
 	wgpu::BindGroupLayout,
 	wgpu::BindGroupLayout,
@@ -155,9 +248,9 @@ pub fn load_models_layouts(render_context: &RenderContextData) -> Result<(
 	let models_bind_0_layout = render_context.device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
 		label: Some("models_bind_0_layout"),
 		entries: &[
-			wgpu::BindGroupLayoutEntry { // camera: proj_view_mat, inv_proj_mat, view_mat
+			wgpu::BindGroupLayoutEntry { // camera: proj_view_mat, inv_proj_mat, view_mat, resolution, inv_resolution
 				binding: 0,
-				visibility: wgpu::ShaderStages::VERTEX,
+				visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
 				ty: wgpu::BindingType::Buffer {
 					ty: wgpu::BufferBindingType::Uniform,
 					has_dynamic_offset: false,
@@ -165,7 +258,7 @@ pub fn load_models_layouts(render_context: &RenderContextData) -> Result<(
 				},
 				count: None,
 			},
-			wgpu::BindGroupLayoutEntry { // shadow_caster: proj_mat
+			wgpu::BindGroupLayoutEntry { // shadow_caster: cascades
 				binding: 1,
 				visibility: wgpu::ShaderStages::FRAGMENT,
 				ty: wgpu::BindingType::Buffer {
@@ -186,7 +279,7 @@ pub fn load_models_layouts(render_context: &RenderContextData) -> Result<(
 				visibility: wgpu::ShaderStages::FRAGMENT,
 				ty: wgpu::BindingType::Texture {
 					multisampled: false,
-					view_dimension: wgpu::TextureViewDimension::D2,
+					view_dimension: wgpu::TextureViewDimension::D2Array, // one layer per cascade
 					sample_type: wgpu::TextureSampleType::Depth,
 				},
 				count: None,
@@ -197,6 +290,72 @@ pub fn load_models_layouts(render_context: &RenderContextData) -> Result<(
 				ty: wgpu::BindingType::Sampler (wgpu::SamplerBindingType::Comparison),
 				count: None,
 			},
+			wgpu::BindGroupLayoutEntry { // lighting: settings
+				binding: 5,
+				visibility: wgpu::ShaderStages::FRAGMENT,
+				ty: wgpu::BindingType::Buffer {
+					ty: wgpu::BufferBindingType::Uniform,
+					has_dynamic_offset: false,
+					min_binding_size: None,
+				},
+				count: None,
+			},
+			wgpu::BindGroupLayoutEntry { // lighting: lights
+				binding: 6,
+				visibility: wgpu::ShaderStages::FRAGMENT,
+				ty: wgpu::BindingType::Buffer {
+					ty: wgpu::BufferBindingType::Storage { read_only: true },
+					has_dynamic_offset: false,
+					min_binding_size: None,
+				},
+				count: None,
+			},
+			wgpu::BindGroupLayoutEntry { // lighting: directional light
+				binding: 7,
+				visibility: wgpu::ShaderStages::FRAGMENT,
+				ty: wgpu::BindingType::Buffer {
+					ty: wgpu::BufferBindingType::Uniform,
+					has_dynamic_offset: false,
+					min_binding_size: None,
+				},
+				count: None,
+			},
+			wgpu::BindGroupLayoutEntry { // ibl: irradiance
+				binding: 8,
+				visibility: wgpu::ShaderStages::FRAGMENT,
+				ty: wgpu::BindingType::Texture {
+					multisampled: false,
+					view_dimension: wgpu::TextureViewDimension::Cube,
+					sample_type: wgpu::TextureSampleType::Float { filterable: true },
+				},
+				count: None,
+			},
+			wgpu::BindGroupLayoutEntry { // ibl: prefiltered
+				binding: 9,
+				visibility: wgpu::ShaderStages::FRAGMENT,
+				ty: wgpu::BindingType::Texture {
+					multisampled: false,
+					view_dimension: wgpu::TextureViewDimension::Cube,
+					sample_type: wgpu::TextureSampleType::Float { filterable: true },
+				},
+				count: None,
+			},
+			wgpu::BindGroupLayoutEntry { // ibl: brdf lut
+				binding: 10,
+				visibility: wgpu::ShaderStages::FRAGMENT,
+				ty: wgpu::BindingType::Texture {
+					multisampled: false,
+					view_dimension: wgpu::TextureViewDimension::D2,
+					sample_type: wgpu::TextureSampleType::Float { filterable: true },
+				},
+				count: None,
+			},
+			wgpu::BindGroupLayoutEntry { // ibl: sampler
+				binding: 11,
+				visibility: wgpu::ShaderStages::FRAGMENT,
+				ty: wgpu::BindingType::Sampler (wgpu::SamplerBindingType::Filtering),
+				count: None,
+			},
 		]
 	});
 	
@@ -214,6 +373,36 @@ pub fn load_models_layouts(render_context: &RenderContextData) -> Result<(
 				},
 				count: None,
 			},
+			wgpu::BindGroupLayoutEntry { // mesh: uv transform
+				binding: 1,
+				visibility: wgpu::ShaderStages::VERTEX,
+				ty: wgpu::BindingType::Buffer {
+					ty: wgpu::BufferBindingType::Uniform,
+					has_dynamic_offset: false,
+					min_binding_size: None,
+				},
+				count: None,
+			},
+			wgpu::BindGroupLayoutEntry { // material: normal map view
+				binding: 2,
+				visibility: wgpu::ShaderStages::FRAGMENT,
+				ty: wgpu::BindingType::Texture {
+					multisampled: false,
+					view_dimension: wgpu::TextureViewDimension::D2,
+					sample_type: wgpu::TextureSampleType::Float { filterable: true },
+				},
+				count: None,
+			},
+			wgpu::BindGroupLayoutEntry { // material: settings (alpha cutoff)
+				binding: 3,
+				visibility: wgpu::ShaderStages::FRAGMENT,
+				ty: wgpu::BindingType::Buffer {
+					ty: wgpu::BufferBindingType::Uniform,
+					has_dynamic_offset: false,
+					min_binding_size: None,
+				},
+				count: None,
+			},
 		],
 	});
 	
@@ -226,8 +415,9 @@ pub fn load_models_layouts(render_context: &RenderContextData) -> Result<(
 		],
 		push_constant_ranges: &[],
 	});
-	let models_pipeline = render_context.device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-		label: Some("models_render_pipeline"),
+	// the wireframe pipeline is the same except for the polygon mode, the transparent pipeline is the same except for the blending and depth writes, and the double-sided pipeline is the same except for the culling
+	let create_models_pipeline = |label: &str, polygon_mode: wgpu::PolygonMode, is_transparent: bool, cull_mode: Option<wgpu::Face>| render_context.device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+		label: Some(label),
 		layout: Some(&models_pipeline_layout),
 		vertex: wgpu::VertexState {
 			module: &models_shader,
@@ -243,8 +433,8 @@ pub fn load_models_layouts(render_context: &RenderContextData) -> Result<(
 			module: &models_shader,
 			entry_point: "fs_main",
 			targets: &[Some(wgpu::ColorTargetState {
-				format: render_context.surface_config.format,
-				blend: Some(wgpu::BlendState::REPLACE),
+				format: RenderAssets::MAIN_TEX_FORMAT,
+				blend: Some(if is_transparent {wgpu::BlendState::ALPHA_BLENDING} else {wgpu::BlendState::REPLACE}),
 				write_mask: wgpu::ColorWrites::ALL,
 			})],
 			compilation_options: wgpu::PipelineCompilationOptions::default(),
@@ -253,29 +443,41 @@ pub fn load_models_layouts(render_context: &RenderContextData) -> Result<(
 			topology: wgpu::PrimitiveTopology::TriangleList,
 			strip_index_format: None,
 			front_face: wgpu::FrontFace::Ccw,
-			cull_mode: Some(wgpu::Face::Back),
-			polygon_mode: wgpu::PolygonMode::Fill,
+			cull_mode,
+			polygon_mode,
 			unclipped_depth: false,
 			conservative: false,
 		},
 		depth_stencil: Some(wgpu::DepthStencilState {
 			format: wgpu::TextureFormat::Depth32Float,
-			depth_write_enabled: true,
+			depth_write_enabled: !is_transparent, // transparent meshes are still hidden behind opaque ones, but they shouldn't hide each other
 			depth_compare: wgpu::CompareFunction::Less,
 			stencil: wgpu::StencilState::default(),
-			bias: wgpu::DepthBiasState::default(),
+			bias: wgpu::DepthBiasState { // HELP: this can be used to fix z-fighting for decals and other coplanar geometry
+				constant: engine_config.models_depth_bias_constant,
+				slope_scale: engine_config.models_depth_bias_slope,
+				clamp: 0.0,
+			},
 		}),
 		multisample: wgpu::MultisampleState {
-			count: 1,
+			count: engine_config.msaa_samples,
 			mask: !0u64,
 			alpha_to_coverage_enabled: false,
 		},
 		multiview: None,
 	});
+	let models_pipeline = create_models_pipeline("models_render_pipeline", wgpu::PolygonMode::Fill, false, Some(wgpu::Face::Back));
+	let models_wireframe_pipeline = render_context.device.features().contains(wgpu::Features::POLYGON_MODE_LINE)
+		.then(|| create_models_pipeline("models_wireframe_render_pipeline", wgpu::PolygonMode::Line, false, Some(wgpu::Face::Back)));
+	let models_transparent_pipeline = create_models_pipeline("models_transparent_render_pipeline", wgpu::PolygonMode::Fill, true, Some(wgpu::Face::Back));
+	let models_double_sided_pipeline = create_models_pipeline("models_double_sided_render_pipeline", wgpu::PolygonMode::Fill, false, None);
 	
 	
 	Ok((
 		models_pipeline,
+		models_wireframe_pipeline,
+		models_transparent_pipeline,
+		models_double_sided_pipeline,
 		models_bind_0_layout,
 		models_bind_1_layout,
 	))
@@ -285,26 +487,26 @@ pub fn load_models_layouts(render_context: &RenderContextData) -> Result<(
 
 
 
-pub fn load_skybox_layouts(render_context: &RenderContextData) -> Result<(
-	wgpu::RenderPipeline,
+pub fn load_culling_layouts(render_context: &RenderContextData) -> Result<(
+	wgpu::ComputePipeline,
 	wgpu::BindGroupLayout,
 )> {
 	
 	
-	let shader_path = utils::get_program_file_path("shaders/skybox.wgsl");
+	let shader_path = utils::get_program_file_path("shaders/culling.wgsl");
 	let shader_source = fs::read_to_string(&shader_path).add_path_to_error(&shader_path)?;
 	let shader = render_context.device.create_shader_module(wgpu::ShaderModuleDescriptor {
-		label: Some("skybox_shader_module"),
+		label: Some("culling_shader_module"),
 		source: wgpu::ShaderSource::Wgsl(shader_source.into()),
 	});
 	
 	
-	let skybox_bind_0_layout = render_context.device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-		label: Some("skybox_bind_0_layout"),
+	let culling_bind_0_layout = render_context.device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+		label: Some("culling_bind_0_layout"),
 		entries: &[
-			wgpu::BindGroupLayoutEntry { // camera: proj_view_mat, inv_proj_mat, view_mat
+			wgpu::BindGroupLayoutEntry { // culling: settings
 				binding: 0,
-				visibility: wgpu::ShaderStages::VERTEX,
+				visibility: wgpu::ShaderStages::COMPUTE,
 				ty: wgpu::BindingType::Buffer {
 					ty: wgpu::BufferBindingType::Uniform,
 					has_dynamic_offset: false,
@@ -312,70 +514,134 @@ pub fn load_skybox_layouts(render_context: &RenderContextData) -> Result<(
 				},
 				count: None,
 			},
-			wgpu::BindGroupLayoutEntry { // skybox: texture
+			wgpu::BindGroupLayoutEntry { // model: total instances
 				binding: 1,
-				visibility: wgpu::ShaderStages::FRAGMENT,
-				ty: wgpu::BindingType::Texture {
-					multisampled: false,
-					view_dimension: wgpu::TextureViewDimension::Cube,
-					sample_type: wgpu::TextureSampleType::Float { filterable: true },
+				visibility: wgpu::ShaderStages::COMPUTE,
+				ty: wgpu::BindingType::Buffer {
+					ty: wgpu::BufferBindingType::Storage { read_only: true },
+					has_dynamic_offset: false,
+					min_binding_size: None,
 				},
 				count: None,
 			},
-			wgpu::BindGroupLayoutEntry { // skybox: sampler
+			wgpu::BindGroupLayoutEntry { // model: culled instances
 				binding: 2,
-				visibility: wgpu::ShaderStages::FRAGMENT,
-				ty: wgpu::BindingType::Sampler (wgpu::SamplerBindingType::Filtering),
+				visibility: wgpu::ShaderStages::COMPUTE,
+				ty: wgpu::BindingType::Buffer {
+					ty: wgpu::BufferBindingType::Storage { read_only: false },
+					has_dynamic_offset: false,
+					min_binding_size: None,
+				},
+				count: None,
+			},
+			wgpu::BindGroupLayoutEntry { // culling: visible count
+				binding: 3,
+				visibility: wgpu::ShaderStages::COMPUTE,
+				ty: wgpu::BindingType::Buffer {
+					ty: wgpu::BufferBindingType::Storage { read_only: false },
+					has_dynamic_offset: false,
+					min_binding_size: None,
+				},
 				count: None,
 			},
 		],
 	});
 	
 	
-	let skybox_pipeline_layout = render_context.device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-		label: Some("skybox_pipeline_layout"),
+	let culling_pipeline_layout = render_context.device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+		label: Some("culling_pipeline_layout"),
 		bind_group_layouts: &[
-			&skybox_bind_0_layout,
+			&culling_bind_0_layout,
 		],
 		push_constant_ranges: &[],
 	});
-	let skybox_pipeline = render_context.device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-		label: Some("skybox_pipeline"),
-		layout: Some(&skybox_pipeline_layout),
+	let culling_pipeline = render_context.device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+		label: Some("culling_pipeline"),
+		layout: Some(&culling_pipeline_layout),
+		module: &shader,
+		entry_point: "cs_main",
+		compilation_options: wgpu::PipelineCompilationOptions::default(),
+	});
+	
+	
+	Ok((
+		culling_pipeline,
+		culling_bind_0_layout,
+	))
+}
+
+
+
+
+
+pub fn load_occlusion_layouts(render_context: &RenderContextData, engine_config: &EngineConfig) -> Result<(
+	wgpu::RenderPipeline,
+	wgpu::BindGroupLayout,
+)> {
+	
+	
+	let shader_path = utils::get_program_file_path("shaders/occlusion boxes.wgsl");
+	let shader_source = fs::read_to_string(&shader_path).add_path_to_error(&shader_path)?;
+	let shader = render_context.device.create_shader_module(wgpu::ShaderModuleDescriptor {
+		label: Some("occlusion_shader_module"),
+		source: wgpu::ShaderSource::Wgsl(shader_source.into()),
+	});
+	
+	
+	let occlusion_bind_0_layout = render_context.device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+		label: Some("occlusion_bind_0_layout"),
+		entries: &[
+			wgpu::BindGroupLayoutEntry { // camera: proj_view_mat, inv_proj_mat, view_mat, resolution, inv_resolution
+				binding: 0,
+				visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+				ty: wgpu::BindingType::Buffer {
+					ty: wgpu::BufferBindingType::Uniform,
+					has_dynamic_offset: false,
+					min_binding_size: None,
+				},
+				count: None,
+			},
+		],
+	});
+	
+	
+	let occlusion_pipeline_layout = render_context.device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+		label: Some("occlusion_pipeline_layout"),
+		bind_group_layouts: &[
+			&occlusion_bind_0_layout,
+		],
+		push_constant_ranges: &[],
+	});
+	let occlusion_pipeline = render_context.device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+		label: Some("occlusion_pipeline"),
+		layout: Some(&occlusion_pipeline_layout),
 		vertex: wgpu::VertexState {
 			module: &shader,
 			entry_point: "vs_main",
-			buffers: &[],
+			buffers: &[
+				OcclusionBox::get_layout(),
+			],
 			compilation_options: wgpu::PipelineCompilationOptions::default(),
 		},
-		fragment: Some(wgpu::FragmentState {
-			module: &shader,
-			entry_point: "fs_main",
-			targets: &[Some(wgpu::ColorTargetState {
-				format: render_context.surface_config.format,
-				blend: Some(wgpu::BlendState::REPLACE),
-				write_mask: wgpu::ColorWrites::ALL,
-			})],
-			compilation_options: wgpu::PipelineCompilationOptions::default(),
-		}),
+		fragment: None,
 		primitive: wgpu::PrimitiveState {
 			topology: wgpu::PrimitiveTopology::TriangleList,
 			strip_index_format: None,
-			front_face: wgpu::FrontFace::Cw,
-			cull_mode: Some(wgpu::Face::Back), // todo: change this to None and draw 2 smaller triangles? easiest way is to generate coords (-1, -1), (-1, 1), (1, -1), (1, 1), but that gives one ccw and one cw. There's probably not anything wrong with one large tri though
+			front_face: wgpu::FrontFace::Ccw,
+			cull_mode: None, // the triangles' winding isn't consistent, and it doesn't matter which side of the box passes
 			polygon_mode: wgpu::PolygonMode::Fill,
 			unclipped_depth: false,
 			conservative: false,
 		},
 		depth_stencil: Some(wgpu::DepthStencilState {
 			format: wgpu::TextureFormat::Depth32Float,
-			depth_write_enabled: true,
+			depth_write_enabled: false, // the boxes are only tested, otherwise they would hide each other
 			depth_compare: wgpu::CompareFunction::LessEqual,
 			stencil: wgpu::StencilState::default(),
 			bias: wgpu::DepthBiasState::default(),
 		}),
 		multisample: wgpu::MultisampleState {
-			count: 1,
+			count: engine_config.msaa_samples,
 			mask: !0u64,
 			alpha_to_coverage_enabled: false,
 		},
@@ -384,8 +650,8 @@ pub fn load_skybox_layouts(render_context: &RenderContextData) -> Result<(
 	
 	
 	Ok((
-		skybox_pipeline,
-		skybox_bind_0_layout,
+		occlusion_pipeline,
+		occlusion_bind_0_layout,
 	))
 }
 
@@ -393,26 +659,26 @@ pub fn load_skybox_layouts(render_context: &RenderContextData) -> Result<(
 
 
 
-pub fn load_color_correction_layouts(render_context: &RenderContextData) -> Result<(
+pub fn load_skybox_layouts(render_context: &RenderContextData, engine_config: &EngineConfig) -> Result<(
 	wgpu::RenderPipeline,
 	wgpu::BindGroupLayout,
 )> {
 	
 	
-	let shader_path = utils::get_program_file_path("shaders/color correction.wgsl");
+	let shader_path = utils::get_program_file_path("shaders/skybox.wgsl");
 	let shader_source = fs::read_to_string(&shader_path).add_path_to_error(&shader_path)?;
 	let shader = render_context.device.create_shader_module(wgpu::ShaderModuleDescriptor {
-		label: Some("color_correction_shader_module"),
+		label: Some("skybox_shader_module"),
 		source: wgpu::ShaderSource::Wgsl(shader_source.into()),
 	});
 	
 	
-	let color_correction_bind_0_layout = render_context.device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-		label: Some("color_correction_bind_0_layout"),
+	let skybox_bind_0_layout = render_context.device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+		label: Some("skybox_bind_0_layout"),
 		entries: &[
-			wgpu::BindGroupLayoutEntry { // color correction: buffer
+			wgpu::BindGroupLayoutEntry { // camera: proj_view_mat, inv_proj_mat, view_mat, resolution, inv_resolution
 				binding: 0,
-				visibility: wgpu::ShaderStages::FRAGMENT,
+				visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
 				ty: wgpu::BindingType::Buffer {
 					ty: wgpu::BufferBindingType::Uniform,
 					has_dynamic_offset: false,
@@ -420,35 +686,730 @@ pub fn load_color_correction_layouts(render_context: &RenderContextData) -> Resu
 				},
 				count: None,
 			},
-			wgpu::BindGroupLayoutEntry { // main_tex: texture
+			wgpu::BindGroupLayoutEntry { // skybox: texture
 				binding: 1,
 				visibility: wgpu::ShaderStages::FRAGMENT,
 				ty: wgpu::BindingType::Texture {
 					multisampled: false,
-					view_dimension: wgpu::TextureViewDimension::D2,
+					view_dimension: wgpu::TextureViewDimension::Cube,
 					sample_type: wgpu::TextureSampleType::Float { filterable: true },
 				},
 				count: None,
 			},
-			wgpu::BindGroupLayoutEntry { // main_tex: sampler
+			wgpu::BindGroupLayoutEntry { // skybox: sampler
 				binding: 2,
 				visibility: wgpu::ShaderStages::FRAGMENT,
 				ty: wgpu::BindingType::Sampler (wgpu::SamplerBindingType::Filtering),
 				count: None,
 			},
+			wgpu::BindGroupLayoutEntry { // secondary skybox: texture
+				binding: 3,
+				visibility: wgpu::ShaderStages::FRAGMENT,
+				ty: wgpu::BindingType::Texture {
+					multisampled: false,
+					view_dimension: wgpu::TextureViewDimension::Cube,
+					sample_type: wgpu::TextureSampleType::Float { filterable: true },
+				},
+				count: None,
+			},
+			wgpu::BindGroupLayoutEntry { // skybox: settings
+				binding: 4,
+				visibility: wgpu::ShaderStages::FRAGMENT,
+				ty: wgpu::BindingType::Buffer {
+					ty: wgpu::BufferBindingType::Uniform,
+					has_dynamic_offset: false,
+					min_binding_size: None,
+				},
+				count: None,
+			},
 		],
 	});
 	
 	
-	let color_correction_pipeline_layout = render_context.device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-		label: Some("color_correction_pipeline_layout"),
+	let skybox_pipeline_layout = render_context.device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+		label: Some("skybox_pipeline_layout"),
 		bind_group_layouts: &[
-			&color_correction_bind_0_layout,
+			&skybox_bind_0_layout,
 		],
 		push_constant_ranges: &[],
 	});
-	let color_correction_pipeline = render_context.device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-		label: Some("color_correction_pipeline"),
+	let skybox_pipeline = render_context.device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+		label: Some("skybox_pipeline"),
+		layout: Some(&skybox_pipeline_layout),
+		vertex: wgpu::VertexState {
+			module: &shader,
+			entry_point: "vs_main",
+			buffers: &[],
+			compilation_options: wgpu::PipelineCompilationOptions::default(),
+		},
+		fragment: Some(wgpu::FragmentState {
+			module: &shader,
+			entry_point: "fs_main",
+			targets: &[Some(wgpu::ColorTargetState {
+				format: RenderAssets::MAIN_TEX_FORMAT,
+				blend: Some(wgpu::BlendState::REPLACE),
+				write_mask: wgpu::ColorWrites::ALL,
+			})],
+			compilation_options: wgpu::PipelineCompilationOptions::default(),
+		}),
+		primitive: wgpu::PrimitiveState {
+			topology: wgpu::PrimitiveTopology::TriangleList,
+			strip_index_format: None,
+			front_face: wgpu::FrontFace::Cw,
+			cull_mode: Some(wgpu::Face::Back), // todo: change this to None and draw 2 smaller triangles? easiest way is to generate coords (-1, -1), (-1, 1), (1, -1), (1, 1), but that gives one ccw and one cw. There's probably not anything wrong with one large tri though
+			polygon_mode: wgpu::PolygonMode::Fill,
+			unclipped_depth: false,
+			conservative: false,
+		},
+		depth_stencil: Some(wgpu::DepthStencilState {
+			format: wgpu::TextureFormat::Depth32Float,
+			depth_write_enabled: true,
+			depth_compare: wgpu::CompareFunction::LessEqual,
+			stencil: wgpu::StencilState::default(),
+			bias: wgpu::DepthBiasState::default(),
+		}),
+		multisample: wgpu::MultisampleState {
+			count: engine_config.msaa_samples,
+			mask: !0u64,
+			alpha_to_coverage_enabled: false,
+		},
+		multiview: None,
+	});
+	
+	
+	Ok((
+		skybox_pipeline,
+		skybox_bind_0_layout,
+	))
+}
+
+
+
+
+
+pub fn load_gradient_background_layouts(render_context: &RenderContextData, engine_config: &EngineConfig) -> Result<(
+	wgpu::RenderPipeline,
+	wgpu::BindGroupLayout,
+)> {
+	
+	
+	let shader_path = utils::get_program_file_path("shaders/gradient background.wgsl");
+	let shader_source = fs::read_to_string(&shader_path).add_path_to_error(&shader_path)?;
+	let shader = render_context.device.create_shader_module(wgpu::ShaderModuleDescriptor {
+		label: Some("gradient_background_shader_module"),
+		source: wgpu::ShaderSource::Wgsl(shader_source.into()),
+	});
+	
+	
+	let gradient_background_bind_0_layout = render_context.device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+		label: Some("gradient_background_bind_0_layout"),
+		entries: &[
+			wgpu::BindGroupLayoutEntry { // gradient background: settings
+				binding: 0,
+				visibility: wgpu::ShaderStages::FRAGMENT,
+				ty: wgpu::BindingType::Buffer {
+					ty: wgpu::BufferBindingType::Uniform,
+					has_dynamic_offset: false,
+					min_binding_size: None,
+				},
+				count: None,
+			},
+		],
+	});
+	
+	
+	let gradient_background_pipeline_layout = render_context.device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+		label: Some("gradient_background_pipeline_layout"),
+		bind_group_layouts: &[
+			&gradient_background_bind_0_layout,
+		],
+		push_constant_ranges: &[],
+	});
+	let gradient_background_pipeline = render_context.device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+		label: Some("gradient_background_pipeline"),
+		layout: Some(&gradient_background_pipeline_layout),
+		vertex: wgpu::VertexState {
+			module: &shader,
+			entry_point: "vs_main",
+			buffers: &[],
+			compilation_options: wgpu::PipelineCompilationOptions::default(),
+		},
+		fragment: Some(wgpu::FragmentState {
+			module: &shader,
+			entry_point: "fs_main",
+			targets: &[Some(wgpu::ColorTargetState {
+				format: RenderAssets::MAIN_TEX_FORMAT,
+				blend: Some(wgpu::BlendState::REPLACE),
+				write_mask: wgpu::ColorWrites::ALL,
+			})],
+			compilation_options: wgpu::PipelineCompilationOptions::default(),
+		}),
+		primitive: wgpu::PrimitiveState {
+			topology: wgpu::PrimitiveTopology::TriangleList,
+			strip_index_format: None,
+			front_face: wgpu::FrontFace::Cw,
+			cull_mode: Some(wgpu::Face::Back),
+			polygon_mode: wgpu::PolygonMode::Fill,
+			unclipped_depth: false,
+			conservative: false,
+		},
+		depth_stencil: Some(wgpu::DepthStencilState { // same as the skybox, this is drawn at the far plane so it only fills pixels that the models didn't cover
+			format: wgpu::TextureFormat::Depth32Float,
+			depth_write_enabled: false,
+			depth_compare: wgpu::CompareFunction::LessEqual,
+			stencil: wgpu::StencilState::default(),
+			bias: wgpu::DepthBiasState::default(),
+		}),
+		multisample: wgpu::MultisampleState {
+			count: engine_config.msaa_samples,
+			mask: !0u64,
+			alpha_to_coverage_enabled: false,
+		},
+		multiview: None,
+	});
+	
+	
+	Ok((
+		gradient_background_pipeline,
+		gradient_background_bind_0_layout,
+	))
+}
+
+
+
+
+
+pub fn load_foliage_layouts(render_context: &RenderContextData, engine_config: &EngineConfig) -> Result<(
+	wgpu::RenderPipeline,
+	wgpu::BindGroupLayout,
+)> {
+	
+	
+	let shader_path = utils::get_program_file_path("shaders/foliage.wgsl");
+	let shader_source = fs::read_to_string(&shader_path).add_path_to_error(&shader_path)?;
+	let shader = render_context.device.create_shader_module(wgpu::ShaderModuleDescriptor {
+		label: Some("foliage_shader_module"),
+		source: wgpu::ShaderSource::Wgsl(shader_source.into()),
+	});
+	
+	
+	let foliage_bind_0_layout = render_context.device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+		label: Some("foliage_bind_0_layout"),
+		entries: &[
+			wgpu::BindGroupLayoutEntry { // camera: proj_view_mat, inv_proj_mat, view_mat, resolution, inv_resolution
+				binding: 0,
+				visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+				ty: wgpu::BindingType::Buffer {
+					ty: wgpu::BufferBindingType::Uniform,
+					has_dynamic_offset: false,
+					min_binding_size: None,
+				},
+				count: None,
+			},
+			wgpu::BindGroupLayoutEntry { // foliage: settings
+				binding: 1,
+				visibility: wgpu::ShaderStages::VERTEX,
+				ty: wgpu::BindingType::Buffer {
+					ty: wgpu::BufferBindingType::Uniform,
+					has_dynamic_offset: false,
+					min_binding_size: None,
+				},
+				count: None,
+			},
+			wgpu::BindGroupLayoutEntry { // foliage: texture
+				binding: 2,
+				visibility: wgpu::ShaderStages::FRAGMENT,
+				ty: wgpu::BindingType::Texture {
+					multisampled: false,
+					view_dimension: wgpu::TextureViewDimension::D2,
+					sample_type: wgpu::TextureSampleType::Float { filterable: true },
+				},
+				count: None,
+			},
+			wgpu::BindGroupLayoutEntry { // foliage: sampler
+				binding: 3,
+				visibility: wgpu::ShaderStages::FRAGMENT,
+				ty: wgpu::BindingType::Sampler (wgpu::SamplerBindingType::Filtering),
+				count: None,
+			},
+		],
+	});
+	
+	
+	let foliage_pipeline_layout = render_context.device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+		label: Some("foliage_pipeline_layout"),
+		bind_group_layouts: &[
+			&foliage_bind_0_layout,
+		],
+		push_constant_ranges: &[],
+	});
+	let foliage_pipeline = render_context.device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+		label: Some("foliage_pipeline"),
+		layout: Some(&foliage_pipeline_layout),
+		vertex: wgpu::VertexState {
+			module: &shader,
+			entry_point: "vs_main",
+			buffers: &[
+				FoliageInstanceData::get_layout(),
+			],
+			compilation_options: wgpu::PipelineCompilationOptions::default(),
+		},
+		fragment: Some(wgpu::FragmentState {
+			module: &shader,
+			entry_point: "fs_main",
+			targets: &[Some(wgpu::ColorTargetState {
+				format: RenderAssets::MAIN_TEX_FORMAT,
+				blend: Some(wgpu::BlendState::REPLACE),
+				write_mask: wgpu::ColorWrites::ALL,
+			})],
+			compilation_options: wgpu::PipelineCompilationOptions::default(),
+		}),
+		primitive: wgpu::PrimitiveState {
+			topology: wgpu::PrimitiveTopology::TriangleList,
+			strip_index_format: None,
+			front_face: wgpu::FrontFace::Ccw,
+			cull_mode: None, // the quads need to be visible from both sides
+			polygon_mode: wgpu::PolygonMode::Fill,
+			unclipped_depth: false,
+			conservative: false,
+		},
+		depth_stencil: Some(wgpu::DepthStencilState {
+			format: wgpu::TextureFormat::Depth32Float,
+			depth_write_enabled: true,
+			depth_compare: wgpu::CompareFunction::Less,
+			stencil: wgpu::StencilState::default(),
+			bias: wgpu::DepthBiasState::default(),
+		}),
+		multisample: wgpu::MultisampleState {
+			count: engine_config.msaa_samples,
+			mask: !0u64,
+			alpha_to_coverage_enabled: false,
+		},
+		multiview: None,
+	});
+	
+	
+	Ok((
+		foliage_pipeline,
+		foliage_bind_0_layout,
+	))
+}
+
+
+
+
+
+pub fn load_shadow_catcher_layouts(render_context: &RenderContextData, engine_config: &EngineConfig) -> Result<(
+	wgpu::RenderPipeline,
+	wgpu::BindGroupLayout,
+)> {
+	
+	
+	let shader_path = utils::get_program_file_path("shaders/shadow catcher.wgsl");
+	let shader_source = fs::read_to_string(&shader_path).add_path_to_error(&shader_path)?;
+	let shader = render_context.device.create_shader_module(wgpu::ShaderModuleDescriptor {
+		label: Some("shadow_catcher_shader_module"),
+		source: wgpu::ShaderSource::Wgsl(shader_source.into()),
+	});
+	
+	
+	let shadow_catcher_bind_0_layout = render_context.device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+		label: Some("shadow_catcher_bind_0_layout"),
+		entries: &[
+			wgpu::BindGroupLayoutEntry { // camera: proj_view_mat, inv_proj_mat, view_mat, resolution, inv_resolution
+				binding: 0,
+				visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+				ty: wgpu::BindingType::Buffer {
+					ty: wgpu::BufferBindingType::Uniform,
+					has_dynamic_offset: false,
+					min_binding_size: None,
+				},
+				count: None,
+			},
+			wgpu::BindGroupLayoutEntry { // shadow_caster: cascades
+				binding: 1,
+				visibility: wgpu::ShaderStages::FRAGMENT,
+				ty: wgpu::BindingType::Buffer {
+					ty: wgpu::BufferBindingType::Uniform,
+					has_dynamic_offset: false,
+					min_binding_size: None,
+				},
+				count: None,
+			},
+			wgpu::BindGroupLayoutEntry { // shadowmap: texture
+				binding: 2,
+				visibility: wgpu::ShaderStages::FRAGMENT,
+				ty: wgpu::BindingType::Texture {
+					multisampled: false,
+					view_dimension: wgpu::TextureViewDimension::D2Array, // one layer per cascade
+					sample_type: wgpu::TextureSampleType::Depth,
+				},
+				count: None,
+			},
+			wgpu::BindGroupLayoutEntry { // shadowmap: sampler
+				binding: 3,
+				visibility: wgpu::ShaderStages::FRAGMENT,
+				ty: wgpu::BindingType::Sampler (wgpu::SamplerBindingType::Comparison),
+				count: None,
+			},
+			wgpu::BindGroupLayoutEntry { // shadow catcher: settings
+				binding: 4,
+				visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+				ty: wgpu::BindingType::Buffer {
+					ty: wgpu::BufferBindingType::Uniform,
+					has_dynamic_offset: false,
+					min_binding_size: None,
+				},
+				count: None,
+			},
+		],
+	});
+	
+	
+	let shadow_catcher_pipeline_layout = render_context.device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+		label: Some("shadow_catcher_pipeline_layout"),
+		bind_group_layouts: &[
+			&shadow_catcher_bind_0_layout,
+		],
+		push_constant_ranges: &[],
+	});
+	let shadow_catcher_pipeline = render_context.device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+		label: Some("shadow_catcher_pipeline"),
+		layout: Some(&shadow_catcher_pipeline_layout),
+		vertex: wgpu::VertexState {
+			module: &shader,
+			entry_point: "vs_main",
+			buffers: &[],
+			compilation_options: wgpu::PipelineCompilationOptions::default(),
+		},
+		fragment: Some(wgpu::FragmentState {
+			module: &shader,
+			entry_point: "fs_main",
+			targets: &[Some(wgpu::ColorTargetState {
+				format: RenderAssets::MAIN_TEX_FORMAT,
+				blend: Some(wgpu::BlendState::ALPHA_BLENDING), // the plane only darkens whatever is already behind it
+				write_mask: wgpu::ColorWrites::COLOR,
+			})],
+			compilation_options: wgpu::PipelineCompilationOptions::default(),
+		}),
+		primitive: wgpu::PrimitiveState {
+			topology: wgpu::PrimitiveTopology::TriangleList,
+			strip_index_format: None,
+			front_face: wgpu::FrontFace::Ccw,
+			cull_mode: None,
+			polygon_mode: wgpu::PolygonMode::Fill,
+			unclipped_depth: false,
+			conservative: false,
+		},
+		depth_stencil: Some(wgpu::DepthStencilState { // tested against the models, but doesn't write so it never hides anything
+			format: wgpu::TextureFormat::Depth32Float,
+			depth_write_enabled: false,
+			depth_compare: wgpu::CompareFunction::LessEqual,
+			stencil: wgpu::StencilState::default(),
+			bias: wgpu::DepthBiasState::default(),
+		}),
+		multisample: wgpu::MultisampleState {
+			count: engine_config.msaa_samples,
+			mask: !0u64,
+			alpha_to_coverage_enabled: false,
+		},
+		multiview: None,
+	});
+	
+	
+	Ok((
+		shadow_catcher_pipeline,
+		shadow_catcher_bind_0_layout,
+	))
+}
+
+
+
+
+
+pub fn load_debug_lines_layouts(render_context: &RenderContextData, engine_config: &EngineConfig) -> Result<(
+	wgpu::RenderPipeline,
+	wgpu::RenderPipeline,
+	wgpu::BindGroupLayout,
+)> {
+	
+	
+	let shader_path = utils::get_program_file_path("shaders/debug lines.wgsl");
+	let shader_source = fs::read_to_string(&shader_path).add_path_to_error(&shader_path)?;
+	let shader = render_context.device.create_shader_module(wgpu::ShaderModuleDescriptor {
+		label: Some("debug_lines_shader_module"),
+		source: wgpu::ShaderSource::Wgsl(shader_source.into()),
+	});
+	
+	
+	let debug_lines_bind_0_layout = render_context.device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+		label: Some("debug_lines_bind_0_layout"),
+		entries: &[
+			wgpu::BindGroupLayoutEntry { // camera: proj_view_mat, inv_proj_mat, view_mat, resolution, inv_resolution
+				binding: 0,
+				visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+				ty: wgpu::BindingType::Buffer {
+					ty: wgpu::BufferBindingType::Uniform,
+					has_dynamic_offset: false,
+					min_binding_size: None,
+				},
+				count: None,
+			},
+		],
+	});
+	
+	
+	let debug_lines_pipeline_layout = render_context.device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+		label: Some("debug_lines_pipeline_layout"),
+		bind_group_layouts: &[
+			&debug_lines_bind_0_layout,
+		],
+		push_constant_ranges: &[],
+	});
+	// HELP: the two pipelines only differ in their depth state, the overlay one ignores the depth buffer so that it's always drawn on top and never hides the normal scene
+	let create_pipeline = |label: &str, depth_write_enabled: bool, depth_compare: wgpu::CompareFunction| render_context.device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+		label: Some(label),
+		layout: Some(&debug_lines_pipeline_layout),
+		vertex: wgpu::VertexState {
+			module: &shader,
+			entry_point: "vs_main",
+			buffers: &[
+				DebugLineVertexData::get_layout(),
+			],
+			compilation_options: wgpu::PipelineCompilationOptions::default(),
+		},
+		fragment: Some(wgpu::FragmentState {
+			module: &shader,
+			entry_point: "fs_main",
+			targets: &[Some(wgpu::ColorTargetState {
+				format: RenderAssets::MAIN_TEX_FORMAT,
+				blend: Some(wgpu::BlendState::REPLACE),
+				write_mask: wgpu::ColorWrites::ALL,
+			})],
+			compilation_options: wgpu::PipelineCompilationOptions::default(),
+		}),
+		primitive: wgpu::PrimitiveState {
+			topology: wgpu::PrimitiveTopology::LineList,
+			strip_index_format: None,
+			front_face: wgpu::FrontFace::Ccw,
+			cull_mode: None,
+			polygon_mode: wgpu::PolygonMode::Fill,
+			unclipped_depth: false,
+			conservative: false,
+		},
+		depth_stencil: Some(wgpu::DepthStencilState {
+			format: wgpu::TextureFormat::Depth32Float,
+			depth_write_enabled,
+			depth_compare,
+			stencil: wgpu::StencilState::default(),
+			bias: wgpu::DepthBiasState::default(),
+		}),
+		multisample: wgpu::MultisampleState {
+			count: engine_config.msaa_samples,
+			mask: !0u64,
+			alpha_to_coverage_enabled: false,
+		},
+		multiview: None,
+	});
+	let debug_lines_pipeline = create_pipeline("debug_lines_pipeline", true, wgpu::CompareFunction::LessEqual);
+	let debug_lines_overlay_pipeline = create_pipeline("debug_lines_overlay_pipeline", false, wgpu::CompareFunction::Always);
+	
+	
+	Ok((
+		debug_lines_pipeline,
+		debug_lines_overlay_pipeline,
+		debug_lines_bind_0_layout,
+	))
+}
+
+
+
+
+
+pub fn load_bloom_layouts(render_context: &RenderContextData) -> Result<(
+	wgpu::RenderPipeline,
+	wgpu::RenderPipeline,
+	wgpu::RenderPipeline,
+	wgpu::BindGroupLayout,
+)> {
+	
+	
+	let shader_path = utils::get_program_file_path("shaders/bloom.wgsl");
+	let shader_source = fs::read_to_string(&shader_path).add_path_to_error(&shader_path)?;
+	let shader = render_context.device.create_shader_module(wgpu::ShaderModuleDescriptor {
+		label: Some("bloom_shader_module"),
+		source: wgpu::ShaderSource::Wgsl(shader_source.into()),
+	});
+	
+	
+	let bloom_bind_0_layout = render_context.device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+		label: Some("bloom_bind_0_layout"),
+		entries: &[
+			wgpu::BindGroupLayoutEntry { // color correction: buffer (for the bloom threshold)
+				binding: 0,
+				visibility: wgpu::ShaderStages::FRAGMENT,
+				ty: wgpu::BindingType::Buffer {
+					ty: wgpu::BufferBindingType::Uniform,
+					has_dynamic_offset: false,
+					min_binding_size: None,
+				},
+				count: None,
+			},
+			wgpu::BindGroupLayoutEntry { // source: texture
+				binding: 1,
+				visibility: wgpu::ShaderStages::FRAGMENT,
+				ty: wgpu::BindingType::Texture {
+					multisampled: false,
+					view_dimension: wgpu::TextureViewDimension::D2,
+					sample_type: wgpu::TextureSampleType::Float { filterable: true },
+				},
+				count: None,
+			},
+			wgpu::BindGroupLayoutEntry { // source: sampler
+				binding: 2,
+				visibility: wgpu::ShaderStages::FRAGMENT,
+				ty: wgpu::BindingType::Sampler (wgpu::SamplerBindingType::Filtering),
+				count: None,
+			},
+		],
+	});
+	
+	
+	let bloom_pipeline_layout = render_context.device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+		label: Some("bloom_pipeline_layout"),
+		bind_group_layouts: &[
+			&bloom_bind_0_layout,
+		],
+		push_constant_ranges: &[],
+	});
+	// the three bloom steps only differ in their fragment shader and blending
+	let create_pipeline = |label: &str, fs_entry_point: &str, blend: wgpu::BlendState| render_context.device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+		label: Some(label),
+		layout: Some(&bloom_pipeline_layout),
+		vertex: wgpu::VertexState {
+			module: &shader,
+			entry_point: "vs_main",
+			buffers: &[],
+			compilation_options: wgpu::PipelineCompilationOptions::default(),
+		},
+		fragment: Some(wgpu::FragmentState {
+			module: &shader,
+			entry_point: fs_entry_point,
+			targets: &[Some(wgpu::ColorTargetState {
+				format: RenderAssets::MAIN_TEX_FORMAT,
+				blend: Some(blend),
+				write_mask: wgpu::ColorWrites::ALL,
+			})],
+			compilation_options: wgpu::PipelineCompilationOptions::default(),
+		}),
+		primitive: wgpu::PrimitiveState {
+			topology: wgpu::PrimitiveTopology::TriangleList,
+			strip_index_format: None,
+			front_face: wgpu::FrontFace::Cw,
+			cull_mode: Some(wgpu::Face::Back),
+			polygon_mode: wgpu::PolygonMode::Fill,
+			unclipped_depth: false,
+			conservative: false,
+		},
+		depth_stencil: None,
+		multisample: wgpu::MultisampleState {
+			count: 1,
+			mask: !0u64,
+			alpha_to_coverage_enabled: false,
+		},
+		multiview: None,
+	});
+	let additive_blend = wgpu::BlendState {
+		color: wgpu::BlendComponent {
+			src_factor: wgpu::BlendFactor::One,
+			dst_factor: wgpu::BlendFactor::One,
+			operation: wgpu::BlendOperation::Add,
+		},
+		alpha: wgpu::BlendComponent::REPLACE,
+	};
+	let bloom_bright_pass_pipeline = create_pipeline("bloom_bright_pass_pipeline", "fs_bright_pass", wgpu::BlendState::REPLACE);
+	let bloom_downsample_pipeline = create_pipeline("bloom_downsample_pipeline", "fs_downsample", wgpu::BlendState::REPLACE);
+	let bloom_upsample_pipeline = create_pipeline("bloom_upsample_pipeline", "fs_upsample", additive_blend); // each mip is added on top of the one above it
+	
+	
+	Ok((
+		bloom_bright_pass_pipeline,
+		bloom_downsample_pipeline,
+		bloom_upsample_pipeline,
+		bloom_bind_0_layout,
+	))
+}
+
+
+
+
+
+pub fn load_color_correction_layouts(render_context: &RenderContextData) -> Result<(
+	wgpu::RenderPipeline,
+	wgpu::BindGroupLayout,
+)> {
+	
+	
+	let shader_path = utils::get_program_file_path("shaders/color correction.wgsl");
+	let shader_source = fs::read_to_string(&shader_path).add_path_to_error(&shader_path)?;
+	let shader = render_context.device.create_shader_module(wgpu::ShaderModuleDescriptor {
+		label: Some("color_correction_shader_module"),
+		source: wgpu::ShaderSource::Wgsl(shader_source.into()),
+	});
+	
+	
+	let color_correction_bind_0_layout = render_context.device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+		label: Some("color_correction_bind_0_layout"),
+		entries: &[
+			wgpu::BindGroupLayoutEntry { // color correction: buffer
+				binding: 0,
+				visibility: wgpu::ShaderStages::FRAGMENT,
+				ty: wgpu::BindingType::Buffer {
+					ty: wgpu::BufferBindingType::Uniform,
+					has_dynamic_offset: false,
+					min_binding_size: None,
+				},
+				count: None,
+			},
+			wgpu::BindGroupLayoutEntry { // main_tex: texture
+				binding: 1,
+				visibility: wgpu::ShaderStages::FRAGMENT,
+				ty: wgpu::BindingType::Texture {
+					multisampled: false,
+					view_dimension: wgpu::TextureViewDimension::D2,
+					sample_type: wgpu::TextureSampleType::Float { filterable: true },
+				},
+				count: None,
+			},
+			wgpu::BindGroupLayoutEntry { // main_tex: sampler
+				binding: 2,
+				visibility: wgpu::ShaderStages::FRAGMENT,
+				ty: wgpu::BindingType::Sampler (wgpu::SamplerBindingType::Filtering),
+				count: None,
+			},
+			wgpu::BindGroupLayoutEntry { // bloom: texture (the largest mip, which has every other mip added to it)
+				binding: 3,
+				visibility: wgpu::ShaderStages::FRAGMENT,
+				ty: wgpu::BindingType::Texture {
+					multisampled: false,
+					view_dimension: wgpu::TextureViewDimension::D2,
+					sample_type: wgpu::TextureSampleType::Float { filterable: true },
+				},
+				count: None,
+			},
+		],
+	});
+	
+	
+	let color_correction_pipeline_layout = render_context.device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+		label: Some("color_correction_pipeline_layout"),
+		bind_group_layouts: &[
+			&color_correction_bind_0_layout,
+		],
+		push_constant_ranges: &[],
+	});
+	let color_correction_pipeline = render_context.device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+		label: Some("color_correction_pipeline"),
 		layout: Some(&color_correction_pipeline_layout),
 		vertex: wgpu::VertexState {
 			module: &shader,
@@ -460,7 +1421,7 @@ pub fn load_color_correction_layouts(render_context: &RenderContextData) -> Resu
 			module: &shader,
 			entry_point: "fs_main",
 			targets: &[Some(wgpu::ColorTargetState {
-				format: render_context.surface_config.format,
+				format: render_context.surface_view_format,
 				blend: Some(wgpu::BlendState::REPLACE),
 				write_mask: wgpu::ColorWrites::ALL,
 			})],
@@ -490,3 +1451,187 @@ pub fn load_color_correction_layouts(render_context: &RenderContextData) -> Resu
 		color_correction_bind_0_layout,
 	))
 }
+
+
+
+
+
+pub fn load_shadowmap_debug_layouts(render_context: &RenderContextData) -> Result<(
+	wgpu::RenderPipeline,
+	wgpu::BindGroupLayout,
+)> {
+	
+	
+	let shader_path = utils::get_program_file_path("shaders/shadowmap debug.wgsl");
+	let shader_source = fs::read_to_string(&shader_path).add_path_to_error(&shader_path)?;
+	let shader = render_context.device.create_shader_module(wgpu::ShaderModuleDescriptor {
+		label: Some("shadowmap_debug_shader_module"),
+		source: wgpu::ShaderSource::Wgsl(shader_source.into()),
+	});
+	
+	
+	let shadowmap_debug_bind_0_layout = render_context.device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+		label: Some("shadowmap_debug_bind_0_layout"),
+		entries: &[
+			wgpu::BindGroupLayoutEntry { // shadowmap: texture
+				binding: 0,
+				visibility: wgpu::ShaderStages::FRAGMENT,
+				ty: wgpu::BindingType::Texture {
+					multisampled: false,
+					view_dimension: wgpu::TextureViewDimension::D2Array, // one layer per cascade
+					sample_type: wgpu::TextureSampleType::Float {filterable: false}, // depth textures can be bound as unfilterable floats, see 'shadowmap debug.wgsl'
+				},
+				count: None,
+			},
+			wgpu::BindGroupLayoutEntry { // lighting: settings
+				binding: 1,
+				visibility: wgpu::ShaderStages::FRAGMENT,
+				ty: wgpu::BindingType::Buffer {
+					ty: wgpu::BufferBindingType::Uniform,
+					has_dynamic_offset: false,
+					min_binding_size: None,
+				},
+				count: None,
+			},
+		],
+	});
+	
+	
+	let shadowmap_debug_pipeline_layout = render_context.device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+		label: Some("shadowmap_debug_pipeline_layout"),
+		bind_group_layouts: &[
+			&shadowmap_debug_bind_0_layout,
+		],
+		push_constant_ranges: &[],
+	});
+	let shadowmap_debug_pipeline = render_context.device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+		label: Some("shadowmap_debug_pipeline"),
+		layout: Some(&shadowmap_debug_pipeline_layout),
+		vertex: wgpu::VertexState {
+			module: &shader,
+			entry_point: "vs_main",
+			buffers: &[],
+			compilation_options: wgpu::PipelineCompilationOptions::default(),
+		},
+		fragment: Some(wgpu::FragmentState {
+			module: &shader,
+			entry_point: "fs_main",
+			targets: &[Some(wgpu::ColorTargetState {
+				format: render_context.surface_view_format,
+				blend: Some(wgpu::BlendState::REPLACE),
+				write_mask: wgpu::ColorWrites::ALL,
+			})],
+			compilation_options: wgpu::PipelineCompilationOptions::default(),
+		}),
+		primitive: wgpu::PrimitiveState {
+			topology: wgpu::PrimitiveTopology::TriangleList,
+			strip_index_format: None,
+			front_face: wgpu::FrontFace::Cw,
+			cull_mode: Some(wgpu::Face::Back),
+			polygon_mode: wgpu::PolygonMode::Fill,
+			unclipped_depth: false,
+			conservative: false,
+		},
+		depth_stencil: None,
+		multisample: wgpu::MultisampleState {
+			count: 1,
+			mask: !0u64,
+			alpha_to_coverage_enabled: false,
+		},
+		multiview: None,
+	});
+	
+	
+	Ok((
+		shadowmap_debug_pipeline,
+		shadowmap_debug_bind_0_layout,
+	))
+}
+
+
+
+
+
+pub fn load_text_layouts(render_context: &RenderContextData) -> Result<(
+	wgpu::RenderPipeline,
+	wgpu::BindGroupLayout,
+)> {
+	
+	
+	let shader_path = utils::get_program_file_path("shaders/text.wgsl");
+	let shader_source = fs::read_to_string(&shader_path).add_path_to_error(&shader_path)?;
+	let shader = render_context.device.create_shader_module(wgpu::ShaderModuleDescriptor {
+		label: Some("text_shader_module"),
+		source: wgpu::ShaderSource::Wgsl(shader_source.into()),
+	});
+	
+	
+	let text_bind_0_layout = render_context.device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+		label: Some("text_bind_0_layout"),
+		entries: &[
+			wgpu::BindGroupLayoutEntry { // text: settings
+				binding: 0,
+				visibility: wgpu::ShaderStages::VERTEX_FRAGMENT, // the fragment shader reads `encode_srgb`
+				ty: wgpu::BindingType::Buffer {
+					ty: wgpu::BufferBindingType::Uniform,
+					has_dynamic_offset: false,
+					min_binding_size: None,
+				},
+				count: None,
+			},
+		],
+	});
+	
+	
+	let text_pipeline_layout = render_context.device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+		label: Some("text_pipeline_layout"),
+		bind_group_layouts: &[
+			&text_bind_0_layout,
+		],
+		push_constant_ranges: &[],
+	});
+	let text_pipeline = render_context.device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+		label: Some("text_pipeline"),
+		layout: Some(&text_pipeline_layout),
+		vertex: wgpu::VertexState {
+			module: &shader,
+			entry_point: "vs_main",
+			buffers: &[
+				TextGlyphData::get_layout(),
+			],
+			compilation_options: wgpu::PipelineCompilationOptions::default(),
+		},
+		fragment: Some(wgpu::FragmentState {
+			module: &shader,
+			entry_point: "fs_main",
+			targets: &[Some(wgpu::ColorTargetState {
+				format: render_context.surface_view_format,
+				blend: Some(wgpu::BlendState::REPLACE), // the empty pixels are discarded
+				write_mask: wgpu::ColorWrites::ALL,
+			})],
+			compilation_options: wgpu::PipelineCompilationOptions::default(),
+		}),
+		primitive: wgpu::PrimitiveState {
+			topology: wgpu::PrimitiveTopology::TriangleList,
+			strip_index_format: None,
+			front_face: wgpu::FrontFace::Cw,
+			cull_mode: None,
+			polygon_mode: wgpu::PolygonMode::Fill,
+			unclipped_depth: false,
+			conservative: false,
+		},
+		depth_stencil: None,
+		multisample: wgpu::MultisampleState {
+			count: 1,
+			mask: !0u64,
+			alpha_to_coverage_enabled: false,
+		},
+		multiview: None,
+	});
+	
+	
+	Ok((
+		text_pipeline,
+		text_bind_0_layout,
+	))
+}
@@ -1,6 +1,8 @@
 use crate::prelude::*;
+use std::env;
 use async_std::task::block_on;
-use winit::{dpi::PhysicalPosition, window::Window};
+use notify::Watcher;
+use winit::{dpi::{PhysicalPosition, PhysicalSize}, window::Window};
 use serde_hjson::{Map, Value};
 
 
@@ -11,42 +13,89 @@ pub mod load_assets;
 pub use load_assets::*;
 pub mod load_bindings;
 pub use load_bindings::*;
+pub mod config_serde;
+pub use config_serde::*;
 
 
 
 
 
-pub fn load_program_data(start_time: Instant, window: &Window) -> Result<ProgramData> {
+// NOTE: the engine config is loaded before this (in `run()`) since the window is created from it
+pub fn load_program_data(start_time: Instant, window: &Window, mut engine_config: EngineConfig) -> Result<ProgramData<'_>> {
+	let render_context = load_render_context_data(window, &mut engine_config)?;
+	load_program_data_with_render_context(start_time, render_context, engine_config)
+}
+
+// HELP: this is meant for automated tests and ci, where there might not be a display to create a window on
+pub fn load_headless_program_data(start_time: Instant, mut engine_config: EngineConfig) -> Result<ProgramData<'static>> {
+	let render_context = load_render_context_headless(&mut engine_config)?;
+	let mut program_data = load_program_data_with_render_context(start_time, render_context, engine_config)?;
+	program_data.ui_state.is_visible = false; // the rendered frames should only contain the scene
+	Ok(program_data)
+}
+
+// NOTE: `load_render_context_*()` can disable parts of the engine config (when the adapter is missing features), so the render context has to be created first
+pub fn load_program_data_with_render_context<'a>(start_time: Instant, render_context: RenderContextData<'a>, engine_config: EngineConfig) -> Result<ProgramData<'a>> {
 	
-	let engine_config = load_engine_config().context("Failed to load engine config.")?;
+	// this is logged here instead of when it's loaded so that it includes anything the render context disabled
+	info!("Resolved engine config: {engine_config:#?}");
+	
+	let shader_watcher = load_shader_watcher(&engine_config).context("Failed to start shader watcher.")?;
+	let config_watcher = load_config_watcher(&engine_config).context("Failed to start config watcher.")?;
 	let input = EngineInput {
 		pressed_keys: HashSet::new(),
 		prev_pressed_keys: HashSet::new(),
 		mouse_pos: PhysicalPosition::default(),
 		mouse_vel: PhysicalPosition::default(),
+		scroll_delta: 0.0,
 		capture_cursor: false,
+		cursor_is_locked: false,
 		pressed_mouse_buttons: PressedMouseButtons::default(),
 		prev_pressed_mouse_buttons: PressedMouseButtons::default(),
+		is_focused: render_context.window.is_some_and(|window| window.has_focus()),
 	};
 	
 	// app data
-	let camera_data = CameraData::new((0., 1., 2.));
-	let shadow_caster_data = ShadowCasterData::default();
-	let example_model_instance_datas = load_example_model_instance_datas();
-	let fps_counter = FpsCounter::new();
-	let color_correction_settings = ColorCorrectionSettings::default();
+	let mut camera_data = CameraData::new((0., 1., 2.));
+	camera_data.move_speed = engine_config.camera_move_speed;
+	camera_data.look_sensitivity = engine_config.camera_look_sensitivity;
+	camera_data.projection = engine_config.get_camera_projection();
+	let shadow_caster_data = ShadowCasterData::new(&engine_config);
+	let directional_light = DirectionalLight::new(&engine_config);
+	let lights = engine_config.lights.clone();
+	let model_instance_datas = load_example_model_instance_datas(engine_config.model_paths.len()).context("Failed to load model instances.")?;
+	let model_instance_lods = vec!(vec!(); model_instance_datas.len());
+	let fps_counter = FpsCounter::new(engine_config.fps_smoothing);
+	let color_correction_settings = ColorCorrectionSettings::new(&engine_config);
+	let frame_exporter = load_frame_exporter(&engine_config).context("Failed to start frame exporter.")?;
+	let lighting_settings = LightingSettings::new(&engine_config);
+	let skybox_settings = SkyboxSettings::new(&engine_config);
+	let render_skybox = engine_config.render_skybox;
+	let show_debug_grid = engine_config.debug_grid_enabled;
+	let show_bounding_boxes = engine_config.bounding_boxes_enabled;
+	let show_stats_text = engine_config.stats_text_enabled;
+	let foliage_instance_datas = if engine_config.foliage_enabled {load_foliage_instance_datas()} else {vec!()};
 	
 	// render data
-	let render_context = load_render_context_data(window, &engine_config)?;
-	let render_layouts = load_render_layouts(&render_context)?;
+	let render_layouts = load_render_layouts(&render_context, &engine_config)?;
+	let gpu_timer = load_gpu_timer(&render_context);
+	let mut ui_state = load_ui_state(&render_context);
+	// NOTE: failing to show the loading frame isn't worth stopping for, the first real frame will still be rendered
+	if let Err(err) = render::render_loading_frame(&render_context, &mut ui_state, "Loading...") {
+		warn!("Failed to render loading frame: {err:?}");
+	}
 	let render_assets = load_render_assets(
 		&camera_data,
 		&shadow_caster_data,
-		&example_model_instance_datas,
+		&directional_light,
+		&lights,
+		&model_instance_datas,
+		&foliage_instance_datas,
 		&render_context,
-		engine_config.shadowmap_size,
+		&engine_config,
 		&color_correction_settings,
-		engine_config.compress_textures,
+		&lighting_settings,
+		&skybox_settings,
 	)?;
 	let render_bindings = load_render_bindings(&render_context, &render_layouts, &render_assets)?;
 	
@@ -56,20 +105,47 @@ pub fn load_program_data(start_time: Instant, window: &Window) -> Result<Program
 		start_time,
 		engine_config,
 		input,
+		shader_watcher,
+		config_watcher,
+		gpu_timer,
+		ui_state,
 		
 		// app data
 		camera_data,
 		shadow_caster_data,
-		example_model_instance_datas,
+		directional_light,
+		lights,
+		model_instance_datas,
+		model_instance_lods,
 		fps_counter,
+		frame_pacer: FramePacer::new(),
+		fatal_error: None,
 		is_moving_camera: false,
+		screenshot_requested: false,
+		wireframe: false,
+		render_skybox,
+		render_targets_resize_time: None,
+		show_shadowmap_debug: false,
+		show_debug_grid,
+		show_bounding_boxes,
+		debug_lines: vec!(),
+		show_stats_text,
+		stats_text: vec!(),
 		color_correction_settings,
+		frame_exporter,
+		lighting_settings,
+		skybox_settings,
+		foliage_instance_datas,
+		total_time: 0.0,
+		fixed_update_accumulator: 0.0,
+		fixed_update_alpha: 0.0,
 		
 		// render data
 		render_context,
 		render_layouts,
 		render_assets,
 		render_bindings,
+		render_passes: render::get_default_render_passes(),
 		frame_start_instant: start_time,
 		
 	})
@@ -84,90 +160,198 @@ pub fn load_program_data(start_time: Instant, window: &Window) -> Result<Program
 // list
 #[allow(clippy::type_complexity)]
 const CONFIG_UPDATER_FUNCTIONS: &[fn(&mut Map<String, Value>) -> Result<()>] = &[
-	
+	update_config_v1_to_v2,
 ];
 const LATEST_CONFIG_VERSION: usize = CONFIG_UPDATER_FUNCTIONS.len() + 1;
 
+// version 1 parsed 'auto_no_vsync' as 'auto_vsync', so it's renamed to what it actually did (version 2 and up parse it correctly)
+pub fn update_config_v1_to_v2(engine_config: &mut Map<String, Value>) -> Result<()> {
+	let Some(Value::String(present_mode)) = engine_config.get_mut("present_mode") else {return Ok(());};
+	if present_mode.to_lowercase() == "auto_no_vsync" {
+		warn!("Entry 'present_mode' in 'engine config.hjson' is 'auto_no_vsync', which was treated as 'auto_vsync' in config version 1, so 'auto_vsync' is being used. To actually disable vsync, set 'config_version' to {LATEST_CONFIG_VERSION}.");
+		*present_mode = String::from("auto_vsync");
+	}
+	Ok(())
+}
+
+// HELP: this brings an older config up to `LATEST_CONFIG_VERSION`, and configs that are newer than that are rejected since their entries might mean something else now
+pub fn update_engine_config_map(engine_config_map: &mut Map<String, Value>) -> Result<()> {
+	let config_version = read_hjson_i64(engine_config_map, "config_version", LATEST_CONFIG_VERSION as i64);
+	if config_version < 1 {return Err(Error::msg(format!("Invalid value for entry 'config_version' in 'engine config.hjson', must be at least 1 (found {config_version}).")));}
+	if config_version as usize > LATEST_CONFIG_VERSION {
+		return Err(Error::msg(format!("Entry 'config_version' in 'engine config.hjson' is {config_version}, but the latest known version is {LATEST_CONFIG_VERSION}, so this config was made for a newer version of the program.")));
+	}
+	// HELP: updater `i - 1` takes a config from version `i` to version `i + 1`
+	for i in config_version as usize .. LATEST_CONFIG_VERSION {
+		CONFIG_UPDATER_FUNCTIONS[i - 1](engine_config_map).with_context(|| format!("Failed to update 'engine config.hjson' from version {i} to version {}.", i + 1))?;
+	}
+	Ok(())
+}
+
+const DEFAULT_ENGINE_CONFIG: &str = include_str!("../../data/default engine config.hjson");
+// HELP: set this env var to stop a missing config from being replaced with the default one (for read-only installs)
+const NO_CONFIG_WRITE_ENV_VAR: &str = "WGPU_TEMPLATE_NO_CONFIG_WRITE";
+
 pub fn load_engine_config() -> Result<EngineConfig> {
 	
 	let engine_config_path = utils::get_program_file_path("engine config.hjson");
 	let engine_config_result = fs::read_to_string(&engine_config_path);
+	let is_config_missing = engine_config_result.as_ref().is_err_and(|err| err.kind() == std::io::ErrorKind::NotFound);
+	let engine_config_result = engine_config_result.add_path_to_error(&engine_config_path);
 	let engine_config_string = match &engine_config_result {
 		StdResult::Ok(v) => &**v,
 		StdResult::Err(err) => {
-			warn!("Failed to read 'engine config.hjson' (full path: {engine_config_path:?}), using default values...  (error: {err})");
-			include_str!("../../data/default engine config.hjson")
+			warn!("Failed to read 'engine config.hjson', using default values...  (error: {err})");
+			if is_config_missing {
+				write_default_engine_config(&engine_config_path);
+			}
+			DEFAULT_ENGINE_CONFIG
 		}
 	};
-	let mut engine_config: Map<String, Value> = serde_hjson::from_str(engine_config_string).context("Failed to decode 'engine config.hjson'")?;
+	let mut engine_config_map: Map<String, Value> = serde_hjson::from_str(engine_config_string).context("Failed to decode 'engine config.hjson'")?;
 	
-	// update config
-	let config_version = read_hjson_i64(&engine_config, "config_version", LATEST_CONFIG_VERSION as i64);
-	for i in config_version as usize .. LATEST_CONFIG_VERSION {
-		CONFIG_UPDATER_FUNCTIONS[i - 1](&mut engine_config)?;
-	}
-	
-	let rendering_backend_str = read_hjson_str(&engine_config, "rendering_backend", "auto");
-	let rendering_backend = match &*rendering_backend_str.to_lowercase() {
-		"auto" => wgpu::Backends::all(),
-		"vulkan" => wgpu::Backends::VULKAN,
-		"dx12" => wgpu::Backends::DX12,
-		"metal" => wgpu::Backends::METAL,
-		"opengl" => wgpu::Backends::GL,
-		_ => {
-			warn!("Unknown value for entry 'rendering_backend' in 'engine config.hjson', must be: 'auto', 'vulkan', 'dx12', 'metal', or 'opengl', defaulting to \"auto\".");
-			wgpu::Backends::all()
-		}
-	};
+	engine_config_from_map(engine_config_map)
+}
+
+pub fn engine_config_from_map(mut engine_config_map: Map<String, Value>) -> Result<EngineConfig> {
+	update_engine_config_map(&mut engine_config_map)?;
+	for key in get_missing_engine_config_entries(&engine_config_map) {
+		warn!("Could not find entry '{key}' in 'engine config.hjson', using the default value.");
+	}
+	let mut engine_config: EngineConfig = deserialize_hjson_map_lenient(&engine_config_map, "engine config.hjson").context("Failed to read 'engine config.hjson'")?;
+	fix_engine_config_values(&mut engine_config);
+	validate_engine_config(&engine_config)?;
+	Ok(engine_config)
+}
+
+// `max_fps` and `min_frame_time` set the same thing, so only one of them needs to exist
+pub fn get_missing_engine_config_entries(engine_config_map: &Map<String, Value>) -> Vec<&'static str> {
+	get_struct_field_names::<EngineConfig>().iter()
+		.copied()
+		.filter(|key| !engine_config_map.contains_key(*key))
+		.filter(|key| match *key {
+			"min_frame_time" => !engine_config_map.contains_key("max_fps"),
+			"max_fps" => !engine_config_map.contains_key("min_frame_time"),
+			_ => true,
+		})
+		.collect()
+}
+
+// HELP: the default config is written as-is (instead of serializing the loaded values) so that its comments are kept
+pub fn write_default_engine_config(engine_config_path: &Path) {
+	if env::var_os(NO_CONFIG_WRITE_ENV_VAR).is_some() {return;}
+	match fs::write(engine_config_path, DEFAULT_ENGINE_CONFIG) {
+		StdResult::Ok(()) => info!("Wrote the default config to {engine_config_path:?}"),
+		StdResult::Err(err) => warn!("Failed to write the default config to {engine_config_path:?} (set {NO_CONFIG_WRITE_ENV_VAR} to disable this)  (error: {err})"),
+	}
+}
+
+// HELP: these values can be silently fixed, so they're clamped (or reset) here instead of being checked in `validate_engine_config()`
+pub fn fix_engine_config_values(engine_config: &mut EngineConfig) {
 	
-	let present_mode_str = read_hjson_str(&engine_config, "present_mode", "auto_vsync");
-	let present_mode = match &*present_mode_str.to_lowercase() {
-		"auto_vsync" => wgpu::PresentMode::AutoVsync,
-		"auto_no_vsync" => wgpu::PresentMode::AutoVsync,
-		"fifo" => wgpu::PresentMode::Fifo,
-		"fifo_relaxed" => wgpu::PresentMode::FifoRelaxed,
-		"immediate" => wgpu::PresentMode::Immediate,
-		"mailbox" => wgpu::PresentMode::Mailbox,
-		_ => {
-			warn!("Unknown value for entry 'present_mode' in 'engine config.hjson', must be: 'auto_vsync', 'auto_no_vsync', 'fifo', 'fifo_relaxed', 'immediate', or 'mailbox', defaulting to \"auto_vsync\".");
-			wgpu::PresentMode::AutoVsync
+	// `max_fps` is preferred, but older configs that only have `min_frame_time` still work
+	if let Some(max_fps) = engine_config.max_fps {
+		engine_config.min_frame_time = if max_fps > 0.0 {Duration::from_secs_f64(1.0 / max_fps)} else {Duration::ZERO};
+	}
+	
+	if !(1..=16).contains(&engine_config.anisotropy) {
+		warn!("Entry 'anisotropy' in 'engine config.hjson' must be from 1 to 16, clamping it.");
+		engine_config.anisotropy = engine_config.anisotropy.clamp(1, 16);
+	}
+	if ![1, 3, 5].contains(&engine_config.shadow_pcf_kernel_size) {
+		warn!("Invalid value for entry 'shadow_pcf_kernel_size' in 'engine config.hjson', must be 1, 3, or 5, defaulting to \"3\".");
+		engine_config.shadow_pcf_kernel_size = 3;
+	}
+	if ![1, 2, 4, 8].contains(&engine_config.msaa_samples) {
+		warn!("Invalid value for entry 'msaa_samples' in 'engine config.hjson', must be 1, 2, 4, or 8, defaulting to \"1\".");
+		engine_config.msaa_samples = 1;
+	}
+	
+	// HELP: `LoadOp::Clear` expects linear values even when the surface is srgb, so colors picked in an image editor (which are srgb) need to be converted
+	if engine_config.clear_color.iter().any(|channel| !(0.0..=1.0).contains(channel)) {
+		warn!("Entry 'clear_color' in 'engine config.hjson' has channels outside of 0 to 1, clamping them.");
+		engine_config.clear_color = engine_config.clear_color.map(|channel| channel.clamp(0.0, 1.0));
+	}
+	if engine_config.clear_color_is_srgb {
+		for channel in &mut engine_config.clear_color[..3] {
+			*channel = utils::srgb_to_linear(*channel);
 		}
-	};
+	}
 	
-	let desired_frame_latency_i64 = read_hjson_i64(&engine_config, "desired_frame_latency", 1);
-	let desired_frame_latency = desired_frame_latency_i64 as u32;
+	engine_config.directional_light_direction = engine_config.directional_light_direction.try_normalize().unwrap_or_else(|| {
+		warn!("Entry 'directional_light_direction' in 'engine config.hjson' can't be all zeros, defaulting to \"[0.7, -0.27, 0.65]\".");
+		glam::Vec3::new(0.7, -0.27, 0.65).normalize()
+	});
 	
-	let min_frame_time_f64 = read_hjson_f64(&engine_config, "min_frame_time", 0.002);
-	let min_frame_time = Duration::from_secs_f64(min_frame_time_f64);
+	if engine_config.lod_distances.iter().any(|distance| *distance <= 0.0) || !engine_config.lod_distances.is_sorted() {
+		warn!("Entry 'lod_distances' in 'engine config.hjson' should only have positive distances in increasing order, removing the other ones and sorting it.");
+		engine_config.lod_distances.retain(|distance| *distance > 0.0);
+		engine_config.lod_distances.sort_by(f32::total_cmp);
+	}
 	
-	let shadowmap_size_i64 = read_hjson_i64(&engine_config, "shadowmap_size", 512);
-	let shadowmap_size = shadowmap_size_i64 as u32;
+	// the compute shader only writes the instance count, so the draws have to read it from the gpu
+	if engine_config.gpu_culling && !engine_config.indirect_draws {
+		info!("'gpu_culling' is enabled, so 'indirect_draws' is being enabled too.");
+		engine_config.indirect_draws = true;
+	}
 	
-	let compress_textures = read_hjson_bool(&engine_config, "compress_textures", true);
+	engine_config.fps_smoothing = engine_config.fps_smoothing.clamp(0.0, 1.0);
+	engine_config.fixed_update_hz = engine_config.fixed_update_hz.max(1.0);
+	engine_config.max_draw_distance = engine_config.max_draw_distance.max(0.0);
+	engine_config.draw_distance_fade_width = engine_config.draw_distance_fade_width.max(0.0);
+	engine_config.export_fps = engine_config.export_fps.max(1.0);
+	// NOTE: once reverse-z is supported, this should default to 0.0 when it's enabled
+	engine_config.clear_depth = engine_config.clear_depth.clamp(0.0, 1.0);
+	engine_config.spotlight_fov_radians = engine_config.spotlight_fov_radians.clamp(1f32.to_radians(), 170f32.to_radians());
+	engine_config.spotlight_range = engine_config.spotlight_range.max(ShadowCasterData::SPOT_NEAR * 2.0);
+	engine_config.shadow_catcher_size = engine_config.shadow_catcher_size.max(0.0);
+	engine_config.shadow_catcher_opacity = engine_config.shadow_catcher_opacity.clamp(0.0, 1.0);
+	engine_config.skybox_blend = engine_config.skybox_blend.clamp(0.0, 1.0);
+	engine_config.camera_move_speed = engine_config.camera_move_speed.max(0.0);
+	engine_config.camera_look_sensitivity = engine_config.camera_look_sensitivity.max(0.0);
+	engine_config.camera_fov_radians = engine_config.camera_fov_radians.clamp(1f32.to_radians(), 170f32.to_radians());
+	engine_config.camera_ortho_height = engine_config.camera_ortho_height.max(0.001);
+	engine_config.window_width = engine_config.window_width.max(1);
+	engine_config.window_height = engine_config.window_height.max(1);
+	engine_config.axis_gizmo_size = engine_config.axis_gizmo_size.max(0.0);
+	engine_config.debug_grid_spacing = engine_config.debug_grid_spacing.max(0.01);
+	engine_config.debug_grid_size = engine_config.debug_grid_size.max(0.0);
+	engine_config.stats_text_scale = engine_config.stats_text_scale.max(1);
+	engine_config.shadow_cascade_count = engine_config.shadow_cascade_count.clamp(1, ShadowCasterData::MAX_CASCADES);
+	engine_config.shadow_cascade_lambda = engine_config.shadow_cascade_lambda.clamp(0.0, 1.0);
+	// the same range as `ColorCorrectionSettings::clamped()`, so the cpu-side value always matches what's uploaded
+	let exposure_range = ColorCorrectionSettings::MIN_EXPOSURE..=ColorCorrectionSettings::MAX_EXPOSURE;
+	if !exposure_range.contains(&engine_config.exposure) {
+		warn!("Entry 'exposure' in 'engine config.hjson' must be from {} to {}, clamping it.", exposure_range.start(), exposure_range.end());
+		engine_config.exposure = engine_config.exposure.clamp(*exposure_range.start(), *exposure_range.end());
+	}
+	engine_config.bloom_threshold = engine_config.bloom_threshold.max(0.0);
+	engine_config.bloom_strength = engine_config.bloom_strength.max(0.0);
+	engine_config.directional_light_intensity = engine_config.directional_light_intensity.max(0.0);
 	
-	Ok(EngineConfig {
-		rendering_backend,
-		present_mode,
-		desired_frame_latency,
-		min_frame_time,
-		shadowmap_size,
-		compress_textures,
-	})
 }
 
+// HELP: most entries are fixed up by `fix_engine_config_values()`, but the ones checked here can't be silently fixed, so every
+// problem is collected into a single error instead
+pub fn validate_engine_config(engine_config: &EngineConfig) -> Result<()> {
+	let mut problems = vec!();
+	
+	// the max size depends on the device, so that's checked by `load_shadow_caster_data()` instead
+	if !engine_config.shadowmap_size.is_power_of_two() {
+		problems.push(format!("'shadowmap_size' must be a power of two (found {})", engine_config.shadowmap_size));
+	}
+	if !(1..=3).contains(&engine_config.desired_frame_latency) {
+		problems.push(format!("'desired_frame_latency' must be from 1 to 3 (found {})", engine_config.desired_frame_latency));
+	}
+	if engine_config.min_frame_time > Duration::from_secs(1) {
+		problems.push(String::from("'max_fps' must be 0 or at least 1 (or 'min_frame_time' must be from 0 to 1 seconds)"));
+	}
+	
+	if problems.is_empty() {return Ok(());}
+	Err(Error::msg(format!("Found {} invalid entries in 'engine config.hjson':\n\t{}", problems.len(), problems.join("\n\t"))))
+}
 
 
-pub fn read_hjson_str<'a>(map: &'a Map<String, Value>, key: &'static str, default: &'static str) -> &'a str {
-	let value_str = map.get(key);
-	let value_str = value_str.map(|v| v.as_str().unwrap_or_else(|| {
-		warn!("Entry '{key}' in 'engine config.hjson' must be a string, defaulting to \"{default}\".");
-		default
-	}));
-	value_str.unwrap_or_else(|| {
-		warn!("Could not find entry '{key}' in 'engine config.hjson', defaulting to \"{default}\".");
-		default
-	})
-}
 
 pub fn read_hjson_i64(map: &Map<String, Value>, key: &'static str, default: i64) -> i64 {
 	let value_str = map.get(key);
@@ -181,57 +365,171 @@ pub fn read_hjson_i64(map: &Map<String, Value>, key: &'static str, default: i64)
 	})
 }
 
-pub fn read_hjson_f64(map: &Map<String, Value>, key: &'static str, default: f64) -> f64 {
-	let value_str = map.get(key);
-	let value_f64 = value_str.map(|v| v.as_f64().unwrap_or_else(|| {
-		warn!("Entry '{key}' in 'engine config.hjson' must be a number, defaulting to \"{default}\".");
-		default
-	}));
-	value_f64.unwrap_or_else(|| {
-		warn!("Could not find entry '{key}' in 'engine config.hjson', defaulting to \"{default}\".");
-		default
-	})
+
+
+
+
+
+pub fn load_ui_state(render_context: &RenderContextData) -> UiState {
+	let size = render_context.surface_size;
+	UiState {
+		context: egui::Context::default(),
+		renderer: egui_wgpu::Renderer::new(&render_context.device, render_context.surface_view_format, None, 1),
+		screen_descriptor: egui_wgpu::ScreenDescriptor {
+			size_in_pixels: [size.width, size.height],
+			pixels_per_point: render_context.window.map_or(1.0, |window| window.scale_factor() as f32),
+		},
+		events: vec!(),
+		modifiers: egui::Modifiers::default(),
+		pointer_pos: None,
+		wants_pointer_input: false,
+		wants_keyboard_input: false,
+		is_visible: true,
+	}
 }
 
-pub fn read_hjson_bool(map: &Map<String, Value>, key: &'static str, default: bool) -> bool {
-	let value_str = map.get(key);
-	let value_bool = value_str.map(|v| v.as_bool().unwrap_or_else(|| {
-		warn!("Entry '{key}' in 'engine config.hjson' must be a bool, defaulting to \"{default}\".");
-		default
-	}));
-	value_bool.unwrap_or_else(|| {
-		warn!("Could not find entry '{key}' in 'engine config.hjson', defaulting to \"{default}\".");
-		default
+
+
+pub fn load_gpu_timer(render_context: &RenderContextData) -> Option<GpuTimer> {
+	if !render_context.device.features().contains(wgpu::Features::TIMESTAMP_QUERY) {return None;}
+	let query_set = render_context.device.create_query_set(&wgpu::QuerySetDescriptor {
+		label: Some("gpu_timer_query_set"),
+		ty: wgpu::QueryType::Timestamp,
+		count: GpuTimer::QUERY_COUNT,
+	});
+	let buffer_size = GpuTimer::QUERY_COUNT as u64 * wgpu::QUERY_RESOLVE_BUFFER_ALIGNMENT;
+	let resolve_buffer = render_context.device.create_buffer(&wgpu::BufferDescriptor {
+		label: Some("gpu_timer_resolve_buffer"),
+		size: buffer_size,
+		usage: wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC,
+		mapped_at_creation: false,
+	});
+	let readback_buffer = render_context.device.create_buffer(&wgpu::BufferDescriptor {
+		label: Some("gpu_timer_readback_buffer"),
+		size: buffer_size,
+		usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+		mapped_at_creation: false,
+	});
+	Some(GpuTimer {
+		query_set,
+		resolve_buffer,
+		readback_buffer,
+		timestamp_period: render_context.command_queue.get_timestamp_period(),
+		is_recording: false,
+		written_queries: std::cell::Cell::new(0),
+		readback_written_queries: 0,
+		readback_state: std::sync::Arc::new(std::sync::atomic::AtomicU8::new(GpuTimer::READBACK_IDLE)),
 	})
 }
 
 
 
+pub fn load_shader_watcher(engine_config: &EngineConfig) -> Result<Option<ShaderWatcher>> {
+	if !engine_config.hot_reload_shaders {return Ok(None);}
+	let (watcher, events) = load_folder_watcher(utils::get_program_file_path("shaders"))?;
+	Ok(Some(ShaderWatcher {
+		_watcher: watcher,
+		events,
+	}))
+}
+
+pub fn load_config_watcher(engine_config: &EngineConfig) -> Result<Option<ConfigWatcher>> {
+	if !engine_config.hot_reload_config {return Ok(None);}
+	let (watcher, events) = load_folder_watcher(utils::get_program_file_path(""))?;
+	Ok(Some(ConfigWatcher {
+		_watcher: watcher,
+		events,
+	}))
+}
 
+pub fn load_folder_watcher(folder: PathBuf) -> Result<(notify::RecommendedWatcher, std::sync::mpsc::Receiver<notify::Result<notify::Event>>)> {
+	let (sender, events) = std::sync::mpsc::channel();
+	let mut watcher = notify::recommended_watcher(sender).context("Failed to create file watcher.")?;
+	watcher.watch(&folder, notify::RecursiveMode::NonRecursive).with_context(|| format!("Failed to watch folder {folder:?}."))?;
+	info!("Watching {folder:?} for changes...");
+	Ok((watcher, events))
+}
+
+
+
+
+
+// HELP: the frame exporter is started with the command-line args `--export-frames <dir> <count>`
+pub fn load_frame_exporter(engine_config: &EngineConfig) -> Result<Option<FrameExporter>> {
+	let args = env::args().collect::<Vec<_>>();
+	let Some(flag_index) = args.iter().position(|arg| arg == "--export-frames") else {return Ok(None);};
+	let output_folder = args.get(flag_index + 1).context("Missing output folder, usage: `--export-frames <dir> <count>`")?;
+	let total_frames_str = args.get(flag_index + 2).context("Missing frame count, usage: `--export-frames <dir> <count>`")?;
+	let total_frames = total_frames_str.parse::<usize>().with_context(|| format!("Invalid frame count \"{total_frames_str}\"."))?;
+	if total_frames == 0 {return Err(Error::msg("Frame count must be at least 1."));}
+	let output_folder = PathBuf::from(output_folder);
+	fs::create_dir_all(&output_folder).add_path_to_error(&output_folder)?;
+	info!("Exporting {total_frames} frames to {output_folder:?}...");
+	Ok(Some(FrameExporter {
+		output_folder,
+		total_frames,
+		exported_frames: 0,
+		dt: 1.0 / engine_config.export_fps,
+		camera_path: engine_config.export_camera_path.clone(),
+	}))
+}
 
-// HELP: this is just random data as an example
-pub fn load_example_model_instance_datas() -> Vec<InstanceData> {
+
+
+
+
+// HELP: this is just random data as an example, every model gets the same grid of instances but each grid is placed next to the previous one
+// this returns a `Result` so that it can be replaced with something that loads the instances from a file
+pub fn load_example_model_instance_datas(model_count: usize) -> Result<Vec<Vec<InstanceData>>> {
 	const X_LEN: usize = 100;
 	const Z_LEN: usize = 100;
-	let mut output = Vec::with_capacity(X_LEN * Z_LEN);
-	for z in 0..Z_LEN {
-		for x in 0..X_LEN {
-			let pos = glam::Vec3 { x: x as f32 * 3.0, y: 0.0, z: z as f32 * 3.0 } - glam::Vec3::new(0.5, 0.0, 0.5);
-			let rot = glam::Quat::from_euler(glam::EulerRot::XYZ, pos.x / 10.0, pos.y / 10.0, pos.z / 10.0);
-			output.push(InstanceData {
-				pos,
-				rot,
-			})
+	const SPACING: f32 = 3.0;
+	let output = (0..model_count).map(|model_index| {
+		let model_offset = glam::Vec3::new(model_index as f32 * X_LEN as f32 * SPACING, 0.0, 0.0);
+		let mut output = Vec::with_capacity(X_LEN * Z_LEN * 2);
+		for y in [0.0, 10.0] {
+			for z in 0..Z_LEN {
+				for x in 0..X_LEN {
+					let pos = glam::Vec3 { x: x as f32 * SPACING, y, z: z as f32 * SPACING } - glam::Vec3::new(0.5, 0.0, 0.5) + model_offset;
+					let rot = glam::Quat::from_euler(glam::EulerRot::XYZ, pos.x / 10.0, pos.y / 10.0, pos.z / 10.0);
+					output.push(InstanceData {
+						pos,
+						rot,
+					})
+				}
+			}
 		}
-	}
+		output
+	}).collect::<Vec<_>>();
+	Ok(output)
+}
+
+
+
+pub fn load_foliage_instance_datas() -> Vec<FoliageInstanceData> {
+	const X_LEN: usize = 200;
+	const Z_LEN: usize = 200;
+	const SPACING: f32 = 0.5;
+	// cheap deterministic noise (pcg hash) so the field looks the same every run
+	let random = |index: usize, seed: u32| {
+		let state = (index as u32).wrapping_mul(747796405).wrapping_add(seed.wrapping_mul(2891336453));
+		let word = ((state >> ((state >> 28) + 4)) ^ state).wrapping_mul(277803737);
+		((word >> 22) ^ word) as f32 / u32::MAX as f32
+	};
+	let mut output = Vec::with_capacity(X_LEN * Z_LEN);
 	for z in 0..Z_LEN {
 		for x in 0..X_LEN {
-			let pos = glam::Vec3 { x: x as f32 * 3.0, y: 10.0, z: z as f32 * 3.0 } - glam::Vec3::new(0.5, 0.0, 0.5);
-			let rot = glam::Quat::from_euler(glam::EulerRot::XYZ, pos.x / 10.0, pos.y / 10.0, pos.z / 10.0);
-			output.push(InstanceData {
-				pos,
-				rot,
-			})
+			let i = z * X_LEN + x;
+			let pos = glam::Vec3 {
+				x: (x as f32 + random(i, 1)) * SPACING,
+				y: -1.0,
+				z: (z as f32 + random(i, 2)) * SPACING,
+			} - glam::Vec3::new(X_LEN as f32 * SPACING * 0.5, 0.0, Z_LEN as f32 * SPACING * 0.5);
+			output.push(FoliageInstanceData {
+				pos: pos.to_array(),
+				rot: random(i, 3) * std::f32::consts::TAU,
+				scale: 0.6 + random(i, 4) * 0.6,
+			});
 		}
 	}
 	output
@@ -241,13 +539,103 @@ pub fn load_example_model_instance_datas() -> Vec<InstanceData> {
 
 
 
-pub fn load_render_context_data<'a>(window: &'a Window, engine_config: &load::EngineConfig) -> Result<RenderContextData<'a>> {
+pub fn load_render_context_data<'a>(window: &'a Window, engine_config: &mut load::EngineConfig) -> Result<RenderContextData<'a>> {
 	block_on(load_render_context_data_async(window, engine_config))
 }
 
-pub async fn load_render_context_data_async<'a>(window: &'a Window, engine_config: &load::EngineConfig) -> Result<RenderContextData<'a>> {
-	let surface_size = window.inner_size();
-	
+// features that the program cannot run without
+pub const REQUIRED_FEATURES: &[(wgpu::Features, &str)] = &[];
+
+// features that are only requested when the adapter supports them, each one disables something in the engine config when missing
+pub const OPTIONAL_FEATURES: &[(wgpu::Features, &str)] = &[
+	(wgpu::Features::TEXTURE_COMPRESSION_BC, "BC"), // these three are only used by `compress_textures`, see `get_compression_format()`
+	(wgpu::Features::TEXTURE_COMPRESSION_ASTC, "ASTC"),
+	(wgpu::Features::TEXTURE_COMPRESSION_ETC2, "ETC2"),
+	(wgpu::Features::DEPTH_CLIP_CONTROL, "depth clip control"),
+	(wgpu::Features::POLYGON_MODE_LINE, "line polygon mode"), // only used by the wireframe mode, which checks for it when it's toggled
+	(wgpu::Features::TIMESTAMP_QUERY, "timestamp queries"), // only used by the gpu timer, which is skipped when it's missing
+	(wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES, "adapter specific format features"), // lets msaa use 2 and 8 samples, see `resolve_sample_count()`
+];
+
+pub fn get_device_features(adapter_features: wgpu::Features, engine_config: &mut load::EngineConfig) -> Result<wgpu::Features> {
+	let mut features = wgpu::Features::empty();
+	for (feature, name) in REQUIRED_FEATURES {
+		if !adapter_features.contains(*feature) {
+			return Err(Error::msg(format!("Adapter is missing required feature {name} ({feature:?}).")));
+		}
+		features |= *feature;
+	}
+	for (feature, name) in OPTIONAL_FEATURES {
+		if adapter_features.contains(*feature) {
+			features |= *feature;
+			continue;
+		}
+		if *feature == wgpu::Features::DEPTH_CLIP_CONTROL && engine_config.shadow_depth_clamp {
+			warn!("shadow depth clamp disabled (adapter lacks {name})");
+			engine_config.shadow_depth_clamp = false;
+		}
+	}
+	Ok(features)
+}
+
+// disables the settings that need downlevel capabilities (mostly missing on webgl and old opengl) that the adapter doesn't have
+pub fn check_downlevel_support(adapter: &wgpu::Adapter, engine_config: &mut load::EngineConfig) {
+	let downlevel_flags = adapter.get_downlevel_capabilities().flags;
+	// NOTE: wgpu doesn't expose a max anisotropy limit (it clamps to the backend's max itself), so the only thing that can be checked is whether it's supported at all
+	if engine_config.anisotropy > 1 && !downlevel_flags.contains(wgpu::DownlevelFlags::ANISOTROPIC_FILTERING) {
+		warn!("anisotropic filtering disabled (adapter lacks support for it), requested anisotropy was {}", engine_config.anisotropy);
+		engine_config.anisotropy = 1;
+	}
+	// NOTE: the draw args always have a `first_instance` of 0, so `Features::INDIRECT_FIRST_INSTANCE` isn't needed
+	if engine_config.indirect_draws && !downlevel_flags.contains(wgpu::DownlevelFlags::INDIRECT_EXECUTION) {
+		warn!("indirect draws disabled (adapter lacks support for indirect execution)");
+		engine_config.indirect_draws = false;
+	}
+	if engine_config.gpu_culling && !(engine_config.indirect_draws && downlevel_flags.contains(wgpu::DownlevelFlags::COMPUTE_SHADERS)) {
+		warn!("gpu culling disabled (adapter lacks support for compute shaders or indirect execution), falling back to cpu culling");
+		engine_config.gpu_culling = false;
+	}
+}
+
+// the scene passes draw to the main texture and the depth texture with the same sample count, so it has to be supported by both formats
+pub fn check_msaa_support(adapter: &wgpu::Adapter, engine_config: &mut load::EngineConfig) {
+	let requested = engine_config.msaa_samples;
+	let sample_count = resolve_sample_count(requested, adapter, RenderAssets::MAIN_TEX_FORMAT);
+	let sample_count = resolve_sample_count(sample_count, adapter, wgpu::TextureFormat::Depth32Float);
+	if sample_count != requested {
+		warn!("msaa samples lowered from {requested} to {sample_count} (adapter lacks support for {requested} samples in the scene's color or depth format)");
+		engine_config.msaa_samples = sample_count;
+	}
+}
+
+// returns the highest sample count that's at most `requested` and is supported for `format` (1 is always supported)
+// HELP: without `TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES`, the device only allows the sample counts that every adapter has to
+// support (1 and 4), no matter what the adapter reports. That feature is requested whenever it's available (see `OPTIONAL_FEATURES`)
+pub fn resolve_sample_count(requested: u32, adapter: &wgpu::Adapter, format: wgpu::TextureFormat) -> u32 {
+	let adapter_features = adapter.features();
+	let format_flags = if adapter_features.contains(wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES) {
+		adapter.get_texture_format_features(format).flags
+	} else {
+		format.guaranteed_format_features(adapter_features).flags
+	};
+	[8, 4, 2].into_iter()
+		.filter(|sample_count| *sample_count <= requested)
+		.find(|sample_count| format_flags.sample_count_supported(*sample_count))
+		.unwrap_or(1)
+}
+
+// picks the first supported format out of bc7, astc, and etc2, and disables `compress_textures` when there aren't any
+pub fn get_compression_format(device_features: wgpu::Features, engine_config: &mut load::EngineConfig) -> CompressionFormat {
+	if !engine_config.compress_textures {return CompressionFormat::None;}
+	if device_features.contains(wgpu::Features::TEXTURE_COMPRESSION_BC) {return CompressionFormat::Bc7;}
+	if device_features.contains(wgpu::Features::TEXTURE_COMPRESSION_ASTC) {return CompressionFormat::Astc4x4;}
+	if device_features.contains(wgpu::Features::TEXTURE_COMPRESSION_ETC2) {return CompressionFormat::Etc2Rgb8;}
+	warn!("texture compression disabled (adapter lacks BC, ASTC, and ETC2)");
+	engine_config.compress_textures = false;
+	CompressionFormat::None
+}
+
+pub async fn load_render_context_data_async<'a>(window: &'a Window, engine_config: &mut load::EngineConfig) -> Result<RenderContextData<'a>> {
 	// The instance is a handle to our GPU
 	// Backends::all => Vulkan + Metal + DX12 + Browser WebGPU
 	let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
@@ -259,56 +647,362 @@ pub async fn load_render_context_data_async<'a>(window: &'a Window, engine_confi
 	let surface = instance.create_surface(window).context("Failed to create drawable surface for window.")?;
 	
 	// Handle to a physical graphics and/or compute device
-	let mut adapter = instance.request_adapter(
-		&wgpu::RequestAdapterOptions {
-			power_preference: wgpu::PowerPreference::default(),
-			compatible_surface: Some(&surface),
-			force_fallback_adapter: false,
-		},
-	).await;
+	let adapter = load_adapter(&instance, Some(&surface), engine_config).await?;
+	log_adapter_info(&adapter);
+	
+	let (device, command_queue) = load_device(&adapter, engine_config).await?;
+	let compression_format = get_compression_format(device.features(), engine_config);
+	let surface_size = clamp_surface_size(window.inner_size(), device.limits().max_texture_dimension_2d);
+	
+	let surface_caps = surface.get_capabilities(&adapter);
+	let auto_surface_format = surface_caps.formats.iter()
+		.copied()
+		.find(|f| f.is_srgb())
+		.unwrap_or(surface_caps.formats[0]);
+	let surface_format = match engine_config.surface_format {
+		Some(format) if surface_caps.formats.contains(&format) => format,
+		Some(format) => {
+			warn!("Surface format {format:?} is not supported by this surface (supported: {:?}), using {auto_surface_format:?} instead.", surface_caps.formats);
+			auto_surface_format
+		}
+		None => auto_surface_format,
+	};
+	let can_view_as_srgb = adapter.get_downlevel_capabilities().flags.contains(wgpu::DownlevelFlags::SURFACE_VIEW_FORMATS);
+	let (surface_view_format, manual_srgb_encode) = get_surface_view_format(surface_format, can_view_as_srgb);
+	// COPY_SRC is needed to read frames back to the cpu (used by the frame exporter)
+	let surface_usage = wgpu::TextureUsages::RENDER_ATTACHMENT | (surface_caps.usages & wgpu::TextureUsages::COPY_SRC);
+	engine_config.present_mode = get_supported_present_mode(engine_config.present_mode, &surface_caps.present_modes);
+	let surface_config = wgpu::SurfaceConfiguration {
+		usage: surface_usage,
+		format: surface_format,
+		width: surface_size.width,
+		height: surface_size.height,
+		present_mode: engine_config.present_mode,
+		alpha_mode: surface_caps.alpha_modes[0],
+		view_formats: if surface_view_format != surface_format {vec![surface_view_format]} else {vec![]},
+		desired_maximum_frame_latency: engine_config.desired_frame_latency,
+	};
+	surface.configure(&device, &surface_config);
+	log_device_info(&device, &surface_config);
+	
+	Ok(RenderContextData {
+		window: Some(window),
+		drawable_surface: Some(surface),
+		headless_texture: None,
+		device,
+		command_queue,
+		surface_config,
+		surface_size,
+		surface_format,
+		surface_view_format,
+		manual_srgb_encode,
+		supported_present_modes: surface_caps.present_modes,
+		aspect_ratio: surface_size.width as f32 / surface_size.height as f32,
+		compression_format,
+	})
+}
+
+// HELP: the window can be bigger than the biggest texture that the device supports (like a window on an 8k display with
+// a limited gpu), so the surface and render targets are scaled down to fit in that size and the surface is stretched over the window
+// NOTE: `max_dimension` should be `device.limits().max_texture_dimension_2d`
+pub fn clamp_surface_size(size: PhysicalSize<u32>, max_dimension: u32) -> PhysicalSize<u32> {
+	if size.width <= max_dimension && size.height <= max_dimension {return size;}
+	// both axes are scaled by the same amount so that the aspect ratio (which the camera uses) stays the same
+	let scale_axis = |axis: u32| ((axis as u64 * max_dimension as u64) / size.width.max(size.height) as u64).max(1) as u32;
+	let clamped_size = PhysicalSize::new(scale_axis(size.width), scale_axis(size.height));
+	warn!("Surface size {}x{} is bigger than the max texture size of the device ({max_dimension}), using {}x{} instead.", size.width, size.height, clamped_size.width, clamped_size.height);
+	clamped_size
+}
+
+// configuring a surface with a present mode that it doesn't support panics, so unsupported modes fall back to 'auto_vsync' (which is always supported)
+// NOTE: the auto modes are never in `supported_present_modes` since they're resolved by wgpu, and headless render contexts don't have any supported modes
+pub fn get_supported_present_mode(present_mode: wgpu::PresentMode, supported_present_modes: &[wgpu::PresentMode]) -> wgpu::PresentMode {
+	let is_auto = matches!(present_mode, wgpu::PresentMode::AutoVsync | wgpu::PresentMode::AutoNoVsync);
+	if is_auto || supported_present_modes.is_empty() || supported_present_modes.contains(&present_mode) {return present_mode;}
+	warn!("Present mode {present_mode:?} is not supported by this surface (supported: {supported_present_modes:?}), using {:?} instead.", wgpu::PresentMode::AutoVsync);
+	wgpu::PresentMode::AutoVsync
+}
+
+// HELP: the shaders output linear colors and leave the srgb encode to the surface, so a surface that isn't srgb would
+// look too dark. When possible, it's drawn to through an srgb view instead (the bytes end up the same as with an srgb
+// surface), and otherwise the last passes that draw to it (color correction and text) do the encode themselves
+// NOTE: this returns the format that the surface should be viewed as, and whether the encode has to be done manually
+pub fn get_surface_view_format(surface_format: wgpu::TextureFormat, can_view_as_srgb: bool) -> (wgpu::TextureFormat, bool) {
+	if surface_format.is_srgb() {
+		info!("Hardware srgb encode is active (the surface format {surface_format:?} is srgb).");
+		return (surface_format, false);
+	}
+	let srgb_format = surface_format.add_srgb_suffix();
+	if srgb_format != surface_format && can_view_as_srgb {
+		info!("Hardware srgb encode is active (the surface format {surface_format:?} isn't srgb, so it's viewed as {srgb_format:?}).");
+		return (srgb_format, false);
+	}
+	// float surfaces are expected to hold linear colors, so they don't need to be encoded at all
+	if surface_format == wgpu::TextureFormat::Rgba16Float {
+		info!("Hardware srgb encode is not active, and not needed (the surface format {surface_format:?} holds linear colors).");
+		return (surface_format, false);
+	}
+	warn!("Hardware srgb encode is not active (the surface format {surface_format:?} isn't srgb and can't be viewed as srgb), the encode will be done in the shaders instead.");
+	(surface_format, true)
+}
+
+// HELP: when `adapter_name` is set, the first compatible adapter with a matching name is used, otherwise (or when
+// nothing matches) wgpu picks one based on `power_preference`, and as a last resort the first compatible adapter is used
+pub async fn load_adapter(instance: &wgpu::Instance, compatible_surface: Option<&wgpu::Surface<'_>>, engine_config: &load::EngineConfig) -> Result<wgpu::Adapter> {
+	let is_compatible = |adapter: &wgpu::Adapter| compatible_surface.is_none_or(|surface| adapter.is_surface_supported(surface));
+	let mut adapters = instance.enumerate_adapters(wgpu::Backends::all());
+	for adapter in &adapters {
+		let info = adapter.get_info();
+		info!("Found adapter \"{}\" ({:?}, {:?}){}", info.name, info.backend, info.device_type, if is_compatible(adapter) {""} else {" (not compatible with the surface)"});
+	}
+	
+	let mut adapter = None;
+	if let Some(adapter_name) = &engine_config.adapter_name {
+		let adapter_name = adapter_name.to_lowercase();
+		let index = adapters.iter().position(|adapter| is_compatible(adapter) && adapter.get_info().name.to_lowercase().contains(&adapter_name));
+		match index {
+			Some(index) => adapter = Some(adapters.swap_remove(index)),
+			None => warn!("Could not find a compatible adapter with a name containing \"{adapter_name}\", falling back to the default selection."),
+		}
+	}
+	if adapter.is_none() {
+		adapter = instance.request_adapter(
+			&wgpu::RequestAdapterOptions {
+				power_preference: engine_config.power_preference,
+				compatible_surface,
+				force_fallback_adapter: false,
+			},
+		).await;
+	}
 	if adapter.is_none() {
-		adapter =
-			instance
-			.enumerate_adapters(wgpu::Backends::all()).into_iter()
-			.find(|adapter| adapter.is_surface_supported(&surface));
+		adapter = adapters.into_iter().find(is_compatible);
 	}
 	let Some(adapter) = adapter else {return Err(Error::msg("Unable to find suitable adapter."));};
+	Ok(adapter)
+}
+
+// HELP: the tests that need a gpu use this to skip themselves on machines that don't have one (like most ci runners)
+pub fn headless_adapter_is_available(engine_config: &load::EngineConfig) -> bool {
+	let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+		backends: engine_config.rendering_backend,
+		..Default::default()
+	});
+	block_on(load_adapter(&instance, None, engine_config)).is_ok()
+}
+
+// HELP: these are logged so that bug reports (which usually include the log) say what gpu and driver the problem happened on
+pub fn log_adapter_info(adapter: &wgpu::Adapter) {
+	let info = adapter.get_info();
+	info!("Using adapter \"{}\" ({:?}, {:?})", info.name, info.backend, info.device_type);
+	info!("Adapter driver: \"{}\" ({}), vendor id: {:#06x}, device id: {:#06x}", info.driver, info.driver_info, info.vendor, info.device);
+	info!("Adapter downlevel capabilities: {:?}", adapter.get_downlevel_capabilities().flags);
+}
+
+pub fn log_device_info(device: &wgpu::Device, surface_config: &wgpu::SurfaceConfiguration) {
+	info!("Surface format: {:?}, present mode: {:?}, size: {}x{}", surface_config.format, surface_config.present_mode, surface_config.width, surface_config.height);
+	info!("Device features: {:?}", device.features());
+	info!("Device limits: {:#?}", device.limits());
+}
+
+pub async fn load_device(adapter: &wgpu::Adapter, engine_config: &mut load::EngineConfig) -> Result<(wgpu::Device, wgpu::Queue)> {
+	let required_features = get_device_features(adapter.features(), engine_config)?;
+	check_downlevel_support(adapter, engine_config);
+	check_msaa_support(adapter, engine_config);
 	
 	// Open connection to a graphics and/or compute device, Handle to a command queue on a device
-	let (device, command_queue) = adapter.request_device(
+	adapter.request_device(
 		&wgpu::DeviceDescriptor {
-			required_features: wgpu::Features::empty() | wgpu::Features::TEXTURE_COMPRESSION_BC,
-			required_limits: wgpu::Limits::downlevel_defaults(),
+			required_features,
+			// the downlevel default for this is only 2048, which is smaller than a lot of displays (see `clamp_surface_size()`)
+			required_limits: wgpu::Limits {
+				max_texture_dimension_2d: adapter.limits().max_texture_dimension_2d,
+				..wgpu::Limits::downlevel_defaults()
+			},
 			label: None,
 		},
 		None,
-	).await.context("Failed to create connection to gpu.")?;
+	).await.context("Failed to create connection to gpu.")
+}
+
+
+
+pub fn load_render_context_headless(engine_config: &mut load::EngineConfig) -> Result<RenderContextData<'static>> {
+	block_on(load_render_context_headless_async(engine_config))
+}
+
+pub async fn load_render_context_headless_async(engine_config: &mut load::EngineConfig) -> Result<RenderContextData<'static>> {
+	let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+		backends: engine_config.rendering_backend,
+		..Default::default()
+	});
+	let adapter = load_adapter(&instance, None, engine_config).await?;
+	log_adapter_info(&adapter);
 	
-	let surface_caps = surface.get_capabilities(&adapter);
-	let surface_format = surface_caps.formats.iter()
-		.copied()
-		.find(|f| f.is_srgb())
-		.unwrap_or(surface_caps.formats[0]);
+	let (device, command_queue) = load_device(&adapter, engine_config).await?;
+	let compression_format = get_compression_format(device.features(), engine_config);
+	let surface_size = clamp_surface_size(PhysicalSize::new(engine_config.window_width, engine_config.window_height), device.limits().max_texture_dimension_2d);
+	
+	// NOTE: the windowed path picks the first srgb format that the surface supports, so an srgb format is used here too so that the frames look the same
+	let surface_format = engine_config.surface_format.unwrap_or(wgpu::TextureFormat::Rgba8UnormSrgb);
+	let can_view_as_srgb = adapter.get_downlevel_capabilities().flags.contains(wgpu::DownlevelFlags::VIEW_FORMATS);
+	let (surface_view_format, manual_srgb_encode) = get_surface_view_format(surface_format, can_view_as_srgb);
+	// the surface config is never used to configure anything, but it's kept so that the size can be read from it like usual
 	let surface_config = wgpu::SurfaceConfiguration {
-		usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+		usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
 		format: surface_format,
 		width: surface_size.width,
 		height: surface_size.height,
 		present_mode: engine_config.present_mode,
-		alpha_mode: surface_caps.alpha_modes[0],
-		view_formats: vec![],
+		alpha_mode: wgpu::CompositeAlphaMode::Opaque,
+		view_formats: if surface_view_format != surface_format {vec![surface_view_format]} else {vec![]},
 		desired_maximum_frame_latency: engine_config.desired_frame_latency,
 	};
-	surface.configure(&device, &surface_config);
+	let headless_texture = load_headless_texture(&device, &surface_config);
+	log_device_info(&device, &surface_config);
 	
 	Ok(RenderContextData {
-		window,
-		drawable_surface: surface,
+		window: None,
+		drawable_surface: None,
+		headless_texture: Some(headless_texture),
 		device,
 		command_queue,
 		surface_config,
 		surface_size,
 		surface_format,
+		surface_view_format,
+		manual_srgb_encode,
+		supported_present_modes: vec!(),
 		aspect_ratio: surface_size.width as f32 / surface_size.height as f32,
+		compression_format,
 	})
 }
+
+pub fn load_headless_texture(device: &wgpu::Device, surface_config: &wgpu::SurfaceConfiguration) -> wgpu::Texture {
+	device.create_texture(&wgpu::TextureDescriptor {
+		label: Some("headless_texture"),
+		size: wgpu::Extent3d {
+			width: surface_config.width,
+			height: surface_config.height,
+			depth_or_array_layers: 1,
+		},
+		mip_level_count: 1,
+		sample_count: 1,
+		dimension: wgpu::TextureDimension::D2,
+		format: surface_config.format,
+		usage: surface_config.usage,
+		view_formats: &surface_config.view_formats,
+	})
+}
+
+
+
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	fn parse_map(hjson: &str) -> Map<String, Value> {
+		serde_hjson::from_str(hjson).expect("Failed to parse test hjson.")
+	}
+	
+	#[test]
+	fn version_1_auto_no_vsync_is_updated_to_auto_vsync() {
+		let mut map = parse_map("{config_version: 1, present_mode: \"auto_no_vsync\"}");
+		update_engine_config_map(&mut map).expect("Failed to update config.");
+		assert_eq!(map.get("present_mode"), Some(&Value::String(String::from("auto_vsync"))));
+	}
+	
+	#[test]
+	fn latest_version_is_left_unchanged() {
+		let hjson = format!("{{config_version: {LATEST_CONFIG_VERSION}, present_mode: \"auto_no_vsync\"}}");
+		let mut map = parse_map(&hjson);
+		let original = map.clone();
+		update_engine_config_map(&mut map).expect("Failed to update config.");
+		assert_eq!(map, original);
+	}
+	
+	#[test]
+	fn newer_than_latest_version_is_rejected() {
+		let hjson = format!("{{config_version: {}}}", LATEST_CONFIG_VERSION + 1);
+		assert!(update_engine_config_map(&mut parse_map(&hjson)).is_err());
+		assert!(update_engine_config_map(&mut parse_map("{config_version: 0}")).is_err());
+	}
+	
+	#[test]
+	fn default_config_has_every_entry() {
+		assert_eq!(get_missing_engine_config_entries(&parse_map(DEFAULT_ENGINE_CONFIG)), Vec::<&str>::new());
+		engine_config_from_map(parse_map(DEFAULT_ENGINE_CONFIG)).expect("Failed to read the default config.");
+	}
+	
+	#[test]
+	fn invalid_entries_use_their_default_values() {
+		let map = parse_map("{shadowmap_size: \"big\", window_width: -5, window_height: 600, camera_projection: \"orthographic\", camera_ortho_height: 8}");
+		let engine_config = engine_config_from_map(map).expect("Failed to read config.");
+		let default_config = EngineConfig::default();
+		assert_eq!(engine_config.shadowmap_size, default_config.shadowmap_size);
+		assert_eq!(engine_config.window_width, default_config.window_width);
+		assert_eq!(engine_config.window_height, 600);
+		assert_eq!(engine_config.get_camera_projection(), CameraProjection::Orthographic {height: 8.0});
+	}
+	
+	#[test]
+	fn max_fps_sets_min_frame_time() {
+		let engine_config = engine_config_from_map(parse_map("{max_fps: 0, min_frame_time: 0.5}")).expect("Failed to read config.");
+		assert_eq!(engine_config.min_frame_time, Duration::ZERO);
+		let engine_config = engine_config_from_map(parse_map("{max_fps: 60}")).expect("Failed to read config.");
+		assert!((engine_config.min_frame_time.as_secs_f64() - 1.0 / 60.0).abs() < 1e-6, "expected about 16.67ms, got {:?}", engine_config.min_frame_time);
+	}
+	
+	#[test]
+	fn min_frame_time_still_works_without_max_fps() {
+		let engine_config = engine_config_from_map(parse_map("{min_frame_time: 0.01}")).expect("Failed to read config.");
+		assert_eq!(engine_config.min_frame_time, Duration::from_millis(10));
+	}
+	
+	#[test]
+	fn max_fps_below_one_is_rejected() {
+		let err = engine_config_from_map(parse_map("{max_fps: 0.5}")).expect_err("A max_fps of 0.5 should be rejected.");
+		assert!(format!("{err}").contains("'max_fps' must be 0 or at least 1"), "unexpected error: {err}");
+	}
+	
+	#[test]
+	fn surface_size_is_clamped_to_the_max_dimension() {
+		assert_eq!(clamp_surface_size(PhysicalSize::new(1920, 1080), 2048), PhysicalSize::new(1920, 1080));
+		assert_eq!(clamp_surface_size(PhysicalSize::new(2048, 2048), 2048), PhysicalSize::new(2048, 2048));
+		assert_eq!(clamp_surface_size(PhysicalSize::new(7680, 1080), 4096), PhysicalSize::new(4096, 576));
+		assert_eq!(clamp_surface_size(PhysicalSize::new(7680, 4320), 4096), PhysicalSize::new(4096, 2304));
+		assert_eq!(clamp_surface_size(PhysicalSize::new(100_000, 1), 4096), PhysicalSize::new(4096, 1));
+	}
+	
+	#[test]
+	fn shadowmap_size_is_checked_against_the_device() {
+		let engine_config = EngineConfig {shadowmap_size: 4096, ..EngineConfig::default()};
+		validate_engine_config(&engine_config).expect("A shadowmap size of 4096 should be valid.");
+		let engine_config = EngineConfig {shadowmap_size: 3000, ..EngineConfig::default()};
+		assert!(validate_engine_config(&engine_config).is_err());
+		
+		let engine_config = EngineConfig {
+			window_width: 64,
+			window_height: 64,
+			compress_textures: false,
+			shadowmap_size: 1 << 30,
+			..EngineConfig::default()
+		};
+		if !headless_adapter_is_available(&engine_config) {
+			eprintln!("Skipping test because there's no adapter available.");
+			return;
+		}
+		let err = load_headless_program_data(Instant::now(), engine_config).err().expect("A shadowmap bigger than the device allows should be rejected.");
+		assert!(format!("{err:?}").contains("'shadowmap_size'"), "unexpected error: {err:?}");
+	}
+	
+	#[test]
+	fn invalid_lights_are_skipped() {
+		let map = parse_map("{lights: [{type: \"point\", position: [1, 2, 3]}, {type: \"spot\"}, {type: \"directional\", direction: [0, 0, 0]}, 5]}");
+		let engine_config = engine_config_from_map(map).expect("Failed to read config.");
+		assert_eq!(engine_config.lights.len(), 1);
+		assert_eq!(engine_config.lights[0].pos, glam::Vec3::new(1.0, 2.0, 3.0));
+	}
+	
+}
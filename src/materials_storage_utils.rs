@@ -1,13 +1,15 @@
 use crate::prelude::*;
+use std::{sync::{atomic::{AtomicUsize, Ordering}, mpsc}, thread};
 
 // HELP: The purpose of this is to make sure textures are only loaded once
 
 
 
-pub fn get_material_id(path: impl AsRef<Path>, list: &[MaterialRenderData]) -> Option<MaterialId> {
+// the same file can be loaded with and without compression or srgb, and those are different textures
+pub fn get_material_id(path: impl AsRef<Path>, compression_format: CompressionFormat, is_srgb: bool, list: &[MaterialRenderData]) -> Option<MaterialId> {
 	let path = path.as_ref();
 	list.iter().enumerate()
-		.find(|(_i, material)| &*material.path == path)
+		.find(|(_i, material)| &*material.path == path && material.compression_format == compression_format && material.is_srgb == is_srgb)
 		.map(|(i, _material)| i)
 }
 
@@ -17,18 +19,135 @@ pub fn insert_material_2d(
 	render_context: &RenderContextData,
 	compress_textures: bool,
 ) -> Result<MaterialId> {
+	let path = path.into();
+	let compression_format = if compress_textures {render_context.compression_format} else {CompressionFormat::None};
+	if let Some(material_id) = get_material_id(&path, compression_format, true, &materials_storage.list_2d) {
+		return Ok(material_id);
+	}
 	let output = materials_storage.list_2d.len();
 	let material = load_material_2d(path, render_context, compress_textures).context("Failed to load material_2d.")?;
 	materials_storage.list_2d.push(material);
 	Ok(output)
 }
 
+// HELP: decoding and compressing is done on `thread_count` worker threads, and the textures are uploaded on the calling thread as they finish
+// NOTE: `is_srgb` should only be false for textures that hold data instead of colors (like normal maps)
+#[allow(clippy::too_many_arguments)]
+pub fn insert_materials_2d(
+	paths: Vec<PathBuf>,
+	materials_storage: &mut MaterialsStorage,
+	render_context: &RenderContextData,
+	compress_textures: bool,
+	is_srgb: bool,
+	thread_count: usize,
+	mut on_progress: impl FnMut(usize, usize),
+) -> Result<Vec<MaterialId>> {
+	
+	// find which textures actually need to be loaded
+	let compression_format = if compress_textures {render_context.compression_format} else {CompressionFormat::None};
+	let first_new_id = materials_storage.list_2d.len();
+	let mut output = Vec::with_capacity(paths.len());
+	let mut new_paths: Vec<PathBuf> = Vec::new();
+	for path in paths {
+		if let Some(material_id) = get_material_id(&path, compression_format, is_srgb, &materials_storage.list_2d) {
+			output.push(material_id);
+			continue;
+		}
+		let new_index = match new_paths.iter().position(|new_path| *new_path == path) {
+			Some(v) => v,
+			None => {
+				new_paths.push(path);
+				new_paths.len() - 1
+			}
+		};
+		output.push(first_new_id + new_index);
+	}
+	if new_paths.is_empty() {return Ok(output);}
+	
+	let thread_count = thread_count.clamp(1, new_paths.len());
+	let next_index = AtomicUsize::new(0);
+	let mut new_materials: Vec<Option<MaterialRenderData>> = new_paths.iter().map(|_| None).collect();
+	let mut first_err = None;
+	thread::scope(|scope| {
+		let (sender, receiver) = mpsc::channel();
+		for _ in 0..thread_count {
+			let sender = sender.clone();
+			let next_index = &next_index;
+			let new_paths = &new_paths;
+			scope.spawn(move || {
+				loop {
+					let index = next_index.fetch_add(1, Ordering::Relaxed);
+					let Some(path) = new_paths.get(index) else {break;};
+					let decoded_texture = decode_texture_2d(path, compression_format, is_srgb);
+					if sender.send((index, decoded_texture)).is_err() {break;}
+				}
+			});
+		}
+		drop(sender);
+		let mut finished_count = 0;
+		for (index, decoded_texture) in receiver {
+			let path = &new_paths[index];
+			let material = decoded_texture.map(|decoded_texture| upload_material_2d(path.clone(), &decoded_texture, render_context));
+			match material {
+				StdResult::Ok(material) => new_materials[index] = Some(material),
+				StdResult::Err(err) => {
+					// makes the workers stop picking up new textures
+					next_index.store(new_paths.len(), Ordering::Relaxed);
+					first_err.get_or_insert(err.context(format!("Failed to load material_2d {path:?}.")));
+				}
+			}
+			finished_count += 1;
+			on_progress(finished_count, new_paths.len());
+		}
+	});
+	if let Some(err) = first_err {return Err(err);}
+	
+	materials_storage.list_2d.extend(new_materials.into_iter().map(|material| material.expect("All new materials should be loaded at this point")));
+	Ok(output)
+}
+
+// used for textures that are already decoded (like the ones embedded in gltf files), `path` is only used to make sure the same texture isn't uploaded twice
+pub fn insert_material_2d_from_rgba8(
+	path: impl Into<PathBuf>,
+	rgba_bytes: Vec<u8>,
+	dimensions: (u32, u32),
+	materials_storage: &mut MaterialsStorage,
+	render_context: &RenderContextData,
+	compress_textures: bool,
+	is_srgb: bool,
+) -> Result<MaterialId> {
+	let path = path.into();
+	let compression_format = if compress_textures {render_context.compression_format} else {CompressionFormat::None};
+	if let Some(material_id) = get_material_id(&path, compression_format, is_srgb, &materials_storage.list_2d) {
+		return Ok(material_id);
+	}
+	let expected_len = dimensions.0 as usize * dimensions.1 as usize * 4;
+	if rgba_bytes.len() != expected_len {
+		return Err(Error::msg(format!("Texture {path:?} has {} bytes but {dimensions:?} needs {expected_len}.", rgba_bytes.len())));
+	}
+	let output = materials_storage.list_2d.len();
+	let decoded_texture = encode_rgba8_texture(rgba_bytes, dimensions, compression_format, is_srgb);
+	materials_storage.list_2d.push(upload_material_2d(path, &decoded_texture, render_context));
+	Ok(output)
+}
+
+// a 1x1 normal map that points straight out of the surface, used by meshes that don't have their own normal map
+pub fn insert_flat_normal_map(materials_storage: &mut MaterialsStorage, render_context: &RenderContextData) -> Result<MaterialId> {
+	// NOTE: this can't be compressed since bc7 works on 4x4 blocks
+	insert_material_2d_from_rgba8("#flat_normal_map", vec!(128, 128, 255, 255), (1, 1), materials_storage, render_context, false, false)
+}
+
 pub fn insert_material_cube(
 	path: impl Into<PathBuf>,
 	materials_storage: &mut MaterialsStorage,
 	render_context: &RenderContextData,
 	compress_textures: bool,
 ) -> Result<MaterialId> {
+	let path = path.into();
+	// NOTE: cube textures are never compressed (see `load_material_cube()`) and are always colors, so only the path can differ
+	if let Some(material_id) = get_material_id(&path, CompressionFormat::None, true, &materials_storage.list_cube) {
+		return Ok(material_id);
+	}
 	let output = materials_storage.list_cube.len();
 	let material = load_material_cube(path, render_context, compress_textures).context("Failed to load material_cube")?;
 	materials_storage.list_cube.push(material);
@@ -46,63 +165,128 @@ pub fn load_material_2d(
 	compress_textures: bool,
 ) -> Result<MaterialRenderData> {
 	let path = path.into();
-	
-	let raw_texture_bytes = fs::read(utils::get_program_file_path(&path)).add_path_to_error(&path)?;
-	let texture_bytes = image::load_from_memory(&raw_texture_bytes).context("Failed to decode texture.")?;
+	let compression_format = if compress_textures {render_context.compression_format} else {CompressionFormat::None};
+	let decoded_texture = decode_texture_2d(&path, compression_format, true)?;
+	Ok(upload_material_2d(path, &decoded_texture, render_context))
+}
+
+
+
+pub struct DecodedTexture {
+	pub mip_levels: Vec<Vec<u8>>, // the first mip is the full size image, and each one after that is half the size of the previous one
+	pub dimensions: (u32, u32), // the size of the first mip
+	pub compression_format: CompressionFormat,
+	pub is_srgb: bool,
+}
+
+// NOTE: this only does cpu work, so it can be called from any thread
+pub fn decode_texture_2d(path: &Path, compression_format: CompressionFormat, is_srgb: bool) -> Result<DecodedTexture> {
+	let full_path = utils::get_program_file_path(path);
+	let raw_texture_bytes = fs::read(&full_path).add_path_to_error(&full_path)?;
+	let texture_bytes = image::load_from_memory(&raw_texture_bytes).with_context(|| format!("Failed to decode texture {full_path:?}."))?;
 	let texture_bytes = texture_bytes.to_rgba8();
 	let dimensions = texture_bytes.dimensions();
-	let mut texture_bytes = texture_bytes.into_raw();
-	
-	if compress_textures {
-		let compress_settings = intel_tex_2::bc7::opaque_fast_settings();
-		texture_bytes = intel_tex_2::bc7::compress_blocks(&compress_settings, &intel_tex_2::Surface {
-			data: &*texture_bytes,
-			width: dimensions.0,
-			height: dimensions.1,
-			stride: dimensions.0 * 4,
-		});
+	Ok(encode_rgba8_texture(texture_bytes.into_raw(), dimensions, compression_format, is_srgb))
+}
+
+// NOTE: this only does cpu work, so it can be called from any thread
+pub fn encode_rgba8_texture(texture_bytes: Vec<u8>, dimensions: (u32, u32), compression_format: CompressionFormat, is_srgb: bool) -> DecodedTexture {
+	let mip_levels = generate_mip_levels(texture_bytes, dimensions, compression_format != CompressionFormat::None);
+	let mip_levels = mip_levels.into_iter().map(|(texture_bytes, dimensions)| {
+		match compression_format {
+			CompressionFormat::None => texture_bytes,
+			CompressionFormat::Bc7 => intel_tex_2::bc7::compress_blocks(&intel_tex_2::bc7::opaque_fast_settings(), &get_rgba8_surface(&texture_bytes, dimensions)),
+			CompressionFormat::Astc4x4 => intel_tex_2::astc::compress_blocks(&intel_tex_2::astc::opaque_fast_settings(4, 4), &get_rgba8_surface(&texture_bytes, dimensions)),
+			CompressionFormat::Etc2Rgb8 => intel_tex_2::etc1::compress_blocks(intel_tex_2::etc1::slow_settings(), &get_rgba8_surface(&texture_bytes, dimensions)),
+		}
+	}).collect();
+	DecodedTexture {
+		mip_levels,
+		dimensions,
+		compression_format,
+		is_srgb,
 	}
-	
-	let texture_size = wgpu::Extent3d {
+}
+
+// HELP: the mips are generated on the cpu (each one is downscaled from the previous one) so that compressed textures can have them
+// too, since compressed textures can't be rendered to. The compressors work on 4x4 blocks, so when `is_compressed` is true the
+// chain stops at the first mip whose size isn't a multiple of 4 (non-power-of-two textures just get fewer mips)
+// NOTE: the downscaling doesn't convert srgb to linear first, so the smaller mips are slightly darker than they should be
+pub fn generate_mip_levels(texture_bytes: Vec<u8>, dimensions: (u32, u32), is_compressed: bool) -> Vec<(Vec<u8>, (u32, u32))> {
+	let mut output = vec!((texture_bytes, dimensions));
+	loop {
+		let (prev_bytes, (prev_width, prev_height)) = &output[output.len() - 1];
+		if *prev_width == 1 && *prev_height == 1 {break;}
+		let width = (prev_width / 2).max(1);
+		let height = (prev_height / 2).max(1);
+		if is_compressed && (width % 4 != 0 || height % 4 != 0) {break;}
+		let Some(prev_image) = image::ImageBuffer::<image::Rgba<u8>, &[u8]>::from_raw(*prev_width, *prev_height, prev_bytes.as_slice()) else {break;};
+		let image = image::imageops::resize(&prev_image, width, height, image::imageops::FilterType::Triangle);
+		output.push((image.into_raw(), (width, height)));
+	}
+	output
+}
+
+pub fn get_rgba8_surface(texture_bytes: &[u8], dimensions: (u32, u32)) -> intel_tex_2::RgbaSurface<'_> {
+	intel_tex_2::RgbaSurface {
+		data: texture_bytes,
 		width: dimensions.0,
 		height: dimensions.1,
-		depth_or_array_layers: 1,
-	};
+		stride: dimensions.0 * 4,
+	}
+}
+
+pub fn upload_material_2d(path: PathBuf, decoded_texture: &DecodedTexture, render_context: &RenderContextData) -> MaterialRenderData {
+	let dimensions = decoded_texture.dimensions;
+	let compression_format = decoded_texture.compression_format;
 	let texture = render_context.device.create_texture(
 		&wgpu::TextureDescriptor {
-			size: texture_size,
-			mip_level_count: 1,
+			size: wgpu::Extent3d {
+				width: dimensions.0,
+				height: dimensions.1,
+				depth_or_array_layers: 1,
+			},
+			mip_level_count: decoded_texture.mip_levels.len() as u32,
 			sample_count: 1,
 			dimension: wgpu::TextureDimension::D2,
-			format: if compress_textures {wgpu::TextureFormat::Bc7RgbaUnormSrgb} else {wgpu::TextureFormat::Rgba8UnormSrgb},
+			format: compression_format.get_texture_format(decoded_texture.is_srgb),
 			usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
 			label: None,
 			view_formats: &[],
 		}
 	);
 	
-	render_context.command_queue.write_texture(
-		wgpu::ImageCopyTexture {
-			texture: &texture,
-			mip_level: 0,
-			origin: wgpu::Origin3d::ZERO,
-			aspect: wgpu::TextureAspect::All,
-		},
-		&texture_bytes,
-		wgpu::ImageDataLayout {
-			offset: 0,
-			bytes_per_row: Some(4 * dimensions.0),
-			rows_per_image: Some(dimensions.1),
-		},
-		texture_size,
-	);
+	for (mip_level, mip_bytes) in decoded_texture.mip_levels.iter().enumerate() {
+		let mip_size = wgpu::Extent3d {
+			width: (dimensions.0 >> mip_level).max(1),
+			height: (dimensions.1 >> mip_level).max(1),
+			depth_or_array_layers: 1,
+		};
+		render_context.command_queue.write_texture(
+			wgpu::ImageCopyTexture {
+				texture: &texture,
+				mip_level: mip_level as u32,
+				origin: wgpu::Origin3d::ZERO,
+				aspect: wgpu::TextureAspect::All,
+			},
+			mip_bytes,
+			wgpu::ImageDataLayout {
+				offset: 0,
+				bytes_per_row: Some(compression_format.get_bytes_per_row(mip_size.width)),
+				rows_per_image: Some(mip_size.height),
+			},
+			mip_size,
+		);
+	}
 	
 	let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
 	
-	Ok(MaterialRenderData {
+	MaterialRenderData {
 		path,
+		compression_format,
+		is_srgb: decoded_texture.is_srgb,
 		view,
-	})
+	}
 }
 
 
@@ -117,16 +301,36 @@ pub fn load_material_cube(
 	let compress_textures = false;
 	let path = path.into();
 	
-	let raw_texture_bytes = fs::read(utils::get_program_file_path(&path)).add_path_to_error(&path)?;
-	let texture_bytes = image::load_from_memory(&raw_texture_bytes).context("Failed to decode texture.")?;
-	let texture_bytes = texture_bytes.to_rgba8();
-	let dimensions = texture_bytes.dimensions();
-	let mut texture_bytes = texture_bytes.into_raw();
+	let full_path = utils::get_program_file_path(&path);
+	let faces = if full_path.is_dir() {
+		load_cube_faces_from_folder(&full_path)?
+	} else {
+		let raw_texture_bytes = fs::read(&full_path).add_path_to_error(&full_path)?;
+		let texture_bytes = image::load_from_memory(&raw_texture_bytes).with_context(|| format!("Failed to decode texture {full_path:?}."))?;
+		
+		// HELP: '.hdr' files are always equirectangular, and other images are only treated as equirectangular when they're twice as wide as they are tall (none of the face layouts are 2:1)
+		let is_hdr = path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("hdr"));
+		if is_hdr || texture_bytes.width() == texture_bytes.height() * 2 {
+			return load_material_cube_from_equirect(path, texture_bytes, is_hdr, render_context);
+		}
+		let Some(layout) = CubeFacesLayout::from_dimensions(texture_bytes.width(), texture_bytes.height()) else {
+			return Err(Error::msg(format!(
+				"Cube texture {path:?} has the dimensions {}x{}, which doesn't match any known layout. It must be 2:1 (equirectangular), 1:6 (vertical strip), 6:1 (horizontal strip), 4:3 (horizontal cross), or 3:4 (vertical cross), or it can be a folder with 6 separate faces.",
+				texture_bytes.width(), texture_bytes.height()
+			)));
+		};
+		layout.get_faces(&texture_bytes.to_rgba8())
+	};
+	
+	// the faces are stacked vertically so that each one becomes a layer of the texture
+	let face_size = faces[0].width();
+	let dimensions = (face_size, face_size * 6);
+	let mut texture_bytes = faces.into_iter().flat_map(|face| face.into_raw()).collect::<Vec<u8>>();
 	
 	if compress_textures {
 		let compress_settings = intel_tex_2::bc7::opaque_fast_settings();
 		texture_bytes = intel_tex_2::bc7::compress_blocks(&compress_settings, &intel_tex_2::Surface {
-			data: &*texture_bytes,
+			data: &texture_bytes,
 			width: dimensions.0,
 			height: dimensions.1,
 			stride: dimensions.0 * 4,
@@ -175,6 +379,253 @@ pub fn load_material_cube(
 	
 	Ok(MaterialRenderData {
 		path,
+		compression_format: CompressionFormat::None,
+		is_srgb: true,
+		view,
+	})
+}
+
+
+
+// HELP: The layouts that a single image of 6 square faces can have. The faces are always returned in the order of the cube texture's layers: +x, -x, +y, -y, +z, -z
+#[derive(Copy, Clone, Debug)]
+pub enum CubeFacesLayout {
+	// 1:6, the faces top to bottom in layer order
+	VerticalStrip,
+	// 6:1, the faces left to right in layer order
+	HorizontalStrip,
+	// 4:3, +y on top, then -x, +z, +x, -z in the middle row, then -y on the bottom
+	HorizontalCross,
+	// 3:4, +y on top, then -x, +z, +x in the second row, then -y, then -z (upside down) on the bottom
+	VerticalCross,
+}
+
+impl CubeFacesLayout {
+	pub fn from_dimensions(width: u32, height: u32) -> Option<Self> {
+		if width == 0 || height == 0 {return None;}
+		if height == width * 6 {return Some(Self::VerticalStrip);}
+		if width == height * 6 {return Some(Self::HorizontalStrip);}
+		if width * 3 == height * 4 {return Some(Self::HorizontalCross);}
+		if width * 4 == height * 3 {return Some(Self::VerticalCross);}
+		None
+	}
+	
+	pub fn get_face_size(self, width: u32) -> u32 {
+		match self {
+			Self::VerticalStrip => width,
+			Self::HorizontalStrip => width / 6,
+			Self::HorizontalCross => width / 4,
+			Self::VerticalCross => width / 3,
+		}
+	}
+	
+	// The column and row of each face (in face-sized cells), in layer order
+	pub fn get_face_cells(self) -> [(u32, u32); 6] {
+		match self {
+			Self::VerticalStrip => [(0, 0), (0, 1), (0, 2), (0, 3), (0, 4), (0, 5)],
+			Self::HorizontalStrip => [(0, 0), (1, 0), (2, 0), (3, 0), (4, 0), (5, 0)],
+			Self::HorizontalCross => [(2, 1), (0, 1), (1, 0), (1, 2), (1, 1), (3, 1)],
+			Self::VerticalCross => [(2, 1), (0, 1), (1, 0), (1, 2), (1, 1), (1, 3)],
+		}
+	}
+	
+	pub fn get_faces(self, texture_bytes: &image::RgbaImage) -> Vec<image::RgbaImage> {
+		let face_size = self.get_face_size(texture_bytes.width());
+		let mut faces = self.get_face_cells().into_iter()
+			.map(|(column, row)| image::imageops::crop_imm(texture_bytes, column * face_size, row * face_size, face_size, face_size).to_image())
+			.collect::<Vec<_>>();
+		// HELP: in a vertical cross, -z is reached by folding down past -y, so it ends up upside down compared to the other faces
+		if let Self::VerticalCross = self {
+			faces[5] = image::imageops::rotate180(&faces[5]);
+		}
+		faces
+	}
+}
+
+
+
+// the names that each face can have when a cube texture is a folder of separate images (like 'px.png' or 'right.png'), in layer order
+pub const CUBE_FACE_FILE_NAMES: [[&str; 2]; 6] = [["px", "right"], ["nx", "left"], ["py", "top"], ["ny", "bottom"], ["pz", "front"], ["nz", "back"]];
+
+pub fn load_cube_faces_from_folder(folder_path: &Path) -> Result<Vec<image::RgbaImage>> {
+	let file_paths = fs::read_dir(folder_path).add_path_to_error(folder_path)?
+		.map(|entry| entry.map(|entry| entry.path()))
+		.collect::<StdResult<Vec<_>, _>>().add_path_to_error(folder_path)?;
+	
+	let mut faces = Vec::with_capacity(6);
+	for names in CUBE_FACE_FILE_NAMES {
+		let face_path = file_paths.iter()
+			.find(|path| path.is_file() && path.file_stem().is_some_and(|stem| names.iter().any(|name| stem.eq_ignore_ascii_case(name))))
+			.with_context(|| format!("Cube texture folder {folder_path:?} doesn't have a face named '{}' or '{}'.", names[0], names[1]))?;
+		let raw_texture_bytes = fs::read(face_path).add_path_to_error(face_path)?;
+		let texture_bytes = image::load_from_memory(&raw_texture_bytes).with_context(|| format!("Failed to decode texture {face_path:?}."))?;
+		faces.push(texture_bytes.to_rgba8());
+	}
+	
+	let face_dimensions = faces[0].dimensions();
+	if face_dimensions.0 != face_dimensions.1 || faces.iter().any(|face| face.dimensions() != face_dimensions) {
+		let all_dimensions = faces.iter().map(|face| format!("{}x{}", face.width(), face.height())).collect::<Vec<_>>().join(", ");
+		return Err(Error::msg(format!("The faces in cube texture folder {folder_path:?} must all be square and the same size, but they are {all_dimensions} (+x, -x, +y, -y, +z, -z).")));
+	}
+	Ok(faces)
+}
+
+
+
+// HELP: The equirectangular image is uploaded as a float texture and projected onto the 6 faces with a one-time compute pass
+// ('shaders/equirect to cube.wgsl'). The output is hdr (Rgba16Float), so it can be bound in the same places as the srgb cube textures
+pub fn load_material_cube_from_equirect(
+	path: PathBuf,
+	texture_bytes: image::DynamicImage,
+	is_hdr: bool,
+	render_context: &RenderContextData,
+) -> Result<MaterialRenderData> {
+	const WORKGROUP_SIZE: u32 = 8;
+	
+	let mut equirect_bytes = texture_bytes.to_rgba32f();
+	// ldr images are stored as srgb, but '.hdr' images are already linear
+	if !is_hdr {
+		for pixel in equirect_bytes.pixels_mut() {
+			for channel in &mut pixel.0[..3] {
+				*channel = utils::srgb_to_linear(*channel);
+			}
+		}
+	}
+	let equirect_dimensions = equirect_bytes.dimensions();
+	// a quarter of the width keeps about the same amount of detail around the horizon
+	let face_size = (equirect_dimensions.0 / 4).clamp(1, render_context.device.limits().max_texture_dimension_2d);
+	
+	let equirect_size = wgpu::Extent3d {
+		width: equirect_dimensions.0,
+		height: equirect_dimensions.1,
+		depth_or_array_layers: 1,
+	};
+	let equirect_texture = render_context.device.create_texture(&wgpu::TextureDescriptor {
+		label: Some("equirect_texture"),
+		size: equirect_size,
+		mip_level_count: 1,
+		sample_count: 1,
+		dimension: wgpu::TextureDimension::D2,
+		format: wgpu::TextureFormat::Rgba32Float,
+		usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+		view_formats: &[],
+	});
+	render_context.command_queue.write_texture(
+		wgpu::ImageCopyTexture {
+			texture: &equirect_texture,
+			mip_level: 0,
+			origin: wgpu::Origin3d::ZERO,
+			aspect: wgpu::TextureAspect::All,
+		},
+		bytemuck::cast_slice(equirect_bytes.as_raw()),
+		wgpu::ImageDataLayout {
+			offset: 0,
+			bytes_per_row: Some(16 * equirect_dimensions.0),
+			rows_per_image: Some(equirect_dimensions.1),
+		},
+		equirect_size,
+	);
+	let equirect_view = equirect_texture.create_view(&wgpu::TextureViewDescriptor::default());
+	
+	let cube_texture = render_context.device.create_texture(&wgpu::TextureDescriptor {
+		label: None,
+		size: wgpu::Extent3d {
+			width: face_size,
+			height: face_size,
+			depth_or_array_layers: 6,
+		},
+		mip_level_count: 1,
+		sample_count: 1,
+		dimension: wgpu::TextureDimension::D2,
+		format: wgpu::TextureFormat::Rgba16Float,
+		usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::STORAGE_BINDING,
+		view_formats: &[],
+	});
+	let cube_storage_view = cube_texture.create_view(&wgpu::TextureViewDescriptor {
+		dimension: Some(wgpu::TextureViewDimension::D2Array),
+		..Default::default()
+	});
+	
+	let shader_path = utils::get_program_file_path("shaders/equirect to cube.wgsl");
+	let shader_source = fs::read_to_string(&shader_path).add_path_to_error(&shader_path)?;
+	let shader = render_context.device.create_shader_module(wgpu::ShaderModuleDescriptor {
+		label: Some("equirect_to_cube_shader_module"),
+		source: wgpu::ShaderSource::Wgsl(shader_source.into()),
+	});
+	let bind_0_layout = render_context.device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+		label: Some("equirect_to_cube_bind_0_layout"),
+		entries: &[
+			wgpu::BindGroupLayoutEntry { // equirect texture
+				binding: 0,
+				visibility: wgpu::ShaderStages::COMPUTE,
+				ty: wgpu::BindingType::Texture {
+					multisampled: false,
+					view_dimension: wgpu::TextureViewDimension::D2,
+					sample_type: wgpu::TextureSampleType::Float { filterable: false },
+				},
+				count: None,
+			},
+			wgpu::BindGroupLayoutEntry { // cube faces
+				binding: 1,
+				visibility: wgpu::ShaderStages::COMPUTE,
+				ty: wgpu::BindingType::StorageTexture {
+					access: wgpu::StorageTextureAccess::WriteOnly,
+					format: wgpu::TextureFormat::Rgba16Float,
+					view_dimension: wgpu::TextureViewDimension::D2Array,
+				},
+				count: None,
+			},
+		],
+	});
+	let pipeline_layout = render_context.device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+		label: Some("equirect_to_cube_pipeline_layout"),
+		bind_group_layouts: &[&bind_0_layout],
+		push_constant_ranges: &[],
+	});
+	let pipeline = render_context.device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+		label: Some("equirect_to_cube_pipeline"),
+		layout: Some(&pipeline_layout),
+		module: &shader,
+		entry_point: "cs_main",
+		compilation_options: wgpu::PipelineCompilationOptions::default(),
+	});
+	let bind_0 = render_context.device.create_bind_group(&wgpu::BindGroupDescriptor {
+		label: Some("equirect_to_cube_bind_0"),
+		layout: &bind_0_layout,
+		entries: &[
+			wgpu::BindGroupEntry {
+				binding: 0,
+				resource: wgpu::BindingResource::TextureView (&equirect_view),
+			},
+			wgpu::BindGroupEntry {
+				binding: 1,
+				resource: wgpu::BindingResource::TextureView (&cube_storage_view),
+			},
+		],
+	});
+	
+	let mut encoder = render_context.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {label: Some("equirect_to_cube_encoder")});
+	{
+		let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+			label: Some("equirect_to_cube_pass"),
+			timestamp_writes: None,
+		});
+		compute_pass.set_pipeline(&pipeline);
+		compute_pass.set_bind_group(0, &bind_0, &[]);
+		let workgroup_count = face_size.div_ceil(WORKGROUP_SIZE);
+		compute_pass.dispatch_workgroups(workgroup_count, workgroup_count, 6);
+	}
+	render_context.command_queue.submit(Some(encoder.finish()));
+	
+	let view = cube_texture.create_view(&wgpu::TextureViewDescriptor {
+		dimension: Some(wgpu::TextureViewDimension::Cube),
+		..Default::default()
+	});
+	
+	Ok(MaterialRenderData {
+		path,
+		compression_format: CompressionFormat::None,
+		is_srgb: true,
 		view,
 	})
 }
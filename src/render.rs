@@ -2,43 +2,385 @@ use crate::prelude::*;
 
 
 
-pub fn render(output: &wgpu::SurfaceTexture, program_data: &mut ProgramData) {
+// `output` is either the surface's current texture or the headless texture
+pub fn render(output: &wgpu::Texture, program_data: &mut ProgramData) {
 	
 	let frustum_planes = get_frustum_planes(&program_data.camera_data, program_data.render_context.aspect_ratio);
-	let visible_models_list = get_visible_models(
-		&program_data.example_model_instance_datas,
-		program_data.render_assets.example_models.bounding_radius,
-		&frustum_planes
-	);
+	// one list of instance indices per model
+	let models_and_instances = program_data.render_assets.models.iter().zip(&program_data.model_instance_datas);
+	let visible_models_lists = models_and_instances.clone().enumerate().map(|(model_index, (model, instance_datas))| {
+		// these are culled by `render_culling_pipeline()` instead
+		if model.gpu_culling.is_some() {return vec!();}
+		let mut visible_models = get_visible_models(
+			instance_datas,
+			model.bounding_radius,
+			&frustum_planes,
+			program_data.camera_data.pos,
+			program_data.engine_config.max_draw_distance,
+		);
+		// NOTE: these results are from a previous frame's queries, see `OcclusionRenderData`
+		if let Some(occlusion) = &program_data.render_assets.occlusion {
+			visible_models.retain(|i| !occlusion.is_occluded(model_index, *i));
+		}
+		// the opaque meshes of these models are drawn in the same order, but that doesn't change how they look
+		if model.has_transparent_meshes() {
+			sort_back_to_front(&mut visible_models, instance_datas, program_data.camera_data.pos);
+		}
+		visible_models
+	}).collect::<Vec<_>>();
+	let transparent_models_order = get_transparent_models_order(&program_data.render_assets.models, &program_data.model_instance_datas, &visible_models_lists, program_data.camera_data.pos);
+	
+	let shadow_casting_models_lists = models_and_instances.map(|(model, instance_datas)| if model.shadow_instances_buffer.is_some() {
+		get_nearest_models(instance_datas, program_data.camera_data.pos, program_data.engine_config.max_shadow_instances)
+	} else {
+		vec!()
+	}).collect::<Vec<_>>();
 	
-	update_gpu_buffers(program_data, &visible_models_list);
+	// the panel's changes should show up this frame, so it runs before the buffers are updated
+	let ui_output = ui_utils::run_ui(program_data);
+	update_gpu_buffers(program_data, &frustum_planes, &visible_models_lists, &shadow_casting_models_lists);
+	update_occlusion_queries(program_data, &frustum_planes);
+	if let Some(gpu_timer) = &mut program_data.gpu_timer {
+		gpu_timer.begin_frame();
+	}
 	
-	let output_view = output.texture.create_view(&wgpu::TextureViewDescriptor::default());
-	let main_tex_view = &program_data.render_assets.main_tex_view;
+	let output_view = output.create_view(&wgpu::TextureViewDescriptor {
+		format: Some(program_data.render_context.surface_view_format),
+		..Default::default()
+	});
 	let encoder_descriptor = wgpu::CommandEncoderDescriptor {label: None};
 	let mut encoder = program_data.render_context.device.create_command_encoder(&encoder_descriptor);
 	
-	render_shadow_caster_pipeline(program_data, &mut encoder);
-	render_models_pipeline(program_data, &mut encoder, &main_tex_view);
-	render_skybox_pipeline(program_data, &mut encoder, &main_tex_view); // HELP: it's better to have this at the end so that only the necessary pixels are rendered
-	render_color_correction_pipeline(program_data, &mut encoder, &output_view);
+	let targets = RenderTargets {
+		main_tex_view: &program_data.render_assets.main_tex_view,
+		output_view: &output_view,
+		transparent_models_order: &transparent_models_order,
+	};
+	for render_pass in &program_data.render_passes {
+		if !render_pass.is_enabled(program_data) {continue;}
+		encoder.push_debug_group(render_pass.name()); // the name shows up in graphics debuggers like renderdoc
+		render_pass.record(program_data, &mut encoder, &targets);
+		encoder.pop_debug_group();
+	}
+	// the ui isn't in `render_passes` since it needs to update its own textures and buffers
+	let ui_command_buffers = match &ui_output {
+		Some(ui_output) => render_ui_pipeline(&program_data.render_context, &mut program_data.ui_state, &mut encoder, &output_view, ui_output),
+		None => vec!(),
+	};
 	
-	program_data.render_context.command_queue.submit(std::iter::once(encoder.finish()));
+	if let Some(gpu_timer) = &program_data.gpu_timer {
+		gpu_timer.resolve(&mut encoder);
+	}
+	if let Some(occlusion) = &program_data.render_assets.occlusion {
+		occlusion.resolve(&mut encoder);
+	}
+	// NOTE: egui-wgpu's command buffers (only used by paint callbacks) have to be submitted before the main encoder
+	program_data.render_context.command_queue.submit(ui_command_buffers.into_iter().chain(std::iter::once(encoder.finish())));
+	if let Some(gpu_timer) = &mut program_data.gpu_timer {
+		gpu_timer.end_frame();
+	}
+	if let Some(occlusion) = &mut program_data.render_assets.occlusion {
+		occlusion.end_frame();
+	}
+}
+
+
+
+// HELP: `render()` records every pass in `ProgramData::render_passes` in order, so passes can be added, removed, or reordered
+// without editing `render()`. Nothing is tracked between the passes, so each one has to come after anything it reads from
+pub trait RenderPass {
+	fn name(&self) -> &'static str;
+	// this is checked every frame, right before `record()`
+	fn is_enabled(&self, program_data: &ProgramData) -> bool {
+		true
+	}
+	fn record(&self, program_data: &ProgramData, encoder: &mut wgpu::CommandEncoder, targets: &RenderTargets);
+}
+
+// the views that the passes draw to, along with the per-frame data that `render()` works out before recording them
+pub struct RenderTargets<'a> {
+	pub main_tex_view: &'a wgpu::TextureView, // the scene passes draw here (or to the msaa texture, see `get_scene_color_target()`)
+	pub output_view: &'a wgpu::TextureView, // the surface, which the passes after color correction draw to
+	pub transparent_models_order: &'a [usize],
+}
+
+pub fn get_default_render_passes() -> Vec<Box<dyn RenderPass>> {
+	vec!(
+		Box::new(ShadowCasterPass),
+		Box::new(CullingPass),
+		Box::new(ModelsPass),
+		Box::new(FoliagePass),
+		Box::new(OcclusionPass),
+		Box::new(BackgroundPass),
+		Box::new(ShadowCatcherPass),
+		Box::new(TransparentModelsPass),
+		Box::new(DebugLinesPass),
+		Box::new(AxisGizmoPass),
+		Box::new(BloomPass),
+		Box::new(ColorCorrectionPass),
+		Box::new(ShadowmapDebugPass),
+		Box::new(TextPass),
+	)
+}
+
+pub struct ShadowCasterPass;
+impl RenderPass for ShadowCasterPass {
+	fn name(&self) -> &'static str {"shadow_caster"}
+	fn record(&self, program_data: &ProgramData, encoder: &mut wgpu::CommandEncoder, _targets: &RenderTargets) {
+		render_shadow_caster_pipeline(program_data, encoder);
+	}
+}
+
+// only does anything when `gpu_culling` is enabled, and has to come before the models pass
+pub struct CullingPass;
+impl RenderPass for CullingPass {
+	fn name(&self) -> &'static str {"culling"}
+	fn record(&self, program_data: &ProgramData, encoder: &mut wgpu::CommandEncoder, _targets: &RenderTargets) {
+		render_culling_pipeline(program_data, encoder);
+	}
+}
+
+pub struct ModelsPass;
+impl RenderPass for ModelsPass {
+	fn name(&self) -> &'static str {"models"}
+	fn record(&self, program_data: &ProgramData, encoder: &mut wgpu::CommandEncoder, targets: &RenderTargets) {
+		render_models_pipeline(program_data, encoder, targets.main_tex_view);
+	}
+}
+
+pub struct FoliagePass;
+impl RenderPass for FoliagePass {
+	fn name(&self) -> &'static str {"foliage"}
+	fn record(&self, program_data: &ProgramData, encoder: &mut wgpu::CommandEncoder, targets: &RenderTargets) {
+		render_foliage_pipeline(program_data, encoder, targets.main_tex_view);
+	}
+}
+
+// needs the depth of everything opaque, but nothing after this writes depth
+pub struct OcclusionPass;
+impl RenderPass for OcclusionPass {
+	fn name(&self) -> &'static str {"occlusion"}
+	fn record(&self, program_data: &ProgramData, encoder: &mut wgpu::CommandEncoder, _targets: &RenderTargets) {
+		render_occlusion_pipeline(program_data, encoder);
+	}
+}
+
+// HELP: it's better to have the background at the end so that only the necessary pixels are rendered
+pub struct BackgroundPass;
+impl RenderPass for BackgroundPass {
+	fn name(&self) -> &'static str {"background"}
+	fn record(&self, program_data: &ProgramData, encoder: &mut wgpu::CommandEncoder, targets: &RenderTargets) {
+		match program_data.engine_config.background_mode {
+			BackgroundMode::Skybox if program_data.render_skybox => render_skybox_pipeline(program_data, encoder, targets.main_tex_view),
+			BackgroundMode::Gradient => render_gradient_background_pipeline(program_data, encoder, targets.main_tex_view),
+			BackgroundMode::Skybox | BackgroundMode::Solid => {}, // the models pass already clears to a solid color
+		}
+	}
+}
+
+// drawn after the background so that it can darken it
+pub struct ShadowCatcherPass;
+impl RenderPass for ShadowCatcherPass {
+	fn name(&self) -> &'static str {"shadow_catcher"}
+	fn is_enabled(&self, program_data: &ProgramData) -> bool {
+		program_data.engine_config.shadow_catcher_enabled
+	}
+	fn record(&self, program_data: &ProgramData, encoder: &mut wgpu::CommandEncoder, targets: &RenderTargets) {
+		render_shadow_catcher_pipeline(program_data, encoder, targets.main_tex_view);
+	}
+}
+
+// transparent meshes don't write depth, so the background would draw over them
+pub struct TransparentModelsPass;
+impl RenderPass for TransparentModelsPass {
+	fn name(&self) -> &'static str {"transparent_models"}
+	fn record(&self, program_data: &ProgramData, encoder: &mut wgpu::CommandEncoder, targets: &RenderTargets) {
+		if targets.transparent_models_order.is_empty() {return;}
+		render_transparent_models_pipeline(program_data, encoder, targets.main_tex_view, targets.transparent_models_order);
+	}
+}
+
+// HELP: these are only anti-aliased when `msaa_samples` is above 1
+pub struct DebugLinesPass;
+impl RenderPass for DebugLinesPass {
+	fn name(&self) -> &'static str {"debug_lines"}
+	fn is_enabled(&self, program_data: &ProgramData) -> bool {
+		program_data.render_assets.debug_lines.vertex_count > 0
+	}
+	fn record(&self, program_data: &ProgramData, encoder: &mut wgpu::CommandEncoder, targets: &RenderTargets) {
+		render_debug_lines_pipeline(program_data, encoder, targets.main_tex_view, &program_data.render_assets.debug_lines);
+	}
+}
+
+pub struct AxisGizmoPass;
+impl RenderPass for AxisGizmoPass {
+	fn name(&self) -> &'static str {"axis_gizmo"}
+	fn record(&self, program_data: &ProgramData, encoder: &mut wgpu::CommandEncoder, targets: &RenderTargets) {
+		let Some(axis_gizmo) = &program_data.render_assets.axis_gizmo else {return;};
+		render_debug_lines_pipeline(program_data, encoder, targets.main_tex_view, axis_gizmo);
+	}
+}
+
+pub struct BloomPass;
+impl RenderPass for BloomPass {
+	fn name(&self) -> &'static str {"bloom"}
+	fn is_enabled(&self, program_data: &ProgramData) -> bool {
+		program_data.color_correction_settings.bloom_strength > 0.0
+	}
+	fn record(&self, program_data: &ProgramData, encoder: &mut wgpu::CommandEncoder, _targets: &RenderTargets) {
+		render_bloom_pipeline(program_data, encoder);
+	}
+}
+
+pub struct ColorCorrectionPass;
+impl RenderPass for ColorCorrectionPass {
+	fn name(&self) -> &'static str {"color_correction"}
+	fn record(&self, program_data: &ProgramData, encoder: &mut wgpu::CommandEncoder, targets: &RenderTargets) {
+		render_color_correction_pipeline(program_data, encoder, targets.output_view);
+	}
+}
+
+// drawn onto the surface so that it isn't tonemapped
+pub struct ShadowmapDebugPass;
+impl RenderPass for ShadowmapDebugPass {
+	fn name(&self) -> &'static str {"shadowmap_debug"}
+	fn is_enabled(&self, program_data: &ProgramData) -> bool {
+		program_data.show_shadowmap_debug
+	}
+	fn record(&self, program_data: &ProgramData, encoder: &mut wgpu::CommandEncoder, targets: &RenderTargets) {
+		render_shadowmap_debug_pipeline(program_data, encoder, targets.output_view);
+	}
+}
+
+// also drawn onto the surface, so that the text isn't tonemapped or bloomed
+pub struct TextPass;
+impl RenderPass for TextPass {
+	fn name(&self) -> &'static str {"text"}
+	fn is_enabled(&self, program_data: &ProgramData) -> bool {
+		program_data.render_assets.text.glyph_count > 0
+	}
+	fn record(&self, program_data: &ProgramData, encoder: &mut wgpu::CommandEncoder, targets: &RenderTargets) {
+		render_text_pipeline(program_data, encoder, targets.output_view);
+	}
+}
+
+
+
+// this is used instead of `render()` once a frame has failed, so it only draws the error (see `FatalError`)
+pub fn render_fatal_error(output: &wgpu::Texture, program_data: &mut ProgramData, ui_output: &UiOutput) {
+	let output_view = output.create_view(&wgpu::TextureViewDescriptor {
+		format: Some(program_data.render_context.surface_view_format),
+		..Default::default()
+	});
+	let encoder_descriptor = wgpu::CommandEncoderDescriptor {label: Some("fatal_error_encoder")};
+	let mut encoder = program_data.render_context.device.create_command_encoder(&encoder_descriptor);
+	
+	// the ui pass draws on top of whatever is already there, so the surface is cleared first
+	encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+		label: Some("fatal_error_clear_pass"),
+		color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+			view: &output_view,
+			resolve_target: None,
+			ops: wgpu::Operations {
+				load: wgpu::LoadOp::Clear(FatalError::get_background_color(program_data.render_context.manual_srgb_encode)),
+				store: wgpu::StoreOp::Store,
+			},
+		})],
+		depth_stencil_attachment: None,
+		occlusion_query_set: None,
+		timestamp_writes: None,
+	});
+	let ui_command_buffers = render_ui_pipeline(&program_data.render_context, &mut program_data.ui_state, &mut encoder, &output_view, ui_output);
+	
+	program_data.render_context.command_queue.submit(ui_command_buffers.into_iter().chain(std::iter::once(encoder.finish())));
+}
+
+
+
+// HELP: this is presented once before the assets are loaded, since loading can take a while and the window would be blank until then
+pub fn render_loading_frame(render_context: &RenderContextData, ui_state: &mut UiState, message: &str) -> Result<()> {
+	let Some(drawable_surface) = &render_context.drawable_surface else {return Ok(());}; // nothing to show when headless
+	let size = render_context.surface_size;
+	if size.width == 0 || size.height == 0 {return Ok(());}
+	
+	let surface_output = drawable_surface.get_current_texture().context("Failed to get current window drawable texture.")?;
+	let output_view = surface_output.texture.create_view(&wgpu::TextureViewDescriptor {
+		format: Some(render_context.surface_view_format),
+		..Default::default()
+	});
+	let encoder_descriptor = wgpu::CommandEncoderDescriptor {label: Some("loading_encoder")};
+	let mut encoder = render_context.device.create_command_encoder(&encoder_descriptor);
+	
+	// NOTE: black is the same whether or not it's srgb encoded, so `manual_srgb_encode` doesn't matter here
+	encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+		label: Some("loading_clear_pass"),
+		color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+			view: &output_view,
+			resolve_target: None,
+			ops: wgpu::Operations {
+				load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+				store: wgpu::StoreOp::Store,
+			},
+		})],
+		depth_stencil_attachment: None,
+		occlusion_query_set: None,
+		timestamp_writes: None,
+	});
+	let ui_output = ui_utils::run_loading_ui(ui_state, render_context, message);
+	let ui_command_buffers = render_ui_pipeline(render_context, ui_state, &mut encoder, &output_view, &ui_output);
+	
+	render_context.command_queue.submit(ui_command_buffers.into_iter().chain(std::iter::once(encoder.finish())));
+	surface_output.present();
+	Ok(())
 }
 
 
 
 // this is an implementation of frustum culling based on: https://learnopengl.com/Guest-Articles/2021/Scene/Frustum-Culling
-pub fn get_visible_models(instance_datas: &[InstanceData], bounding_radius: f32, frustum_planes: &[(glam::Vec3, f32); 5]) -> Vec<usize> {
+pub fn get_visible_models(instance_datas: &[InstanceData], bounding_radius: f32, frustum_planes: &[(glam::Vec3, f32); 5], camera_pos: glam::Vec3, max_draw_distance: f32) -> Vec<usize> {
 	let mut output = Vec::with_capacity(instance_datas.len());
 	for (i, instance) in instance_datas.iter().enumerate() {
-		if model_is_visible(&instance.pos, bounding_radius, &frustum_planes) {
+		if max_draw_distance > 0.0 && instance.pos.distance(camera_pos) > max_draw_distance {continue;}
+		if model_is_visible(&instance.pos, bounding_radius, frustum_planes) {
 			output.push(i);
 		}
 	}
 	output
 }
 
+// the shadow caster is centered on the camera, so the instances closest to the camera are the ones that matter most for the shadowmap
+pub fn get_nearest_models(instance_datas: &[InstanceData], camera_pos: glam::Vec3, max_count: usize) -> Vec<usize> {
+	let mut output = (0..instance_datas.len()).collect::<Vec<_>>();
+	if max_count == 0 || max_count >= output.len() {return output;}
+	let get_dist = |i: &usize| instance_datas[*i].pos.distance_squared(camera_pos);
+	output.select_nth_unstable_by(max_count, |a, b| get_dist(a).total_cmp(&get_dist(b)));
+	output.truncate(max_count);
+	output
+}
+
+pub fn sort_back_to_front(visible_models: &mut [usize], instance_datas: &[InstanceData], camera_pos: glam::Vec3) {
+	let get_dist = |i: &usize| instance_datas[*i].pos.distance_squared(camera_pos);
+	visible_models.sort_by(|a, b| get_dist(b).total_cmp(&get_dist(a)));
+}
+
+// HELP: the instances of each model are drawn in one call, so models can only be sorted as a whole, this uses
+// each model's farthest visible instance (the first one, since the lists are already sorted back-to-front)
+pub fn get_transparent_models_order(models: &[ModelsRenderData], model_instance_datas: &[Vec<InstanceData>], visible_models_lists: &[Vec<usize>], camera_pos: glam::Vec3) -> Vec<usize> {
+	let mut output = (0..models.len())
+		.filter(|model_index| models[*model_index].has_transparent_meshes() && !visible_models_lists[*model_index].is_empty())
+		.collect::<Vec<_>>();
+	let get_dist = |model_index: &usize| model_instance_datas[*model_index][visible_models_lists[*model_index][0]].pos.distance_squared(camera_pos);
+	output.sort_by(|a, b| get_dist(b).total_cmp(&get_dist(a)));
+	output
+}
+
+// returns 1 for instances closer than the fade band and goes to 0 at `max_draw_distance`
+pub fn get_distance_fade(distance: f32, max_draw_distance: f32, fade_width: f32) -> f32 {
+	if max_draw_distance <= 0.0 {return 1.0;}
+	if fade_width <= 0.0 {return if distance <= max_draw_distance {1.0} else {0.0};}
+	((max_draw_distance - distance) / fade_width).clamp(0.0, 1.0)
+}
+
 pub fn get_frustum_planes(camera: &CameraData, aspect_ratio: f32) -> [(glam::Vec3, f32); 5] {
 	let forward = glam::Vec3::new(
 		camera.rot_xz.cos() * camera.rot_y.cos(),
@@ -49,37 +391,54 @@ pub fn get_frustum_planes(camera: &CameraData, aspect_ratio: f32) -> [(glam::Vec
 	let up_dir = glam::Vec3::Y;
 	let right_dir = forward.cross(up_dir).normalize();
 	let up_dir = forward.cross(-right_dir).normalize();
-	const FOV_MULT: f32 = 1.0; // lower this to see the culling work
-	let half_height = (camera.fov_radians * FOV_MULT / 2.0).tan() * camera.near;
-	let half_width = half_height * aspect_ratio;
 	let near_plane_center = camera.pos + forward * camera.near;
 	
-	let left_plane = {
-		let point_on_plane = near_plane_center - right_dir * half_width;
-		let normal = -up_dir.cross(point_on_plane - camera.pos).normalize();
-		let dist = point_on_plane.dot(normal);
-		(normal, dist)
-	};
-	
-	let right_plane = {
-		let point_on_plane = near_plane_center + right_dir * half_width;
-		let normal = up_dir.cross(point_on_plane - camera.pos).normalize();
-		let dist = point_on_plane.dot(normal);
-		(normal, dist)
-	};
-	
-	let top_plane = {
-		let point_on_plane = near_plane_center + up_dir * half_height;
-		let normal = -right_dir.cross(point_on_plane - camera.pos).normalize();
-		let dist = point_on_plane.dot(normal);
-		(normal, dist)
-	};
-	
-	let bottom_plane = {
-		let point_on_plane = near_plane_center - up_dir * half_height;
-		let normal = right_dir.cross(point_on_plane - camera.pos).normalize();
-		let dist = point_on_plane.dot(normal);
-		(normal, dist)
+	let (left_plane, right_plane, top_plane, bottom_plane) = match camera.projection {
+		CameraProjection::Perspective {fov_radians} => {
+			const FOV_MULT: f32 = 1.0; // lower this to see the culling work
+			let half_height = (fov_radians * FOV_MULT / 2.0).tan() * camera.near;
+			let half_width = half_height * aspect_ratio;
+			
+			let left_plane = {
+				let point_on_plane = near_plane_center - right_dir * half_width;
+				let normal = -up_dir.cross(point_on_plane - camera.pos).normalize();
+				let dist = point_on_plane.dot(normal);
+				(normal, dist)
+			};
+			
+			let right_plane = {
+				let point_on_plane = near_plane_center + right_dir * half_width;
+				let normal = up_dir.cross(point_on_plane - camera.pos).normalize();
+				let dist = point_on_plane.dot(normal);
+				(normal, dist)
+			};
+			
+			let top_plane = {
+				let point_on_plane = near_plane_center + up_dir * half_height;
+				let normal = -right_dir.cross(point_on_plane - camera.pos).normalize();
+				let dist = point_on_plane.dot(normal);
+				(normal, dist)
+			};
+			
+			let bottom_plane = {
+				let point_on_plane = near_plane_center - up_dir * half_height;
+				let normal = right_dir.cross(point_on_plane - camera.pos).normalize();
+				let dist = point_on_plane.dot(normal);
+				(normal, dist)
+			};
+			
+			(left_plane, right_plane, top_plane, bottom_plane)
+		}
+		// the sides of an orthographic frustum are parallel to the view direction, so the normals are just the camera's axes
+		CameraProjection::Orthographic {height} => {
+			let half_height = height * 0.5;
+			let half_width = half_height * aspect_ratio;
+			let left_plane = (right_dir, (camera.pos - right_dir * half_width).dot(right_dir));
+			let right_plane = (-right_dir, (camera.pos + right_dir * half_width).dot(-right_dir));
+			let top_plane = (-up_dir, (camera.pos + up_dir * half_height).dot(-up_dir));
+			let bottom_plane = (up_dir, (camera.pos - up_dir * half_height).dot(up_dir));
+			(left_plane, right_plane, top_plane, bottom_plane)
+		}
 	};
 	
 	//let near_plane = { // there's not really any point in checking this plane but you still can if you just want to
@@ -113,69 +472,442 @@ pub fn is_sphere_past_plane(pos: &glam::Vec3, radius: f32, plane_normal: &glam::
 
 
 
-pub fn update_gpu_buffers(program_data: &mut ProgramData, visible_models: &[usize]) {
+// `visible_models` and `shadow_casting_models` have one list of instance indices per model
+pub fn update_gpu_buffers(program_data: &mut ProgramData, frustum_planes: &[(glam::Vec3, f32); 5], visible_models: &[Vec<usize>], shadow_casting_models: &[Vec<usize>]) {
 	
-	// culled_instances_buffer
-	let mut new_model_instances_data = Vec::with_capacity(visible_models.len());
-	for index in visible_models {
-		new_model_instances_data.push(program_data.example_model_instance_datas[*index].to_raw())
+	let camera_pos = program_data.camera_data.pos;
+	let engine_config = &program_data.engine_config;
+	for (model_index, model) in program_data.render_assets.models.iter_mut().enumerate() {
+		let instance_datas = &program_data.model_instance_datas[model_index];
+		
+		// total_instances_buffer
+		for dirty_range in model.take_dirty_instance_ranges(instance_datas.len()) {
+			let raw_instance_datas = instance_datas[dirty_range.clone()].iter().map(InstanceData::to_raw).collect::<Vec<_>>();
+			program_data.render_context.command_queue.write_buffer(
+				&model.total_instances_buffer,
+				(dirty_range.start * std::mem::size_of::<RawInstanceData>()) as wgpu::BufferAddress,
+				bytemuck::cast_slice(&raw_instance_datas),
+			);
+		}
+		
+		// culled_instances_buffer (or the culling settings, when the compute shader writes it instead)
+		model.rotate_culled_instances_buffers();
+		if let Some(gpu_culling) = &model.gpu_culling {
+			program_data.render_context.command_queue.write_buffer(
+				&gpu_culling.settings_buffer,
+				0,
+				bytemuck::bytes_of(&CullingSettings::new(engine_config, frustum_planes, camera_pos, model)),
+			);
+			// the real count is only known on the gpu, so this is just the upper bound
+			model.culled_instances_counts[model.culled_instances_index] = model.total_instances_count;
+		} else {
+			// lod 0 goes in the culled instances buffer and the rest go in `model.lods`
+			let visible_models = &visible_models[model_index];
+			let instance_lods = &program_data.model_instance_lods[model_index];
+			let mut lods_instances_data = vec!(vec!(); model.lods.len() + 1);
+			for index in visible_models {
+				let instance = &instance_datas[*index];
+				let mut raw_instance = instance.to_raw();
+				raw_instance.fade = get_distance_fade(instance.pos.distance(camera_pos), engine_config.max_draw_distance, engine_config.draw_distance_fade_width);
+				let lod = instance_lods.get(*index).map_or(0, |lod| *lod as usize);
+				lods_instances_data[lod].push(raw_instance);
+			}
+			program_data.render_context.command_queue.write_buffer(
+				model.culled_instances_buffer(),
+				0,
+				bytemuck::cast_slice(&lods_instances_data[0]),
+			);
+			model.culled_instances_counts[model.culled_instances_index] = lods_instances_data[0].len() as u32;
+			for (lod, lod_instances_data) in model.lods.iter_mut().zip(&lods_instances_data[1..]) {
+				program_data.render_context.command_queue.write_buffer(
+					&lod.instances_buffer,
+					0,
+					bytemuck::cast_slice(lod_instances_data),
+				);
+				lod.instances_count = lod_instances_data.len() as u32;
+			}
+		}
+		
+		// indirect_args_buffer
+		if let Some(indirect_args_buffer) = &model.indirect_args_buffer {
+			// with gpu culling, `render_culling_pipeline()` copies the real instance count over this
+			let instance_count = model.culled_instances_count();
+			for (i, mesh) in model.meshes.iter().enumerate() {
+				let args_bytes = match &mesh.index_buffer {
+					Some(_) => wgpu::util::DrawIndexedIndirectArgs {
+						index_count: mesh.index_count,
+						instance_count,
+						first_index: 0,
+						base_vertex: 0,
+						first_instance: 0,
+					}.as_bytes().to_vec(),
+					None => wgpu::util::DrawIndirectArgs {
+						vertex_count: mesh.vertex_count,
+						instance_count,
+						first_vertex: 0,
+						first_instance: 0,
+					}.as_bytes().to_vec(),
+				};
+				program_data.render_context.command_queue.write_buffer(indirect_args_buffer, ModelsRenderData::INDIRECT_ARGS_STRIDE * i as wgpu::BufferAddress, &args_bytes);
+			}
+		}
+		
+		// shadow_instances_buffer
+		if let Some(shadow_instances_buffer) = &model.shadow_instances_buffer {
+			let shadow_instances_data = shadow_casting_models[model_index].iter().map(|i| instance_datas[*i].to_raw()).collect::<Vec<_>>();
+			program_data.render_context.command_queue.write_buffer(
+				shadow_instances_buffer,
+				0,
+				bytemuck::cast_slice(&shadow_instances_data),
+			);
+		}
+		
 	}
-	program_data.render_context.command_queue.write_buffer(
-		&program_data.render_assets.example_models.culled_instances_buffer,
-		0,
-		bytemuck::cast_slice(&new_model_instances_data),
-	);
-	program_data.render_assets.example_models.culled_instances_count = visible_models.len() as u32;
 	
 	// camera.buffer
-	let camera_gpu_data = program_data.camera_data.build_gpu_data(program_data.render_context.aspect_ratio);
+	let camera_gpu_data = program_data.camera_data.build_gpu_data(&program_data.render_context);
 	program_data.render_context.command_queue.write_buffer(
 		&program_data.render_assets.camera.buffer,
 		0,
 		bytemuck::cast_slice(&camera_gpu_data),
 	);
 	
-	// shadow_caster.proj_mat_buffer
-	let shadow_caster_gpu_data = program_data.shadow_caster_data.build_gpu_data(program_data.camera_data.pos);
+	// shadow_caster.cascades_buffer and shadow_caster.cascade_proj_mat_buffers
+	let shadow_caster = &program_data.render_assets.shadow_caster;
+	let cascades_gpu_data = program_data.shadow_caster_data.build_gpu_data(
+		&program_data.camera_data,
+		program_data.render_context.aspect_ratio,
+		program_data.directional_light.direction,
+		program_data.engine_config.shadowmap_size,
+	);
+	program_data.render_context.command_queue.write_buffer(
+		&shadow_caster.cascades_buffer,
+		0,
+		bytemuck::bytes_of(&cascades_gpu_data),
+	);
+	for (proj_mat_buffer, proj_mat) in shadow_caster.cascade_proj_mat_buffers.iter().zip(&cascades_gpu_data.proj_mats) {
+		program_data.render_context.command_queue.write_buffer(
+			proj_mat_buffer,
+			0,
+			bytemuck::cast_slice(proj_mat),
+		);
+	}
+	
+	// color_correction_buffer
+	let color_correction_settings = ColorCorrectionSettings {
+		encode_srgb: program_data.render_context.manual_srgb_encode as u32,
+		..program_data.color_correction_settings.clamped()
+	};
+	program_data.render_context.command_queue.write_buffer(
+		&program_data.render_assets.color_correction_buffer,
+		0,
+		bytemuck::bytes_of(&color_correction_settings),
+	);
+	
+	// directional_light_buffer
 	program_data.render_context.command_queue.write_buffer(
-		&program_data.render_assets.shadow_caster.proj_mat_buffer,
+		&program_data.render_assets.directional_light_buffer,
 		0,
-		bytemuck::cast_slice(&shadow_caster_gpu_data),
+		bytemuck::cast_slice(&program_data.directional_light.build_gpu_data()),
 	);
 	
+	// lights_buffer
+	let lights = &program_data.lights;
+	if lights.len() > program_data.render_assets.lights_capacity {
+		// the capacity is doubled so that adding lights one at a time doesn't re-allocate every frame
+		let min_capacity = lights.len().next_power_of_two();
+		let (lights_buffer, lights_capacity) = load::load_lights_buffer(&program_data.render_context, lights, min_capacity);
+		program_data.render_assets.lights_buffer = lights_buffer;
+		program_data.render_assets.lights_capacity = lights_capacity;
+		// the old bind group still points at the old buffer
+		program_data.render_bindings.models_bind_0 = load::load_models_bind_0(&program_data.render_context, &program_data.render_layouts, &program_data.render_assets);
+	} else if !lights.is_empty() {
+		let raw_lights = lights.iter().map(LightData::to_raw).collect::<Vec<_>>();
+		program_data.render_context.command_queue.write_buffer(
+			&program_data.render_assets.lights_buffer,
+			0,
+			bytemuck::cast_slice(&raw_lights),
+		);
+	}
+	
+	// debug_lines.vertex_buffer
+	let debug_lines = &program_data.debug_lines;
+	if debug_lines.len() > program_data.render_assets.debug_lines_capacity {
+		let min_capacity = debug_lines.len().next_power_of_two();
+		let (vertex_buffer, debug_lines_capacity) = load::load_debug_lines_buffer(&program_data.render_context, min_capacity);
+		program_data.render_assets.debug_lines.vertex_buffer = vertex_buffer;
+		program_data.render_assets.debug_lines_capacity = debug_lines_capacity;
+	}
+	if !debug_lines.is_empty() {
+		program_data.render_context.command_queue.write_buffer(
+			&program_data.render_assets.debug_lines.vertex_buffer,
+			0,
+			bytemuck::cast_slice(debug_lines),
+		);
+	}
+	program_data.render_assets.debug_lines.vertex_count = debug_lines.len() as u32;
+	program_data.debug_lines.clear();
+	
+	// text.settings_buffer and text.glyphs_buffer
+	let cell_size = program_data.engine_config.stats_text_scale as f32;
+	let glyphs = get_text_glyphs(&program_data.stats_text, cell_size);
+	if glyphs.len() > program_data.render_assets.text.glyphs_capacity {
+		let min_capacity = glyphs.len().next_power_of_two();
+		let (glyphs_buffer, glyphs_capacity) = load::load_text_glyphs_buffer(&program_data.render_context, min_capacity);
+		program_data.render_assets.text.glyphs_buffer = glyphs_buffer;
+		program_data.render_assets.text.glyphs_capacity = glyphs_capacity;
+	}
+	if !glyphs.is_empty() {
+		let surface_config = &program_data.render_context.surface_config;
+		let text_settings = TextSettings {
+			screen_size: [surface_config.width as f32, surface_config.height as f32],
+			cell_size,
+			encode_srgb: program_data.render_context.manual_srgb_encode as u32,
+		};
+		program_data.render_context.command_queue.write_buffer(
+			&program_data.render_assets.text.settings_buffer,
+			0,
+			bytemuck::bytes_of(&text_settings),
+		);
+		program_data.render_context.command_queue.write_buffer(
+			&program_data.render_assets.text.glyphs_buffer,
+			0,
+			bytemuck::cast_slice(&glyphs),
+		);
+	}
+	program_data.render_assets.text.glyph_count = glyphs.len() as u32;
+	
+	// lighting_buffer
+	let mut lighting_settings = program_data.lighting_settings;
+	lighting_settings.light_count = program_data.lights.len() as u32;
+	lighting_settings.set_shadow_caster(&program_data.shadow_caster_data);
+	lighting_settings.environment_rotation = program_data.skybox_settings.rotation;
+	program_data.render_context.command_queue.write_buffer(
+		&program_data.render_assets.lighting_buffer,
+		0,
+		bytemuck::bytes_of(&lighting_settings),
+	);
+	
+	// foliage.settings_buffer
+	if let Some(foliage) = &program_data.render_assets.foliage {
+		let foliage_settings = FoliageSettings::new(&program_data.engine_config, program_data.get_interpolated_time());
+		program_data.render_context.command_queue.write_buffer(
+			&foliage.settings_buffer,
+			0,
+			bytemuck::bytes_of(&foliage_settings),
+		);
+	}
+	
+	// shadow_catcher_buffer
+	if program_data.engine_config.shadow_catcher_enabled {
+		let shadow_catcher_settings = ShadowCatcherSettings::new(&program_data.engine_config, program_data.camera_data.pos);
+		program_data.render_context.command_queue.write_buffer(
+			&program_data.render_assets.shadow_catcher_buffer,
+			0,
+			bytemuck::bytes_of(&shadow_catcher_settings),
+		);
+	}
+	
+	// skybox_settings_buffer
+	let mut skybox_settings = program_data.skybox_settings;
+	if program_data.render_assets.secondary_skybox_material_id.is_none() {
+		skybox_settings.blend = 0.0;
+	}
+	skybox_settings.blend = skybox_settings.blend.clamp(0.0, 1.0);
+	program_data.render_context.command_queue.write_buffer(
+		&program_data.render_assets.skybox_settings_buffer,
+		0,
+		bytemuck::bytes_of(&skybox_settings),
+	);
+	
+}
+
+
+
+
+
+// when msaa is enabled, the scene passes draw to the multisampled texture and resolve it into `main_tex_view`
+// HELP: every scene pass resolves (instead of only the last one) since which passes run depends on the engine config
+pub fn get_scene_color_target<'a>(render_assets: &'a RenderAssets, main_tex_view: &'a wgpu::TextureView) -> (&'a wgpu::TextureView, Option<&'a wgpu::TextureView>) {
+	match &render_assets.msaa_tex_view {
+		Some(msaa_tex_view) => (msaa_tex_view, Some(main_tex_view)),
+		None => (main_tex_view, None),
+	}
+}
+
+// picks which instance groups get an occlusion query this frame and writes their bounding boxes, the groups that
+// aren't queried (because they're off-screen, contain the camera, or are past `MAX_QUERIES`) count as visible
+pub fn update_occlusion_queries(program_data: &mut ProgramData, frustum_planes: &[(glam::Vec3, f32); 5]) {
+	let Some(occlusion) = &mut program_data.render_assets.occlusion else {return;};
+	if !occlusion.can_record() {return;}
+	
+	let camera = &program_data.camera_data;
+	let mut boxes = vec!();
+	for (model_index, model) in program_data.render_assets.models.iter().enumerate() {
+		if model.gpu_culling.is_some() {continue;}
+		let instance_datas = &program_data.model_instance_datas[model_index];
+		for (group_index, group) in instance_datas.chunks(OcclusionRenderData::GROUP_SIZE).enumerate() {
+			let (min, max) = group.iter().fold((glam::Vec3::INFINITY, glam::Vec3::NEG_INFINITY), |(min, max), instance| (min.min(instance.pos), max.max(instance.pos)));
+			let min = min - glam::Vec3::splat(model.bounding_radius);
+			let max = max + glam::Vec3::splat(model.bounding_radius);
+			let center = (min + max) * 0.5;
+			let is_on_screen = model_is_visible(&center, center.distance(max), frustum_planes);
+			// HELP: from inside the box, its front faces are clipped by the near plane and its back faces are behind the group itself, so it would always look occluded
+			let margin = glam::Vec3::splat(camera.near);
+			let contains_camera = camera.pos.cmpge(min - margin).all() && camera.pos.cmple(max + margin).all();
+			if !is_on_screen || contains_camera || boxes.len() >= OcclusionRenderData::MAX_QUERIES as usize {
+				occlusion.occluded_groups[model_index][group_index] = false;
+				continue;
+			}
+			boxes.push(OcclusionBox {min: min.to_array(), max: max.to_array()});
+			occlusion.queried_groups.push((model_index, group_index));
+		}
+	}
+	
+	program_data.render_context.command_queue.write_buffer(
+		&occlusion.boxes_buffer,
+		0,
+		bytemuck::cast_slice(&boxes),
+	);
+}
+
+
+
+// uses `draw_indexed()` when the mesh has an index buffer, and `draw()` otherwise
+pub fn draw_mesh<'a>(pass_handle: &mut wgpu::RenderPass<'a>, mesh: &'a MeshRenderData, instances: std::ops::Range<u32>) {
+	match &mesh.index_buffer {
+		Some(index_buffer) => {
+			pass_handle.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+			pass_handle.draw_indexed(0..mesh.index_count, 0, instances);
+		}
+		None => pass_handle.draw(0..mesh.vertex_count, instances),
+	}
+}
+
+// `indirect_offset` is where this mesh's args start in `indirect_buffer`, see `ModelsRenderData::indirect_args_buffer`
+pub fn draw_mesh_indirect<'a>(pass_handle: &mut wgpu::RenderPass<'a>, mesh: &'a MeshRenderData, indirect_buffer: &'a wgpu::Buffer, indirect_offset: wgpu::BufferAddress) {
+	match &mesh.index_buffer {
+		Some(index_buffer) => {
+			pass_handle.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+			pass_handle.draw_indexed_indirect(indirect_buffer, indirect_offset);
+		}
+		None => pass_handle.draw_indirect(indirect_buffer, indirect_offset),
+	}
 }
 
 
 
 
 
+// this runs one pass per shadow cascade
 pub fn render_shadow_caster_pipeline(program_data: &ProgramData, encoder: &mut wgpu::CommandEncoder) {
 	let render_assets = &program_data.render_assets;
+	let cascades = render_assets.shadow_caster.cascade_depth_tex_views.iter().zip(&program_data.render_bindings.shadow_caster_bind_0s);
+	let cascade_count = render_assets.shadow_caster.cascade_depth_tex_views.len();
+	for (i, (cascade_depth_tex_view, shadow_caster_bind_0)) in cascades.enumerate() {
+		
+		// the first cascade's pass is the first pass of the frame, so the gpu timer starts here
+		let timestamp_start = (i == 0).then_some(GpuTimer::FRAME_START);
+		let timestamp_end = (i + 1 == cascade_count).then_some(GpuTimer::SHADOW_CASTER_END);
+		
+		// I've tried to move these RenderPassDescriptor-s to `load_layouts.rs`, but the complexity required just isn't worth it
+		let mut shadow_caster_pass_handle = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+			label: Some("shadow_caster_render_pass"),
+			color_attachments: &[],
+			depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+				view: cascade_depth_tex_view,
+				depth_ops: Some(wgpu::Operations {
+					load: wgpu::LoadOp::Clear (program_data.engine_config.clear_depth),
+					store: wgpu::StoreOp::Store,
+				}),
+				stencil_ops: None,
+			}),
+			occlusion_query_set: None,
+			timestamp_writes: program_data.gpu_timer.as_ref().and_then(|gpu_timer| gpu_timer.get_timestamp_writes(timestamp_start, timestamp_end)),
+		});
+		
+		shadow_caster_pass_handle.set_pipeline(&program_data.render_layouts.shadow_caster_pipeline);
+		shadow_caster_pass_handle.set_bind_group(0, shadow_caster_bind_0, &[]);
+		
+		for model in &render_assets.models {
+			// the instances are the same for every mesh in a model, so they only need to be bound once per model
+			let (shadow_instances_buffer, shadow_instances_count) = model.shadow_instances();
+			shadow_caster_pass_handle.set_vertex_buffer(1, shadow_instances_buffer.slice(..));
+			for mesh in &model.meshes {
+				shadow_caster_pass_handle.set_vertex_buffer(0, mesh.basic_vertex_buffer.slice(..));
+				draw_mesh(&mut shadow_caster_pass_handle, mesh, 0..shadow_instances_count);
+			}
+		}
+		
+	}
+}
+
+
+
+
+
+pub fn render_culling_pipeline(program_data: &ProgramData, encoder: &mut wgpu::CommandEncoder) {
+	let Some(culling_pipeline) = &program_data.render_layouts.culling_pipeline else {return;};
+	let models = &program_data.render_assets.models;
+	
+	for gpu_culling in models.iter().filter_map(|model| model.gpu_culling.as_ref()) {
+		encoder.clear_buffer(&gpu_culling.visible_count_buffer, 0, None);
+	}
+	
+	{
+		let mut culling_pass_handle = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {label: Some("culling_compute_pass"), timestamp_writes: None});
+		culling_pass_handle.set_pipeline(culling_pipeline);
+		for (model_index, model) in models.iter().enumerate() {
+			if model.gpu_culling.is_none() {continue;}
+			culling_pass_handle.set_bind_group(0, &program_data.render_bindings.culling_bind_0s[model_index][model.culled_instances_index], &[]);
+			culling_pass_handle.dispatch_workgroups(model.total_instances_count.div_ceil(ComputeRenderData::WORKGROUP_SIZE), 1, 1);
+		}
+	}
+	
+	// every mesh of a model draws the same instances, so they all get the same count
+	for model in models {
+		let (Some(gpu_culling), Some(indirect_args_buffer)) = (&model.gpu_culling, &model.indirect_args_buffer) else {continue;};
+		for i in 0..model.meshes.len() {
+			let args_offset = ModelsRenderData::INDIRECT_ARGS_STRIDE * i as wgpu::BufferAddress;
+			encoder.copy_buffer_to_buffer(&gpu_culling.visible_count_buffer, 0, indirect_args_buffer, args_offset + ModelsRenderData::INDIRECT_INSTANCE_COUNT_OFFSET, 4);
+		}
+	}
+	
+}
+
+
+
+
+
+pub fn render_occlusion_pipeline(program_data: &ProgramData, encoder: &mut wgpu::CommandEncoder) {
+	let render_assets = &program_data.render_assets;
+	let Some(occlusion) = &render_assets.occlusion else {return;};
+	if occlusion.queried_groups.is_empty() {return;}
 	
-	// I've tried to move these RenderPassDescriptor-s to `load_layouts.rs`, but the complexity required just isn't worth it
-	let mut shadow_caster_pass_handle = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-		label: Some("shadow_caster_render_pass"),
+	let mut occlusion_pass_handle = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+		label: Some("occlusion_render_pass"),
 		color_attachments: &[],
 		depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
-			view: &render_assets.shadow_caster.depth_tex_view,
+			view: &render_assets.depth.view,
 			depth_ops: Some(wgpu::Operations {
-				load: wgpu::LoadOp::Clear (1.0),
+				load: wgpu::LoadOp::Load,
 				store: wgpu::StoreOp::Store,
 			}),
 			stencil_ops: None,
 		}),
-		occlusion_query_set: None,
+		occlusion_query_set: Some(&occlusion.query_set),
 		timestamp_writes: None,
 	});
 	
-	shadow_caster_pass_handle.set_pipeline(&program_data.render_layouts.shadow_caster_pipeline);
-	shadow_caster_pass_handle.set_bind_group(0, &program_data.render_bindings.shadow_caster_bind_0, &[]);
+	occlusion_pass_handle.set_pipeline(&program_data.render_layouts.occlusion_pipeline);
+	occlusion_pass_handle.set_bind_group(0, &program_data.render_bindings.occlusion_bind_0, &[]);
+	occlusion_pass_handle.set_vertex_buffer(0, occlusion.boxes_buffer.slice(..));
 	
-	for mesh in &render_assets.example_models.meshes {
-		shadow_caster_pass_handle.set_vertex_buffer(0, mesh.basic_vertex_buffer.slice(..));
-		shadow_caster_pass_handle.set_vertex_buffer(1, render_assets.example_models.total_instances_buffer.slice(..));
-		shadow_caster_pass_handle.set_index_buffer(mesh.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
-		shadow_caster_pass_handle.draw_indexed(0..mesh.index_count, 0, 0..render_assets.example_models.total_instances_count);
+	// the query index is the same as the box index
+	for i in 0..occlusion.queried_groups.len() as u32 {
+		occlusion_pass_handle.begin_occlusion_query(i);
+		occlusion_pass_handle.draw(0..36, i..i + 1);
+		occlusion_pass_handle.end_occlusion_query();
 	}
 	
 }
@@ -187,17 +919,18 @@ pub fn render_shadow_caster_pipeline(program_data: &ProgramData, encoder: &mut w
 pub fn render_models_pipeline(program_data: &ProgramData, encoder: &mut wgpu::CommandEncoder, main_tex_view: &wgpu::TextureView) {
 	let render_assets = &program_data.render_assets;
 	
+	let (scene_view, scene_resolve_target) = get_scene_color_target(render_assets, main_tex_view);
 	let mut models_pass_handle = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
 		label: Some("models_render_pass"),
 		color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-			view: main_tex_view,
-			resolve_target: None,
+			view: scene_view,
+			resolve_target: scene_resolve_target,
 			ops: wgpu::Operations {
 				load: wgpu::LoadOp::Clear (wgpu::Color {
-					r: 0.1,
-					g: 0.2,
-					b: 0.3,
-					a: 1.0,
+					r: program_data.engine_config.clear_color[0] as f64,
+					g: program_data.engine_config.clear_color[1] as f64,
+					b: program_data.engine_config.clear_color[2] as f64,
+					a: program_data.engine_config.clear_color[3] as f64,
 				}),
 				store: wgpu::StoreOp::Store,
 			},
@@ -205,7 +938,75 @@ pub fn render_models_pipeline(program_data: &ProgramData, encoder: &mut wgpu::Co
 		depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
 			view: &render_assets.depth.view,
 			depth_ops: Some(wgpu::Operations {
-				load: wgpu::LoadOp::Clear (1.0),
+				load: wgpu::LoadOp::Clear (program_data.engine_config.clear_depth),
+				store: wgpu::StoreOp::Store,
+			}),
+			stencil_ops: None,
+		}),
+		occlusion_query_set: None,
+		timestamp_writes: program_data.gpu_timer.as_ref().and_then(|gpu_timer| gpu_timer.get_timestamp_writes(Some(GpuTimer::MODELS_START), Some(GpuTimer::MODELS_END))),
+	});
+	
+	models_pass_handle.set_bind_group(0, &program_data.render_bindings.models_bind_0, &[]);
+	
+	// the single-sided meshes are all drawn first so that the pipeline only has to be switched once
+	for double_sided in [false, true] {
+		let models_pipeline = match &program_data.render_layouts.models_wireframe_pipeline {
+			Some(wireframe_pipeline) if program_data.wireframe => wireframe_pipeline,
+			_ if double_sided => &program_data.render_layouts.models_double_sided_pipeline,
+			_ => &program_data.render_layouts.models_pipeline,
+		};
+		models_pass_handle.set_pipeline(models_pipeline);
+		for (model_index, model) in render_assets.models.iter().enumerate() {
+			models_pass_handle.set_vertex_buffer(2, model.culled_instances_buffer().slice(..));
+			for (i, mesh) in model.meshes.iter().enumerate() {
+				if mesh.alpha_mode == MaterialAlphaMode::Blend || mesh.double_sided != double_sided {continue;}
+				models_pass_handle.set_bind_group(1, &program_data.render_bindings.models_bind_1s[model_index][i], &[]);
+				models_pass_handle.set_vertex_buffer(0, mesh.basic_vertex_buffer.slice(..));
+				models_pass_handle.set_vertex_buffer(1, mesh.extended_vertex_buffer.slice(..));
+				match &model.indirect_args_buffer {
+					Some(indirect_args_buffer) => draw_mesh_indirect(&mut models_pass_handle, mesh, indirect_args_buffer, ModelsRenderData::INDIRECT_ARGS_STRIDE * i as wgpu::BufferAddress),
+					None => draw_mesh(&mut models_pass_handle, mesh, 0..model.culled_instances_count()),
+				}
+			}
+			// NOTE: the lods are always drawn directly, only lod 0 has indirect args
+			for (lod_index, lod) in model.lods.iter().enumerate() {
+				if lod.instances_count == 0 {continue;}
+				models_pass_handle.set_vertex_buffer(2, lod.instances_buffer.slice(..));
+				for (i, mesh) in lod.meshes.iter().enumerate() {
+					if mesh.alpha_mode == MaterialAlphaMode::Blend || mesh.double_sided != double_sided {continue;}
+					models_pass_handle.set_bind_group(1, &program_data.render_bindings.models_lod_bind_1s[model_index][lod_index][i], &[]);
+					models_pass_handle.set_vertex_buffer(0, mesh.basic_vertex_buffer.slice(..));
+					models_pass_handle.set_vertex_buffer(1, mesh.extended_vertex_buffer.slice(..));
+					draw_mesh(&mut models_pass_handle, mesh, 0..lod.instances_count);
+				}
+			}
+		}
+	}
+	
+}
+
+
+
+// `models_order` is back-to-front and only has the models that have transparent meshes
+pub fn render_transparent_models_pipeline(program_data: &ProgramData, encoder: &mut wgpu::CommandEncoder, main_tex_view: &wgpu::TextureView, models_order: &[usize]) {
+	let render_assets = &program_data.render_assets;
+	
+	let (scene_view, scene_resolve_target) = get_scene_color_target(render_assets, main_tex_view);
+	let mut models_pass_handle = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+		label: Some("transparent_models_render_pass"),
+		color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+			view: scene_view,
+			resolve_target: scene_resolve_target,
+			ops: wgpu::Operations {
+				load: wgpu::LoadOp::Load,
+				store: wgpu::StoreOp::Store,
+			},
+		})],
+		depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+			view: &render_assets.depth.view,
+			depth_ops: Some(wgpu::Operations {
+				load: wgpu::LoadOp::Load,
 				store: wgpu::StoreOp::Store,
 			}),
 			stencil_ops: None,
@@ -214,16 +1015,23 @@ pub fn render_models_pipeline(program_data: &ProgramData, encoder: &mut wgpu::Co
 		timestamp_writes: None,
 	});
 	
-	models_pass_handle.set_pipeline(&program_data.render_layouts.models_pipeline);
+	let models_pipeline = match &program_data.render_layouts.models_wireframe_pipeline {
+		Some(wireframe_pipeline) if program_data.wireframe => wireframe_pipeline,
+		_ => &program_data.render_layouts.models_transparent_pipeline,
+	};
+	models_pass_handle.set_pipeline(models_pipeline);
 	models_pass_handle.set_bind_group(0, &program_data.render_bindings.models_bind_0, &[]);
 	
-	for (i, mesh) in program_data.render_assets.example_models.meshes.iter().enumerate() {
-		models_pass_handle.set_bind_group(1, &program_data.render_bindings.example_models_bind_1s[i], &[]);
-		models_pass_handle.set_vertex_buffer(0, mesh.basic_vertex_buffer.slice(..));
-		models_pass_handle.set_vertex_buffer(1, mesh.extended_vertex_buffer.slice(..));
-		models_pass_handle.set_vertex_buffer(2, render_assets.example_models.culled_instances_buffer.slice(..));
-		models_pass_handle.set_index_buffer(mesh.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
-		models_pass_handle.draw_indexed(0..mesh.index_count, 0, 0..render_assets.example_models.culled_instances_count);
+	for model_index in models_order {
+		let model = &render_assets.models[*model_index];
+		models_pass_handle.set_vertex_buffer(2, model.culled_instances_buffer().slice(..));
+		for (i, mesh) in model.meshes.iter().enumerate() {
+			if mesh.alpha_mode != MaterialAlphaMode::Blend {continue;}
+			models_pass_handle.set_bind_group(1, &program_data.render_bindings.models_bind_1s[*model_index][i], &[]);
+			models_pass_handle.set_vertex_buffer(0, mesh.basic_vertex_buffer.slice(..));
+			models_pass_handle.set_vertex_buffer(1, mesh.extended_vertex_buffer.slice(..));
+			draw_mesh(&mut models_pass_handle, mesh, 0..model.culled_instances_count());
+		}
 	}
 	
 }
@@ -234,12 +1042,14 @@ pub fn render_models_pipeline(program_data: &ProgramData, encoder: &mut wgpu::Co
 
 pub fn render_skybox_pipeline(program_data: &ProgramData, encoder: &mut wgpu::CommandEncoder, main_tex_view: &wgpu::TextureView) {
 	let render_assets = &program_data.render_assets;
+	let Some(skybox_bind_0) = &program_data.render_bindings.skybox_bind_0 else {return;};
 	
+	let (scene_view, scene_resolve_target) = get_scene_color_target(render_assets, main_tex_view);
 	let mut skybox_pass_handle = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
 		label: Some("skybox_render_pass"),
 		color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-			view: main_tex_view,
-			resolve_target: None,
+			view: scene_view,
+			resolve_target: scene_resolve_target,
 			ops: wgpu::Operations {
 				load: wgpu::LoadOp::Load,
 				store: wgpu::StoreOp::Store,
@@ -254,11 +1064,11 @@ pub fn render_skybox_pipeline(program_data: &ProgramData, encoder: &mut wgpu::Co
 			stencil_ops: None,
 		}),
 		occlusion_query_set: None,
-		timestamp_writes: None,
+		timestamp_writes: program_data.gpu_timer.as_ref().and_then(|gpu_timer| gpu_timer.get_timestamp_writes(Some(GpuTimer::SKYBOX_START), Some(GpuTimer::SKYBOX_END))),
 	});
 	
 	skybox_pass_handle.set_pipeline(&program_data.render_layouts.skybox_pipeline);
-	skybox_pass_handle.set_bind_group(0, &program_data.render_bindings.skybox_bind_0, &[]);
+	skybox_pass_handle.set_bind_group(0, skybox_bind_0, &[]);
 	
 	skybox_pass_handle.draw(0..3, 0..1)
 	
@@ -268,6 +1078,207 @@ pub fn render_skybox_pipeline(program_data: &ProgramData, encoder: &mut wgpu::Co
 
 
 
+pub fn render_foliage_pipeline(program_data: &ProgramData, encoder: &mut wgpu::CommandEncoder, main_tex_view: &wgpu::TextureView) {
+	let render_assets = &program_data.render_assets;
+	let (Some(foliage), Some(foliage_bind_0)) = (&render_assets.foliage, &program_data.render_bindings.foliage_bind_0) else {return;};
+	
+	let (scene_view, scene_resolve_target) = get_scene_color_target(render_assets, main_tex_view);
+	let mut foliage_pass_handle = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+		label: Some("foliage_render_pass"),
+		color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+			view: scene_view,
+			resolve_target: scene_resolve_target,
+			ops: wgpu::Operations {
+				load: wgpu::LoadOp::Load,
+				store: wgpu::StoreOp::Store,
+			},
+		})],
+		depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+			view: &render_assets.depth.view,
+			depth_ops: Some(wgpu::Operations {
+				load: wgpu::LoadOp::Load,
+				store: wgpu::StoreOp::Store,
+			}),
+			stencil_ops: None,
+		}),
+		occlusion_query_set: None,
+		timestamp_writes: None,
+	});
+	
+	foliage_pass_handle.set_pipeline(&program_data.render_layouts.foliage_pipeline);
+	foliage_pass_handle.set_bind_group(0, foliage_bind_0, &[]);
+	foliage_pass_handle.set_vertex_buffer(0, foliage.instances_buffer.slice(..));
+	
+	foliage_pass_handle.draw(0..12, 0..foliage.instances_count);
+	
+}
+
+
+
+
+
+pub fn render_gradient_background_pipeline(program_data: &ProgramData, encoder: &mut wgpu::CommandEncoder, main_tex_view: &wgpu::TextureView) {
+	let render_assets = &program_data.render_assets;
+	
+	let (scene_view, scene_resolve_target) = get_scene_color_target(render_assets, main_tex_view);
+	let mut gradient_background_pass_handle = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+		label: Some("gradient_background_render_pass"),
+		color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+			view: scene_view,
+			resolve_target: scene_resolve_target,
+			ops: wgpu::Operations {
+				load: wgpu::LoadOp::Load,
+				store: wgpu::StoreOp::Store,
+			},
+		})],
+		depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+			view: &render_assets.depth.view,
+			depth_ops: Some(wgpu::Operations {
+				load: wgpu::LoadOp::Load,
+				store: wgpu::StoreOp::Store,
+			}),
+			stencil_ops: None,
+		}),
+		occlusion_query_set: None,
+		timestamp_writes: None,
+	});
+	
+	gradient_background_pass_handle.set_pipeline(&program_data.render_layouts.gradient_background_pipeline);
+	gradient_background_pass_handle.set_bind_group(0, &program_data.render_bindings.gradient_background_bind_0, &[]);
+	
+	gradient_background_pass_handle.draw(0..3, 0..1)
+	
+}
+
+
+
+
+
+pub fn render_shadow_catcher_pipeline(program_data: &ProgramData, encoder: &mut wgpu::CommandEncoder, main_tex_view: &wgpu::TextureView) {
+	let render_assets = &program_data.render_assets;
+	
+	let (scene_view, scene_resolve_target) = get_scene_color_target(render_assets, main_tex_view);
+	let mut shadow_catcher_pass_handle = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+		label: Some("shadow_catcher_render_pass"),
+		color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+			view: scene_view,
+			resolve_target: scene_resolve_target,
+			ops: wgpu::Operations {
+				load: wgpu::LoadOp::Load,
+				store: wgpu::StoreOp::Store,
+			},
+		})],
+		depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+			view: &render_assets.depth.view,
+			depth_ops: Some(wgpu::Operations {
+				load: wgpu::LoadOp::Load,
+				store: wgpu::StoreOp::Store,
+			}),
+			stencil_ops: None,
+		}),
+		occlusion_query_set: None,
+		timestamp_writes: None,
+	});
+	
+	shadow_catcher_pass_handle.set_pipeline(&program_data.render_layouts.shadow_catcher_pipeline);
+	shadow_catcher_pass_handle.set_bind_group(0, &program_data.render_bindings.shadow_catcher_bind_0, &[]);
+	
+	shadow_catcher_pass_handle.draw(0..6, 0..1);
+	
+}
+
+
+
+
+
+// `lines.mode` picks between the depth-tested pipeline and the overlay pipeline, so each debug draw can choose whether it's hidden by the scene
+pub fn render_debug_lines_pipeline(program_data: &ProgramData, encoder: &mut wgpu::CommandEncoder, main_tex_view: &wgpu::TextureView, lines: &DebugLinesRenderData) {
+	let render_assets = &program_data.render_assets;
+	
+	let (scene_view, scene_resolve_target) = get_scene_color_target(render_assets, main_tex_view);
+	let mut debug_lines_pass_handle = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+		label: Some("debug_lines_render_pass"),
+		color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+			view: scene_view,
+			resolve_target: scene_resolve_target,
+			ops: wgpu::Operations {
+				load: wgpu::LoadOp::Load,
+				store: wgpu::StoreOp::Store,
+			},
+		})],
+		depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+			view: &render_assets.depth.view,
+			depth_ops: Some(wgpu::Operations {
+				load: wgpu::LoadOp::Load,
+				store: wgpu::StoreOp::Store,
+			}),
+			stencil_ops: None,
+		}),
+		occlusion_query_set: None,
+		timestamp_writes: None,
+	});
+	
+	let pipeline = match lines.mode {
+		DebugDrawMode::DepthTested => &program_data.render_layouts.debug_lines_pipeline,
+		DebugDrawMode::Overlay => &program_data.render_layouts.debug_lines_overlay_pipeline,
+	};
+	debug_lines_pass_handle.set_pipeline(pipeline);
+	debug_lines_pass_handle.set_bind_group(0, &program_data.render_bindings.debug_lines_bind_0, &[]);
+	debug_lines_pass_handle.set_vertex_buffer(0, lines.vertex_buffer.slice(..));
+	
+	debug_lines_pass_handle.draw(0..lines.vertex_count, 0..1);
+	
+}
+
+
+
+
+
+// the main texture's bright parts are downsampled through every bloom mip, then each mip is upsampled and added to the one above it, which leaves the full blur in the first mip
+pub fn render_bloom_pipeline(program_data: &ProgramData, encoder: &mut wgpu::CommandEncoder) {
+	let render_layouts = &program_data.render_layouts;
+	let mip_views = &program_data.render_assets.bloom.mip_views;
+	let bloom_bind_0s = &program_data.render_bindings.bloom_bind_0s;
+	
+	for (i, mip_view) in mip_views.iter().enumerate() {
+		let pipeline = if i == 0 {&render_layouts.bloom_bright_pass_pipeline} else {&render_layouts.bloom_downsample_pipeline};
+		render_bloom_pass(encoder, mip_view, pipeline, &bloom_bind_0s[i], wgpu::LoadOp::Clear (wgpu::Color::BLACK));
+	}
+	
+	for i in (0..mip_views.len().saturating_sub(1)).rev() {
+		render_bloom_pass(encoder, &mip_views[i], &render_layouts.bloom_upsample_pipeline, &bloom_bind_0s[i + 2], wgpu::LoadOp::Load);
+	}
+	
+}
+
+pub fn render_bloom_pass(encoder: &mut wgpu::CommandEncoder, target_view: &wgpu::TextureView, pipeline: &wgpu::RenderPipeline, bind_0: &wgpu::BindGroup, load: wgpu::LoadOp<wgpu::Color>) {
+	
+	let mut bloom_pass_handle = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+		label: Some("bloom_render_pass"),
+		color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+			view: target_view,
+			resolve_target: None,
+			ops: wgpu::Operations {
+				load,
+				store: wgpu::StoreOp::Store,
+			},
+		})],
+		depth_stencil_attachment: None,
+		occlusion_query_set: None,
+		timestamp_writes: None,
+	});
+	
+	bloom_pass_handle.set_pipeline(pipeline);
+	bloom_pass_handle.set_bind_group(0, bind_0, &[]);
+	
+	bloom_pass_handle.draw(0..3, 0..1)
+	
+}
+
+
+
+
+
 pub fn render_color_correction_pipeline(program_data: &ProgramData, encoder: &mut wgpu::CommandEncoder, output_view: &wgpu::TextureView) {
 	let render_assets = &program_data.render_assets;
 	
@@ -283,7 +1294,8 @@ pub fn render_color_correction_pipeline(program_data: &ProgramData, encoder: &mu
 		})],
 		depth_stencil_attachment: None,
 		occlusion_query_set: None,
-		timestamp_writes: None,
+		// the last pass of the frame, so the gpu timer ends here
+		timestamp_writes: program_data.gpu_timer.as_ref().and_then(|gpu_timer| gpu_timer.get_timestamp_writes(None, Some(GpuTimer::FRAME_END))),
 	});
 	
 	skybox_pass_handle.set_pipeline(&program_data.render_layouts.color_correction_pipeline);
@@ -292,3 +1304,150 @@ pub fn render_color_correction_pipeline(program_data: &ProgramData, encoder: &mu
 	skybox_pass_handle.draw(0..3, 0..1)
 	
 }
+
+
+
+pub fn render_shadowmap_debug_pipeline(program_data: &ProgramData, encoder: &mut wgpu::CommandEncoder, output_view: &wgpu::TextureView) {
+	const MARGIN: f32 = 10.0;
+	let surface_config = &program_data.render_context.surface_config;
+	let cascade_count = program_data.render_assets.shadow_caster.cascade_depth_tex_views.len();
+	
+	let mut shadowmap_debug_pass_handle = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+		label: Some("shadowmap_debug_render_pass"),
+		color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+			view: output_view,
+			resolve_target: None,
+			ops: wgpu::Operations {
+				load: wgpu::LoadOp::Load,
+				store: wgpu::StoreOp::Store,
+			},
+		})],
+		depth_stencil_attachment: None,
+		occlusion_query_set: None,
+		timestamp_writes: None,
+	});
+	
+	shadowmap_debug_pass_handle.set_pipeline(&program_data.render_layouts.shadowmap_debug_pipeline);
+	shadowmap_debug_pass_handle.set_bind_group(0, &program_data.render_bindings.shadowmap_debug_bind_0, &[]);
+	
+	// the cascades are lined up along the bottom of the screen, and they shrink so that they always fit
+	let width = surface_config.width as f32;
+	let height = surface_config.height as f32;
+	let size = ((width - MARGIN) / cascade_count as f32 - MARGIN).min(height / 4.0).min(256.0);
+	if size < 1.0 {return;}
+	for i in 0..cascade_count {
+		let x = MARGIN + i as f32 * (size + MARGIN);
+		shadowmap_debug_pass_handle.set_viewport(x, height - MARGIN - size, size, size, 0.0, 1.0);
+		shadowmap_debug_pass_handle.draw(0..3, i as u32..i as u32 + 1);
+	}
+	
+}
+
+
+
+// the lines start in the top-left corner, and every glyph is drawn twice so that it has a drop shadow (which keeps it readable on bright backgrounds)
+pub fn get_text_glyphs(lines: &[String], cell_size: f32) -> Vec<TextGlyphData> {
+	const MARGIN: f32 = 10.0;
+	const COLOR: [f32; 3] = [1.0, 1.0, 1.0];
+	const SHADOW_COLOR: [f32; 3] = [0.0, 0.0, 0.0];
+	let mut glyphs = vec!();
+	for (line_index, line) in lines.iter().enumerate() {
+		let y = MARGIN + (line_index as u32 * TextGlyphData::LINE_HEIGHT) as f32 * cell_size;
+		for (char_index, character) in line.chars().enumerate() {
+			if character == ' ' {continue;}
+			let x = MARGIN + (char_index as u32 * TextGlyphData::ADVANCE) as f32 * cell_size;
+			let bits = TextGlyphData::get_bits(character);
+			glyphs.push(TextGlyphData { pos: [x + cell_size, y + cell_size], color: SHADOW_COLOR, bits });
+			glyphs.push(TextGlyphData { pos: [x, y], color: COLOR, bits });
+		}
+	}
+	glyphs
+}
+
+pub fn render_text_pipeline(program_data: &ProgramData, encoder: &mut wgpu::CommandEncoder, output_view: &wgpu::TextureView) {
+	let text = &program_data.render_assets.text;
+	
+	let mut text_pass_handle = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+		label: Some("text_render_pass"),
+		color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+			view: output_view,
+			resolve_target: None,
+			ops: wgpu::Operations {
+				load: wgpu::LoadOp::Load,
+				store: wgpu::StoreOp::Store,
+			},
+		})],
+		depth_stencil_attachment: None,
+		occlusion_query_set: None,
+		timestamp_writes: None,
+	});
+	
+	text_pass_handle.set_pipeline(&program_data.render_layouts.text_pipeline);
+	text_pass_handle.set_bind_group(0, &program_data.render_bindings.text_bind_0, &[]);
+	text_pass_handle.set_vertex_buffer(0, text.glyphs_buffer.slice(..));
+	
+	text_pass_handle.draw(0..6, 0..text.glyph_count);
+	
+}
+
+
+
+// HELP: this draws directly onto the swapchain texture, after color correction, so the panel isn't tonemapped or bloomed
+// this takes the render context and ui state instead of `ProgramData` so that it can also be used before `ProgramData` exists (see `render_loading_frame()`)
+pub fn render_ui_pipeline(render_context: &RenderContextData, ui_state: &mut UiState, encoder: &mut wgpu::CommandEncoder, output_view: &wgpu::TextureView, ui_output: &UiOutput) -> Vec<wgpu::CommandBuffer> {
+	for (texture_id, image_delta) in &ui_output.textures_delta.set {
+		ui_state.renderer.update_texture(&render_context.device, &render_context.command_queue, *texture_id, image_delta);
+	}
+	let command_buffers = ui_state.renderer.update_buffers(&render_context.device, &render_context.command_queue, encoder, &ui_output.paint_jobs, &ui_state.screen_descriptor);
+	
+	{
+		let mut ui_pass_handle = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+			label: Some("ui_render_pass"),
+			color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+				view: output_view,
+				resolve_target: None,
+				ops: wgpu::Operations {
+					load: wgpu::LoadOp::Load,
+					store: wgpu::StoreOp::Store,
+				},
+			})],
+			depth_stencil_attachment: None,
+			occlusion_query_set: None,
+			timestamp_writes: None,
+		});
+		ui_state.renderer.render(&mut ui_pass_handle, &ui_output.paint_jobs, &ui_state.screen_descriptor);
+	}
+	
+	for texture_id in &ui_output.textures_delta.free {
+		ui_state.renderer.free_texture(texture_id);
+	}
+	command_buffers
+}
+
+
+
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	// HELP: this catches drift between the pipelines, bindings, and shaders, since all of them have to line up for a frame to be recorded
+	#[test]
+	fn renders_a_frame_into_the_headless_texture() {
+		let engine_config = EngineConfig {
+			window_width: 64,
+			window_height: 64,
+			compress_textures: false,
+			..EngineConfig::default()
+		};
+		if !load::headless_adapter_is_available(&engine_config) {
+			eprintln!("Skipping test because there's no adapter available.");
+			return;
+		}
+		let mut program_data = load::load_headless_program_data(Instant::now(), engine_config).expect("Failed to load headless program data.");
+		let frame = capture_utils::render_headless_frame(&mut program_data).expect("Failed to render headless frame.");
+		assert_eq!(frame.dimensions(), (64, 64));
+	}
+	
+}
@@ -0,0 +1,280 @@
+use crate::prelude::*;
+use std::f32::consts::PI;
+use winit::{event::{ElementState, KeyEvent, MouseButton, MouseScrollDelta, WindowEvent}, keyboard::{KeyCode, PhysicalKey}};
+
+// HELP: The purpose of this is to run the egui debug panel. egui-winit isn't used because its
+// latest version that works with wgpu 0.20 (0.28) needs winit 0.29, so the winit events that
+// egui cares about are translated here instead
+
+
+
+// HELP: this is called for every window event, but the events are only given to egui at the start of the next frame
+pub fn process_window_event(ui_state: &mut UiState, event: &WindowEvent, pixels_per_point: f32, cursor_is_captured: bool) {
+	match event {
+		
+		WindowEvent::ModifiersChanged (modifiers) => {
+			let state = modifiers.state();
+			ui_state.modifiers = egui::Modifiers {
+				alt: state.alt_key(),
+				ctrl: state.control_key(),
+				shift: state.shift_key(),
+				mac_cmd: cfg!(target_os = "macos") && state.super_key(),
+				command: if cfg!(target_os = "macos") {state.super_key()} else {state.control_key()},
+			};
+		}
+		
+		WindowEvent::Focused (is_focused) => {
+			ui_state.events.push(egui::Event::WindowFocused(*is_focused));
+		}
+		
+		// NOTE: while the camera is being moved the cursor is hidden and kept at the center of the window, so it shouldn't be able to hover or click on the panel
+		WindowEvent::CursorMoved {position, ..} if !cursor_is_captured => {
+			let pos = egui::pos2(position.x as f32 / pixels_per_point, position.y as f32 / pixels_per_point);
+			ui_state.pointer_pos = Some(pos);
+			ui_state.events.push(egui::Event::PointerMoved(pos));
+		}
+		
+		WindowEvent::CursorLeft {..} => {
+			ui_state.pointer_pos = None;
+			ui_state.events.push(egui::Event::PointerGone);
+		}
+		
+		WindowEvent::MouseInput {state, button, ..} if !cursor_is_captured => {
+			let Some(pos) = ui_state.pointer_pos else {return;};
+			let button = match button {
+				MouseButton::Left    => egui::PointerButton::Primary,
+				MouseButton::Right   => egui::PointerButton::Secondary,
+				MouseButton::Middle  => egui::PointerButton::Middle,
+				MouseButton::Back    => egui::PointerButton::Extra1,
+				MouseButton::Forward => egui::PointerButton::Extra2,
+				MouseButton::Other (_) => return,
+			};
+			ui_state.events.push(egui::Event::PointerButton {
+				pos,
+				button,
+				pressed: state.is_pressed(),
+				modifiers: ui_state.modifiers,
+			});
+		}
+		
+		WindowEvent::MouseWheel {delta, ..} if !cursor_is_captured => {
+			let (unit, delta) = match delta {
+				MouseScrollDelta::LineDelta (x, y) => (egui::MouseWheelUnit::Line, egui::vec2(*x, *y)),
+				MouseScrollDelta::PixelDelta (delta) => (egui::MouseWheelUnit::Point, egui::vec2(delta.x as f32, delta.y as f32) / pixels_per_point),
+			};
+			ui_state.events.push(egui::Event::MouseWheel {
+				unit,
+				delta,
+				modifiers: ui_state.modifiers,
+			});
+		}
+		
+		WindowEvent::KeyboardInput {event: KeyEvent {physical_key, state, text, repeat, ..}, ..} => {
+			let pressed = *state == ElementState::Pressed;
+			if let Some(key) = get_egui_key(*physical_key) {
+				ui_state.events.push(egui::Event::Key {
+					key,
+					physical_key: Some(key),
+					pressed,
+					repeat: *repeat,
+					modifiers: ui_state.modifiers,
+				});
+			}
+			// egui handles shortcuts like ctrl+c through the key events, so those shouldn't also be typed
+			let is_typing = pressed && !ui_state.modifiers.ctrl && !ui_state.modifiers.mac_cmd;
+			let text = text.iter().flat_map(|text| text.chars()).filter(|c| !c.is_control()).collect::<String>();
+			if is_typing && !text.is_empty() {
+				ui_state.events.push(egui::Event::Text(text));
+			}
+		}
+		
+		_ => {},
+	}
+}
+
+// only the keys that are needed for editing the panel's values are translated
+pub fn get_egui_key(physical_key: PhysicalKey) -> Option<egui::Key> {
+	let PhysicalKey::Code (key_code) = physical_key else {return None;};
+	Some(match key_code {
+		KeyCode::ArrowDown  => egui::Key::ArrowDown,
+		KeyCode::ArrowLeft  => egui::Key::ArrowLeft,
+		KeyCode::ArrowRight => egui::Key::ArrowRight,
+		KeyCode::ArrowUp    => egui::Key::ArrowUp,
+		KeyCode::Escape     => egui::Key::Escape,
+		KeyCode::Tab        => egui::Key::Tab,
+		KeyCode::Backspace  => egui::Key::Backspace,
+		KeyCode::Enter | KeyCode::NumpadEnter => egui::Key::Enter,
+		KeyCode::Space      => egui::Key::Space,
+		KeyCode::Insert     => egui::Key::Insert,
+		KeyCode::Delete     => egui::Key::Delete,
+		KeyCode::Home       => egui::Key::Home,
+		KeyCode::End        => egui::Key::End,
+		KeyCode::PageUp     => egui::Key::PageUp,
+		KeyCode::PageDown   => egui::Key::PageDown,
+		KeyCode::KeyA       => egui::Key::A,
+		KeyCode::KeyC       => egui::Key::C,
+		KeyCode::KeyV       => egui::Key::V,
+		KeyCode::KeyX       => egui::Key::X,
+		KeyCode::KeyZ       => egui::Key::Z,
+		_ => return None,
+	})
+}
+
+
+
+
+
+// returns `None` when the panel is hidden, otherwise returns everything that's needed for `render::render_ui_pipeline()`
+pub fn run_ui(program_data: &mut ProgramData) -> Option<UiOutput> {
+	let ui_state = &mut program_data.ui_state;
+	let events = std::mem::take(&mut ui_state.events);
+	if !ui_state.is_visible {
+		ui_state.wants_pointer_input = false;
+		ui_state.wants_keyboard_input = false;
+		return None;
+	}
+	
+	let raw_input = get_raw_input(program_data, events);
+	
+	// the context is just an Arc, so it's cloned so that the panel can borrow `program_data`
+	let context = program_data.ui_state.context.clone();
+	let full_output = context.run(raw_input, |context| build_debug_panel(context, program_data));
+	program_data.ui_state.wants_pointer_input = context.wants_pointer_input() || context.is_pointer_over_area();
+	program_data.ui_state.wants_keyboard_input = context.wants_keyboard_input();
+	
+	Some(UiOutput {
+		paint_jobs: context.tessellate(full_output.shapes, full_output.pixels_per_point),
+		textures_delta: full_output.textures_delta,
+	})
+}
+
+
+
+// the paint jobs are sized to the surface, and `ui_state.screen_descriptor` is updated in `resize()`
+pub fn get_raw_input(program_data: &ProgramData, events: Vec<egui::Event>) -> egui::RawInput {
+	let ui_state = &program_data.ui_state;
+	let [width, height] = ui_state.screen_descriptor.size_in_pixels;
+	let pixels_per_point = ui_state.screen_descriptor.pixels_per_point;
+	let mut raw_input = egui::RawInput {
+		screen_rect: Some(egui::Rect::from_min_size(egui::Pos2::ZERO, egui::vec2(width as f32, height as f32) / pixels_per_point)),
+		max_texture_side: Some(program_data.render_context.device.limits().max_texture_dimension_2d as usize),
+		time: Some(program_data.start_time.elapsed().as_secs_f64()),
+		modifiers: ui_state.modifiers,
+		events,
+		focused: program_data.input.is_focused,
+		..egui::RawInput::default()
+	};
+	raw_input.viewports.entry(egui::ViewportId::ROOT).or_default().native_pixels_per_point = Some(pixels_per_point);
+	raw_input
+}
+
+
+
+// this is shown whether or not the debug panel is visible, and the error text can be selected so it can be copied into bug reports
+pub fn run_fatal_error_ui(program_data: &mut ProgramData) -> UiOutput {
+	let events = std::mem::take(&mut program_data.ui_state.events);
+	let raw_input = get_raw_input(program_data, events);
+	let (message, time_left) = match &program_data.fatal_error {
+		Some(fatal_error) => (fatal_error.message.as_str(), fatal_error.time_left()),
+		None => ("", Duration::ZERO),
+	};
+	
+	let context = program_data.ui_state.context.clone();
+	let full_output = context.run(raw_input, |context| {
+		egui::CentralPanel::default().frame(egui::Frame::none().inner_margin(24.0)).show(context, |ui| {
+			ui.heading(egui::RichText::new("Fatal error").color(egui::Color32::WHITE));
+			ui.label(format!("The program will exit in {} seconds (or press escape to exit now).", time_left.as_secs() + 1));
+			ui.add_space(12.0);
+			egui::ScrollArea::vertical().show(ui, |ui| {
+				ui.add(egui::Label::new(egui::RichText::new(message).monospace().color(egui::Color32::WHITE)).selectable(true));
+			});
+		});
+	});
+	
+	UiOutput {
+		paint_jobs: context.tessellate(full_output.shapes, full_output.pixels_per_point),
+		textures_delta: full_output.textures_delta,
+	}
+}
+
+
+
+// this runs before `ProgramData` exists, so the raw input is only what's needed to lay out the text
+pub fn run_loading_ui(ui_state: &UiState, render_context: &RenderContextData, message: &str) -> UiOutput {
+	let [width, height] = ui_state.screen_descriptor.size_in_pixels;
+	let pixels_per_point = ui_state.screen_descriptor.pixels_per_point;
+	let mut raw_input = egui::RawInput {
+		screen_rect: Some(egui::Rect::from_min_size(egui::Pos2::ZERO, egui::vec2(width as f32, height as f32) / pixels_per_point)),
+		max_texture_side: Some(render_context.device.limits().max_texture_dimension_2d as usize),
+		..egui::RawInput::default()
+	};
+	raw_input.viewports.entry(egui::ViewportId::ROOT).or_default().native_pixels_per_point = Some(pixels_per_point);
+	
+	let context = ui_state.context.clone();
+	let full_output = context.run(raw_input, |context| {
+		egui::CentralPanel::default().frame(egui::Frame::none()).show(context, |ui| {
+			ui.centered_and_justified(|ui| {
+				ui.label(egui::RichText::new(message).heading().color(egui::Color32::WHITE));
+			});
+		});
+	});
+	
+	UiOutput {
+		paint_jobs: context.tessellate(full_output.shapes, full_output.pixels_per_point),
+		textures_delta: full_output.textures_delta,
+	}
+}
+
+
+
+pub fn build_debug_panel(context: &egui::Context, program_data: &mut ProgramData) {
+	egui::Window::new("Debug").default_pos((10.0, 10.0)).resizable(false).show(context, |ui| {
+		
+		let fps_counter = &program_data.fps_counter;
+		match (fps_counter.get_smoothed_fps(), fps_counter.get_smoothed_frame_time()) {
+			(Some(fps), Some(frame_time)) => ui.label(format!("FPS: {fps:.0}  ({frame_time:.2?})")),
+			_ => ui.label("FPS: -"),
+		};
+		
+		ui.separator();
+		let camera_data = &program_data.camera_data;
+		ui.label(format!("Camera position: ({:.2}, {:.2}, {:.2})", camera_data.pos.x, camera_data.pos.y, camera_data.pos.z));
+		ui.label(format!("Camera rotation: ({:.1}°, {:.1}°)", camera_data.rot_xz.to_degrees(), camera_data.rot_y.to_degrees()));
+		
+		ui.collapsing("Color correction", |ui| {
+			let color_correction_settings = &mut program_data.color_correction_settings;
+			ui.add(egui::Slider::new(&mut color_correction_settings.saturation, 0.0..=3.0).text("Saturation"));
+			ui.add(egui::Slider::new(&mut color_correction_settings.brightness, 0.0..=4.0).text("Brightness"));
+			ui.add(egui::Slider::new(&mut color_correction_settings.contrast, 0.0..=3.0).text("Contrast"));
+			ui.add(egui::Slider::new(&mut color_correction_settings.exposure, 0.01..=100.0).logarithmic(true).text("Exposure"));
+			ui.add(egui::Slider::new(&mut color_correction_settings.bloom_threshold, 0.0..=10.0).text("Bloom threshold"));
+			ui.add(egui::Slider::new(&mut color_correction_settings.bloom_strength, 0.0..=1.0).text("Bloom strength"));
+			ui.horizontal(|ui| {
+				ui.label("Tonemapper:");
+				ui.radio_value(&mut color_correction_settings.tonemapper, ColorCorrectionSettings::TONEMAPPER_NONE, "None");
+				ui.radio_value(&mut color_correction_settings.tonemapper, ColorCorrectionSettings::TONEMAPPER_REINHARD, "Reinhard");
+				ui.radio_value(&mut color_correction_settings.tonemapper, ColorCorrectionSettings::TONEMAPPER_ACES, "ACES");
+			});
+		});
+		
+		ui.collapsing("Skybox", |ui| {
+			ui.checkbox(&mut program_data.render_skybox, "Visible");
+			let skybox_settings = &mut program_data.skybox_settings;
+			ui.add(egui::Slider::new(&mut skybox_settings.rotation, 0.0..=2.0 * PI).text("Rotation"));
+			if program_data.render_assets.secondary_skybox_material_id.is_some() {
+				ui.add(egui::Slider::new(&mut skybox_settings.blend, 0.0..=1.0).text("Blend"));
+			}
+		});
+		
+		ui.collapsing("Shadow caster", |ui| {
+			// HELP: `DirectionalLight::rotate_y()` rotates the light around the world y axis, which adds the angle to this value
+			let direction = program_data.directional_light.direction;
+			let old_rotation = direction.x.atan2(direction.z);
+			let mut rotation = old_rotation;
+			if ui.add(egui::Slider::new(&mut rotation, -PI..=PI).text("Rotation")).changed() {
+				program_data.directional_light.rotate_y(rotation - old_rotation);
+			}
+		});
+	
+	});
+}
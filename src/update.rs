@@ -1,25 +1,70 @@
 use crate::prelude::*;
-use winit::{dpi::PhysicalPosition, keyboard::KeyCode};
+use winit::{dpi::PhysicalPosition, keyboard::KeyCode, window::{CursorGrabMode, Fullscreen}};
 
 
 
 pub type ShouldExit = bool;
 
 pub fn update(program_data: &mut ProgramData, dt: f32) -> Result<ShouldExit> {
-	let is_focused = program_data.render_context.window.has_focus();
+	let is_focused = program_data.input.is_focused;
 	
 	let should_exit = process_pre_frame_inputs(program_data);
 	if should_exit {return Ok(true);}
 	
+	// whichever frame's occlusion results finished reading are used by this frame's `render()`
+	if let Some(occlusion) = &mut program_data.render_assets.occlusion {
+		occlusion.take_results(&program_data.render_context.device);
+	}
+	
 	//if is_focused && program_data.is_moving_camera {
 	//}
 	
 	if is_focused && program_data.is_moving_camera {
-		update_camera(program_data, dt);
+		program_data.camera_data.apply_input(&program_data.input, dt);
 		//program_data.camera_data.rot_xz = 0.5; // for benchmarking
 		//program_data.camera_data.rot_y = 0.0;
 	}
 	
+	// scroll to zoom (unless the cursor is over the debug panel, which uses the scrolling itself)
+	if is_focused && !program_data.ui_state.wants_pointer_input {
+		program_data.camera_data.apply_zoom(program_data.input.scroll_delta);
+	}
+	
+	// left / right arrows to rotate the directional light
+	let input = &program_data.input;
+	let mut light_rotation = 0.0;
+	if input.key_is_down(KeyCode::ArrowLeft) {light_rotation += 1.0;}
+	if input.key_is_down(KeyCode::ArrowRight) {light_rotation -= 1.0;}
+	if light_rotation != 0.0 {
+		program_data.directional_light.rotate_y(light_rotation * dt);
+	}
+	
+	// ',' / '.' to rotate the skybox (on top of the auto-rotation), this also rotates the ibl so the lighting stays aligned with the background
+	let mut skybox_rotation = program_data.engine_config.skybox_rotation_speed;
+	if input.key_is_down(KeyCode::Comma) {skybox_rotation += 1.0;}
+	if input.key_is_down(KeyCode::Period) {skybox_rotation -= 1.0;}
+	if skybox_rotation != 0.0 {
+		let skybox_settings = &mut program_data.skybox_settings;
+		skybox_settings.rotation = (skybox_settings.rotation + skybox_rotation * dt).rem_euclid(std::f32::consts::TAU);
+	}
+	
+	// cross-fades between the main and secondary skybox (like going from a night sky to a day sky)
+	let skybox_blend_speed = program_data.engine_config.skybox_blend_speed;
+	if skybox_blend_speed != 0.0 {
+		let skybox_settings = &mut program_data.skybox_settings;
+		skybox_settings.blend = (skybox_settings.blend + skybox_blend_speed * dt).clamp(0.0, 1.0);
+	}
+	
+	update_spotlight(program_data);
+	update_instance_lods(program_data);
+	if program_data.show_debug_grid {
+		push_debug_grid(program_data);
+	}
+	if program_data.show_bounding_boxes {
+		push_bounding_boxes(program_data);
+	}
+	update_stats_text(program_data);
+	
 	let should_exit = process_post_frame_inputs(program_data);
 	if should_exit {return Ok(true);}
 	
@@ -28,6 +73,32 @@ pub fn update(program_data: &mut ProgramData, dt: f32) -> Result<ShouldExit> {
 
 
 
+// HELP: `update()` runs once per frame (with a dt that depends on the frame rate), and this runs `fixed_update()` as many
+// times as needed to keep it at `fixed_update_hz`, so anything that should behave the same at every frame rate goes there
+pub fn run_fixed_updates(program_data: &mut ProgramData, dt: f32) {
+	let fixed_dt = program_data.get_fixed_dt();
+	program_data.fixed_update_accumulator += dt;
+	let mut update_count = 0;
+	while program_data.fixed_update_accumulator >= fixed_dt {
+		if update_count == ProgramData::MAX_FIXED_UPDATES_PER_FRAME {
+			warn!("Fixed updates are falling behind, skipping {:.3} seconds.", program_data.fixed_update_accumulator);
+			program_data.fixed_update_accumulator %= fixed_dt;
+			break;
+		}
+		fixed_update(program_data, fixed_dt);
+		program_data.fixed_update_accumulator -= fixed_dt;
+		update_count += 1;
+	}
+	program_data.fixed_update_alpha = program_data.fixed_update_accumulator / fixed_dt;
+}
+
+pub fn fixed_update(program_data: &mut ProgramData, dt: f32) {
+	program_data.total_time += dt;
+	program_data.camera_data.shake.update(dt, program_data.total_time);
+}
+
+
+
 pub fn process_pre_frame_inputs(program_data: &mut ProgramData) -> ShouldExit {
 	let window = program_data.render_context.window;
 	let input = &program_data.input;
@@ -40,11 +111,132 @@ pub fn process_pre_frame_inputs(program_data: &mut ProgramData) -> ShouldExit {
 		return true;
 	}
 	
+	// n to cycle through normals debug views
+	if input.key_just_pressed(KeyCode::KeyN) {
+		let lighting_settings = &mut program_data.lighting_settings;
+		lighting_settings.debug_view = (lighting_settings.debug_view + 1) % LightingSettings::DEBUG_VIEW_COUNT;
+	}
+	
+	// f11 to toggle borderless fullscreen
+	if input.key_just_pressed(KeyCode::F11) {
+		if let Some(window) = window {
+			let fullscreen = if window.fullscreen().is_some() {None} else {Some(Fullscreen::Borderless(None))};
+			window.set_fullscreen(fullscreen);
+		}
+	}
+	
+	// v to cycle through the present modes that the surface supports
+	if input.key_just_pressed(KeyCode::KeyV) {
+		cycle_present_mode(&mut program_data.render_context, &mut program_data.engine_config);
+	}
+	
+	// f1 to toggle the debug panel
+	if input.key_just_pressed(KeyCode::F1) {
+		program_data.ui_state.is_visible = !program_data.ui_state.is_visible;
+	}
+	
+	// f2 to take a screenshot
+	if input.key_just_pressed(KeyCode::F2) {
+		program_data.screenshot_requested = true;
+	}
+	
+	// f3 to toggle the stats text
+	if input.key_just_pressed(KeyCode::F3) {
+		program_data.show_stats_text = !program_data.show_stats_text;
+	}
+	
+	// l to toggle the wireframe mode
+	if input.key_just_pressed(KeyCode::KeyL) {
+		if program_data.render_layouts.models_wireframe_pipeline.is_some() {
+			program_data.wireframe = !program_data.wireframe;
+		} else {
+			warn!("Cannot enable wireframe mode, the adapter doesn't support line polygon mode.");
+		}
+	}
+	
+	// m to show the shadowmap (what the shadow caster sees)
+	if input.key_just_pressed(KeyCode::KeyM) {
+		program_data.show_shadowmap_debug = !program_data.show_shadowmap_debug;
+	}
+	
+	// g to toggle the debug grid
+	if input.key_just_pressed(KeyCode::KeyG) {
+		program_data.show_debug_grid = !program_data.show_debug_grid;
+	}
+	
+	// x to toggle the bounding boxes
+	if input.key_just_pressed(KeyCode::KeyX) {
+		program_data.show_bounding_boxes = !program_data.show_bounding_boxes;
+	}
+	
+	// o to toggle the skybox (the models pass's clear color fills the background when it's off)
+	if input.key_just_pressed(KeyCode::KeyO) {
+		program_data.render_skybox = !program_data.render_skybox;
+	}
+	
+	// b to cycle through the shadow softness (pcf kernel sizes)
+	if input.key_just_pressed(KeyCode::KeyB) {
+		let lighting_settings = &mut program_data.lighting_settings;
+		let sizes = LightingSettings::PCF_KERNEL_SIZES;
+		let index = sizes.iter().position(|size| *size == lighting_settings.pcf_kernel_size).unwrap_or(0);
+		lighting_settings.pcf_kernel_size = sizes[(index + 1) % sizes.len()];
+	}
+	
+	// p to place a point light at the camera
+	if input.key_just_pressed(KeyCode::KeyP) {
+		program_data.lights.push(LightData {
+			kind: LightKind::Point,
+			pos: program_data.camera_data.pos,
+			direction: glam::Vec3::ZERO,
+			color: [1.0, 0.8, 0.6],
+			intensity: 2.0,
+			range: 15.0,
+		});
+	}
+	
+	// - and = to change the exposure
+	if input.key_just_pressed(KeyCode::Minus) {
+		program_data.color_correction_settings.exposure /= 1.25;
+	}
+	if input.key_just_pressed(KeyCode::Equal) {
+		program_data.color_correction_settings.exposure *= 1.25;
+	}
+	
+	// [ and ] to change the brightness (hold shift for saturation, or ctrl for contrast)
+	let color_correction_step = if input.key_just_pressed(KeyCode::BracketLeft) {
+		-0.05
+	} else if input.key_just_pressed(KeyCode::BracketRight) {
+		0.05
+	} else {
+		0.0
+	};
+	if color_correction_step != 0.0 {
+		let color_correction_settings = &mut program_data.color_correction_settings;
+		let value = if shift_down {
+			&mut color_correction_settings.saturation
+		} else if control_down {
+			&mut color_correction_settings.contrast
+		} else {
+			&mut color_correction_settings.brightness
+		};
+		*value += color_correction_step;
+		*color_correction_settings = color_correction_settings.clamped();
+	}
+	
+	// k to test the camera shake
+	if input.key_just_pressed(KeyCode::KeyK) {
+		program_data.camera_data.shake(1.0, 0.6);
+	}
+	
 	// esc to lose camera focus
-	if input.key_just_pressed(KeyCode::Escape) {
-		window.set_cursor_visible(true);
-		program_data.is_moving_camera = false;
-		program_data.input.capture_cursor = false;
+	if input.key_just_pressed(KeyCode::Escape) && program_data.input.capture_cursor {
+		set_cursor_captured(program_data, false);
+	}
+	
+	// tab to toggle camera focus
+	if program_data.input.key_just_pressed(KeyCode::Tab) && !program_data.ui_state.wants_keyboard_input {
+		let capture_cursor = !program_data.input.capture_cursor;
+		set_cursor_captured(program_data, capture_cursor);
 	}
 	
 	false
@@ -53,17 +245,11 @@ pub fn process_pre_frame_inputs(program_data: &mut ProgramData) -> ShouldExit {
 
 
 pub fn process_post_frame_inputs(program_data: &mut ProgramData) -> ShouldExit {
-	let window = program_data.render_context.window;
 	let input = &program_data.input;
 	
-	// click to gain camera focus
-	if input.button_just_pressed(MouseButton::Left) {
-		window.set_cursor_visible(false);
-		program_data.is_moving_camera = true;
-		program_data.input.capture_cursor = true;
-		let size = program_data.render_context.surface_size;
-		let window_center = PhysicalPosition::new(size.width as f64 / 2.0, size.height as f64 / 2.0);
-		let _ = program_data.render_context.window.set_cursor_position(window_center);
+	// click to gain camera focus (unless the click was on the debug panel)
+	if input.mouse_just_pressed(MouseButton::Left) && !program_data.input.capture_cursor && !program_data.ui_state.wants_pointer_input {
+		set_cursor_captured(program_data, true);
 	}
 	
 	false
@@ -71,48 +257,168 @@ pub fn process_post_frame_inputs(program_data: &mut ProgramData) -> ShouldExit {
 
 
 
-fn update_camera(program_data: &mut ProgramData, dt: f32) {
-	let input = &program_data.input;
-	let camera_data = &mut program_data.camera_data;
-	let mut speed = 30.0 * dt;
-	if program_data.input.key_is_down(KeyCode::ShiftLeft) {
-		speed *= 5.0;
+// HELP: `Locked` is the best mode for mouse-look since the cursor can't move at all, but not every platform supports
+// it (windows and x11 only support `Confined`), so this falls back to `Confined` and re-centers the cursor manually
+pub fn set_cursor_captured(program_data: &mut ProgramData, capture_cursor: bool) {
+	let Some(window) = program_data.render_context.window else {return;};
+	let input = &mut program_data.input;
+	program_data.is_moving_camera = capture_cursor;
+	input.capture_cursor = capture_cursor;
+	window.set_cursor_visible(!capture_cursor);
+	
+	if capture_cursor {
+		input.cursor_is_locked = match window.set_cursor_grab(CursorGrabMode::Locked) {
+			StdResult::Ok(()) => true,
+			StdResult::Err(err) => {
+				warn!("Could not lock the cursor, falling back to confining it to the window. (error: {err})");
+				if let Err(err) = window.set_cursor_grab(CursorGrabMode::Confined) {
+					warn!("Could not confine the cursor either, it will only be re-centered. (error: {err})");
+				}
+				false
+			}
+		};
+	} else {
+		if let Err(err) = window.set_cursor_grab(CursorGrabMode::None) {
+			warn!("Could not release the cursor grab. (error: {err})");
+		}
+		input.cursor_is_locked = false;
+	}
+	
+	// when capturing this is where the manual re-centering starts from, and when releasing this makes the cursor reappear somewhere predictable
+	let size = program_data.render_context.surface_size;
+	let window_center = PhysicalPosition::new(size.width as f64 / 2.0, size.height as f64 / 2.0);
+	let _ = window.set_cursor_position(window_center);
+	input.mouse_pos = window_center;
+}
+
+
+
+// HELP: the auto modes aren't in the surface's capabilities since they're resolved to one of the
+// other modes, so starting from one of them moves to the first supported mode
+pub fn cycle_present_mode(render_context: &mut RenderContextData, engine_config: &mut EngineConfig) {
+	let present_modes = &render_context.supported_present_modes;
+	if present_modes.is_empty() {
+		warn!("Cannot change present mode, the surface doesn't report any supported present modes.");
+		return;
+	}
+	let next_index = match present_modes.iter().position(|mode| *mode == render_context.surface_config.present_mode) {
+		Some(index) => (index + 1) % present_modes.len(),
+		None => 0,
+	};
+	let present_mode = present_modes[next_index];
+	render_context.surface_config.present_mode = present_mode;
+	engine_config.present_mode = present_mode;
+	if let Some(drawable_surface) = &render_context.drawable_surface {
+		if render_context.surface_config.width > 0 && render_context.surface_config.height > 0 {
+			drawable_surface.configure(&render_context.device, &render_context.surface_config);
+		}
 	}
-	let forward = glam::Vec3::new(
+	println!("Present mode is now {present_mode:?}.");
+}
+
+
+
+// the spot shadow caster acts as a flashlight, so it follows the camera
+fn update_spotlight(program_data: &mut ProgramData) {
+	let shadow_caster_data = &mut program_data.shadow_caster_data;
+	if shadow_caster_data.kind != ShadowCasterKind::Spot {return;}
+	let camera_data = &program_data.camera_data;
+	shadow_caster_data.pos = camera_data.pos;
+	shadow_caster_data.dir = glam::Vec3::new(
 		camera_data.rot_xz.cos() * camera_data.rot_y.cos(),
 		camera_data.rot_y.sin(),
 		camera_data.rot_xz.sin() * camera_data.rot_y.cos(),
 	);
-	let forward_dir = forward.normalize();
-	let right_dir = forward_dir.cross(glam::Vec3::Y).normalize();
-	
-	if input.key_is_down(KeyCode::KeyW) {
-		camera_data.pos += forward_dir * speed;
-	}
-	if input.key_is_down(KeyCode::KeyS) {
-		camera_data.pos -= forward_dir * speed;
+}
+
+// HELP: this only picks each instance's lod, `render::update_gpu_buffers()` is what splits the visible instances into the lods' buffers
+fn update_instance_lods(program_data: &mut ProgramData) {
+	let lod_distances = &program_data.engine_config.lod_distances;
+	let camera_pos = program_data.camera_data.pos;
+	let models_and_instances = program_data.render_assets.models.iter().zip(&program_data.model_instance_datas);
+	for ((model, instance_datas), instance_lods) in models_and_instances.zip(&mut program_data.model_instance_lods) {
+		if model.lods.is_empty() {continue;}
+		let lod_distances = &lod_distances[..model.lods.len()];
+		instance_lods.clear();
+		instance_lods.extend(instance_datas.iter().map(|instance| {
+			let distance = instance.pos.distance(camera_pos);
+			lod_distances.iter().take_while(|lod_distance| distance > **lod_distance).count() as u8
+		}));
 	}
+}
+
+// the grid is centered on the camera (snapped to the spacing so that the lines don't slide along with it), and the axes go through the origin
+fn push_debug_grid(program_data: &mut ProgramData) {
+	const MAX_HALF_LINE_COUNT: i32 = 500;
+	const GRID_COLOR: [f32; 3] = [0.35, 0.35, 0.35];
+	let spacing = program_data.engine_config.debug_grid_spacing;
+	let half_line_count = ((program_data.engine_config.debug_grid_size / spacing / 2.0).ceil() as i32).min(MAX_HALF_LINE_COUNT);
+	let half_size = half_line_count as f32 * spacing;
+	let center_x = (program_data.camera_data.pos.x / spacing).round() as i32;
+	let center_z = (program_data.camera_data.pos.z / spacing).round() as i32;
+	let (min_x, max_x) = ((center_x - half_line_count) as f32 * spacing, (center_x + half_line_count) as f32 * spacing);
+	let (min_z, max_z) = ((center_z - half_line_count) as f32 * spacing, (center_z + half_line_count) as f32 * spacing);
 	
-	if input.key_is_down(KeyCode::KeyD) {
-		camera_data.pos += right_dir * speed;
-	}
-	if input.key_is_down(KeyCode::KeyA) {
-		camera_data.pos -= right_dir * speed;
+	for i in -half_line_count..=half_line_count {
+		// the lines that lie on the axes are skipped so they don't z-fight with them
+		if center_x + i != 0 {
+			let x = (center_x + i) as f32 * spacing;
+			program_data.push_debug_line(glam::Vec3::new(x, 0.0, min_z), glam::Vec3::new(x, 0.0, max_z), GRID_COLOR);
+		}
+		if center_z + i != 0 {
+			let z = (center_z + i) as f32 * spacing;
+			program_data.push_debug_line(glam::Vec3::new(min_x, 0.0, z), glam::Vec3::new(max_x, 0.0, z), GRID_COLOR);
+		}
 	}
 	
-	if input.key_is_down(KeyCode::KeyE) {
-		camera_data.pos.y += speed;
-	}
-	if input.key_is_down(KeyCode::KeyQ) {
-		camera_data.pos.y -= speed;
+	program_data.push_debug_line(glam::Vec3::new(min_x, 0.0, 0.0), glam::Vec3::new(max_x, 0.0, 0.0), [1.0, 0.0, 0.0]);
+	program_data.push_debug_line(glam::Vec3::ZERO, glam::Vec3::new(0.0, half_size, 0.0), [0.0, 1.0, 0.0]);
+	program_data.push_debug_line(glam::Vec3::new(0.0, 0.0, min_z), glam::Vec3::new(0.0, 0.0, max_z), [0.0, 0.0, 1.0]);
+}
+
+// the boxes are colored by the instance's lod, or red when the last occlusion results hid it
+fn push_bounding_boxes(program_data: &mut ProgramData) {
+	const LOD_COLORS: [[f32; 3]; 4] = [[1.0, 1.0, 0.0], [0.0, 1.0, 1.0], [1.0, 0.0, 1.0], [1.0, 0.5, 0.0]];
+	const OCCLUDED_COLOR: [f32; 3] = [1.0, 0.0, 0.0];
+	let mode = program_data.engine_config.bounding_boxes_mode;
+	let occlusion = &program_data.render_assets.occlusion;
+	let mut boxes = vec!();
+	for (model_index, (model, instance_datas)) in program_data.render_assets.models.iter().zip(&program_data.model_instance_datas).enumerate() {
+		if model.aabb.is_empty() {continue;}
+		let instance_lods = &program_data.model_instance_lods[model_index];
+		for (i, instance) in instance_datas.iter().enumerate() {
+			let transform = instance.get_transform();
+			let corners = match mode {
+				BoundingBoxesMode::Oriented => model.aabb.get_corners().map(|corner| transform.transform_point3(corner)),
+				BoundingBoxesMode::World => model.aabb.transformed(&transform).get_corners(),
+			};
+			let is_occluded = occlusion.as_ref().is_some_and(|occlusion| occlusion.is_occluded(model_index, i));
+			let color = if is_occluded {
+				OCCLUDED_COLOR
+			} else {
+				let lod = instance_lods.get(i).copied().unwrap_or(0) as usize;
+				LOD_COLORS[lod.min(LOD_COLORS.len() - 1)]
+			};
+			boxes.push((corners, color));
+		}
 	}
 	
-	let sensitivity = 0.005;
-	let mouse_dt = (
-		input.mouse_vel.x.clamp(-50.0, 50.0) as f32 * sensitivity,
-		input.mouse_vel.y.clamp(-50.0, 50.0) as f32 * sensitivity,
-	);
-	camera_data.rot_xz += mouse_dt.0;
-	camera_data.rot_y = (camera_data.rot_y - mouse_dt.1).clamp(-std::f32::consts::FRAC_PI_2 * 0.999, std::f32::consts::FRAC_PI_2 * 0.999);
-	
+	for (corners, color) in boxes {
+		for (a, b) in Aabb::EDGES {
+			program_data.push_debug_line(corners[a], corners[b], color);
+		}
+	}
+}
+
+// uses the smoothed values (the same ones as the debug panel), since the per-second averages would only change once a second
+fn update_stats_text(program_data: &mut ProgramData) {
+	let stats_text = &mut program_data.stats_text;
+	stats_text.clear();
+	if !program_data.show_stats_text {return;}
+	let fps_counter = &program_data.fps_counter;
+	let (Some(fps), Some(frame_time)) = (fps_counter.get_smoothed_fps(), fps_counter.get_smoothed_frame_time()) else {return;};
+	stats_text.push(format!("FPS: {fps:.0}"));
+	stats_text.push(format!("FRAME: {:.2} MS", frame_time.as_secs_f64() * 1000.0));
+	let camera_pos = program_data.camera_data.pos;
+	stats_text.push(format!("POS: {:.1}, {:.1}, {:.1}", camera_pos.x, camera_pos.y, camera_pos.z));
 }
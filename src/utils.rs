@@ -6,6 +6,8 @@ use crate::prelude::*;
 pub fn get_program_file_path(input: impl AsRef<Path>) -> PathBuf {
 	let mut output = std::env::current_exe().expect("Could not retrieve the path for the current exe.");
 	output.pop();
+	// test executables are one folder deeper, in 'target/debug/deps'
+	if output.ends_with("deps") {output.pop();}
 	output.pop();
 	output.pop();
 	output.join("data").join(input)
@@ -20,15 +22,45 @@ pub fn get_program_file_path(input: impl AsRef<Path>) -> PathBuf {
 
 
 
+// converts one srgb color channel (0 to 1) to linear, using the exact piecewise srgb curve
+pub fn srgb_to_linear(value: f32) -> f32 {
+	if value <= 0.04045 {
+		value / 12.92
+	} else {
+		((value + 0.055) / 1.055).powf(2.4)
+	}
+}
+
+// the inverse of `srgb_to_linear()`
+pub fn linear_to_srgb(value: f32) -> f32 {
+	if value <= 0.0031308 {
+		value * 12.92
+	} else {
+		1.055 * value.powf(1.0 / 2.4) - 0.055
+	}
+}
+
+
+
 pub trait IoResultFns<T> {
 	fn add_path_to_error(self, path: impl AsRef<Path>) -> Result<T>;
 }
 
 impl<T> IoResultFns<T> for std::io::Result<T> {
+	// the full path is included since relative paths depend on the working directory, which usually isn't obvious from an error message
 	fn add_path_to_error(self, path: impl AsRef<Path>) -> Result<T> {
 		match self {
 			StdResult::Ok(v) => Ok(v),
-			StdResult::Err(err) => Err(Error::msg(format!("Error with file {:?}: {err}", path.as_ref()))),
+			StdResult::Err(err) => {
+				let path = path.as_ref();
+				let full_path = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+				let kind = err.kind();
+				if full_path == path {
+					Err(Error::msg(format!("Error with file {path:?} ({kind}): {err}")))
+				} else {
+					Err(Error::msg(format!("Error with file {path:?} (full path: {full_path:?}) ({kind}): {err}")))
+				}
+			}
 		}
 	}
 }
@@ -0,0 +1,100 @@
+// HELP: these render a few fixed scenes without a window and compare them against the reference images in
+// 'tests/golden', which catches shader and pipeline changes that silently change the output. After an intended
+// change, run the tests with `BLESS=1` to overwrite the references (and check the new images before committing them)
+
+use wgpu_template::prelude::*;
+
+
+
+const WIDTH: u32 = 160;
+const HEIGHT: u32 = 90;
+// the references are made on one gpu, and other gpus (or drivers) round things a bit differently
+const MAX_CHANNEL_DIFF: u8 = 8;
+const MAX_DIFFERENT_PIXELS: f64 = 0.005;
+
+
+
+fn get_scene_config() -> EngineConfig {
+	EngineConfig {
+		window_width: WIDTH,
+		window_height: HEIGHT,
+		compress_textures: false, // compressors aren't guaranteed to give the same output everywhere
+		model_paths: vec!(PathBuf::from("assets/cube.obj")),
+		lod_distances: vec!(),
+		shadow_catcher_enabled: true, // so that the shadows have something to land on
+		hot_reload_shaders: false,
+		hot_reload_config: false,
+		..EngineConfig::default()
+	}
+}
+
+// renders from just outside the corner of the example instance grid, looking at the corner cube
+fn render_scene(engine_config: EngineConfig) -> Option<image::RgbaImage> {
+	if !load::headless_adapter_is_available(&engine_config) {
+		eprintln!("Skipping test because there's no adapter available.");
+		return None;
+	}
+	let mut program_data = load::load_headless_program_data(Instant::now(), engine_config).expect("Failed to load headless program data.");
+	let camera_data = &mut program_data.camera_data;
+	camera_data.pos = glam::Vec3::new(-6.0, 3.0, -6.0);
+	let look_dir = glam::Vec3::new(-0.5, 0.0, -0.5) - camera_data.pos;
+	camera_data.rot_xz = look_dir.z.atan2(look_dir.x);
+	camera_data.rot_y = (look_dir.y / look_dir.length()).asin();
+	Some(capture_utils::render_headless_frame(&mut program_data).expect("Failed to render headless frame."))
+}
+
+fn compare_to_golden(name: &str, actual: &image::RgbaImage) {
+	let golden_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden").join(format!("{name}.png"));
+	if std::env::var("BLESS").is_ok_and(|value| value == "1") {
+		actual.save_with_format(&golden_path, image::ImageFormat::Png).expect("Failed to save golden image.");
+		println!("Blessed {golden_path:?}.");
+		return;
+	}
+	
+	let Some(golden) = image::open(&golden_path).ok().map(|image| image.to_rgba8()) else {
+		panic!("Could not open golden image {golden_path:?}, run with BLESS=1 to create it.");
+	};
+	assert_eq!(golden.dimensions(), actual.dimensions(), "The size of {name} doesn't match its golden image.");
+	let different_pixels = golden.pixels().zip(actual.pixels())
+		.filter(|(golden_pixel, actual_pixel)| golden_pixel.0.iter().zip(actual_pixel.0).any(|(a, b)| a.abs_diff(b) > MAX_CHANNEL_DIFF))
+		.count();
+	let different_fraction = different_pixels as f64 / (WIDTH * HEIGHT) as f64;
+	if different_fraction > MAX_DIFFERENT_PIXELS {
+		// the actual image is kept so that it can be compared by hand
+		let actual_path = std::env::temp_dir().join(format!("{name}.actual.png"));
+		let _ = actual.save_with_format(&actual_path, image::ImageFormat::Png);
+		panic!("{name} differs from its golden image in {different_pixels} pixels ({:.2}%), the rendered image was saved to {actual_path:?}.", different_fraction * 100.0);
+	}
+}
+
+
+
+#[test]
+fn skybox_with_directional_shadow() {
+	let Some(actual) = render_scene(get_scene_config()) else {return;};
+	compare_to_golden("skybox_with_directional_shadow", &actual);
+}
+
+#[test]
+fn gradient_with_spot_shadow() {
+	let engine_config = EngineConfig {
+		background_mode: BackgroundMode::Gradient,
+		shadow_caster_kind: ShadowCasterKind::Spot,
+		..get_scene_config()
+	};
+	let Some(actual) = render_scene(engine_config) else {return;};
+	compare_to_golden("gradient_with_spot_shadow", &actual);
+}
+
+#[test]
+fn orthographic_with_flat_shading() {
+	let engine_config = EngineConfig {
+		background_mode: BackgroundMode::Solid,
+		camera_projection: CameraProjectionKind::Orthographic,
+		camera_ortho_height: 12.0,
+		flat_shading: true,
+		..get_scene_config()
+	};
+	let Some(actual) = render_scene(engine_config) else {return;};
+	compare_to_golden("orthographic_with_flat_shading", &actual);
+}